@@ -0,0 +1,563 @@
+use std::collections::BTreeMap;
+
+use super::labels::LabelReference;
+
+use crate::{AddressBase, MemoryAddress, Operand, OperandWidth, Register16, Register8, SegmentRegister};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AssembleError {
+    /// The instruction has no encoding taking this combination of operands.
+    InvalidOperands{mnemonic: &'static str},
+    /// The operands of the instruction have different widths.
+    OperandSizeMismatch{mnemonic: &'static str},
+    /// An immediate does not fit in the width of the operand it is used with.
+    ImmediateOutOfRange{mnemonic: &'static str, value: u16},
+    /// A label was referenced but never defined.
+    UnresolvedLabel{label: String},
+    /// A label is too far from the jump which references it for the displacement to be encoded.
+    JumpOutOfRange{label: String, distance: isize},
+    /// A line of source text could not be parsed.
+    InvalidSyntax{line: usize},
+    /// A line of source text uses a mnemonic the assembler does not support.
+    UnknownMnemonic{line: usize, mnemonic: String}
+}
+
+/// Returns the 3-bit encoding of a general purpose register operand, or `None` for any other operand.
+const fn register_index(operand: Operand) -> Option<u8> {
+    match operand {
+        Operand::Register8(register) => Some(register as u8),
+        Operand::Register16(register) => Some(register as u8),
+        _ => None
+    }
+}
+
+/// Returns `true` for the operands which can be encoded in the `r/m` field of a `ModRM` byte.
+const fn is_rm(operand: Operand) -> bool {
+    matches!(operand, Operand::Register8(_) | Operand::Register16(_) | Operand::Memory { .. })
+}
+
+/// Returns `true` for `AL` and `AX`, which have shorter encodings for some instructions.
+const fn is_accumulator(operand: Operand) -> bool {
+    matches!(operand, Operand::Register8(Register8::Al) | Operand::Register16(Register16::Ax))
+}
+
+/// Returns the offset of a direct (`[disp16]`) memory operand, which can use the accumulator `MOV` forms.
+const fn direct_offset(operand: Operand) -> Option<u16> {
+    match operand {
+        Operand::Memory { address: MemoryAddress { base: AddressBase::Direct, displacement, .. }, .. } => {
+            Some(match displacement {
+                Some(displacement) => displacement.cast_unsigned(),
+                None => 0
+            })
+        }
+        _ => None
+    }
+}
+
+/// Encodes the `mod` and `r/m` fields and any displacement for a memory operand, picking the shortest displacement.
+fn encode_memory(address: MemoryAddress) -> (u8, u8, Vec<u8>) {
+    let displacement = address.displacement.unwrap_or(0);
+
+    let rm = match address.base {
+        AddressBase::Direct => return (0b00, 0b110, displacement.to_le_bytes().to_vec()),
+        AddressBase::BxSi => 0,
+        AddressBase::BxDi => 1,
+        AddressBase::BpSi => 2,
+        AddressBase::BpDi => 3,
+        AddressBase::Si => 4,
+        AddressBase::Di => 5,
+        AddressBase::Bp => 6,
+        AddressBase::Bx => 7
+    };
+
+    // `[BP]` without a displacement shares its encoding with `[disp16]`, so always takes an 8-bit displacement
+    if displacement == 0 && address.base != AddressBase::Bp {
+        (0b00, rm, Vec::new())
+    }
+    else if let Ok(byte) = i8::try_from(displacement) {
+        (0b01, rm, vec![byte.cast_unsigned()])
+    }
+    else {
+        (0b10, rm, displacement.to_le_bytes().to_vec())
+    }
+}
+
+/// Assembles 8086 instructions into machine code.
+///
+/// Each instruction method appends the encoding of one instruction to the output, or returns an error leaving the
+/// output unchanged. Where several encodings exist the shortest is chosen.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Assembler {
+    pub(super) bytes: Vec<u8>,
+    pub(super) labels: BTreeMap<String, usize>,
+    pub(super) references: Vec<LabelReference>
+}
+
+impl Assembler {
+    #[must_use]
+    /// Constructs a new assembler with no output.
+    pub const fn new() -> Self {
+        Self { bytes: Vec::new(), labels: BTreeMap::new(), references: Vec::new() }
+    }
+
+    #[must_use]
+    /// Returns the machine code assembled so far.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    #[must_use]
+    /// Returns the offset of the next instruction, the number of bytes assembled so far.
+    pub const fn position(&self) -> usize {
+        self.bytes.len()
+    }
+
+    #[must_use]
+    /// Consumes the assembler, returning the machine code. Label references which have not been resolved are left as
+    /// placeholders.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Appends raw bytes to the output.
+    pub fn db(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// Assembles `INT vector`.
+    pub fn int(&mut self, vector: u8) {
+        self.bytes.extend_from_slice(&[0xCD, vector]);
+    }
+
+    /// Appends the segment override prefix of `operand`, if it is a memory operand with an override.
+    fn emit_segment_override(&mut self, operand: Operand) {
+        if let Operand::Memory { address: MemoryAddress { segment_override: Some(segment), .. }, .. } = operand {
+            self.bytes.push(0x26 | (segment as u8) << 3);
+        }
+    }
+
+    /// Appends an instruction made up of any segment override prefix of `rm`, the `opcode`, and a `ModRM` byte
+    /// encoding `reg` and the register or memory operand `rm`, followed by any displacement.
+    fn emit_rm(&mut self, opcode: u8, reg: u8, rm: Operand) {
+        self.emit_segment_override(rm);
+        self.bytes.push(opcode);
+
+        let (mod_field, rm_field, displacement) = match rm {
+            Operand::Memory { address, .. } => encode_memory(address),
+            _ => (0b11, register_index(rm).unwrap_or(0), Vec::new())
+        };
+
+        self.bytes.push(mod_field << 6 | (reg & 0b111) << 3 | rm_field);
+        self.bytes.extend(displacement);
+    }
+
+    /// Appends an immediate of the given width.
+    fn emit_immediate(&mut self, width: OperandWidth, value: u16) {
+        match width {
+            OperandWidth::Byte => self.bytes.push(value.to_le_bytes()[0]),
+            OperandWidth::Word => self.bytes.extend(value.to_le_bytes())
+        }
+    }
+
+    /// Checks that `dst` and `src` have the same width if both have one, returning the width of the instruction.
+    ///
+    /// Immediates take the width of the other operand, so must fit within it. Byte immediates given as words are
+    /// accepted if they are in `0x0000-0x00FF` or are a sign extended negative byte (`0xFF80-0xFFFF`).
+    fn operation_width(mnemonic: &'static str, dst: Operand, src: Operand) -> Result<OperandWidth, AssembleError> {
+        match (dst, src) {
+            (Operand::Immediate8(_) | Operand::Immediate16(_), _) => Err(AssembleError::InvalidOperands { mnemonic }),
+            (_, Operand::Immediate8(_)) => dst.width().ok_or(AssembleError::InvalidOperands { mnemonic }),
+            (_, Operand::Immediate16(value)) => match dst.width() {
+                Some(OperandWidth::Byte) if value > 0xFF && value < 0xFF80 => Err(AssembleError::ImmediateOutOfRange { mnemonic, value }),
+                Some(width) => Ok(width),
+                None => Err(AssembleError::InvalidOperands { mnemonic })
+            },
+            _ => match (dst.width(), src.width()) {
+                (Some(a), Some(b)) if a == b => Ok(a),
+                (Some(_), Some(_)) => Err(AssembleError::OperandSizeMismatch { mnemonic }),
+                _ => Err(AssembleError::InvalidOperands { mnemonic })
+            }
+        }
+    }
+
+    /// Assembles `MOV dst, src`.
+    ///
+    /// Register to register or memory, register or memory to register, immediate to register or memory and the
+    /// segment register forms are supported. `AL` or `AX` to or from a direct memory operand uses the shorter
+    /// accumulator encodings.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the operands cannot be encoded, such as a memory to memory move, an
+    /// immediate moved into a segment register or `CS` as the destination, or if their widths do not match.
+    pub fn mov(&mut self, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        const MNEMONIC: &str = "MOV";
+        let invalid = AssembleError::InvalidOperands { mnemonic: MNEMONIC };
+
+        let width = Self::operation_width(MNEMONIC, dst, src)?;
+        let w = u8::from(width == OperandWidth::Word);
+
+        match (dst, src) {
+            (Operand::Segment(SegmentRegister::Cs), _) => return Err(invalid),
+            (Operand::Segment(segment), rm) if is_rm(rm) => self.emit_rm(0x8E, segment as u8, rm),
+            (rm, Operand::Segment(segment)) if is_rm(rm) => self.emit_rm(0x8C, segment as u8, rm),
+            (memory, accumulator) | (accumulator, memory) if is_accumulator(accumulator) && direct_offset(memory).is_some() => {
+                // MOV AL/AX, [disp16] and MOV [disp16], AL/AX have their own opcodes without a `ModRM` byte
+                let to_memory = memory == dst;
+                self.emit_segment_override(memory);
+                self.bytes.push(0xA0 | u8::from(to_memory) << 1 | w);
+                self.emit_immediate(OperandWidth::Word, direct_offset(memory).unwrap_or(0));
+            }
+            (rm, reg @ (Operand::Register8(_) | Operand::Register16(_))) if is_rm(rm) => {
+                self.emit_rm(0x88 | w, register_index(reg).unwrap_or(0), rm);
+            }
+            (reg @ (Operand::Register8(_) | Operand::Register16(_)), rm @ Operand::Memory { .. }) => {
+                self.emit_rm(0x8A | w, register_index(reg).unwrap_or(0), rm);
+            }
+            (reg @ (Operand::Register8(_) | Operand::Register16(_)), Operand::Immediate8(_) | Operand::Immediate16(_)) => {
+                self.bytes.push(0xB0 | w << 3 | register_index(reg).unwrap_or(0));
+                self.emit_immediate(width, immediate_value(src));
+            }
+            (memory @ Operand::Memory { .. }, Operand::Immediate8(_) | Operand::Immediate16(_)) => {
+                self.emit_rm(0xC6 | w, 0, memory);
+                self.emit_immediate(width, immediate_value(src));
+            }
+            _ => return Err(invalid)
+        }
+
+        Ok(())
+    }
+
+    /// Assembles one of the eight arithmetic and logic instructions sharing the `/r` and `/i` encodings, where `index`
+    /// is the position of the operation in `ADD`, `OR`, `ADC`, `SBB`, `AND`, `SUB`, `XOR`, `CMP` order.
+    fn arithmetic(&mut self, mnemonic: &'static str, index: u8, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        let width = Self::operation_width(mnemonic, dst, src)?;
+        let w = u8::from(width == OperandWidth::Word);
+
+        match (dst, src) {
+            (rm, reg @ (Operand::Register8(_) | Operand::Register16(_))) if is_rm(rm) => {
+                self.emit_rm(index << 3 | w, register_index(reg).unwrap_or(0), rm);
+            }
+            (reg @ (Operand::Register8(_) | Operand::Register16(_)), rm @ Operand::Memory { .. }) => {
+                self.emit_rm(index << 3 | 0b10 | w, register_index(reg).unwrap_or(0), rm);
+            }
+            (accumulator, Operand::Immediate8(_) | Operand::Immediate16(_)) if is_accumulator(accumulator) => {
+                self.bytes.push(index << 3 | 0b100 | w);
+                self.emit_immediate(width, immediate_value(src));
+            }
+            (rm, Operand::Immediate8(_) | Operand::Immediate16(_)) if is_rm(rm) => {
+                let value = immediate_value(src);
+
+                // Word immediates which survive sign extension from a byte have a shorter encoding
+                if width == OperandWidth::Word && i8::try_from(value.cast_signed()).is_ok() {
+                    self.emit_rm(0x83, index, rm);
+                    self.emit_immediate(OperandWidth::Byte, value);
+                }
+                else {
+                    self.emit_rm(0x80 | w, index, rm);
+                    self.emit_immediate(width, value);
+                }
+            }
+            _ => return Err(AssembleError::InvalidOperands { mnemonic })
+        }
+
+        Ok(())
+    }
+
+    /// Assembles `ADD dst, src`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the operands cannot be encoded or their widths do not match.
+    pub fn add(&mut self, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        self.arithmetic("ADD", 0, dst, src)
+    }
+
+    /// Assembles `OR dst, src`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the operands cannot be encoded or their widths do not match.
+    pub fn or(&mut self, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        self.arithmetic("OR", 1, dst, src)
+    }
+
+    /// Assembles `ADC dst, src`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the operands cannot be encoded or their widths do not match.
+    pub fn adc(&mut self, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        self.arithmetic("ADC", 2, dst, src)
+    }
+
+    /// Assembles `SBB dst, src`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the operands cannot be encoded or their widths do not match.
+    pub fn sbb(&mut self, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        self.arithmetic("SBB", 3, dst, src)
+    }
+
+    /// Assembles `AND dst, src`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the operands cannot be encoded or their widths do not match.
+    pub fn and(&mut self, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        self.arithmetic("AND", 4, dst, src)
+    }
+
+    /// Assembles `SUB dst, src`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the operands cannot be encoded or their widths do not match.
+    pub fn sub(&mut self, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        self.arithmetic("SUB", 5, dst, src)
+    }
+
+    /// Assembles `XOR dst, src`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the operands cannot be encoded or their widths do not match.
+    pub fn xor(&mut self, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        self.arithmetic("XOR", 6, dst, src)
+    }
+
+    /// Assembles `CMP dst, src`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the operands cannot be encoded or their widths do not match.
+    pub fn cmp(&mut self, dst: Operand, src: Operand) -> Result<(), AssembleError> {
+        self.arithmetic("CMP", 7, dst, src)
+    }
+}
+
+/// Returns the value of an immediate operand, zero extending byte immediates.
+pub(super) const fn immediate_value(operand: Operand) -> u16 {
+    match operand {
+        Operand::Immediate8(value) => value as u16,
+        Operand::Immediate16(value) => value,
+        _ => 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::Memory;
+
+    use crate::{Instruction, InstructionDecoder, Opcode};
+
+    use super::*;
+
+    fn mov(dst: Operand, src: Operand) -> Result<Vec<u8>, AssembleError> {
+        let mut assembler = Assembler::new();
+        assembler.mov(dst, src)?;
+        Ok(assembler.into_bytes())
+    }
+
+    /// Assembles `MOV dst, src`, checks it against `expected` and that it decodes back to the same operands.
+    fn check_mov(dst: Operand, src: Operand, expected: &[u8]) {
+        let bytes = mov(dst, src).unwrap();
+        assert_eq!(bytes, expected, "MOV {dst:?}, {src:?}");
+
+        let decoded = InstructionDecoder::new().decode(&Memory::<16>::populated(&bytes), 0).unwrap();
+        assert_eq!(decoded.opcode, Opcode::Mov);
+        assert_eq!(usize::from(decoded.byte_length), bytes.len());
+
+        assert_eq!((decoded.dst, decoded.src), (Some(dst), Some(src)), "MOV {dst:?}, {src:?}");
+    }
+
+    fn memory(base: AddressBase, displacement: Option<i16>, width: OperandWidth) -> Operand {
+        Operand::Memory { address: MemoryAddress::new(base, displacement), width }
+    }
+
+    fn direct(offset: u16, width: OperandWidth) -> Operand {
+        Operand::Memory { address: MemoryAddress::direct(offset), width }
+    }
+
+    #[test]
+    fn test_mov_register_to_register() {
+        check_mov(Operand::Register16(Register16::Ax), Operand::Register16(Register16::Bx), &[0x89, 0xD8]);
+        check_mov(Operand::Register16(Register16::Di), Operand::Register16(Register16::Sp), &[0x89, 0xE7]);
+        check_mov(Operand::Register8(Register8::Ah), Operand::Register8(Register8::Cl), &[0x88, 0xCC]);
+    }
+
+    #[test]
+    fn test_mov_immediate_to_register() {
+        check_mov(Operand::Register16(Register16::Ax), Operand::Immediate16(0x1234), &[0xB8, 0x34, 0x12]);
+        check_mov(Operand::Register8(Register8::Bh), Operand::Immediate8(0x7F), &[0xB7, 0x7F]);
+
+        // Immediates are sized to fit the register
+        assert_eq!(mov(Operand::Register16(Register16::Di), Operand::Immediate8(0x12)), Ok(vec![0xBF, 0x12, 0x00]));
+        assert_eq!(mov(Operand::Register8(Register8::Dl), Operand::Immediate16(0xFFFF)), Ok(vec![0xB2, 0xFF]));
+    }
+
+    #[test]
+    fn test_mov_immediate_to_memory() {
+        check_mov(memory(AddressBase::Bx, None, OperandWidth::Byte), Operand::Immediate8(0x41), &[0xC6, 0x07, 0x41]);
+        check_mov(direct(0x1000, OperandWidth::Word), Operand::Immediate16(0x5678), &[0xC7, 0x06, 0x00, 0x10, 0x78, 0x56]);
+        check_mov(memory(AddressBase::BpDi, Some(-4), OperandWidth::Word), Operand::Immediate16(1), &[0xC7, 0x43, 0xFC, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn test_mov_register_and_memory() {
+        check_mov(memory(AddressBase::BxSi, Some(4), OperandWidth::Word), Operand::Register16(Register16::Cx), &[0x89, 0x48, 0x04]);
+        check_mov(Operand::Register8(Register8::Dl), memory(AddressBase::Bp, Some(-2), OperandWidth::Byte), &[0x8A, 0x56, 0xFE]);
+        check_mov(Operand::Register16(Register16::Si), memory(AddressBase::Di, Some(0x1234), OperandWidth::Word), &[0x8B, 0xB5, 0x34, 0x12]);
+
+        check_mov(Operand::Register16(Register16::Bx), memory(AddressBase::Si, None, OperandWidth::Word), &[0x8B, 0x1C]);
+
+        // [BP] needs an explicit zero displacement, while a zero displacement is otherwise dropped
+        check_mov(Operand::Register16(Register16::Bx), memory(AddressBase::Bp, Some(0), OperandWidth::Word), &[0x8B, 0x5E, 0x00]);
+        assert_eq!(mov(Operand::Register16(Register16::Bx), memory(AddressBase::Bp, None, OperandWidth::Word)), Ok(vec![0x8B, 0x5E, 0x00]));
+        assert_eq!(mov(Operand::Register16(Register16::Bx), memory(AddressBase::Si, Some(0), OperandWidth::Word)), Ok(vec![0x8B, 0x1C]));
+    }
+
+    #[test]
+    fn test_mov_accumulator_forms() {
+        check_mov(Operand::Register8(Register8::Al), direct(0x1234, OperandWidth::Byte), &[0xA0, 0x34, 0x12]);
+        check_mov(Operand::Register16(Register16::Ax), direct(0x1234, OperandWidth::Word), &[0xA1, 0x34, 0x12]);
+        check_mov(direct(0x0010, OperandWidth::Byte), Operand::Register8(Register8::Al), &[0xA2, 0x10, 0x00]);
+        check_mov(direct(0x0010, OperandWidth::Word), Operand::Register16(Register16::Ax), &[0xA3, 0x10, 0x00]);
+
+        // Other registers use the general form
+        check_mov(Operand::Register16(Register16::Bx), direct(0x1234, OperandWidth::Word), &[0x8B, 0x1E, 0x34, 0x12]);
+    }
+
+    #[test]
+    fn test_mov_segment_registers() {
+        check_mov(Operand::Segment(SegmentRegister::Ds), Operand::Register16(Register16::Ax), &[0x8E, 0xD8]);
+        check_mov(Operand::Segment(SegmentRegister::Es), memory(AddressBase::Bx, Some(2), OperandWidth::Word), &[0x8E, 0x47, 0x02]);
+        check_mov(Operand::Register16(Register16::Dx), Operand::Segment(SegmentRegister::Cs), &[0x8C, 0xCA]);
+        check_mov(direct(0x0100, OperandWidth::Word), Operand::Segment(SegmentRegister::Ss), &[0x8C, 0x16, 0x00, 0x01]);
+    }
+
+    #[test]
+    fn test_mov_segment_override() {
+        let address = MemoryAddress { segment_override: Some(SegmentRegister::Es), ..MemoryAddress::new(AddressBase::Bx, None) };
+        check_mov(Operand::Register16(Register16::Ax), Operand::Memory { address, width: OperandWidth::Word }, &[0x26, 0x8B, 0x07]);
+
+        let address = MemoryAddress { segment_override: Some(SegmentRegister::Cs), ..MemoryAddress::direct(0x10) };
+        check_mov(Operand::Register8(Register8::Al), Operand::Memory { address, width: OperandWidth::Byte }, &[0x2E, 0xA0, 0x10, 0x00]);
+    }
+
+    #[test]
+    fn test_mov_invalid() {
+        let invalid = Err(AssembleError::InvalidOperands { mnemonic: "MOV" });
+        let mismatch = Err(AssembleError::OperandSizeMismatch { mnemonic: "MOV" });
+
+        assert_eq!(mov(direct(0, OperandWidth::Word), memory(AddressBase::Bx, None, OperandWidth::Word)), invalid);
+        assert_eq!(mov(Operand::Immediate8(1), Operand::Register8(Register8::Al)), invalid);
+        assert_eq!(mov(Operand::Segment(SegmentRegister::Ds), Operand::Immediate16(0)), invalid);
+        assert_eq!(mov(Operand::Segment(SegmentRegister::Cs), Operand::Register16(Register16::Ax)), invalid);
+        assert_eq!(mov(Operand::Segment(SegmentRegister::Ds), Operand::Segment(SegmentRegister::Es)), invalid);
+        assert_eq!(mov(Operand::Register16(Register16::Ax), Operand::Relative(2)), invalid);
+
+        assert_eq!(mov(Operand::Register16(Register16::Ax), Operand::Register8(Register8::Al)), mismatch);
+        assert_eq!(mov(Operand::Segment(SegmentRegister::Ds), Operand::Register8(Register8::Al)), mismatch);
+        assert_eq!(mov(Operand::Register8(Register8::Al), memory(AddressBase::Bx, None, OperandWidth::Word)), mismatch);
+
+        assert_eq!(mov(Operand::Register8(Register8::Al), Operand::Immediate16(0x100)), Err(AssembleError::ImmediateOutOfRange { mnemonic: "MOV", value: 0x100 }));
+    }
+
+    type ArithmeticFn = fn(&mut Assembler, Operand, Operand) -> Result<(), AssembleError>;
+
+    const ARITHMETIC: [(ArithmeticFn, Opcode); 8] = [
+        (Assembler::add, Opcode::Add), (Assembler::or, Opcode::Or), (Assembler::adc, Opcode::Adc), (Assembler::sbb, Opcode::Sbb),
+        (Assembler::and, Opcode::And), (Assembler::sub, Opcode::Sub), (Assembler::xor, Opcode::Xor), (Assembler::cmp, Opcode::Cmp)
+    ];
+
+    fn arithmetic(function: ArithmeticFn, dst: Operand, src: Operand) -> Result<Vec<u8>, AssembleError> {
+        let mut assembler = Assembler::new();
+        function(&mut assembler, dst, src)?;
+        Ok(assembler.into_bytes())
+    }
+
+    #[test]
+    fn test_arithmetic_register_forms() {
+        for (index, (function, opcode)) in (0u8..).zip(ARITHMETIC) {
+            let base = index << 3;
+
+            let cases = [
+                // ADD BX, CX / ADD [BX+SI+4], AL / ADD DX, [BP-2] / ADD AH, [1234h]
+                (Operand::Register16(Register16::Bx), Operand::Register16(Register16::Cx), vec![base | 1, 0xCB]),
+                (memory(AddressBase::BxSi, Some(4), OperandWidth::Byte), Operand::Register8(Register8::Al), vec![base, 0x40, 0x04]),
+                (Operand::Register16(Register16::Dx), memory(AddressBase::Bp, Some(-2), OperandWidth::Word), vec![base | 3, 0x56, 0xFE]),
+                (Operand::Register8(Register8::Ah), direct(0x1234, OperandWidth::Byte), vec![base | 2, 0x26, 0x34, 0x12])
+            ];
+
+            for (dst, src, expected) in cases {
+                let bytes = arithmetic(function, dst, src).unwrap();
+                assert_eq!(bytes, expected);
+
+                let decoded = InstructionDecoder::new().decode(&Memory::<16>::populated(&bytes), 0).unwrap();
+                assert_eq!((decoded.opcode, decoded.dst, decoded.src), (opcode, Some(dst), Some(src)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_immediate_forms() {
+        for (index, (function, opcode)) in (0u8..).zip(ARITHMETIC) {
+            let base = index << 3;
+            let reg = index << 3;
+
+            let cases = [
+                // Accumulator forms
+                (Operand::Register8(Register8::Al), Operand::Immediate8(0x12), vec![base | 4, 0x12], Operand::Immediate8(0x12)),
+                (Operand::Register16(Register16::Ax), Operand::Immediate16(0x1234), vec![base | 5, 0x34, 0x12], Operand::Immediate16(0x1234)),
+                // Byte and word immediates
+                (Operand::Register8(Register8::Bl), Operand::Immediate8(0x80), vec![0x80, 0xC3 | reg, 0x80], Operand::Immediate8(0x80)),
+                (memory(AddressBase::Di, None, OperandWidth::Word), Operand::Immediate16(0x1234), vec![0x81, 0x05 | reg, 0x34, 0x12], Operand::Immediate16(0x1234)),
+                // Sign extended byte immediates
+                (Operand::Register16(Register16::Cx), Operand::Immediate16(0xFFFF), vec![0x83, 0xC1 | reg, 0xFF], Operand::Immediate16(0xFFFF)),
+                (memory(AddressBase::Bx, Some(2), OperandWidth::Word), Operand::Immediate8(0x7F), vec![0x83, 0x47 | reg, 0x02, 0x7F], Operand::Immediate16(0x7F)),
+                (Operand::Register16(Register16::Sp), Operand::Immediate16(0xFF80), vec![0x83, 0xC4 | reg, 0x80], Operand::Immediate16(0xFF80)),
+                // 0x80 does not survive sign extension so needs a word immediate
+                (Operand::Register16(Register16::Si), Operand::Immediate8(0x80), vec![0x81, 0xC6 | reg, 0x80, 0x00], Operand::Immediate16(0x80))
+            ];
+
+            for (dst, src, expected, decoded_src) in cases {
+                let bytes = arithmetic(function, dst, src).unwrap();
+                assert_eq!(bytes, expected, "{opcode:?} {dst:?}, {src:?}");
+
+                let decoded = InstructionDecoder::new().decode(&Memory::<16>::populated(&bytes), 0).unwrap();
+                assert_eq!((decoded.opcode, decoded.dst, decoded.src), (opcode, Some(dst), Some(decoded_src)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_invalid() {
+        let mut assembler = Assembler::new();
+
+        assert_eq!(assembler.add(direct(0, OperandWidth::Word), memory(AddressBase::Bx, None, OperandWidth::Word)), Err(AssembleError::InvalidOperands { mnemonic: "ADD" }));
+        assert_eq!(assembler.sub(Operand::Segment(SegmentRegister::Ds), Operand::Immediate16(1)), Err(AssembleError::InvalidOperands { mnemonic: "SUB" }));
+        assert_eq!(assembler.cmp(Operand::Immediate8(1), Operand::Register8(Register8::Al)), Err(AssembleError::InvalidOperands { mnemonic: "CMP" }));
+        assert_eq!(assembler.xor(Operand::Register16(Register16::Ax), Operand::Register8(Register8::Al)), Err(AssembleError::OperandSizeMismatch { mnemonic: "XOR" }));
+        assert_eq!(assembler.and(Operand::Register8(Register8::Al), Operand::Immediate16(0x1234)), Err(AssembleError::ImmediateOutOfRange { mnemonic: "AND", value: 0x1234 }));
+
+        assert!(assembler.bytes().is_empty());
+    }
+
+    #[test]
+    fn test_mov_sequence() {
+        let mut assembler = Assembler::new();
+
+        assembler.mov(Operand::Register16(Register16::Ax), Operand::Immediate16(0xB800)).unwrap();
+        assembler.mov(Operand::Segment(SegmentRegister::Es), Operand::Register16(Register16::Ax)).unwrap();
+        assert!(assembler.mov(Operand::Register16(Register16::Ax), Operand::Register8(Register8::Al)).is_err());
+        assembler.db(&[0xF4]);
+
+        assert_eq!(assembler.position(), 6);
+        assert_eq!(assembler.bytes(), &[0xB8, 0x00, 0xB8, 0x8E, 0xC0, 0xF4]);
+
+        let decoded = InstructionDecoder::new().decode(&Memory::<8>::populated(assembler.bytes()), 5).unwrap();
+        assert_eq!(decoded, Instruction::new(Opcode::Hlt, 1));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{AssembleError, Assembler, OperandWidth};
+
+/// A displacement in the output which refers to a label, to be patched once the label's position is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct LabelReference {
+    /// Name of the referenced label.
+    label: String,
+    /// Offset of the placeholder displacement.
+    position: usize,
+    /// Width of the displacement, `Byte` for short jumps and `Word` for near jumps and calls.
+    width: OperandWidth
+}
+
+impl Assembler {
+    /// Defines the label `name` at the current output position. Defining a label which already exists moves it.
+    pub fn label(&mut self, name: &str) {
+        self.labels.insert(name.to_string(), self.position());
+    }
+
+    #[must_use]
+    /// Returns the position of the label `name`, or `None` if it has not been defined.
+    pub fn label_position(&self, name: &str) -> Option<usize> {
+        self.labels.get(name).copied()
+    }
+
+    /// Appends `opcode` followed by a placeholder displacement of the given `width` referring to the label `name`.
+    fn emit_label_reference(&mut self, opcode: u8, name: &str, width: OperandWidth) {
+        self.bytes.push(opcode);
+        self.references.push(LabelReference { label: name.to_string(), position: self.position(), width });
+        self.bytes.resize(self.position() + width.bytes(), 0);
+    }
+
+    /// Assembles `JMP SHORT name`, leaving a placeholder displacement to be patched by `resolve_labels`.
+    pub fn jmp_short_label(&mut self, name: &str) {
+        self.emit_label_reference(0xEB, name, OperandWidth::Byte);
+    }
+
+    /// Assembles `JMP NEAR name`, leaving a placeholder displacement to be patched by `resolve_labels`.
+    pub fn jmp_near_label(&mut self, name: &str) {
+        self.emit_label_reference(0xE9, name, OperandWidth::Word);
+    }
+
+    /// Assembles `JMP name`, using a short jump if `name` is already defined within reach and a near jump otherwise.
+    pub fn jmp_label(&mut self, name: &str) {
+        let short = self.label_position(name)
+            .is_some_and(|target| i8::try_from(target.cast_signed() - (self.position() + 2).cast_signed()).is_ok());
+
+        if short {
+            self.jmp_short_label(name);
+        }
+        else {
+            self.jmp_near_label(name);
+        }
+    }
+
+    /// Assembles `CALL name`, leaving a placeholder displacement to be patched by `resolve_labels`.
+    pub fn call_label(&mut self, name: &str) {
+        self.emit_label_reference(0xE8, name, OperandWidth::Word);
+    }
+
+    /// Patches every label reference with the displacement to its label.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a referenced label has not been defined, or is too far from the
+    /// reference for its displacement to be encoded. On error no references are patched, so they can be resolved
+    /// again once the missing labels have been defined.
+    pub fn resolve_labels(&mut self) -> Result<(), AssembleError> {
+        let patches = self.references.iter()
+            .map(|reference| {
+                let target = self.label_position(&reference.label)
+                    .ok_or_else(|| AssembleError::UnresolvedLabel { label: reference.label.clone() })?;
+
+                // Displacements are relative to the end of the instruction, just after the displacement
+                let distance = target.cast_signed() - (reference.position + reference.width.bytes()).cast_signed();
+                let out_of_range = |_| AssembleError::JumpOutOfRange { label: reference.label.clone(), distance };
+
+                let displacement = match reference.width {
+                    OperandWidth::Byte => vec![i8::try_from(distance).map_err(out_of_range)?.cast_unsigned()],
+                    OperandWidth::Word => i16::try_from(distance).map_err(out_of_range)?.to_le_bytes().to_vec()
+                };
+
+                Ok((reference.position, displacement))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (position, displacement) in patches {
+            self.bytes[position..position + displacement.len()].copy_from_slice(&displacement);
+        }
+
+        self.references.clear();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::Memory;
+
+    use crate::{InstructionDecoder, Opcode, Operand, Register16};
+
+    use super::*;
+
+    #[test]
+    fn test_backward_loop() {
+        let mut assembler = Assembler::new();
+
+        assembler.mov(Operand::Register16(Register16::Cx), Operand::Immediate16(10)).unwrap();
+        assembler.label("loop");
+        assembler.add(Operand::Register16(Register16::Ax), Operand::Register16(Register16::Cx)).unwrap();
+        assembler.sub(Operand::Register16(Register16::Cx), Operand::Immediate8(1)).unwrap();
+        assembler.jmp_short_label("loop");
+
+        assert_eq!(assembler.label_position("loop"), Some(3));
+        assert_eq!(assembler.resolve_labels(), Ok(()));
+
+        // The jump ends at offset 10, seven bytes past the label
+        assert_eq!(assembler.bytes(), &[0xB9, 0x0A, 0x00, 0x01, 0xC8, 0x83, 0xE9, 0x01, 0xEB, 0xF9]);
+
+        let decoded = InstructionDecoder::new().decode(&Memory::<16>::populated(assembler.bytes()), 8).unwrap();
+        assert_eq!((decoded.opcode, decoded.dst), (Opcode::Jmp, Some(Operand::Relative(-7))));
+    }
+
+    #[test]
+    fn test_forward_reference() {
+        let mut assembler = Assembler::new();
+
+        assembler.jmp_short_label("end");
+        assembler.jmp_short_label("self");
+        assembler.label("self");
+        assembler.db(&[0x90, 0x90]);
+        assembler.label("end");
+
+        assert_eq!(assembler.resolve_labels(), Ok(()));
+        assert_eq!(assembler.into_bytes(), [0xEB, 0x04, 0xEB, 0x00, 0x90, 0x90]);
+    }
+
+    #[test]
+    fn test_unresolved_label() {
+        let mut assembler = Assembler::new();
+
+        assembler.jmp_short_label("start");
+        assembler.jmp_short_label("missing");
+        assembler.label("start");
+
+        assert_eq!(assembler.resolve_labels(), Err(AssembleError::UnresolvedLabel { label: "missing".to_string() }));
+        assert_eq!(assembler.bytes(), &[0xEB, 0x00, 0xEB, 0x00]);
+
+        // Defining the missing label lets the references be resolved
+        assembler.label("missing");
+        assert_eq!(assembler.resolve_labels(), Ok(()));
+        assert_eq!(assembler.bytes(), &[0xEB, 0x02, 0xEB, 0x00]);
+    }
+
+    #[test]
+    fn test_near_references() {
+        let mut assembler = Assembler::new();
+
+        assembler.label("start");
+        assembler.call_label("function");
+        assembler.jmp_label("start");
+        assembler.jmp_label("function");
+        assembler.db(&[0x90; 0x100]);
+        assembler.label("function");
+        assembler.jmp_label("start");
+
+        assert_eq!(assembler.resolve_labels(), Ok(()));
+
+        let bytes = assembler.into_bytes();
+        assert_eq!(bytes[..8], [0xE8, 0x05, 0x01, 0xEB, 0xFB, 0xE9, 0x00, 0x01]);
+        assert_eq!(bytes[0x108..], [0xE9, 0xF5, 0xFE]);
+    }
+
+    #[test]
+    fn test_jump_out_of_range() {
+        let mut assembler = Assembler::new();
+
+        assembler.jmp_short_label("far");
+        assembler.db(&[0x90; 128]);
+        assembler.label("far");
+
+        assert_eq!(assembler.resolve_labels(), Err(AssembleError::JumpOutOfRange { label: "far".to_string(), distance: 128 }));
+
+        let mut assembler = Assembler::new();
+
+        assembler.label("back");
+        assembler.db(&[0x90; 126]);
+        assembler.jmp_short_label("back");
+
+        assert_eq!(assembler.resolve_labels(), Ok(()));
+        assert_eq!(assembler.bytes()[127], 0x80);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod encoder;
+pub use encoder::*;
+
+pub mod labels;
+
+pub mod parser;
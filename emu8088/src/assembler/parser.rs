@@ -0,0 +1,405 @@
+use crate::{AddressBase, AssembleError, Assembler, MemoryAddress, Operand, OperandWidth, Register16, Register8, SegmentRegister};
+
+/// Signature shared by the instruction methods taking a destination and source operand.
+type BinaryFn = fn(&mut Assembler, Operand, Operand) -> Result<(), AssembleError>;
+
+/// Mnemonics taking a destination and source operand.
+const BINARY: [(&str, BinaryFn); 9] = [
+    ("MOV", Assembler::mov), ("ADD", Assembler::add), ("OR", Assembler::or), ("ADC", Assembler::adc),
+    ("SBB", Assembler::sbb), ("AND", Assembler::and), ("SUB", Assembler::sub), ("XOR", Assembler::xor),
+    ("CMP", Assembler::cmp)
+];
+
+/// Mnemonics taking no operands, with their encodings.
+const IMPLIED: [(&str, u8); 11] = [
+    ("NOP", 0x90), ("HLT", 0xF4), ("RET", 0xC3), ("IRET", 0xCF), ("CLI", 0xFA), ("STI", 0xFB), ("CLC", 0xF8),
+    ("STC", 0xF9), ("CMC", 0xF5), ("CLD", 0xFC), ("STD", 0xFD)
+];
+
+/// Returns `true` if `text` can be used as a label name.
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '.')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Parses a number written in decimal, in hex with a `0x` prefix or `h` suffix, or as a quoted character, with an
+/// optional leading minus sign.
+fn parse_number(text: &str) -> Option<i32> {
+    let (negative, digits) = text.strip_prefix('-').map_or((false, text), |digits| (true, digits.trim_start()));
+
+    let value = if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        i32::from_str_radix(hex, 16).ok()?
+    }
+    else if let Some(hex) = digits.strip_suffix('h').or_else(|| digits.strip_suffix('H')) {
+        // A leading digit distinguishes numbers such as `0FFh` from identifiers
+        if !hex.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+
+        i32::from_str_radix(hex, 16).ok()?
+    }
+    else if let Some(character) = digits.strip_prefix('\'').and_then(|rest| rest.strip_suffix('\'')) {
+        let mut chars = character.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii() => c as i32,
+            _ => return None
+        }
+    }
+    else {
+        digits.parse().ok()?
+    };
+
+    Some(if negative { -value } else { value })
+}
+
+/// Returns the 16-bit two's complement encoding of `value`, if it fits in 16 bits either signed or unsigned.
+fn to_word(value: i32) -> Option<u16> {
+    u16::try_from(value).ok().or_else(|| i16::try_from(value).ok().map(i16::cast_unsigned))
+}
+
+/// Parses a number which fits in 16 bits, either signed or unsigned, returning its 16-bit two's complement encoding.
+fn parse_word(text: &str) -> Option<u16> {
+    to_word(parse_number(text)?)
+}
+
+/// Parses a register name, in any case.
+fn parse_register(text: &str) -> Option<Operand> {
+    let name = text.to_ascii_uppercase();
+
+    (0..8).map(Register16::from_index).find(|register| register.name() == name).map(Operand::Register16)
+        .or_else(|| (0..8).map(Register8::from_index).find(|register| register.name() == name).map(Operand::Register8))
+        .or_else(|| parse_segment(&name).map(Operand::Segment))
+}
+
+/// Parses a segment register name, in any case.
+fn parse_segment(text: &str) -> Option<SegmentRegister> {
+    let name = text.trim().to_ascii_uppercase();
+
+    (0..4).map(SegmentRegister::from_index).find(|register| register.name() == name)
+}
+
+/// Splits a leading `ES:` style segment override off `text`.
+fn split_segment_override(text: &str) -> Option<(Option<SegmentRegister>, &str)> {
+    match text.split_once(':') {
+        Some((segment, rest)) => Some((Some(parse_segment(segment)?), rest.trim())),
+        None => Some((None, text))
+    }
+}
+
+/// Parses the expression between the brackets of a memory operand, such as `BX+SI+4`, `BP-2` or `0x1234`.
+fn parse_address(text: &str) -> Option<MemoryAddress> {
+    let (mut bx, mut bp, mut si, mut di) = (false, false, false, false);
+    let mut displacement: Option<i32> = None;
+
+    for term in text.replace('-', "+-").split('+').map(str::trim).filter(|term| !term.is_empty()) {
+        let register = match term.to_ascii_uppercase().as_str() {
+            "BX" => &mut bx,
+            "BP" => &mut bp,
+            "SI" => &mut si,
+            "DI" => &mut di,
+            _ => {
+                displacement = Some(displacement.unwrap_or(0) + parse_number(term)?);
+                continue;
+            }
+        };
+
+        if std::mem::replace(register, true) {
+            return None;
+        }
+    }
+
+    let base = match (bx, bp, si, di) {
+        (true, false, true, false) => AddressBase::BxSi,
+        (true, false, false, true) => AddressBase::BxDi,
+        (false, true, true, false) => AddressBase::BpSi,
+        (false, true, false, true) => AddressBase::BpDi,
+        (false, false, true, false) => AddressBase::Si,
+        (false, false, false, true) => AddressBase::Di,
+        (false, true, false, false) => AddressBase::Bp,
+        (true, false, false, false) => AddressBase::Bx,
+        (false, false, false, false) if displacement.is_some() => AddressBase::Direct,
+        _ => return None
+    };
+
+    let displacement = match displacement {
+        Some(value) => Some(to_word(value)?.cast_signed()),
+        None => None
+    };
+
+    Some(MemoryAddress::new(base, displacement))
+}
+
+/// Parses a register, immediate or memory operand. Memory operands take their width from a `BYTE` or `WORD` prefix,
+/// falling back to `default_width`.
+fn parse_operand(text: &str, default_width: Option<OperandWidth>) -> Option<Operand> {
+    let upper = text.to_ascii_uppercase();
+
+    let (size, text) = if upper.starts_with("BYTE ") {
+        (Some(OperandWidth::Byte), text[5..].trim_start())
+    }
+    else if upper.starts_with("WORD ") {
+        (Some(OperandWidth::Word), text[5..].trim_start())
+    }
+    else {
+        (None, text)
+    };
+
+    let text = if text.to_ascii_uppercase().starts_with("PTR ") { text[4..].trim_start() } else { text };
+
+    if let Some((outer_segment, rest)) = text.split_once('[') {
+        let inner = rest.strip_suffix(']')?;
+
+        // The segment override may be written either outside or inside the brackets
+        let outer_segment = match outer_segment.trim().strip_suffix(':') {
+            Some(segment) => Some(parse_segment(segment)?),
+            None if outer_segment.trim().is_empty() => None,
+            None => return None
+        };
+
+        let (inner_segment, inner) = split_segment_override(inner)?;
+
+        let address = MemoryAddress { segment_override: outer_segment.or(inner_segment), ..parse_address(inner)? };
+        return Some(Operand::Memory { address, width: size.or(default_width)? });
+    }
+
+    if size.is_some() {
+        return None;
+    }
+
+    parse_register(text).or_else(|| {
+        let value = parse_word(text)?;
+
+        Some(u8::try_from(value).map_or(Operand::Immediate16(value), Operand::Immediate8))
+    })
+}
+
+/// Parses the operands of a two operand instruction, taking the width of memory operands without an explicit size
+/// from the other operand if it is a register.
+fn parse_operand_pair(dst: &str, src: &str) -> Option<(Operand, Operand)> {
+    let register_width = |text| parse_operand(text, None)
+        .filter(|operand| !matches!(operand, Operand::Immediate8(_) | Operand::Immediate16(_)))
+        .and_then(|operand| operand.width());
+
+    Some((parse_operand(dst, register_width(src))?, parse_operand(src, register_width(dst))?))
+}
+
+impl Assembler {
+    /// Assembles NASM-like source text, one instruction per line.
+    ///
+    /// Each line may start with a label ending in `:`, and anything after a `;` is a comment. The supported
+    /// instructions are `MOV`, `ADD`, `OR`, `ADC`, `SBB`, `AND`, `SUB`, `XOR` and `CMP` with any operands they can
+    /// encode, `JMP` and `CALL` to a label, `INT` and a handful of instructions without operands, along with a `DB`
+    /// directive. `JMP` takes a short jump where possible, which can be forced with `JMP SHORT` or avoided with
+    /// `JMP NEAR`.
+    ///
+    /// Immediates can be written in decimal, in hex with a `0x` prefix or `h` suffix, or as a quoted character. Memory
+    /// operands are written as `[BX+SI+4]`, with an optional segment override either before or inside the brackets,
+    /// and need a `BYTE` or `WORD` size unless the other operand is a register.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a line cannot be parsed, if an instruction cannot be encoded, or if a
+    /// label is used without being defined.
+    pub fn assemble_str(source: &str) -> Result<Vec<u8>, AssembleError> {
+        let mut assembler = Self::new();
+
+        for (index, line) in source.lines().enumerate() {
+            assembler.assemble_line(index + 1, line)?;
+        }
+
+        assembler.resolve_labels()?;
+
+        Ok(assembler.into_bytes())
+    }
+
+    /// Assembles a single line of source text, where `number` is the line number used in errors.
+    fn assemble_line(&mut self, number: usize, line: &str) -> Result<(), AssembleError> {
+        let syntax = || AssembleError::InvalidSyntax { line: number };
+
+        let mut text = line.split(';').next().unwrap_or_default().trim();
+
+        if let Some((label, rest)) = text.split_once(':').filter(|(label, _)| is_identifier(label.trim())) {
+            self.label(label.trim());
+            text = rest.trim();
+        }
+
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let mnemonic = mnemonic.to_ascii_uppercase();
+
+        let operands: Vec<&str> = operands.split(',').map(str::trim).filter(|operand| !operand.is_empty()).collect();
+
+        if let Some((_, function)) = BINARY.iter().find(|(name, _)| *name == mnemonic) {
+            let [dst, src] = operands[..] else { return Err(syntax()) };
+            let (dst, src) = parse_operand_pair(dst, src).ok_or_else(syntax)?;
+
+            return function(self, dst, src);
+        }
+
+        if let Some((_, opcode)) = IMPLIED.iter().find(|(name, _)| *name == mnemonic) {
+            if !operands.is_empty() {
+                return Err(syntax());
+            }
+
+            self.db(&[*opcode]);
+            return Ok(());
+        }
+
+        match (mnemonic.as_str(), &operands[..]) {
+            ("JMP" | "CALL", [target]) => {
+                let (kind, label) = target.split_once(char::is_whitespace).map_or(("", *target), |(kind, label)| (kind, label.trim()));
+
+                if !is_identifier(label) || parse_register(label).is_some() {
+                    return Err(syntax());
+                }
+
+                match (mnemonic.as_str(), kind.to_ascii_uppercase().as_str()) {
+                    ("JMP", "") => self.jmp_label(label),
+                    ("JMP", "SHORT") => self.jmp_short_label(label),
+                    ("JMP", "NEAR") => self.jmp_near_label(label),
+                    ("CALL", "" | "NEAR") => self.call_label(label),
+                    _ => return Err(syntax())
+                }
+            }
+            ("INT", [vector]) => {
+                let vector = parse_number(vector).and_then(|value| u8::try_from(value).ok()).ok_or_else(syntax)?;
+                self.int(vector);
+            }
+            ("DB", values) if !values.is_empty() => {
+                let bytes = values.iter()
+                    .map(|value| parse_number(value).and_then(|value| u8::try_from(value).ok().or_else(|| i8::try_from(value).ok().map(i8::cast_unsigned))))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(syntax)?;
+
+                self.db(&bytes);
+            }
+            ("JMP" | "CALL" | "INT" | "DB", _) => return Err(syntax()),
+            _ => return Err(AssembleError::UnknownMnemonic { line: number, mnemonic })
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(base: AddressBase, displacement: Option<i16>, width: OperandWidth) -> Operand {
+        Operand::Memory { address: MemoryAddress::new(base, displacement), width }
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        assert_eq!(parse_number("42"), Some(42));
+        assert_eq!(parse_number("0x1F"), Some(0x1F));
+        assert_eq!(parse_number("0B800h"), Some(0xB800));
+        assert_eq!(parse_number("-2"), Some(-2));
+        assert_eq!(parse_number("'A'"), Some(0x41));
+        assert_eq!(parse_number("FFh"), None);
+        assert_eq!(parse_number("label"), None);
+
+        assert_eq!(parse_word("-1"), Some(0xFFFF));
+        assert_eq!(parse_word("65535"), Some(0xFFFF));
+        assert_eq!(parse_word("65536"), None);
+    }
+
+    #[test]
+    fn test_parse_operands() {
+        assert_eq!(parse_operand("ax", None), Some(Operand::Register16(Register16::Ax)));
+        assert_eq!(parse_operand("Dh", None), Some(Operand::Register8(Register8::Dh)));
+        assert_eq!(parse_operand("ES", None), Some(Operand::Segment(SegmentRegister::Es)));
+        assert_eq!(parse_operand("0x10", None), Some(Operand::Immediate8(0x10)));
+        assert_eq!(parse_operand("1234h", None), Some(Operand::Immediate16(0x1234)));
+
+        assert_eq!(parse_operand("byte [bx+si+4]", None), Some(memory(AddressBase::BxSi, Some(4), OperandWidth::Byte)));
+        assert_eq!(parse_operand("WORD PTR [bp-2]", None), Some(memory(AddressBase::Bp, Some(-2), OperandWidth::Word)));
+        assert_eq!(parse_operand("[di + 2 - 4]", Some(OperandWidth::Word)), Some(memory(AddressBase::Di, Some(-2), OperandWidth::Word)));
+        assert_eq!(parse_operand("[0x0472]", Some(OperandWidth::Byte)), Some(memory(AddressBase::Direct, Some(0x0472), OperandWidth::Byte)));
+
+        let overridden = Operand::Memory { address: MemoryAddress { segment_override: Some(SegmentRegister::Es), ..MemoryAddress::new(AddressBase::Bx, None) }, width: OperandWidth::Word };
+        assert_eq!(parse_operand("es:[bx]", Some(OperandWidth::Word)), Some(overridden));
+        assert_eq!(parse_operand("word [es:bx]", None), Some(overridden));
+
+        // Memory operands need a width, and only valid register combinations can be used
+        assert_eq!(parse_operand("[bx]", None), None);
+        assert_eq!(parse_operand("[bx+bp]", Some(OperandWidth::Word)), None);
+        assert_eq!(parse_operand("[ax]", Some(OperandWidth::Word)), None);
+        assert_eq!(parse_operand("[si+si]", Some(OperandWidth::Word)), None);
+        assert_eq!(parse_operand("fs:[bx]", Some(OperandWidth::Word)), None);
+        assert_eq!(parse_operand("byte ax", None), None);
+    }
+
+    #[test]
+    fn test_assemble_str() {
+        let source = "
+            ; Print characters until a null terminator is reached
+            start:  mov ax, 0B800h
+                    mov es, ax
+                    mov si, message
+            next:   mov al, [si]        ; memory width taken from AL
+                    cmp al, 0
+                    jmp short done
+                    mov es:[di], al
+                    add di, 2
+                    add word [bx+4], -1
+                    jmp next
+            done:   call finish
+                    int 0x21
+            finish: ret
+            message: db 'H', 'i', 0
+        ";
+
+        // Labels are not immediates, so `mov si, message` is rejected
+        assert_eq!(Assembler::assemble_str(source), Err(AssembleError::InvalidSyntax { line: 5 }));
+
+        let source = source.replace("mov si, message", "mov si, 0x1234");
+
+        let mut expected = Assembler::new();
+        expected.mov(Operand::Register16(Register16::Ax), Operand::Immediate16(0xB800)).unwrap();
+        expected.mov(Operand::Segment(SegmentRegister::Es), Operand::Register16(Register16::Ax)).unwrap();
+        expected.mov(Operand::Register16(Register16::Si), Operand::Immediate16(0x1234)).unwrap();
+        expected.label("next");
+        expected.mov(Operand::Register8(Register8::Al), memory(AddressBase::Si, None, OperandWidth::Byte)).unwrap();
+        expected.cmp(Operand::Register8(Register8::Al), Operand::Immediate8(0)).unwrap();
+        expected.jmp_short_label("done");
+        expected.mov(Operand::Memory { address: MemoryAddress { segment_override: Some(SegmentRegister::Es), ..MemoryAddress::new(AddressBase::Di, None) }, width: OperandWidth::Byte }, Operand::Register8(Register8::Al)).unwrap();
+        expected.add(Operand::Register16(Register16::Di), Operand::Immediate8(2)).unwrap();
+        expected.add(memory(AddressBase::Bx, Some(4), OperandWidth::Word), Operand::Immediate16(0xFFFF)).unwrap();
+        expected.jmp_short_label("next");
+        expected.label("done");
+        expected.call_label("finish");
+        expected.int(0x21);
+        expected.label("finish");
+        expected.db(&[0xC3, b'H', b'i', 0]);
+        expected.resolve_labels().unwrap();
+
+        assert_eq!(Assembler::assemble_str(&source), Ok(expected.into_bytes()));
+    }
+
+    #[test]
+    fn test_assemble_str_jumps() {
+        assert_eq!(Assembler::assemble_str("back: nop\njmp back\njmp ahead\njmp near back\nahead: hlt"), Ok(vec![0x90, 0xEB, 0xFD, 0xE9, 0x03, 0x00, 0xE9, 0xF7, 0xFF, 0xF4]));
+        assert_eq!(Assembler::assemble_str("call far_away\nfar_away:"), Ok(vec![0xE8, 0x00, 0x00]));
+        assert_eq!(Assembler::assemble_str("jmp missing"), Err(AssembleError::UnresolvedLabel { label: "missing".to_string() }));
+    }
+
+    #[test]
+    fn test_assemble_str_errors() {
+        assert_eq!(Assembler::assemble_str("nop\nfoo ax, bx"), Err(AssembleError::UnknownMnemonic { line: 2, mnemonic: "FOO".to_string() }));
+        assert_eq!(Assembler::assemble_str("mov ax"), Err(AssembleError::InvalidSyntax { line: 1 }));
+        assert_eq!(Assembler::assemble_str("mov [bx], 1"), Err(AssembleError::InvalidSyntax { line: 1 }));
+        assert_eq!(Assembler::assemble_str("\n\nint 256"), Err(AssembleError::InvalidSyntax { line: 3 }));
+        assert_eq!(Assembler::assemble_str("hlt 1"), Err(AssembleError::InvalidSyntax { line: 1 }));
+        assert_eq!(Assembler::assemble_str("jmp ax"), Err(AssembleError::InvalidSyntax { line: 1 }));
+        assert_eq!(Assembler::assemble_str("jmp far label"), Err(AssembleError::InvalidSyntax { line: 1 }));
+        assert_eq!(Assembler::assemble_str("mov ax, bl"), Err(AssembleError::OperandSizeMismatch { mnemonic: "MOV" }));
+        assert_eq!(Assembler::assemble_str("db 300"), Err(AssembleError::InvalidSyntax { line: 1 }));
+    }
+}
@@ -0,0 +1,120 @@
+use mem::BusDevice;
+
+use crate::{Cpu, CpuFault, Operand, OperandWidth, SegmentedAddress};
+
+impl Cpu {
+    /// Reads the byte at `address`.
+    pub(super) fn read_byte(&self, address: SegmentedAddress) -> Result<u8, CpuFault> {
+        Ok(self.memory.read(address.to_linear())?)
+    }
+
+    /// Reads the little endian word at `address`. At offset `FFFFh` the high byte is read from the start of the
+    /// segment.
+    pub(super) fn read_word(&self, address: SegmentedAddress) -> Result<u16, CpuFault> {
+        let low = self.read_byte(address)?;
+        let high = self.read_byte(address.wrapping_offset_add(1))?;
+
+        Ok(u16::from_le_bytes([low, high]))
+    }
+
+    /// Writes `value` to the byte at `address`.
+    pub(super) fn write_byte(&mut self, address: SegmentedAddress, value: u8) -> Result<(), CpuFault> {
+        Ok(self.memory.write(address.to_linear(), value)?)
+    }
+
+    /// Writes `value` as a little endian word at `address`. At offset `FFFFh` the high byte is written to the start of
+    /// the segment.
+    pub(super) fn write_word(&mut self, address: SegmentedAddress, value: u16) -> Result<(), CpuFault> {
+        let [low, high] = value.to_le_bytes();
+
+        self.write_byte(address, low)?;
+        self.write_byte(address.wrapping_offset_add(1), high)
+    }
+
+    /// Pushes `value` onto the stack, decrementing `SP` by two and writing the word at `SS:SP`. `SP` wraps around
+    /// within the stack segment unless a stack limit is set, and is left unchanged if the push faults.
+    pub(super) fn push_word(&mut self, value: u16) -> Result<(), CpuFault> {
+        if let Some(limit) = self.stack_limit {
+            if self.registers.sp.checked_sub(2).is_none_or(|sp| sp < limit) {
+                return Err(CpuFault::StackOverflow);
+            }
+        }
+
+        let sp = self.registers.sp.wrapping_sub(2);
+
+        self.write_word(SegmentedAddress::new(self.registers.ss, sp), value)?;
+        self.registers.sp = sp;
+
+        Ok(())
+    }
+
+    /// Pops a word from the stack, reading it from `SS:SP` and incrementing `SP` by two. `SP` wraps around within the
+    /// stack segment, and is left unchanged if the read fails.
+    pub(super) fn pop_word(&mut self) -> Result<u16, CpuFault> {
+        let value = self.read_word(SegmentedAddress::new(self.registers.ss, self.registers.sp))?;
+        self.registers.sp = self.registers.sp.wrapping_add(2);
+
+        Ok(value)
+    }
+
+    /// Reads the segment and offset of a far pointer, given either directly in the instruction or as a memory operand
+    /// holding the offset followed by the segment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if given a register, immediate or relative operand.
+    pub(super) fn read_far_pointer(&self, operand: Operand) -> Result<(u16, u16), CpuFault> {
+        match operand {
+            Operand::FarPointer { segment, offset } => Ok((segment, offset)),
+            Operand::Memory { address, .. } => {
+                let address = address.resolve(&self.registers);
+                let offset = self.read_word(address)?;
+                let segment = self.read_word(address.wrapping_offset_add(2))?;
+
+                Ok((segment, offset))
+            }
+            _ => panic!("Operand {operand:?} is not a far pointer")
+        }
+    }
+
+    /// Reads the value of a register, immediate or memory operand, with byte operands zero extended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if given a relative or far pointer operand, which only appear as the targets of jumps and calls.
+    pub(super) fn read_operand(&self, operand: Operand) -> Result<u16, CpuFault> {
+        match operand {
+            Operand::Register8(register) => Ok(u16::from(self.registers.get8(register))),
+            Operand::Register16(register) => Ok(self.registers.get16(register)),
+            Operand::Segment(register) => Ok(self.registers.segment(register)),
+            Operand::Immediate8(value) => Ok(u16::from(value)),
+            Operand::Immediate16(value) => Ok(value),
+            Operand::Memory { address, width: OperandWidth::Byte } => Ok(u16::from(self.read_byte(address.resolve(&self.registers))?)),
+            Operand::Memory { address, width: OperandWidth::Word } => self.read_word(address.resolve(&self.registers)),
+            Operand::Relative(_) | Operand::FarPointer { .. } => panic!("Operand {operand:?} cannot be read as a value")
+        }
+    }
+
+    /// Writes `value` to a register or memory operand, truncating it to the low byte for byte operands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if given an immediate, relative or far pointer operand, none of which the decoder produces as a
+    /// destination.
+    pub(super) fn write_operand(&mut self, operand: Operand, value: u16) -> Result<(), CpuFault> {
+        let [low, _] = value.to_le_bytes();
+
+        match operand {
+            Operand::Register8(register) => self.registers.set8(register, low),
+            Operand::Register16(register) => self.registers.set16(register, value),
+            Operand::Segment(register) => self.registers.set_segment(register, value),
+            Operand::Memory { address, width: OperandWidth::Byte } => self.write_byte(address.resolve(&self.registers), low)?,
+            Operand::Memory { address, width: OperandWidth::Word } => self.write_word(address.resolve(&self.registers), value)?,
+            Operand::Immediate8(_) | Operand::Immediate16(_) | Operand::Relative(_) | Operand::FarPointer { .. } => {
+                panic!("Operand {operand:?} cannot be written")
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt::Display;
+
+/// A `segment:offset` pair addressing the 20-bit physical address space of the 8086.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SegmentedAddress {
+    pub segment: u16,
+    pub offset: u16
+}
+
+impl SegmentedAddress {
+    #[must_use]
+    /// Constructs a new `SegmentedAddress` from a `segment` and `offset`.
+    pub const fn new(segment: u16, offset: u16) -> Self {
+        Self { segment, offset }
+    }
+
+    #[must_use]
+    /// Computes the physical address `segment * 16 + offset`.
+    ///
+    /// Note that this can exceed `0xFFFFF`, any wrapping to 20 bits is left to the bus.
+    pub const fn to_linear(self) -> usize {
+        (self.segment as usize) * 16 + self.offset as usize
+    }
+
+    #[must_use]
+    /// Adds `delta` to the offset, wrapping within the 64 KiB segment rather than carrying into the segment.
+    pub const fn wrapping_offset_add(self, delta: i16) -> Self {
+        Self {
+            segment: self.segment,
+            offset: self.offset.wrapping_add_signed(delta)
+        }
+    }
+}
+
+impl Display for SegmentedAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04X}:{:04X}", self.segment, self.offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segmented_address_to_linear() {
+        assert_eq!(SegmentedAddress::new(0, 0).to_linear(), 0);
+        assert_eq!(SegmentedAddress::new(0x07C0, 0x0000).to_linear(), 0x7C00);
+        assert_eq!(SegmentedAddress::new(0x0000, 0x7C00).to_linear(), 0x7C00);
+        assert_eq!(SegmentedAddress::new(0x1234, 0x5678).to_linear(), 0x179B8);
+        assert_eq!(SegmentedAddress::new(0xFFFF, 0x0000).to_linear(), 0xFFFF0);
+        assert_eq!(SegmentedAddress::new(0xFFFF, 0xFFFF).to_linear(), 0x10_FFEF);
+    }
+
+    #[test]
+    fn test_segmented_address_wrapping_offset_add() {
+        assert_eq!(SegmentedAddress::new(0x1000, 0x0010).wrapping_offset_add(0x10), SegmentedAddress::new(0x1000, 0x0020));
+        assert_eq!(SegmentedAddress::new(0x1000, 0x0010).wrapping_offset_add(-0x10), SegmentedAddress::new(0x1000, 0x0000));
+        assert_eq!(SegmentedAddress::new(0x1000, 0xFFFF).wrapping_offset_add(1), SegmentedAddress::new(0x1000, 0x0000));
+        assert_eq!(SegmentedAddress::new(0x1000, 0x0000).wrapping_offset_add(-1), SegmentedAddress::new(0x1000, 0xFFFF));
+        assert_eq!(SegmentedAddress::new(0x1000, 0x8000).wrapping_offset_add(i16::MAX), SegmentedAddress::new(0x1000, 0xFFFF));
+        assert_eq!(SegmentedAddress::new(0x1000, 0x7FFF).wrapping_offset_add(i16::MIN), SegmentedAddress::new(0x1000, 0xFFFF));
+    }
+
+    #[test]
+    fn test_segmented_address_display() {
+        assert_eq!(SegmentedAddress::new(0xF000, 0xFFF0).to_string(), "F000:FFF0");
+        assert_eq!(SegmentedAddress::new(0x1, 0x2).to_string(), "0001:0002");
+    }
+}
@@ -0,0 +1,592 @@
+use crate::{Flags, Opcode, OperandWidth};
+
+/// Returns the most significant bit of an operand of the given width.
+const fn sign_bit(width: OperandWidth) -> u16 {
+    match width {
+        OperandWidth::Byte => 0x80,
+        OperandWidth::Word => 0x8000
+    }
+}
+
+/// Truncates `value` to the given width.
+const fn truncate(value: u32, width: OperandWidth) -> u16 {
+    let [low, high, _, _] = value.to_le_bytes();
+
+    match width {
+        OperandWidth::Byte => low as u16,
+        OperandWidth::Word => u16::from_le_bytes([low, high])
+    }
+}
+
+/// Sets `SF`, `ZF` and `PF` from a result of the given width. `PF` only ever reflects the low byte.
+pub(super) const fn set_result_flags(flags: &mut Flags, width: OperandWidth, result: u16) {
+    let [low, _] = result.to_le_bytes();
+
+    flags.set_sign(result & sign_bit(width) != 0);
+    flags.set_zero(result == 0);
+    flags.set_parity(low.count_ones() % 2 == 0);
+}
+
+/// Computes `a + b + carry` at the given width, as `ADD` and `ADC` do, setting all six arithmetic flags.
+pub(super) const fn add(flags: &mut Flags, width: OperandWidth, a: u16, b: u16, carry: bool) -> u16 {
+    let full = a as u32 + b as u32 + carry as u32;
+    let result = truncate(full, width);
+
+    flags.set_carry(full > truncate(u32::MAX, width) as u32);
+    flags.set_auxiliary((a ^ b ^ result) & 0x10 != 0);
+    // Both operands have the same sign, which differs from that of the result
+    flags.set_overflow((a ^ result) & (b ^ result) & sign_bit(width) != 0);
+    set_result_flags(flags, width, result);
+
+    result
+}
+
+/// Computes `a - b - borrow` at the given width, as `SUB`, `SBB` and `CMP` do, setting all six arithmetic flags.
+pub(super) const fn sub(flags: &mut Flags, width: OperandWidth, a: u16, b: u16, borrow: bool) -> u16 {
+    let result = truncate((a as u32).wrapping_sub(b as u32).wrapping_sub(borrow as u32), width);
+
+    flags.set_carry((a as u32) < b as u32 + borrow as u32);
+    flags.set_auxiliary((a ^ b ^ result) & 0x10 != 0);
+    // The operands have different signs, and the result has the sign of the subtrahend
+    flags.set_overflow((a ^ b) & (a ^ result) & sign_bit(width) != 0);
+    set_result_flags(flags, width, result);
+
+    result
+}
+
+/// Computes the unsigned product `a * b` at the given width, as `MUL` does, returning the double width result. `CF` and
+/// `OF` are set if the high half of the result is non-zero, the other arithmetic flags are undefined and left
+/// unchanged.
+pub(super) const fn mul(flags: &mut Flags, width: OperandWidth, a: u16, b: u16) -> u32 {
+    let (a, b) = (truncate(a as u32, width), truncate(b as u32, width));
+    let product = a as u32 * b as u32;
+    let extended = product > truncate(u32::MAX, width) as u32;
+
+    flags.set_carry(extended);
+    flags.set_overflow(extended);
+
+    product
+}
+
+/// Computes the signed product `a * b` at the given width, as `IMUL` does, returning the double width result. `CF` and
+/// `OF` are set if the high half of the result is not just the sign extension of the low half, the other arithmetic
+/// flags are undefined and left unchanged.
+pub(super) fn imul(flags: &mut Flags, width: OperandWidth, a: u16, b: u16) -> u32 {
+    let sign_extend = |value: u16| match width {
+        OperandWidth::Byte => i32::from(value.to_le_bytes()[0].cast_signed()),
+        OperandWidth::Word => i32::from(value.cast_signed())
+    };
+
+    let product = sign_extend(a) * sign_extend(b);
+    let extended = match width {
+        OperandWidth::Byte => i8::try_from(product).is_err(),
+        OperandWidth::Word => i16::try_from(product).is_err()
+    };
+
+    flags.set_carry(extended);
+    flags.set_overflow(extended);
+
+    match width {
+        OperandWidth::Byte => product.cast_unsigned() & 0xFFFF,
+        OperandWidth::Word => product.cast_unsigned()
+    }
+}
+
+/// Divides the double width `dividend` by `divisor` at the given width, as `DIV` does, returning the quotient and
+/// remainder, or `None` if the divisor is zero or the quotient does not fit in the width. The arithmetic flags are
+/// undefined and left unchanged, so no flags are taken.
+pub(super) fn div(width: OperandWidth, dividend: u32, divisor: u16) -> Option<(u16, u16)> {
+    let divisor = u32::from(truncate(u32::from(divisor), width));
+    let quotient = dividend.checked_div(divisor)?;
+
+    if quotient > u32::from(truncate(u32::MAX, width)) {
+        return None;
+    }
+
+    Some((truncate(quotient, width), truncate(dividend % divisor, width)))
+}
+
+/// Divides the double width `dividend` by `divisor` at the given width treating both as signed, as `IDIV` does,
+/// returning the quotient and remainder, or `None` if the divisor is zero or the quotient does not fit in the width.
+///
+/// The quotient is rounded towards zero and the remainder has the sign of the dividend. As on the 8086 (unlike later
+/// processors) the most negative value of the width is not an allowed quotient, so the allowed range is `-127..=127`
+/// for bytes and `-32767..=32767` for words.
+pub(super) fn idiv(width: OperandWidth, dividend: u32, divisor: u16) -> Option<(u16, u16)> {
+    let (dividend, divisor) = match width {
+        OperandWidth::Byte => (i32::from(truncate(dividend, OperandWidth::Word).cast_signed()), i32::from(divisor.to_le_bytes()[0].cast_signed())),
+        OperandWidth::Word => (dividend.cast_signed(), i32::from(divisor.cast_signed()))
+    };
+
+    let quotient = dividend.checked_div(divisor)?;
+    let limit = i32::from(sign_bit(width)) - 1;
+
+    if !(-limit..=limit).contains(&quotient) {
+        return None;
+    }
+
+    let remainder = dividend % divisor;
+
+    Some((truncate(quotient.cast_unsigned(), width), truncate(remainder.cast_unsigned(), width)))
+}
+
+/// Shifts or rotates `value` by `count` bits at the given width, for any of `ROL`, `ROR`, `RCL`, `RCR`, `SHL`, `SHR` and
+/// `SAR`. The count is not masked, as on the 8086.
+///
+/// A count of zero changes neither the value nor the flags. Otherwise `CF` receives the last bit shifted or rotated
+/// out, and the shifts also set `SF`, `ZF` and `PF` from the result. `OF` is only defined for single bit counts, and is
+/// left unchanged for larger counts. `AF` is undefined and left unchanged.
+///
+/// # Panics
+///
+/// Panics if `operation` is not a shift or rotate.
+pub(super) fn shift(flags: &mut Flags, width: OperandWidth, operation: Opcode, value: u16, count: u8) -> u16 {
+    if count == 0 {
+        return value;
+    }
+
+    let sign = sign_bit(width);
+    let mut result = truncate(u32::from(value), width);
+    let mut carry = flags.carry();
+
+    for _ in 0..count {
+        let (high, low) = (result & sign != 0, result & 1 != 0);
+
+        (result, carry) = match operation {
+            Opcode::Rol => (result << 1 | u16::from(high), high),
+            Opcode::Ror => (result >> 1 | if low { sign } else { 0 }, low),
+            Opcode::Rcl => (result << 1 | u16::from(carry), high),
+            Opcode::Rcr => (result >> 1 | if carry { sign } else { 0 }, low),
+            Opcode::Shl => (result << 1, high),
+            Opcode::Shr => (result >> 1, low),
+            Opcode::Sar => (result >> 1 | result & sign, low),
+            _ => panic!("{operation:?} is not a shift or rotate")
+        };
+
+        result = truncate(u32::from(result), width);
+    }
+
+    let (high, next) = (result & sign != 0, result & sign >> 1 != 0);
+    flags.set_carry(carry);
+
+    if count == 1 {
+        flags.set_overflow(match operation {
+            Opcode::Rol | Opcode::Rcl | Opcode::Shl => high != carry,
+            Opcode::Ror | Opcode::Rcr => high != next,
+            // The sign bit of the original value, which a single bit shift has just moved down
+            Opcode::Shr => next,
+            _ => false
+        });
+    }
+
+    if matches!(operation, Opcode::Shl | Opcode::Shr | Opcode::Sar) {
+        set_result_flags(flags, width, result);
+    }
+
+    result
+}
+
+/// Adjusts `al` after an addition of two packed BCD bytes, as `DAA` does, setting `AF` and `CF` if the low or high
+/// digit needed adjusting and `SF`, `ZF` and `PF` from the result. `OF` is undefined and left unchanged.
+pub(super) const fn daa(flags: &mut Flags, al: u8) -> u8 {
+    let (mut result, carry) = (al, flags.carry());
+
+    flags.set_auxiliary(al & 0x0F > 9 || flags.auxiliary());
+    if flags.auxiliary() {
+        result = result.wrapping_add(0x06);
+    }
+
+    flags.set_carry(al > 0x99 || carry);
+    if flags.carry() {
+        result = result.wrapping_add(0x60);
+    }
+
+    set_result_flags(flags, OperandWidth::Byte, result as u16);
+
+    result
+}
+
+/// Adjusts `al` after a subtraction of two packed BCD bytes, as `DAS` does, setting `AF` and `CF` if the low or high
+/// digit needed adjusting and `SF`, `ZF` and `PF` from the result. `OF` is undefined and left unchanged.
+pub(super) const fn das(flags: &mut Flags, al: u8) -> u8 {
+    let (mut result, carry) = (al, flags.carry());
+
+    flags.set_auxiliary(al & 0x0F > 9 || flags.auxiliary());
+    if flags.auxiliary() {
+        // A borrow out of the low digit adjustment also sets the carry
+        let (adjusted, borrow) = result.overflowing_sub(0x06);
+        result = adjusted;
+        flags.set_carry(carry || borrow);
+    }
+
+    if al > 0x99 || carry {
+        result = result.wrapping_sub(0x60);
+        flags.set_carry(true);
+    }
+
+    set_result_flags(flags, OperandWidth::Byte, result as u16);
+
+    result
+}
+
+/// Adjusts `ax` after an addition of two unpacked BCD digits in `AL`, as `AAA` does, carrying into `AH` and setting
+/// `AF` and `CF` if the digit needed adjusting. On the 8086 the adjustment of `AL` never carries into `AH` by itself.
+/// The other arithmetic flags are undefined and left unchanged.
+pub(super) const fn aaa(flags: &mut Flags, ax: u16) -> u16 {
+    let [mut al, mut ah] = ax.to_le_bytes();
+    let adjust = al & 0x0F > 9 || flags.auxiliary();
+
+    if adjust {
+        al = al.wrapping_add(0x06);
+        ah = ah.wrapping_add(1);
+    }
+
+    flags.set_auxiliary(adjust);
+    flags.set_carry(adjust);
+
+    u16::from_le_bytes([al & 0x0F, ah])
+}
+
+/// Adjusts `ax` after a subtraction of two unpacked BCD digits in `AL`, as `AAS` does, borrowing from `AH` and setting
+/// `AF` and `CF` if the digit needed adjusting. The other arithmetic flags are undefined and left unchanged.
+pub(super) const fn aas(flags: &mut Flags, ax: u16) -> u16 {
+    let [mut al, mut ah] = ax.to_le_bytes();
+    let adjust = al & 0x0F > 9 || flags.auxiliary();
+
+    if adjust {
+        al = al.wrapping_sub(0x06);
+        ah = ah.wrapping_sub(1);
+    }
+
+    flags.set_auxiliary(adjust);
+    flags.set_carry(adjust);
+
+    u16::from_le_bytes([al & 0x0F, ah])
+}
+
+/// Splits the binary value `al` into the digits of the given `base`, as `AAM` does after multiplying two unpacked BCD
+/// digits, returning the high digit in `AH` and the low digit in `AL`. `SF`, `ZF` and `PF` are set from `AL`, the other
+/// arithmetic flags are undefined and left unchanged. Returns `None` for a zero base, which raises a divide error.
+pub(super) const fn aam(flags: &mut Flags, al: u8, base: u8) -> Option<u16> {
+    if base == 0 {
+        return None;
+    }
+
+    let (ah, al) = (al / base, al % base);
+    set_result_flags(flags, OperandWidth::Byte, al as u16);
+
+    Some(u16::from_le_bytes([al, ah]))
+}
+
+/// Combines the digits of the given `base` in `AH` and `AL` into a binary value in `AL`, clearing `AH`, as `AAD` does
+/// before dividing. `SF`, `ZF` and `PF` are set from `AL`, the other arithmetic flags are undefined and left
+/// unchanged.
+pub(super) const fn aad(flags: &mut Flags, ax: u16, base: u8) -> u16 {
+    let [al, ah] = ax.to_le_bytes();
+    let result = al.wrapping_add(ah.wrapping_mul(base));
+
+    set_result_flags(flags, OperandWidth::Byte, result as u16);
+
+    result as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the arithmetic flags as a string of the set flags, in `OSZAPC` order, to keep expectations readable.
+    fn arithmetic_flags(flags: Flags) -> String {
+        [(flags.overflow(), 'O'), (flags.sign(), 'S'), (flags.zero(), 'Z'), (flags.auxiliary(), 'A'), (flags.parity(), 'P'), (flags.carry(), 'C')]
+            .into_iter()
+            .filter_map(|(set, name)| set.then_some(name))
+            .collect()
+    }
+
+    fn run(operation: fn(&mut Flags, OperandWidth, u16, u16, bool) -> u16, width: OperandWidth, a: u16, b: u16, carry: bool) -> (u16, String) {
+        // Start from every flag set, so that flags which should be cleared are checked as well
+        let mut flags = Flags::from_u16(0xFFFF);
+        let result = operation(&mut flags, width, a, b, carry);
+
+        (result, arithmetic_flags(flags))
+    }
+
+    #[test]
+    fn test_alu_add_word() {
+        let cases = [
+            (0x7FFF, 0x0001, false, 0x8000, "OSAP"),
+            (0xFFFF, 0x0001, false, 0x0000, "ZAPC"),
+            (0x8000, 0x8000, false, 0x0000, "OZPC"),
+            (0x1234, 0x1111, false, 0x2345, ""),
+            (0x000F, 0x0001, false, 0x0010, "A"),
+            (0xFFFF, 0xFFFF, true, 0xFFFF, "SAPC"),
+            (0x7FFF, 0x0000, true, 0x8000, "OSAP"),
+            (0x0000, 0x0000, false, 0x0000, "ZP")
+        ];
+
+        for (a, b, carry, result, flags) in cases {
+            assert_eq!(run(add, OperandWidth::Word, a, b, carry), (result, flags.to_owned()), "{a:#06X} + {b:#06X} + {carry}");
+        }
+    }
+
+    #[test]
+    fn test_alu_add_byte() {
+        let cases = [
+            (0x7F, 0x01, false, 0x80, "OSA"),
+            (0xFF, 0x01, false, 0x00, "ZAPC"),
+            (0x80, 0xFF, false, 0x7F, "OC"),
+            (0x08, 0x08, false, 0x10, "A"),
+            (0x0F, 0x00, true, 0x10, "A"),
+            (0x01, 0x02, false, 0x03, "P"),
+            (0x40, 0x3F, true, 0x80, "OSA")
+        ];
+
+        for (a, b, carry, result, flags) in cases {
+            assert_eq!(run(add, OperandWidth::Byte, a, b, carry), (result, flags.to_owned()), "{a:#04X} + {b:#04X} + {carry}");
+        }
+    }
+
+    #[test]
+    fn test_alu_sub_word() {
+        let cases = [
+            (0x8000, 0x0001, false, 0x7FFF, "OAP"),
+            (0x0000, 0x0001, false, 0xFFFF, "SAPC"),
+            (0x1234, 0x1234, false, 0x0000, "ZP"),
+            (0x0010, 0x0001, false, 0x000F, "AP"),
+            (0x7FFF, 0xFFFF, false, 0x8000, "OSPC"),
+            (0x0000, 0x0000, true, 0xFFFF, "SAPC"),
+            (0x0005, 0x0003, true, 0x0001, "")
+        ];
+
+        for (a, b, borrow, result, flags) in cases {
+            assert_eq!(run(sub, OperandWidth::Word, a, b, borrow), (result, flags.to_owned()), "{a:#06X} - {b:#06X} - {borrow}");
+        }
+    }
+
+    #[test]
+    fn test_alu_sub_byte() {
+        let cases = [
+            (0x80, 0x01, false, 0x7F, "OA"),
+            (0x00, 0x01, false, 0xFF, "SAPC"),
+            (0x7F, 0x80, false, 0xFF, "OSPC"),
+            (0x10, 0x10, true, 0xFF, "SAPC"),
+            (0x03, 0x02, false, 0x01, "")
+        ];
+
+        for (a, b, borrow, result, flags) in cases {
+            assert_eq!(run(sub, OperandWidth::Byte, a, b, borrow), (result, flags.to_owned()), "{a:#04X} - {b:#04X} - {borrow}");
+        }
+    }
+
+    #[test]
+    fn test_alu_mul() {
+        let cases = [
+            (OperandWidth::Byte, 0x00, 0x00, 0x0000, false),
+            (OperandWidth::Byte, 0xFF, 0xFF, 0xFE01, true),
+            (OperandWidth::Byte, 0x10, 0x0F, 0x00F0, false),
+            (OperandWidth::Word, 0x0000, 0x0000, 0x0000_0000, false),
+            (OperandWidth::Word, 0x8000, 0x8000, 0x4000_0000, true),
+            (OperandWidth::Word, 0xFFFF, 0xFFFF, 0xFFFE_0001, true),
+            (OperandWidth::Word, 0x0100, 0x00FF, 0x0000_FF00, false)
+        ];
+
+        for (width, a, b, product, extended) in cases {
+            let mut flags = Flags::from_u16(Flags::ZERO | Flags::AUXILIARY);
+
+            assert_eq!(mul(&mut flags, width, a, b), product, "{a:#06X} * {b:#06X}");
+            assert_eq!((flags.carry(), flags.overflow()), (extended, extended), "{a:#06X} * {b:#06X}");
+            // The undefined flags are left alone
+            assert!(flags.zero() && flags.auxiliary());
+        }
+    }
+
+    #[test]
+    fn test_alu_imul() {
+        let cases = [
+            (OperandWidth::Byte, 0x00, 0x00, 0x0000, false),
+            (OperandWidth::Byte, 0xFF, 0xFF, 0x0001, false),
+            (OperandWidth::Byte, 0x80, 0xFF, 0x0080, true),
+            (OperandWidth::Byte, 0xF0, 0x08, 0xFF80, false),
+            (OperandWidth::Byte, 0x7F, 0x7F, 0x3F01, true),
+            (OperandWidth::Word, 0x0000, 0x0000, 0x0000_0000, false),
+            (OperandWidth::Word, 0x8000, 0x8000, 0x4000_0000, true),
+            (OperandWidth::Word, 0xFFFF, 0x8000, 0x0000_8000, true),
+            (OperandWidth::Word, 0xFFFE, 0x0003, 0xFFFF_FFFA, false)
+        ];
+
+        for (width, a, b, product, extended) in cases {
+            let mut flags = Flags::new();
+
+            assert_eq!(imul(&mut flags, width, a, b), product, "{a:#06X} * {b:#06X}");
+            assert_eq!((flags.carry(), flags.overflow()), (extended, extended), "{a:#06X} * {b:#06X}");
+        }
+    }
+
+    #[test]
+    fn test_alu_div() {
+        assert_eq!(div(OperandWidth::Byte, 0x0064, 0x07), Some((0x0E, 0x02)));
+        assert_eq!(div(OperandWidth::Byte, 0x00FF, 0x01), Some((0xFF, 0x00)));
+        assert_eq!(div(OperandWidth::Byte, 0x0100, 0x01), None);
+        assert_eq!(div(OperandWidth::Byte, 0x1234, 0x00), None);
+        // Only the low byte of the divisor is used
+        assert_eq!(div(OperandWidth::Byte, 0x0010, 0xFF02), Some((0x08, 0x00)));
+
+        assert_eq!(div(OperandWidth::Word, 0x0001_0000, 0x0002), Some((0x8000, 0x0000)));
+        assert_eq!(div(OperandWidth::Word, 0xFFFE_FFFF, 0xFFFF), Some((0xFFFF, 0xFFFE)));
+        assert_eq!(div(OperandWidth::Word, 0xFFFF_0000, 0xFFFF), None);
+        assert_eq!(div(OperandWidth::Word, 0x0000_0000, 0x0000), None);
+    }
+
+    #[test]
+    fn test_alu_idiv() {
+        // -7 / 2 rounds towards zero, and the remainder takes the sign of the dividend
+        assert_eq!(idiv(OperandWidth::Byte, 0xFFF9, 0x02), Some((0xFD, 0xFF)));
+        assert_eq!(idiv(OperandWidth::Byte, 0x0007, 0xFE), Some((0xFD, 0x01)));
+        assert_eq!(idiv(OperandWidth::Byte, 0xFF81, 0x01), Some((0x81, 0x00)));
+        assert_eq!(idiv(OperandWidth::Byte, 0xFF80, 0x01), None);
+        assert_eq!(idiv(OperandWidth::Byte, 0x8000, 0xFF), None);
+        assert_eq!(idiv(OperandWidth::Byte, 0x0005, 0x00), None);
+
+        assert_eq!(idiv(OperandWidth::Word, 0xFFFF_8000, 0xFFFF), None);
+        assert_eq!(idiv(OperandWidth::Word, 0x0000_8000, 0xFFFF), None);
+        assert_eq!(idiv(OperandWidth::Word, 0xFFFF_8001, 0xFFFF), Some((0x7FFF, 0x0000)));
+        assert_eq!(idiv(OperandWidth::Word, 0xFFFF_FFF9, 0x0002), Some((0xFFFD, 0xFFFF)));
+        assert_eq!(idiv(OperandWidth::Word, 0x8000_0000, 0x0001), None);
+    }
+
+    /// Runs a shift or rotate with `CF` initially set as given, returning the result and the `OSZPC` flags.
+    fn run_shift(operation: Opcode, width: OperandWidth, value: u16, count: u8, carry: bool) -> (u16, String) {
+        let mut flags = Flags::new();
+        flags.set_carry(carry);
+
+        let result = shift(&mut flags, width, operation, value, count);
+
+        (result, arithmetic_flags(flags).replace('A', ""))
+    }
+
+    #[test]
+    fn test_alu_shift_by_zero() {
+        for operation in [Opcode::Rol, Opcode::Ror, Opcode::Rcl, Opcode::Rcr, Opcode::Shl, Opcode::Shr, Opcode::Sar] {
+            let mut flags = Flags::from_u16(Flags::CARRY | Flags::OVERFLOW | Flags::ZERO);
+
+            assert_eq!(shift(&mut flags, OperandWidth::Byte, operation, 0x81, 0), 0x81);
+            assert_eq!(flags, Flags::from_u16(Flags::CARRY | Flags::OVERFLOW | Flags::ZERO), "{operation:?}");
+        }
+    }
+
+    #[test]
+    fn test_alu_shift_by_one() {
+        let cases = [
+            (Opcode::Shl, OperandWidth::Byte, 0x81, false, 0x02, "OC"),
+            (Opcode::Shl, OperandWidth::Byte, 0x40, false, 0x80, "OS"),
+            (Opcode::Shl, OperandWidth::Word, 0xC000, false, 0x8000, "SPC"),
+            (Opcode::Shr, OperandWidth::Byte, 0x81, false, 0x40, "OC"),
+            (Opcode::Shr, OperandWidth::Word, 0x0001, false, 0x0000, "ZPC"),
+            (Opcode::Sar, OperandWidth::Byte, 0x81, false, 0xC0, "SPC"),
+            (Opcode::Sar, OperandWidth::Word, 0x8000, false, 0xC000, "SP"),
+            (Opcode::Rol, OperandWidth::Byte, 0x81, false, 0x03, "OC"),
+            (Opcode::Rol, OperandWidth::Word, 0x4000, true, 0x8000, "O"),
+            (Opcode::Ror, OperandWidth::Byte, 0x01, false, 0x80, "OC"),
+            (Opcode::Ror, OperandWidth::Word, 0x0002, true, 0x0001, ""),
+            (Opcode::Rcl, OperandWidth::Byte, 0x80, false, 0x00, "OC"),
+            (Opcode::Rcl, OperandWidth::Byte, 0x40, true, 0x81, "O"),
+            (Opcode::Rcr, OperandWidth::Byte, 0x01, false, 0x00, "C"),
+            (Opcode::Rcr, OperandWidth::Word, 0x0000, true, 0x8000, "O")
+        ];
+
+        for (operation, width, value, carry, result, flags) in cases {
+            assert_eq!(run_shift(operation, width, value, 1, carry), (result, flags.to_owned()), "{operation:?} {value:#06X}");
+        }
+    }
+
+    #[test]
+    fn test_alu_shift_by_count() {
+        // `OF` is left unchanged (clear here) for counts above one
+        let cases = [
+            (Opcode::Shl, OperandWidth::Byte, 0x81, 4, 0x10, ""),
+            (Opcode::Shl, OperandWidth::Word, 0x1234, 16, 0x0000, "ZP"),
+            (Opcode::Shl, OperandWidth::Word, 0xFFFF, 17, 0x0000, "ZP"),
+            (Opcode::Shr, OperandWidth::Word, 0x8431, 5, 0x0421, "PC"),
+            (Opcode::Sar, OperandWidth::Byte, 0x80, 7, 0xFF, "SP"),
+            (Opcode::Sar, OperandWidth::Word, 0x8000, 200, 0xFFFF, "SPC"),
+            (Opcode::Rol, OperandWidth::Byte, 0x12, 4, 0x21, "C"),
+            (Opcode::Rol, OperandWidth::Word, 0x1234, 16, 0x1234, ""),
+            (Opcode::Ror, OperandWidth::Word, 0x1234, 4, 0x4123, ""),
+            // A 9 bit rotate of a byte through the carry returns to where it started
+            (Opcode::Rcl, OperandWidth::Byte, 0x5A, 9, 0x5A, ""),
+            (Opcode::Rcr, OperandWidth::Byte, 0x01, 2, 0x80, "")
+        ];
+
+        for (operation, width, value, count, result, flags) in cases {
+            assert_eq!(run_shift(operation, width, value, count, false), (result, flags.to_owned()), "{operation:?} {value:#06X}, {count}");
+        }
+    }
+
+    #[test]
+    fn test_alu_daa_das() {
+        // The examples of the Intel manual: 79h + 35h gives AEh with both flags clear, adjusted to 14h carrying
+        let mut flags = Flags::new();
+        assert_eq!(daa(&mut flags, 0xAE), 0x14);
+        assert_eq!(arithmetic_flags(flags), "APC");
+
+        // 35h - 47h gives EEh with both flags set, adjusted to 88h borrowing
+        flags.set_auxiliary(true);
+        flags.set_carry(true);
+        assert_eq!(das(&mut flags, 0xEE), 0x88);
+        assert_eq!(arithmetic_flags(flags), "SAPC");
+
+        // 35h + 47h gives 7Ch, adjusted to 82h, and 99h + 01h gives 9Ah, adjusted to 00h carrying
+        let mut flags = Flags::new();
+        assert_eq!(daa(&mut flags, 0x7C), 0x82);
+        assert_eq!(arithmetic_flags(flags), "SAP");
+        assert_eq!(daa(&mut Flags::new(), 0x9A), 0x00);
+
+        // 18h + 29h gives 41h with an auxiliary carry out of 8h + 9h, adjusted to 47h
+        let mut flags = Flags::new();
+        flags.set_auxiliary(true);
+        assert_eq!(daa(&mut flags, 0x41), 0x47);
+
+        // 10h - 01h gives 0Fh, adjusted to 09h, and 00h - 01h gives FFh, adjusted to 99h borrowing
+        let mut flags = Flags::new();
+        assert_eq!(das(&mut flags, 0x0F), 0x09);
+        assert_eq!(arithmetic_flags(flags), "AP");
+        flags.set_carry(true);
+        assert_eq!(das(&mut flags, 0xFF), 0x99);
+        assert!(flags.carry());
+    }
+
+    #[test]
+    fn test_alu_ascii_adjust() {
+        // '8' + '9' gives 71h with an auxiliary carry, adjusted to 17 in unpacked BCD
+        let mut flags = Flags::new();
+        flags.set_auxiliary(true);
+        assert_eq!(aaa(&mut flags, 0x0071), 0x0107);
+        assert!(flags.auxiliary() && flags.carry());
+
+        // '3' + '4' needs no adjustment beyond clearing the high nibble
+        let mut flags = Flags::new();
+        assert_eq!(aaa(&mut flags, 0x0067), 0x0007);
+        assert!(!flags.auxiliary() && !flags.carry());
+
+        // The 8086 does not carry the adjustment of AL into AH
+        assert_eq!(aaa(&mut Flags::new(), 0x00FA), 0x0100);
+
+        // '3' - '9' gives FAh with an auxiliary borrow, adjusted to 4 borrowing from AH
+        let mut flags = Flags::new();
+        flags.set_auxiliary(true);
+        assert_eq!(aas(&mut flags, 0x00FA), 0xFF04);
+        assert!(flags.auxiliary() && flags.carry());
+
+        // 7 * 9 is 63, and back again
+        let mut flags = Flags::new();
+        assert_eq!(aam(&mut flags, 63, 10), Some(0x0603));
+        assert_eq!(arithmetic_flags(flags), "P");
+        assert_eq!(aad(&mut flags, 0x0603, 10), 0x003F);
+        assert_eq!(arithmetic_flags(flags), "P");
+
+        // Other bases are supported by the encoding, and zero faults
+        assert_eq!(aam(&mut flags, 0x3F, 16), Some(0x030F));
+        assert_eq!(aad(&mut flags, 0x0000, 10), 0x0000);
+        assert_eq!(arithmetic_flags(flags), "ZP");
+        assert_eq!(aam(&mut flags, 63, 0), None);
+    }
+}
@@ -0,0 +1,305 @@
+use std::{cell::RefCell, rc::Rc};
+
+use mem::{BusDevice, MemoryMap, Shared, WatchEvent, WatchHandle, WatchKind, Watched};
+
+use crate::{Cpu, CpuFault, Registers, SegmentedAddress, StepResult};
+
+/// Handle identifying a breakpoint added to a `Debugger`, used to remove it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BreakpointId(usize);
+
+/// Handle identifying a watchpoint added to a `Debugger`, used to remove it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WatchpointId(usize);
+
+/// Predicate deciding whether a conditional breakpoint is hit, given the registers as the instruction under it is
+/// about to execute.
+pub type BreakpointCondition = Box<dyn Fn(&Registers) -> bool>;
+
+/// The outcome of stepping a processor under a `Debugger`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DebugEvent {
+    /// An instruction was executed without hitting a breakpoint or watchpoint.
+    Stepped,
+    /// The instruction at `CS:IP` has a breakpoint, and was not executed.
+    BreakpointHit(BreakpointId),
+    /// The instruction executed accessed the watched address, reading or writing the given value.
+    WatchpointHit(WatchpointId, usize, u8),
+    /// The processor is halted, waiting for an interrupt.
+    Halted
+}
+
+/// The memory of a processor under a `Debugger`, watched for accesses to the watchpoints.
+type WatchedMemory = Shared<Watched<MemoryMap>>;
+
+/// Runs a processor with software breakpoints on instructions and watchpoints on the linear addresses of bytes in
+/// memory.
+///
+/// The memory of the processor is wrapped so that its accesses can be watched, with instruction fetches counting as
+/// reads. The wrapping is undone by `into_cpu`, and only covers the devices mapped when the debugger was constructed,
+/// so devices mapped through `cpu_mut` afterwards are neither watched nor kept by `into_cpu`.
+pub struct Debugger {
+    cpu: Cpu,
+    memory: WatchedMemory,
+    events: Rc<RefCell<Vec<WatchEvent>>>,
+    breakpoints: Vec<(BreakpointId, SegmentedAddress, Option<BreakpointCondition>)>,
+    watchpoints: Vec<(WatchpointId, usize, WatchKind, WatchHandle)>,
+    next_id: usize,
+    /// The address of the breakpoint last hit, which is passed over by the next step so that execution can resume.
+    resume_address: Option<usize>
+}
+
+impl Debugger {
+    /// Takes control of `cpu`, with no breakpoints or watchpoints.
+    #[must_use]
+    pub fn new(mut cpu: Cpu) -> Self {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&events);
+
+        let inner = std::mem::take(cpu.memory_mut());
+        let size = inner.size().unwrap_or_default();
+        let memory = Shared::new(Watched::new(inner, move |event| sink.borrow_mut().push(event)));
+
+        if size > 0 {
+            cpu.memory_mut().add_range(0..=size - 1, Box::new(memory.clone()));
+        }
+
+        Self { cpu, memory, events, breakpoints: Vec::new(), watchpoints: Vec::new(), next_id: 0, resume_address: None }
+    }
+
+    /// Returns the processor being debugged.
+    #[must_use]
+    pub const fn cpu(&self) -> &Cpu {
+        &self.cpu
+    }
+
+    /// Returns the processor being debugged, to change its registers or memory between steps.
+    #[must_use]
+    pub const fn cpu_mut(&mut self) -> &mut Cpu {
+        &mut self.cpu
+    }
+
+    /// Releases the processor, with its memory as it was before it was wrapped for watching.
+    #[must_use]
+    pub fn into_cpu(mut self) -> Cpu {
+        *self.cpu.memory_mut() = std::mem::take(self.memory.borrow_mut().inner_mut());
+
+        self.cpu
+    }
+
+    /// Returns a fresh identifier for a breakpoint or watchpoint.
+    const fn next_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Adds a breakpoint on the instruction at `address`, returning an identifier which can be used to remove it.
+    ///
+    /// The breakpoint is hit by any `CS:IP` with the same linear address, so `0000:7C00` and `07C0:0000` are the same
+    /// breakpoint.
+    pub fn add_breakpoint(&mut self, address: SegmentedAddress) -> BreakpointId {
+        let id = BreakpointId(self.next_id());
+        self.breakpoints.push((id, address, None));
+
+        id
+    }
+
+    /// Adds a breakpoint on the instruction at `address` as `add_breakpoint` does, which is only hit when `predicate`
+    /// returns `true` for the registers at that point.
+    pub fn add_conditional_breakpoint(&mut self, address: SegmentedAddress, predicate: BreakpointCondition) -> BreakpointId {
+        let id = BreakpointId(self.next_id());
+        self.breakpoints.push((id, address, Some(predicate)));
+
+        id
+    }
+
+    /// Removes the breakpoint `id`, returning `false` if it had already been removed.
+    pub fn remove_breakpoint(&mut self, id: BreakpointId) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|(other, ..)| *other != id);
+
+        self.breakpoints.len() != count
+    }
+
+    /// Adds a watchpoint on the byte at the linear `address`, firing on accesses of the given `kind`, and returns an
+    /// identifier which can be used to remove it.
+    pub fn add_watchpoint(&mut self, address: usize, kind: WatchKind) -> WatchpointId {
+        let id = WatchpointId(self.next_id());
+        let handle = self.memory.borrow_mut().watch(address..=address, kind);
+        self.watchpoints.push((id, address, kind, handle));
+
+        id
+    }
+
+    /// Removes the watchpoint `id`, returning `false` if it had already been removed.
+    pub fn remove_watchpoint(&mut self, id: WatchpointId) -> bool {
+        let Some(index) = self.watchpoints.iter().position(|(other, ..)| *other == id) else {
+            return false;
+        };
+
+        let (.., handle) = self.watchpoints.remove(index);
+        self.memory.borrow_mut().unwatch(handle)
+    }
+
+    /// Executes a single instruction, unless there is a breakpoint on it.
+    ///
+    /// A breakpoint is reported before its instruction is executed, leaving `CS:IP` pointing at it, and the next step
+    /// executes the instruction rather than reporting the breakpoint again. If the instruction hits several
+    /// watchpoints, the first access to hit one is reported.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the processor faults, as with `Cpu::step`.
+    pub fn step(&mut self) -> Result<DebugEvent, CpuFault> {
+        let address = self.cpu.instruction_pointer().to_linear();
+
+        if self.resume_address.take() != Some(address) {
+            let registers = self.cpu.registers();
+            let hit = self.breakpoints.iter().find(|(_, breakpoint, condition)| {
+                breakpoint.to_linear() == address && condition.as_ref().is_none_or(|condition| condition(registers))
+            });
+
+            if let Some((id, ..)) = hit {
+                self.resume_address = Some(address);
+                return Ok(DebugEvent::BreakpointHit(*id));
+            }
+        }
+
+        self.events.borrow_mut().clear();
+
+        if self.cpu.step()? == StepResult::Halted {
+            return Ok(DebugEvent::Halted);
+        }
+
+        let events = self.events.borrow();
+        let hit = events.iter().find_map(|event| {
+            self.watchpoints.iter()
+                .find(|(_, address, kind, _)| *address == event.address && kind.matches(event.kind))
+                .map(|(id, ..)| DebugEvent::WatchpointHit(*id, event.address, event.value))
+        });
+
+        Ok(hit.unwrap_or(DebugEvent::Stepped))
+    }
+
+    /// Steps the processor until a breakpoint or watchpoint is hit or it halts, returning that event.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the processor faults, as with `Cpu::step`.
+    pub fn run(&mut self) -> Result<DebugEvent, CpuFault> {
+        loop {
+            match self.step()? {
+                DebugEvent::Stepped => {}
+                event => return Ok(event)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::RegionBusDevice;
+
+    use crate::cpu::testing::cpu_with_program;
+
+    use super::*;
+
+    /// Constructs a debugger over a processor running `program` from 1000:0000, with its stack at 1800:0100.
+    fn debugger(program: &[u8]) -> Debugger {
+        let mut cpu = cpu_with_program(program);
+        (cpu.registers_mut().ss, cpu.registers_mut().sp) = (0x1800, 0x0100);
+
+        Debugger::new(cpu)
+    }
+
+    #[test]
+    fn test_debugger_breakpoints() {
+        // NOP, NOP, NOP, HLT
+        let mut debugger = debugger(&[0x90, 0x90, 0x90, 0xF4]);
+        debugger.cpu_mut().registers_mut().flags.set_interrupt(true);
+
+        let first = debugger.add_breakpoint(SegmentedAddress::new(0x1000, 0x0001));
+        let second = debugger.add_breakpoint(SegmentedAddress::new(0x0FFF, 0x0012));
+
+        assert_eq!(debugger.step(), Ok(DebugEvent::Stepped));
+        assert_eq!(debugger.run(), Ok(DebugEvent::BreakpointHit(first)));
+        assert_eq!(debugger.cpu().registers().ip, 1);
+
+        // Resuming executes the instruction under the breakpoint
+        assert_eq!(debugger.run(), Ok(DebugEvent::BreakpointHit(second)));
+        assert_eq!(debugger.cpu().registers().ip, 2);
+
+        assert!(debugger.remove_breakpoint(second));
+        assert!(!debugger.remove_breakpoint(second));
+        debugger.cpu_mut().registers_mut().ip = 2;
+        assert_eq!(debugger.run(), Ok(DebugEvent::Halted));
+    }
+
+    #[test]
+    fn test_debugger_watchpoints() {
+        // MOV AL, [0100h]; MOV [0101h], AL; PUSH AX
+        let mut debugger = debugger(&[0xA0, 0x00, 0x01, 0xA2, 0x01, 0x01, 0x50]);
+        debugger.cpu_mut().registers_mut().ax = 0x1234;
+        assert_eq!(debugger.cpu_mut().memory_mut().write(0x100, 0x56), Ok(()));
+
+        let read = debugger.add_watchpoint(0x100, WatchKind::Read);
+        let write = debugger.add_watchpoint(0x101, WatchKind::Write);
+        let stack = debugger.add_watchpoint(0x180FF, WatchKind::ReadWrite);
+        let unused = debugger.add_watchpoint(0x101, WatchKind::Read);
+
+        assert_eq!(debugger.step(), Ok(DebugEvent::WatchpointHit(read, 0x100, 0x56)));
+        assert_eq!(debugger.step(), Ok(DebugEvent::WatchpointHit(write, 0x101, 0x56)));
+
+        assert!(debugger.remove_watchpoint(unused));
+        assert!(!debugger.remove_watchpoint(unused));
+        assert_eq!(debugger.run(), Ok(DebugEvent::WatchpointHit(stack, 0x180FF, 0x12)));
+
+        let cpu = debugger.into_cpu();
+        assert_eq!(cpu.memory().mapping(0x180FF).map(|(range, _)| range.clone()), Some(0x00000..=0x1FFFF));
+        assert_eq!(cpu.memory().read_region(0x180FE), Ok([0x56, 0x12]));
+    }
+
+    #[test]
+    fn test_debugger_breakpoint_once_per_pass() {
+        // NOP; LOOP back to the NOP
+        let mut debugger = debugger(&[0x90, 0xE2, 0xFD]);
+        debugger.cpu_mut().registers_mut().cx = 100;
+        let id = debugger.add_breakpoint(SegmentedAddress::new(0x1000, 0x0000));
+
+        let mut hits = 0;
+
+        for _ in 0..30 {
+            match debugger.step() {
+                Ok(DebugEvent::BreakpointHit(hit)) => {
+                    assert_eq!((hit, debugger.cpu().registers().ip), (id, 0));
+                    hits += 1;
+                }
+                event => assert_eq!(event, Ok(DebugEvent::Stepped))
+            }
+        }
+
+        // Each pass is the breakpoint followed by the NOP and the LOOP
+        assert_eq!(hits, 10);
+    }
+
+    #[test]
+    fn test_debugger_conditional_breakpoint() {
+        // NOP; LOOP back to the NOP; HLT
+        let mut debugger = debugger(&[0x90, 0xE2, 0xFD, 0xF4]);
+        debugger.cpu_mut().registers_mut().cx = 200;
+        debugger.cpu_mut().registers_mut().flags.set_interrupt(true);
+
+        let id = debugger.add_conditional_breakpoint(SegmentedAddress::new(0x1000, 0x0000), Box::new(|regs| regs.cx % 100 == 0));
+        let never = debugger.add_conditional_breakpoint(SegmentedAddress::new(0x1000, 0x0001), Box::new(|_| false));
+
+        assert_eq!(debugger.run(), Ok(DebugEvent::BreakpointHit(id)));
+        assert_eq!(debugger.cpu().registers().cx, 200);
+        assert_eq!(debugger.run(), Ok(DebugEvent::BreakpointHit(id)));
+        assert_eq!(debugger.cpu().registers().cx, 100);
+
+        assert!(debugger.remove_breakpoint(id));
+        assert!(debugger.remove_breakpoint(never));
+        assert_eq!(debugger.run(), Ok(DebugEvent::Halted));
+        assert_eq!(debugger.cpu().registers().cx, 0);
+    }
+}
@@ -0,0 +1,257 @@
+use super::{Registers, SegmentRegister, SegmentedAddress};
+
+/// The base and index registers summed to form the offset of a memory operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AddressBase {
+    BxSi,
+    BxDi,
+    BpSi,
+    BpDi,
+    Si,
+    Di,
+    Bp,
+    Bx,
+    /// No registers, the displacement alone is the offset (`[disp16]`).
+    Direct
+}
+
+impl AddressBase {
+    #[must_use]
+    /// Returns the base for the given 3-bit `r/m` field of a memory operand. The `Direct` case (`r/m = 110` with
+    /// `mod = 00`) is not handled here, `0b110` always gives `Bp`.
+    pub const fn from_rm(rm: u8) -> Self {
+        match rm & 0b111 {
+            0 => Self::BxSi,
+            1 => Self::BxDi,
+            2 => Self::BpSi,
+            3 => Self::BpDi,
+            4 => Self::Si,
+            5 => Self::Di,
+            6 => Self::Bp,
+            _ => Self::Bx
+        }
+    }
+
+    #[must_use]
+    /// Returns the segment used when no override is present, `SS` for addresses based on `BP` and `DS` otherwise.
+    pub const fn default_segment(self) -> SegmentRegister {
+        match self {
+            Self::BpSi | Self::BpDi | Self::Bp => SegmentRegister::Ss,
+            _ => SegmentRegister::Ds
+        }
+    }
+
+    #[must_use]
+    /// Sums the registers making up the base.
+    pub const fn offset(self, regs: &Registers) -> u16 {
+        match self {
+            Self::BxSi => regs.bx.wrapping_add(regs.si),
+            Self::BxDi => regs.bx.wrapping_add(regs.di),
+            Self::BpSi => regs.bp.wrapping_add(regs.si),
+            Self::BpDi => regs.bp.wrapping_add(regs.di),
+            Self::Si => regs.si,
+            Self::Di => regs.di,
+            Self::Bp => regs.bp,
+            Self::Bx => regs.bx,
+            Self::Direct => 0
+        }
+    }
+}
+
+/// A memory operand, made up of a base, an optional displacement and an optional segment override.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MemoryAddress {
+    pub base: AddressBase,
+    pub displacement: Option<i16>,
+    pub segment_override: Option<SegmentRegister>
+}
+
+impl MemoryAddress {
+    #[must_use]
+    /// Constructs a memory operand with no segment override.
+    pub const fn new(base: AddressBase, displacement: Option<i16>) -> Self {
+        Self { base, displacement, segment_override: None }
+    }
+
+    #[must_use]
+    /// Constructs a direct `[disp16]` memory operand with no segment override.
+    pub const fn direct(address: u16) -> Self {
+        Self::new(AddressBase::Direct, Some(address.cast_signed()))
+    }
+
+    #[must_use]
+    /// Returns the segment register used by the operand, taking any override into account.
+    pub const fn segment(&self) -> SegmentRegister {
+        match self.segment_override {
+            Some(segment) => segment,
+            None => self.base.default_segment()
+        }
+    }
+
+    #[must_use]
+    /// Computes the offset of the operand within its segment, wrapping at 16 bits.
+    pub const fn offset(&self, regs: &Registers) -> u16 {
+        let displacement = match self.displacement {
+            Some(displacement) => displacement.cast_unsigned(),
+            None => 0
+        };
+
+        self.base.offset(regs).wrapping_add(displacement)
+    }
+
+    #[must_use]
+    /// Computes the `segment:offset` address of the operand.
+    pub const fn resolve(&self, regs: &Registers) -> SegmentedAddress {
+        SegmentedAddress::new(regs.segment(self.segment()), self.offset(regs))
+    }
+
+    #[must_use]
+    /// Returns the number of clock cycles the 8086 takes to compute the address (`EA` in the instruction timings),
+    /// including the two cycles added by a segment override.
+    pub const fn cycles(&self) -> u32 {
+        let base = match (self.base, self.displacement.is_some()) {
+            (AddressBase::Direct, _) => 6,
+            (AddressBase::Si | AddressBase::Di | AddressBase::Bp | AddressBase::Bx, false) => 5,
+            (AddressBase::Si | AddressBase::Di | AddressBase::Bp | AddressBase::Bx, true) => 9,
+            (AddressBase::BpDi | AddressBase::BxSi, false) => 7,
+            (AddressBase::BpSi | AddressBase::BxDi, false) => 8,
+            (AddressBase::BpDi | AddressBase::BxSi, true) => 11,
+            (AddressBase::BpSi | AddressBase::BxDi, true) => 12
+        };
+
+        if self.segment_override.is_some() { base + 2 } else { base }
+    }
+}
+
+/// The operand selected by the `mod` and `r/m` fields of a `ModRM` byte.
+///
+/// Together the two variants cover all 24 memory addressing modes of the 8086 (eight bases, each with no, an 8-bit or
+/// a 16-bit displacement, with `[disp16]` taking the place of `[BP]` without displacement) and the eight register
+/// direct modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EffectiveAddress {
+    /// A register operand (`mod = 11`), given by its 3-bit encoding. Whether it names an 8-bit or a 16-bit register
+    /// depends on the width of the instruction.
+    Register(u8),
+    /// A memory operand.
+    Memory(MemoryAddress)
+}
+
+impl EffectiveAddress {
+    #[must_use]
+    /// Computes the `segment:offset` address of a memory operand, returning `None` for a register operand.
+    pub const fn resolve(&self, regs: &Registers) -> Option<SegmentedAddress> {
+        match self {
+            Self::Register(_) => None,
+            Self::Memory(memory) => Some(memory.resolve(regs))
+        }
+    }
+
+    #[must_use]
+    /// Returns a copy of the operand using `segment` in place of the default segment. Register operands are returned
+    /// unchanged.
+    pub const fn with_segment_override(self, segment: Option<SegmentRegister>) -> Self {
+        match self {
+            Self::Memory(memory) if segment.is_some() => Self::Memory(MemoryAddress { segment_override: segment, ..memory }),
+            _ => self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_registers() -> Registers {
+        Registers {
+            bx: 0x1000, bp: 0x2000, si: 0x0030, di: 0x0004,
+            cs: 0xF000, ds: 0x0100, es: 0x0200, ss: 0x0300,
+            ..Registers::new()
+        }
+    }
+
+    #[test]
+    fn test_effective_address_bases() {
+        let regs = test_registers();
+
+        let expected = [
+            (AddressBase::BxSi, SegmentedAddress::new(0x0100, 0x1030)),
+            (AddressBase::BxDi, SegmentedAddress::new(0x0100, 0x1004)),
+            (AddressBase::BpSi, SegmentedAddress::new(0x0300, 0x2030)),
+            (AddressBase::BpDi, SegmentedAddress::new(0x0300, 0x2004)),
+            (AddressBase::Si, SegmentedAddress::new(0x0100, 0x0030)),
+            (AddressBase::Di, SegmentedAddress::new(0x0100, 0x0004)),
+            (AddressBase::Bp, SegmentedAddress::new(0x0300, 0x2000)),
+            (AddressBase::Bx, SegmentedAddress::new(0x0100, 0x1000)),
+        ];
+
+        for (rm, (base, address)) in expected.into_iter().enumerate() {
+            assert_eq!(AddressBase::from_rm(u8::try_from(rm).unwrap()), base);
+            assert_eq!(EffectiveAddress::Memory(MemoryAddress::new(base, None)).resolve(&regs), Some(address));
+        }
+    }
+
+    #[test]
+    fn test_effective_address_displacements() {
+        let regs = test_registers();
+
+        assert_eq!(MemoryAddress::new(AddressBase::BxSi, Some(4)).resolve(&regs), SegmentedAddress::new(0x0100, 0x1034));
+        assert_eq!(MemoryAddress::new(AddressBase::BxSi, Some(-0x31)).resolve(&regs), SegmentedAddress::new(0x0100, 0x0FFF));
+        assert_eq!(MemoryAddress::new(AddressBase::Bp, Some(0)).resolve(&regs), SegmentedAddress::new(0x0300, 0x2000));
+        assert_eq!(MemoryAddress::new(AddressBase::Di, Some(-5)).resolve(&regs), SegmentedAddress::new(0x0100, 0xFFFF));
+        assert_eq!(MemoryAddress::new(AddressBase::Bx, Some(0x7FFF)).resolve(&regs), SegmentedAddress::new(0x0100, 0x8FFF));
+    }
+
+    #[test]
+    fn test_effective_address_direct() {
+        let regs = test_registers();
+
+        assert_eq!(MemoryAddress::direct(0x1234).resolve(&regs), SegmentedAddress::new(0x0100, 0x1234));
+        assert_eq!(MemoryAddress::direct(0xFFFF).resolve(&regs), SegmentedAddress::new(0x0100, 0xFFFF));
+    }
+
+    #[test]
+    fn test_effective_address_offset_wraps() {
+        let regs = Registers { bx: 0xFFFF, si: 0x0002, ..test_registers() };
+
+        assert_eq!(MemoryAddress::new(AddressBase::BxSi, None).resolve(&regs), SegmentedAddress::new(0x0100, 0x0001));
+        assert_eq!(MemoryAddress::new(AddressBase::BxSi, Some(0x7FFF)).resolve(&regs), SegmentedAddress::new(0x0100, 0x8000));
+    }
+
+    #[test]
+    fn test_effective_address_segment_override() {
+        let regs = test_registers();
+
+        let address = EffectiveAddress::Memory(MemoryAddress::new(AddressBase::Bp, Some(2)));
+        assert_eq!(address.resolve(&regs), Some(SegmentedAddress::new(0x0300, 0x2002)));
+
+        let overridden = address.with_segment_override(Some(SegmentRegister::Es));
+        assert_eq!(overridden.resolve(&regs), Some(SegmentedAddress::new(0x0200, 0x2002)));
+        assert_eq!(address.with_segment_override(None), address);
+
+        let direct = MemoryAddress { segment_override: Some(SegmentRegister::Cs), ..MemoryAddress::direct(0x10) };
+        assert_eq!(direct.resolve(&regs), SegmentedAddress::new(0xF000, 0x0010));
+    }
+
+    #[test]
+    fn test_effective_address_register() {
+        let regs = test_registers();
+
+        assert_eq!(EffectiveAddress::Register(3).resolve(&regs), None);
+        assert_eq!(EffectiveAddress::Register(3).with_segment_override(Some(SegmentRegister::Es)), EffectiveAddress::Register(3));
+    }
+
+    #[test]
+    fn test_effective_address_cycles() {
+        assert_eq!(MemoryAddress::direct(0x10).cycles(), 6);
+        assert_eq!(MemoryAddress::new(AddressBase::Si, None).cycles(), 5);
+        assert_eq!(MemoryAddress::new(AddressBase::Bp, Some(2)).cycles(), 9);
+        assert_eq!(MemoryAddress::new(AddressBase::BxSi, None).cycles(), 7);
+        assert_eq!(MemoryAddress::new(AddressBase::BxDi, None).cycles(), 8);
+        assert_eq!(MemoryAddress::new(AddressBase::BpDi, Some(-4)).cycles(), 11);
+        assert_eq!(MemoryAddress::new(AddressBase::BpSi, Some(4)).cycles(), 12);
+
+        let overridden = MemoryAddress { segment_override: Some(SegmentRegister::Es), ..MemoryAddress::direct(0x10) };
+        assert_eq!(overridden.cycles(), 8);
+    }
+}
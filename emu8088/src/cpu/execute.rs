@@ -0,0 +1,1489 @@
+use crate::{Cpu, CpuFault, Flags, Instruction, MemoryAddress, Opcode, Operand, OperandWidth, Register16, SegmentRegister, SegmentedAddress, StepResult};
+
+use super::alu;
+
+impl Cpu {
+    /// Executes a decoded instruction, with `IP` already pointing at the following instruction. The `opcode` byte is
+    /// reported if the instruction is not implemented.
+    pub(super) fn execute(&mut self, instruction: Instruction, opcode: u8) -> Result<StepResult, CpuFault> {
+        match instruction.opcode {
+            Opcode::Nop => Ok(StepResult::Ok(3)),
+            Opcode::Hlt => {
+                self.halted = true;
+                Ok(StepResult::Halted)
+            }
+            Opcode::Mov => {
+                let (dst, src) = binary_operands(instruction, opcode)?;
+                self.mov(dst, src, opcode)
+            }
+            Opcode::Lea | Opcode::Lds | Opcode::Les => match binary_operands(instruction, opcode)? {
+                (dst, Operand::Memory { address, .. }) => self.load_address(instruction.opcode, dst, address),
+                _ => Err(CpuFault::InvalidOpcode(opcode))
+            },
+            Opcode::Push => {
+                let src = instruction.dst.ok_or(CpuFault::InvalidOpcode(opcode))?;
+                self.push(src)
+            }
+            Opcode::Pop => {
+                let dst = instruction.dst.ok_or(CpuFault::InvalidOpcode(opcode))?;
+                self.pop(dst)
+            }
+            Opcode::Call | Opcode::CallFar => {
+                let target = instruction.dst.ok_or(CpuFault::InvalidOpcode(opcode))?;
+                self.call(instruction.opcode, target)
+            }
+            Opcode::Ret | Opcode::RetFar => self.ret(instruction.opcode, instruction.dst),
+            Opcode::Int | Opcode::Int3 => match instruction.dst {
+                Some(Operand::Immediate8(vector)) => {
+                    self.software_interrupt(vector)?;
+                    Ok(StepResult::Ok(if instruction.opcode == Opcode::Int3 { 52 } else { 51 }))
+                }
+                _ => Err(CpuFault::InvalidOpcode(opcode))
+            },
+            Opcode::Into if self.registers.flags.overflow() => {
+                self.software_interrupt(4)?;
+                Ok(StepResult::Ok(53))
+            }
+            Opcode::Into => Ok(StepResult::Ok(4)),
+            Opcode::Iret => self.iret(),
+            Opcode::In | Opcode::Out => {
+                let (dst, src) = binary_operands(instruction, opcode)?;
+                self.port_io(instruction.opcode, dst, src)
+            }
+            Opcode::Loop | Opcode::Loope | Opcode::Loopne | Opcode::Jcxz => match instruction.dst {
+                Some(Operand::Relative(displacement)) => Ok(self.loop_jump(instruction.opcode, displacement)),
+                _ => Err(CpuFault::InvalidOpcode(opcode))
+            },
+            Opcode::Movsb | Opcode::Movsw | Opcode::Cmpsb | Opcode::Cmpsw | Opcode::Scasb | Opcode::Scasw |
+            Opcode::Lodsb | Opcode::Lodsw | Opcode::Stosb | Opcode::Stosw => self.string_operation(instruction),
+            Opcode::Jo | Opcode::Jno | Opcode::Jb | Opcode::Jnb | Opcode::Je | Opcode::Jne | Opcode::Jbe | Opcode::Ja |
+            Opcode::Js | Opcode::Jns | Opcode::Jp | Opcode::Jnp | Opcode::Jl | Opcode::Jge | Opcode::Jle | Opcode::Jg => {
+                match instruction.dst {
+                    Some(Operand::Relative(displacement)) => Ok(self.jump_if(condition_met(self.registers.flags, instruction.opcode), displacement)),
+                    _ => Err(CpuFault::InvalidOpcode(opcode))
+                }
+            }
+            Opcode::Add | Opcode::Adc | Opcode::Sub | Opcode::Sbb => {
+                let (dst, src) = binary_operands(instruction, opcode)?;
+                self.add_sub(instruction.opcode, dst, src)
+            }
+            Opcode::Mul | Opcode::Imul => {
+                let src = instruction.dst.ok_or(CpuFault::InvalidOpcode(opcode))?;
+                self.multiply(instruction.opcode, src)
+            }
+            Opcode::Rol | Opcode::Ror | Opcode::Rcl | Opcode::Rcr | Opcode::Shl | Opcode::Shr | Opcode::Sar => {
+                let (dst, count) = binary_operands(instruction, opcode)?;
+                self.shift(instruction.opcode, dst, count)
+            }
+            Opcode::Div | Opcode::Idiv => {
+                let src = instruction.dst.ok_or(CpuFault::InvalidOpcode(opcode))?;
+                self.divide(instruction.opcode, src)
+            }
+            Opcode::Daa | Opcode::Das | Opcode::Aaa | Opcode::Aas => Ok(self.decimal_adjust(instruction.opcode)),
+            Opcode::Aam | Opcode::Aad => match instruction.dst {
+                Some(Operand::Immediate8(base)) => self.adjust_base(instruction.opcode, base),
+                _ => Err(CpuFault::InvalidOpcode(opcode))
+            },
+            _ => Err(CpuFault::InvalidOpcode(opcode))
+        }
+    }
+
+    /// Copies `src` into `dst`, covering every register, memory, segment register and immediate form.
+    fn mov(&mut self, dst: Operand, src: Operand, opcode: u8) -> Result<StepResult, CpuFault> {
+        let value = self.read_operand(src)?;
+        self.write_operand(dst, value)?;
+
+        let cycles = match (dst, src) {
+            // The accumulator forms with a direct address take a fixed time
+            _ if matches!(opcode, 0xA0..=0xA3) => 10,
+            (Operand::Memory { address, .. }, Operand::Immediate8(_) | Operand::Immediate16(_)) => 10 + address.cycles(),
+            (Operand::Memory { address, .. }, _) => 9 + address.cycles(),
+            (_, Operand::Memory { address, .. }) => 8 + address.cycles(),
+            (_, Operand::Immediate8(_) | Operand::Immediate16(_)) => 4,
+            _ => 2
+        };
+
+        Ok(StepResult::Ok(cycles))
+    }
+
+    /// Executes `LEA`, loading the offset of `address` into `dst` without accessing memory, or `LDS` or `LES`, loading
+    /// the far pointer stored at `address` into `dst` and `DS` or `ES`.
+    fn load_address(&mut self, operation: Opcode, dst: Operand, address: MemoryAddress) -> Result<StepResult, CpuFault> {
+        if operation == Opcode::Lea {
+            self.write_operand(dst, address.offset(&self.registers))?;
+            return Ok(StepResult::Ok(2 + address.cycles()));
+        }
+
+        let (segment, offset) = self.read_far_pointer(Operand::Memory { address, width: OperandWidth::Word })?;
+        let segment_register = if operation == Opcode::Lds { SegmentRegister::Ds } else { SegmentRegister::Es };
+
+        self.write_operand(dst, offset)?;
+        self.registers.set_segment(segment_register, segment);
+
+        Ok(StepResult::Ok(16 + address.cycles()))
+    }
+
+    /// Pushes a register, segment register or memory word onto the stack.
+    fn push(&mut self, src: Operand) -> Result<StepResult, CpuFault> {
+        // The 8086 decrements `SP` before reading the operand, so `PUSH SP` pushes the decremented value where the 286
+        // and later push the original value
+        let value = match src {
+            Operand::Register16(Register16::Sp) => self.registers.sp.wrapping_sub(2),
+            _ => self.read_operand(src)?
+        };
+
+        self.push_word(value)?;
+
+        Ok(StepResult::Ok(match src {
+            Operand::Memory { address, .. } => 16 + address.cycles(),
+            Operand::Segment(_) => 10,
+            _ => 11
+        }))
+    }
+
+    /// Pops a word from the stack into a register, segment register or memory word.
+    fn pop(&mut self, dst: Operand) -> Result<StepResult, CpuFault> {
+        let value = self.pop_word()?;
+        self.write_operand(dst, value)?;
+
+        Ok(StepResult::Ok(match dst {
+            Operand::Memory { address, .. } => 17 + address.cycles(),
+            _ => 8
+        }))
+    }
+
+    /// Executes a near or far `CALL`, pushing the return address and transferring control to `target`. Near calls push
+    /// `IP`, far calls push `CS` and then `IP`.
+    fn call(&mut self, operation: Opcode, target: Operand) -> Result<StepResult, CpuFault> {
+        if operation == Opcode::CallFar {
+            let (segment, offset) = self.read_far_pointer(target)?;
+
+            self.push_word(self.registers.cs)?;
+            self.push_word(self.registers.ip)?;
+            (self.registers.cs, self.registers.ip) = (segment, offset);
+
+            return Ok(StepResult::Ok(match target {
+                Operand::Memory { address, .. } => 37 + address.cycles(),
+                _ => 28
+            }));
+        }
+
+        let offset = match target {
+            Operand::Relative(displacement) => self.registers.ip.wrapping_add_signed(displacement),
+            _ => self.read_operand(target)?
+        };
+
+        self.push_word(self.registers.ip)?;
+        self.registers.ip = offset;
+
+        Ok(StepResult::Ok(match target {
+            Operand::Relative(_) => 19,
+            Operand::Memory { address, .. } => 21 + address.cycles(),
+            _ => 16
+        }))
+    }
+
+    /// Enters the handler for interrupt `vector`, pushing `FLAGS`, `CS` and `IP` and jumping to the far pointer stored
+    /// at `vector * 4` in the interrupt vector table at the start of memory. The interrupt and trap flags are cleared,
+    /// so that the handler is not itself interrupted or single stepped.
+    pub(super) fn interrupt(&mut self, vector: u8) -> Result<(), CpuFault> {
+        let entry = SegmentedAddress::new(0x0000, u16::from(vector) * 4);
+        let offset = self.read_word(entry)?;
+        let segment = self.read_word(entry.wrapping_offset_add(2))?;
+
+        self.push_word(self.registers.flags.to_u16())?;
+        self.registers.flags.set_interrupt(false);
+        self.registers.flags.set_trap(false);
+
+        self.push_word(self.registers.cs)?;
+        self.push_word(self.registers.ip)?;
+        (self.registers.cs, self.registers.ip) = (segment, offset);
+
+        Ok(())
+    }
+
+    /// Raises software interrupt `vector`, calling the host side handler registered for it if there is one, or otherwise
+    /// entering the handler in the interrupt vector table.
+    fn software_interrupt(&mut self, vector: u8) -> Result<(), CpuFault> {
+        match self.interrupt_handlers.get_mut(&vector) {
+            Some(handler) => handler(&mut self.registers, &mut self.memory),
+            None => self.interrupt(vector)
+        }
+    }
+
+    /// Executes `IRET`, popping `IP`, `CS` and `FLAGS` in that order to return from an interrupt handler.
+    fn iret(&mut self) -> Result<StepResult, CpuFault> {
+        self.registers.ip = self.pop_word()?;
+        self.registers.cs = self.pop_word()?;
+        self.registers.flags = Flags::from_u16(self.pop_word()?);
+
+        Ok(StepResult::Ok(24))
+    }
+
+    /// Executes a near or far `RET`, popping `IP`, and then `CS` for far returns. With an immediate `release` operand
+    /// that many further bytes of arguments are discarded from the stack.
+    fn ret(&mut self, operation: Opcode, release: Option<Operand>) -> Result<StepResult, CpuFault> {
+        let offset = self.pop_word()?;
+        let segment = if operation == Opcode::RetFar { self.pop_word()? } else { self.registers.cs };
+
+        (self.registers.cs, self.registers.ip) = (segment, offset);
+
+        if let Some(Operand::Immediate16(bytes)) = release {
+            self.registers.sp = self.registers.sp.wrapping_add(bytes);
+        }
+
+        Ok(StepResult::Ok(match (operation, release) {
+            (Opcode::Ret, None) => 8,
+            (Opcode::Ret, Some(_)) => 12,
+            (_, None) => 18,
+            (_, Some(_)) => 17
+        }))
+    }
+
+    /// Adds `displacement` to `IP` if `taken`, for the conditional jumps.
+    const fn jump_if(&mut self, taken: bool, displacement: i16) -> StepResult {
+        if taken {
+            self.registers.ip = self.registers.ip.wrapping_add_signed(displacement);
+            StepResult::Ok(16)
+        }
+        else {
+            StepResult::Ok(4)
+        }
+    }
+
+    /// Executes `LOOP`, `LOOPE`, `LOOPNE` or `JCXZ`, adding `displacement` to `IP` if the branch is taken. The loops
+    /// first decrement `CX`, while `JCXZ` only tests it. None of them affect the flags.
+    const fn loop_jump(&mut self, operation: Opcode, displacement: i16) -> StepResult {
+        if !matches!(operation, Opcode::Jcxz) {
+            self.registers.cx = self.registers.cx.wrapping_sub(1);
+        }
+
+        let (cx, zero) = (self.registers.cx, self.registers.flags.zero());
+
+        // Cycles taken when the branch is taken and when it is not
+        let (taken, (taken_cycles, not_taken_cycles)) = match operation {
+            Opcode::Loop => (cx != 0, (17, 5)),
+            Opcode::Loope => (cx != 0 && zero, (18, 6)),
+            Opcode::Loopne => (cx != 0 && !zero, (19, 5)),
+            _ => (cx == 0, (18, 6))
+        };
+
+        if taken {
+            self.registers.ip = self.registers.ip.wrapping_add_signed(displacement);
+            StepResult::Ok(taken_cycles)
+        }
+        else {
+            StepResult::Ok(not_taken_cycles)
+        }
+    }
+
+    /// Executes `ADD`, `ADC`, `SUB` or `SBB`, storing the result in `dst` and updating all six arithmetic flags.
+    fn add_sub(&mut self, operation: Opcode, dst: Operand, src: Operand) -> Result<StepResult, CpuFault> {
+        let width = dst.width().unwrap_or(OperandWidth::Word);
+        let (a, b) = (self.read_operand(dst)?, self.read_operand(src)?);
+        let carry = self.registers.flags.carry();
+        let flags = &mut self.registers.flags;
+
+        let result = match operation {
+            Opcode::Add => alu::add(flags, width, a, b, false),
+            Opcode::Adc => alu::add(flags, width, a, b, carry),
+            Opcode::Sub => alu::sub(flags, width, a, b, false),
+            _ => alu::sub(flags, width, a, b, carry)
+        };
+
+        self.write_operand(dst, result)?;
+
+        Ok(StepResult::Ok(arithmetic_cycles(dst, src)))
+    }
+
+    /// Executes `MUL` or `IMUL`, multiplying the accumulator by `src` and storing the double width product in `AX` for
+    /// byte operands or `DX:AX` for word operands.
+    fn multiply(&mut self, operation: Opcode, src: Operand) -> Result<StepResult, CpuFault> {
+        let width = src.width().unwrap_or(OperandWidth::Word);
+        let (a, b) = (self.registers.ax, self.read_operand(src)?);
+        let flags = &mut self.registers.flags;
+
+        let product = if operation == Opcode::Mul { alu::mul(flags, width, a, b) } else { alu::imul(flags, width, a, b) };
+        let [low, high, upper, top] = product.to_le_bytes();
+
+        self.registers.ax = u16::from_le_bytes([low, high]);
+
+        if width == OperandWidth::Word {
+            self.registers.dx = u16::from_le_bytes([upper, top]);
+        }
+
+        // The fastest time given for each form, the actual time depends on the operands
+        let cycles = match (operation, width) {
+            (Opcode::Mul, OperandWidth::Byte) => 70,
+            (Opcode::Mul, OperandWidth::Word) => 118,
+            (_, OperandWidth::Byte) => 80,
+            (_, OperandWidth::Word) => 128
+        };
+
+        Ok(StepResult::Ok(match src {
+            Operand::Memory { address, .. } => cycles + 6 + address.cycles(),
+            _ => cycles
+        }))
+    }
+
+    /// Executes one of the shifts or rotates, by a single bit for the `D0h` and `D1h` forms or by `CL` bits for the `D2h`
+    /// and `D3h` forms.
+    fn shift(&mut self, operation: Opcode, dst: Operand, count: Operand) -> Result<StepResult, CpuFault> {
+        let width = dst.width().unwrap_or(OperandWidth::Word);
+        let value = self.read_operand(dst)?;
+        let [count_value, _] = self.read_operand(count)?.to_le_bytes();
+
+        let result = alu::shift(&mut self.registers.flags, width, operation, value, count_value);
+        self.write_operand(dst, result)?;
+
+        let cycles = match (dst, count) {
+            (Operand::Memory { address, .. }, Operand::Immediate8(_)) => 15 + address.cycles(),
+            (Operand::Memory { address, .. }, _) => 20 + address.cycles() + 4 * u32::from(count_value),
+            (_, Operand::Immediate8(_)) => 2,
+            _ => 8 + 4 * u32::from(count_value)
+        };
+
+        Ok(StepResult::Ok(cycles))
+    }
+
+    /// Executes `DIV` or `IDIV`, dividing `AX` for byte operands or `DX:AX` for word operands by `src`. The quotient is
+    /// stored in `AL` or `AX` and the remainder in `AH` or `DX`.
+    ///
+    /// A zero divisor or a quotient too large for the destination faults with `DivisionByZero`, leaving the registers
+    /// unchanged and `IP` pointing past the instruction.
+    fn divide(&mut self, operation: Opcode, src: Operand) -> Result<StepResult, CpuFault> {
+        let width = src.width().unwrap_or(OperandWidth::Word);
+        let divisor = self.read_operand(src)?;
+
+        let dividend = match width {
+            OperandWidth::Byte => u32::from(self.registers.ax),
+            OperandWidth::Word => u32::from(self.registers.dx) << 16 | u32::from(self.registers.ax)
+        };
+
+        let result = if operation == Opcode::Div { alu::div(width, dividend, divisor) } else { alu::idiv(width, dividend, divisor) };
+        let (quotient, remainder) = result.ok_or(CpuFault::DivisionByZero)?;
+
+        match width {
+            OperandWidth::Byte => self.registers.ax = u16::from_le_bytes([quotient.to_le_bytes()[0], remainder.to_le_bytes()[0]]),
+            OperandWidth::Word => (self.registers.ax, self.registers.dx) = (quotient, remainder)
+        }
+
+        // The fastest time given for each form, the actual time depends on the operands
+        let cycles = match (operation, width) {
+            (Opcode::Div, OperandWidth::Byte) => 80,
+            (Opcode::Div, OperandWidth::Word) => 144,
+            (_, OperandWidth::Byte) => 101,
+            (_, OperandWidth::Word) => 165
+        };
+
+        Ok(StepResult::Ok(match src {
+            Operand::Memory { address, .. } => cycles + 6 + address.cycles(),
+            _ => cycles
+        }))
+    }
+
+    /// Executes `IN`, reading the port given by `src` into the accumulator `dst`, or `OUT`, writing the accumulator
+    /// `src` to the port given by `dst`. The port is either an immediate byte or `DX`.
+    fn port_io(&mut self, operation: Opcode, dst: Operand, src: Operand) -> Result<StepResult, CpuFault> {
+        let (port, accumulator) = if operation == Opcode::In { (src, dst) } else { (dst, src) };
+        let port_number = self.read_operand(port)?;
+        let width = accumulator.width().unwrap_or(OperandWidth::Byte);
+
+        match (operation, width) {
+            (Opcode::In, OperandWidth::Byte) => {
+                let value = self.in_byte(port_number)?;
+                self.write_operand(accumulator, u16::from(value))?;
+            }
+            (Opcode::In, OperandWidth::Word) => {
+                let value = self.in_word(port_number)?;
+                self.write_operand(accumulator, value)?;
+            }
+            (_, OperandWidth::Byte) => self.out_byte(port_number, self.registers.al())?,
+            (_, OperandWidth::Word) => self.out_word(port_number, self.registers.ax)?
+        }
+
+        // Addressing the port through `DX` saves fetching the immediate
+        let cycles = match (port, width) {
+            (Operand::Immediate8(_), OperandWidth::Byte) => 10,
+            (Operand::Immediate8(_), OperandWidth::Word) => 14,
+            (_, OperandWidth::Byte) => 8,
+            (_, OperandWidth::Word) => 12
+        };
+
+        Ok(StepResult::Ok(cycles))
+    }
+
+    /// Executes `DAA`, `DAS`, `AAA` or `AAS`, adjusting the accumulator after an addition or subtraction of BCD digits.
+    const fn decimal_adjust(&mut self, operation: Opcode) -> StepResult {
+        let flags = &mut self.registers.flags;
+        let ax = self.registers.ax;
+        let [al, ah] = ax.to_le_bytes();
+
+        self.registers.ax = match operation {
+            Opcode::Daa => u16::from_le_bytes([alu::daa(flags, al), ah]),
+            Opcode::Das => u16::from_le_bytes([alu::das(flags, al), ah]),
+            Opcode::Aaa => alu::aaa(flags, ax),
+            _ => alu::aas(flags, ax)
+        };
+
+        StepResult::Ok(4)
+    }
+
+    /// Executes `AAM` or `AAD`, converting between a binary value in `AL` and two digits of `base` in `AH` and `AL`.
+    /// The assemblers only emit a base of ten, but any base is supported, with a base of zero faulting as a division.
+    fn adjust_base(&mut self, operation: Opcode, base: u8) -> Result<StepResult, CpuFault> {
+        let [al, _] = self.registers.ax.to_le_bytes();
+        let flags = &mut self.registers.flags;
+
+        if operation == Opcode::Aam {
+            self.registers.ax = alu::aam(flags, al, base).ok_or(CpuFault::DivisionByZero)?;
+            Ok(StepResult::Ok(83))
+        }
+        else {
+            self.registers.ax = alu::aad(flags, self.registers.ax, base);
+            Ok(StepResult::Ok(60))
+        }
+    }
+}
+
+/// Returns the clock cycles taken by one of the two operand arithmetic and logic instructions with the given operands.
+const fn arithmetic_cycles(dst: Operand, src: Operand) -> u32 {
+    match (dst, src) {
+        (Operand::Memory { address, .. }, Operand::Immediate8(_) | Operand::Immediate16(_)) => 17 + address.cycles(),
+        (Operand::Memory { address, .. }, _) => 16 + address.cycles(),
+        (_, Operand::Memory { address, .. }) => 9 + address.cycles(),
+        (_, Operand::Immediate8(_) | Operand::Immediate16(_)) => 4,
+        _ => 3
+    }
+}
+
+/// Returns `true` if the condition tested by one of the sixteen conditional jumps holds for `flags`. The unsigned
+/// comparisons (below and above) test the carry flag, and the signed comparisons (less and greater) test the sign
+/// flag against the overflow flag.
+const fn condition_met(flags: Flags, operation: Opcode) -> bool {
+    match operation {
+        Opcode::Jo => flags.overflow(),
+        Opcode::Jno => !flags.overflow(),
+        Opcode::Jb => flags.carry(),
+        Opcode::Jnb => !flags.carry(),
+        Opcode::Je => flags.zero(),
+        Opcode::Jne => !flags.zero(),
+        Opcode::Jbe => flags.carry() || flags.zero(),
+        Opcode::Ja => !flags.carry() && !flags.zero(),
+        Opcode::Js => flags.sign(),
+        Opcode::Jns => !flags.sign(),
+        Opcode::Jp => flags.parity(),
+        Opcode::Jnp => !flags.parity(),
+        Opcode::Jl => flags.sign() != flags.overflow(),
+        Opcode::Jge => flags.sign() == flags.overflow(),
+        Opcode::Jle => flags.zero() || flags.sign() != flags.overflow(),
+        Opcode::Jg => !flags.zero() && flags.sign() == flags.overflow(),
+        _ => false
+    }
+}
+
+/// Returns the destination and source operands of a two operand instruction, reporting `opcode` as invalid if either
+/// is missing.
+const fn binary_operands(instruction: Instruction, opcode: u8) -> Result<(Operand, Operand), CpuFault> {
+    match (instruction.dst, instruction.src) {
+        (Some(dst), Some(src)) => Ok((dst, src)),
+        _ => Err(CpuFault::InvalidOpcode(opcode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use mem::{BusDevice, BusDeviceError, InitTracked, IoDevice, IoMap, Latch, Memory, MemoryMap, RegionBusDevice, UninitPolicy};
+
+    use crate::{cpu::testing::{cpu_with_memory, cpu_with_program, cpu_with_source}, Assembler, SegmentedAddress};
+
+    use super::*;
+
+    #[test]
+    fn test_mov_rm8_r8() {
+        let mut cpu = cpu_with_source("mov al, cl\nmov [bx+si+2], ch");
+        (cpu.registers_mut().cx, cpu.registers_mut().bx, cpu.registers_mut().si) = (0xA55A, 0x0100, 0x0010);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(2)));
+        assert_eq!(cpu.registers().al(), 0x5A);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(9 + 11)));
+        assert_eq!(cpu.memory().read(0x0112), Ok(0xA5));
+        assert_eq!(cpu.registers().ip, 5);
+    }
+
+    #[test]
+    fn test_mov_rm16_r16() {
+        let mut cpu = cpu_with_source("mov di, dx\nmov [bp-2], dx");
+        (cpu.registers_mut().dx, cpu.registers_mut().bp, cpu.registers_mut().ss) = (0x1234, 0x0100, 0x0100);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(2)));
+        assert_eq!(cpu.registers().di, 0x1234);
+
+        // Addresses based on `BP` default to the stack segment
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(9 + 9)));
+        assert_eq!(cpu.memory().read_region::<2>(0x10FE), Ok([0x34, 0x12]));
+    }
+
+    #[test]
+    fn test_mov_r8_rm8() {
+        // MOV DH, BL in the register direction, followed by MOV AH, [DI]
+        let mut cpu = cpu_with_program(&[0x8A, 0xF3, 0x8A, 0x25]);
+        (cpu.registers_mut().bx, cpu.registers_mut().di) = (0x0077, 0x0300);
+        assert_eq!(cpu.memory_mut().write(0x0300, 0xC3), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(2)));
+        assert_eq!(cpu.registers().dh(), 0x77);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8 + 5)));
+        assert_eq!(cpu.registers().ah(), 0xC3);
+    }
+
+    #[test]
+    fn test_mov_r16_rm16() {
+        // MOV SP, AX in the register direction, followed by MOV CX, [BX+DI+0x1000]
+        let mut cpu = cpu_with_program(&[0x8B, 0xE0, 0x8B, 0x89, 0x00, 0x10]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx, cpu.registers_mut().di) = (0xFFFE, 0x0020, 0x0002);
+        assert_eq!(cpu.memory_mut().write_region(0x1022, &[0xEF, 0xBE]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(2)));
+        assert_eq!(cpu.registers().sp, 0xFFFE);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8 + 12)));
+        assert_eq!(cpu.registers().cx, 0xBEEF);
+    }
+
+    #[test]
+    fn test_mov_rm8_imm8() {
+        // MOV BYTE [0x0400], 0x99, followed by MOV BL, 0x42 using the `r/m` encoding
+        let mut cpu = cpu_with_program(&[0xC6, 0x06, 0x00, 0x04, 0x99, 0xC6, 0xC3, 0x42]);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10 + 6)));
+        assert_eq!(cpu.memory().read(0x0400), Ok(0x99));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.registers().bl(), 0x42);
+    }
+
+    #[test]
+    fn test_mov_rm16_imm16() {
+        let mut cpu = cpu_with_source("mov word [si], 0x8001\nmov word es:[si+4], -1");
+        (cpu.registers_mut().si, cpu.registers_mut().es) = (0x0500, 0x0010);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10 + 5)));
+        assert_eq!(cpu.memory().read_region::<2>(0x0500), Ok([0x01, 0x80]));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10 + 9 + 2)));
+        assert_eq!(cpu.memory().read_region::<2>(0x0604), Ok([0xFF, 0xFF]));
+    }
+
+    #[test]
+    fn test_mov_r8_imm8() {
+        let mut cpu = cpu_with_source("mov ch, 0x12\nmov dl, 'A'");
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!((cpu.registers().ch(), cpu.registers().dl()), (0x12, b'A'));
+        assert_eq!(cpu.registers().ip, 4);
+    }
+
+    #[test]
+    fn test_mov_r16_imm16() {
+        let mut cpu = cpu_with_source("mov bp, 0x1234\nmov ax, 7");
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!((cpu.registers().bp, cpu.registers().ax), (0x1234, 7));
+        assert_eq!(cpu.registers().ip, 6);
+    }
+
+    #[test]
+    fn test_mov_accumulator_direct() {
+        let mut cpu = cpu_with_source("mov al, [0x0600]\nmov ax, [0x0602]\nmov [0x0610], al\nmov [0x0612], ax");
+        cpu.registers_mut().ds = 0x0010;
+        assert_eq!(cpu.memory_mut().write_region(0x0700, &[0x11, 0x00, 0x33, 0x22]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.registers().ax, 0x0011);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.registers().ax, 0x2233);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.memory().read_region::<4>(0x0710), Ok([0x33, 0x00, 0x33, 0x22]));
+    }
+
+    #[test]
+    fn test_mov_rm16_segment() {
+        let mut cpu = cpu_with_source("mov bx, cs\nmov [0x0800], ss\nmov [bx+di], es");
+        (cpu.registers_mut().ss, cpu.registers_mut().es, cpu.registers_mut().di) = (0x0000, 0xB800, 0x0100);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(2)));
+        assert_eq!(cpu.registers().bx, 0x1000);
+
+        cpu.registers_mut().ss = 0x1234;
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(9 + 6)));
+        assert_eq!(cpu.memory().read_region::<2>(0x0800), Ok([0x34, 0x12]));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(9 + 8)));
+        assert_eq!(cpu.memory().read_region::<2>(0x1100), Ok([0x00, 0xB8]));
+    }
+
+    #[test]
+    fn test_mov_segment_rm16() {
+        let mut cpu = cpu_with_source("mov ds, ax\nmov es, [bx]\nmov ss, [bp+4]");
+        (cpu.registers_mut().ax, cpu.registers_mut().bx, cpu.registers_mut().bp) = (0x0040, 0x0010, 0x0020);
+        assert_eq!(cpu.memory_mut().write_region(0x0410, &[0x00, 0xA0]), Ok(()));
+        assert_eq!(cpu.memory_mut().write_region(0x0024, &[0x00, 0x30]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(2)));
+        assert_eq!(cpu.registers().ds, 0x0040);
+
+        // The new data segment is used by the following instruction
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8 + 5)));
+        assert_eq!(cpu.registers().es, 0xA000);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8 + 9)));
+        assert_eq!(cpu.registers().ss, 0x3000);
+    }
+
+    #[test]
+    fn test_mov_word_wraps_within_segment() {
+        let mut cpu = cpu_with_source("mov [0xFFFF], ax\nmov dx, [0xFFFF]");
+        (cpu.registers_mut().ds, cpu.registers_mut().ax) = (0x0100, 0xBEEF);
+
+        // The high byte of a word at offset `FFFFh` is at the start of the segment, not past its end
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.memory().read(0x10FFF), Ok(0xEF));
+        assert_eq!(cpu.memory().read(0x01000), Ok(0xBE));
+        assert_eq!(cpu.memory().read(0x11000), Ok(0x00));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8 + 6)));
+        assert_eq!(cpu.registers().dx, 0xBEEF);
+    }
+
+    #[test]
+    fn test_mov_memory_fault() {
+        let mut cpu = cpu_with_source("mov [0x0000], al");
+        cpu.registers_mut().ds = 0x8000;
+
+        assert_eq!(cpu.step(), Err(CpuFault::MemoryFault(BusDeviceError::AddressNotMapped { address: 0x80000 })));
+        assert_eq!(cpu.instruction_pointer(), SegmentedAddress::new(0x1000, 0x0003));
+    }
+
+    #[test]
+    fn test_add_register_forms() {
+        let mut cpu = cpu_with_source("add ax, bx\nadd cl, [si]\nadd [si+1], cl");
+        (cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x7FFF, 0x0001);
+        (cpu.registers_mut().cx, cpu.registers_mut().si) = (0x00F0, 0x0200);
+        assert_eq!(cpu.memory_mut().write_region(0x0200, &[0x10, 0x22]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(3)));
+        assert_eq!(cpu.registers().ax, 0x8000);
+        let flags = cpu.registers().flags;
+        assert!(flags.overflow() && flags.sign() && flags.auxiliary() && flags.parity() && !flags.zero() && !flags.carry());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(9 + 5)));
+        assert_eq!(cpu.registers().cl(), 0x00);
+        let flags = cpu.registers().flags;
+        assert!(flags.carry() && flags.zero() && !flags.overflow() && !flags.auxiliary());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(16 + 9)));
+        assert_eq!(cpu.memory().read(0x0201), Ok(0x22));
+        assert!(!cpu.registers().flags.carry());
+    }
+
+    #[test]
+    fn test_add_immediate_forms() {
+        // ADD AL, imm8 uses the accumulator form, and ADD WORD [BX], -2 the sign extended 83h form
+        let mut cpu = cpu_with_source("add al, 0x0F\nadd word [bx], -2\nadd dx, 0x1000");
+        (cpu.registers_mut().ax, cpu.registers_mut().bx, cpu.registers_mut().dx) = (0x1201, 0x0300, 0xF123);
+        assert_eq!(cpu.memory_mut().write_region(0x0300, &[0x01, 0x00]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.registers().ax, 0x1210);
+        assert!(cpu.registers().flags.auxiliary());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(17 + 5)));
+        assert_eq!(cpu.memory().read_region::<2>(0x0300), Ok([0xFF, 0xFF]));
+        assert!(cpu.registers().flags.sign() && !cpu.registers().flags.carry());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.registers().dx, 0x0123);
+        assert!(cpu.registers().flags.carry() && !cpu.registers().flags.overflow());
+    }
+
+    #[test]
+    fn test_adc_chains_carry() {
+        // A 32 bit addition of 0x0001FFFF and 0x00000001 in DX:AX
+        let mut cpu = cpu_with_source("add ax, 1\nadc dx, 0\nadc byte [0x10], 0x7F");
+        (cpu.registers_mut().dx, cpu.registers_mut().ax) = (0x0001, 0xFFFF);
+        assert_eq!(cpu.memory_mut().write(0x0010, 0x00), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert!(cpu.registers().flags.carry());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!((cpu.registers().dx, cpu.registers().ax), (0x0002, 0x0000));
+        assert!(!cpu.registers().flags.carry());
+
+        cpu.registers_mut().flags.set_carry(true);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(17 + 6)));
+        assert_eq!(cpu.memory().read(0x0010), Ok(0x80));
+        assert!(cpu.registers().flags.overflow() && cpu.registers().flags.auxiliary());
+    }
+
+    #[test]
+    fn test_sub_forms() {
+        let mut cpu = cpu_with_source("sub ax, bx\nsub bl, 1\nsub [di], ax\nsub ax, [di]");
+        (cpu.registers_mut().ax, cpu.registers_mut().bx, cpu.registers_mut().di) = (0x8000, 0x0001, 0x0400);
+        assert_eq!(cpu.memory_mut().write_region(0x0400, &[0xFF, 0x7F]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(3)));
+        assert_eq!(cpu.registers().ax, 0x7FFF);
+        let flags = cpu.registers().flags;
+        assert!(flags.overflow() && flags.auxiliary() && !flags.sign() && !flags.carry());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.registers().bx, 0x0000);
+        assert!(cpu.registers().flags.zero());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(16 + 5)));
+        assert_eq!(cpu.memory().read_region::<2>(0x0400), Ok([0x00, 0x00]));
+        assert!(cpu.registers().flags.zero() && cpu.registers().flags.parity());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(9 + 5)));
+        assert_eq!(cpu.registers().ax, 0x7FFF);
+        assert!(!cpu.registers().flags.carry());
+    }
+
+    #[test]
+    fn test_sbb_chains_borrow() {
+        // A 32 bit subtraction of 0x00000001 from 0x00010000 in DX:AX
+        let mut cpu = cpu_with_source("sub ax, 1\nsbb dx, 0\nsbb cl, ch");
+        (cpu.registers_mut().dx, cpu.registers_mut().ax, cpu.registers_mut().cx) = (0x0001, 0x0000, 0x0000);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.registers().ax, 0xFFFF);
+        assert!(cpu.registers().flags.carry());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.registers().dx, 0x0000);
+        assert!(cpu.registers().flags.zero() && !cpu.registers().flags.carry());
+
+        cpu.registers_mut().flags.set_carry(true);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(3)));
+        assert_eq!(cpu.registers().cx, 0x00FF);
+        let flags = cpu.registers().flags;
+        assert!(flags.carry() && flags.sign() && flags.auxiliary() && flags.parity() && !flags.overflow());
+    }
+
+    #[test]
+    fn test_mov_uninitialized_read() {
+        let program = Assembler::assemble_str("mov [0x10], ax\nmov bx, [0x10]\nmov cx, [0x11]").unwrap();
+        let memory = MemoryMap::new()
+            .with_range(0x00000..=0x0FFFF, Box::new(InitTracked::<0x10000>::new(UninitPolicy::Error)))
+            .with_range(0x10000..=0x1FFFF, Box::new(Memory::<0x10000>::populated(&program)));
+
+        let mut cpu = Cpu::new(memory, IoMap::new());
+        (cpu.registers_mut().cs, cpu.registers_mut().ip, cpu.registers_mut().ds) = (0x1000, 0x0000, 0x0000);
+
+        assert!(matches!(cpu.step(), Ok(StepResult::Ok(_))));
+        assert!(matches!(cpu.step(), Ok(StepResult::Ok(_))));
+
+        // Only the high byte of the word at 0x11 was never written
+        assert_eq!(cpu.step(), Err(CpuFault::MemoryFault(BusDeviceError::UninitializedRead { address: 0x12 })));
+    }
+
+    #[test]
+    fn test_mul_byte() {
+        // MUL BL, followed by MUL BYTE [SI]
+        let mut cpu = cpu_with_program(&[0xF6, 0xE3, 0xF6, 0x24]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx, cpu.registers_mut().dx) = (0x12FF, 0x00FF, 0x5555);
+        cpu.registers_mut().si = 0x0100;
+        assert_eq!(cpu.memory_mut().write(0x0100, 0x00), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(70)));
+        assert_eq!(cpu.registers().ax, 0xFE01);
+        assert!(cpu.registers().flags.carry() && cpu.registers().flags.overflow());
+
+        // `DX` is untouched by byte multiplication
+        assert_eq!(cpu.registers().dx, 0x5555);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(76 + 5)));
+        assert_eq!(cpu.registers().ax, 0x0000);
+        assert!(!cpu.registers().flags.carry() && !cpu.registers().flags.overflow());
+    }
+
+    #[test]
+    fn test_mul_word() {
+        // MUL BX, followed by MUL WORD [BX]
+        let mut cpu = cpu_with_program(&[0xF7, 0xE3, 0xF7, 0x27]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x8000, 0x8000);
+        assert_eq!(cpu.memory_mut().write_region(0x8000, &[0x02, 0x00]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(118)));
+        assert_eq!((cpu.registers().dx, cpu.registers().ax), (0x4000, 0x0000));
+        assert!(cpu.registers().flags.carry() && cpu.registers().flags.overflow());
+
+        cpu.registers_mut().ax = 0x1234;
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(124 + 5)));
+        assert_eq!((cpu.registers().dx, cpu.registers().ax), (0x0000, 0x2468));
+        assert!(!cpu.registers().flags.carry() && !cpu.registers().flags.overflow());
+    }
+
+    #[test]
+    fn test_imul() {
+        // IMUL BL, IMUL CX and IMUL BYTE [BX]
+        let mut cpu = cpu_with_program(&[0xF6, 0xEB, 0xF7, 0xE9, 0xF6, 0x2F]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx, cpu.registers_mut().cx) = (0x00FF, 0x00FF, 0x8000);
+        cpu.registers_mut().flags.set_zero(true);
+
+        // -1 * -1 fits in the low half
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(80)));
+        assert_eq!(cpu.registers().ax, 0x0001);
+        assert!(!cpu.registers().flags.carry() && !cpu.registers().flags.overflow());
+        assert!(cpu.registers().flags.zero());
+
+        // 1 * -32768 is the sign extension of the low half
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(128)));
+        assert_eq!((cpu.registers().dx, cpu.registers().ax), (0xFFFF, 0x8000));
+        assert!(!cpu.registers().flags.carry());
+
+        cpu.registers_mut().ax = 0x0080;
+        assert_eq!(cpu.memory_mut().write(0x00FF, 0x80), Ok(()));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(86 + 5)));
+        assert_eq!(cpu.registers().ax, 0x4000);
+        assert!(cpu.registers().flags.carry() && cpu.registers().flags.overflow());
+    }
+
+    #[test]
+    fn test_div() {
+        // DIV BL, DIV CX and DIV WORD [BX]
+        let mut cpu = cpu_with_program(&[0xF6, 0xF3, 0xF7, 0xF1, 0xF7, 0x37]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x0064, 0x0007);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(80)));
+        assert_eq!((cpu.registers().al(), cpu.registers().ah()), (0x0E, 0x02));
+
+        (cpu.registers_mut().dx, cpu.registers_mut().ax, cpu.registers_mut().cx) = (0x0001, 0x0001, 0x0010);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(144)));
+        assert_eq!((cpu.registers().ax, cpu.registers().dx), (0x1000, 0x0001));
+
+        (cpu.registers_mut().dx, cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x0000, 0x0009, 0x0200);
+        assert_eq!(cpu.memory_mut().write_region(0x0200, &[0x04, 0x00]), Ok(()));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(150 + 5)));
+        assert_eq!((cpu.registers().ax, cpu.registers().dx), (0x0002, 0x0001));
+    }
+
+    #[test]
+    fn test_div_faults() {
+        // DIV BL, DIV BX and IDIV BL
+        let mut cpu = cpu_with_program(&[0xF6, 0xF3, 0xF7, 0xF3, 0xF6, 0xFB]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x1234, 0x0000);
+
+        // Division by zero leaves the registers unchanged, with `IP` past the instruction as interrupt 0 expects
+        assert_eq!(cpu.step(), Err(CpuFault::DivisionByZero));
+        assert_eq!((cpu.registers().ax, cpu.registers().ip), (0x1234, 2));
+
+        // 0x0001_0000 / 1 does not fit in `AX`
+        (cpu.registers_mut().dx, cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x0001, 0x0000, 0x0001);
+        assert_eq!(cpu.step(), Err(CpuFault::DivisionByZero));
+        assert_eq!((cpu.registers().dx, cpu.registers().ax, cpu.registers().ip), (0x0001, 0x0000, 4));
+
+        // -32768 / -1 does not fit in `AL`
+        (cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x8000, 0x00FF);
+        assert_eq!(cpu.step(), Err(CpuFault::DivisionByZero));
+        assert_eq!(cpu.registers().ax, 0x8000);
+    }
+
+    #[test]
+    fn test_idiv() {
+        // IDIV BL, IDIV CX twice
+        let mut cpu = cpu_with_program(&[0xF6, 0xFB, 0xF7, 0xF9, 0xF7, 0xF9]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx) = (0xFFF9, 0x0002);
+
+        // -7 / 2 is -3 remainder -1
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(101)));
+        assert_eq!((cpu.registers().al(), cpu.registers().ah()), (0xFD, 0xFF));
+
+        // 0x8000 / 0xFFFF, as -32768 / -1, overflows
+        (cpu.registers_mut().dx, cpu.registers_mut().ax, cpu.registers_mut().cx) = (0xFFFF, 0x8000, 0xFFFF);
+        assert_eq!(cpu.step(), Err(CpuFault::DivisionByZero));
+        assert_eq!((cpu.registers().dx, cpu.registers().ax), (0xFFFF, 0x8000));
+
+        // -100000 / 7 is -14285 remainder -5
+        (cpu.registers_mut().dx, cpu.registers_mut().ax, cpu.registers_mut().cx) = (0xFFFE, 0x7960, 0x0007);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(165)));
+        assert_eq!((cpu.registers().ax, cpu.registers().dx), (0xC833, 0xFFFB));
+    }
+
+    #[test]
+    fn test_shift_by_one() {
+        // SHL AL, 1, SAR WORD [BX], 1 and RCR DX, 1
+        let mut cpu = cpu_with_program(&[0xD0, 0xE0, 0xD1, 0x3F, 0xD1, 0xDA]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx, cpu.registers_mut().dx) = (0x00C1, 0x0100, 0x0003);
+        assert_eq!(cpu.memory_mut().write_region(0x0100, &[0x02, 0x80]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(2)));
+        assert_eq!(cpu.registers().ax, 0x0082);
+        let flags = cpu.registers().flags;
+        assert!(flags.carry() && flags.sign() && !flags.overflow());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(15 + 5)));
+        assert_eq!(cpu.memory().read_region::<2>(0x0100), Ok([0x01, 0xC0]));
+        assert!(!cpu.registers().flags.carry() && !cpu.registers().flags.overflow());
+
+        cpu.registers_mut().flags.set_carry(true);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(2)));
+        assert_eq!(cpu.registers().dx, 0x8001);
+        assert!(cpu.registers().flags.carry() && cpu.registers().flags.overflow());
+    }
+
+    #[test]
+    fn test_shift_by_cl() {
+        // SHR BX, CL, ROL BYTE [SI], CL and RCL AH, CL
+        let mut cpu = cpu_with_program(&[0xD3, 0xEB, 0xD2, 0x04, 0xD2, 0xD4]);
+        (cpu.registers_mut().bx, cpu.registers_mut().cx, cpu.registers_mut().si) = (0x8421, 0x0004, 0x0200);
+        assert_eq!(cpu.memory_mut().write(0x0200, 0x96), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8 + 4 * 4)));
+        assert_eq!(cpu.registers().bx, 0x0842);
+        assert!(!cpu.registers().flags.carry());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(20 + 5 + 4 * 4)));
+        assert_eq!(cpu.memory().read(0x0200), Ok(0x69));
+        assert!(cpu.registers().flags.carry());
+
+        // The count is not masked, so a 9 bit rotate through the carry returns the byte to where it started
+        (cpu.registers_mut().ax, cpu.registers_mut().cx) = (0x5A00, 0x0009);
+        cpu.registers_mut().flags.set_carry(false);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8 + 4 * 9)));
+        assert_eq!(cpu.registers().ah(), 0x5A);
+    }
+
+    #[test]
+    fn test_shift_by_zero() {
+        // SHL AX, CL and RCR BYTE [DI], CL with CL = 0
+        let mut cpu = cpu_with_program(&[0xD3, 0xE0, 0xD2, 0x1D]);
+        (cpu.registers_mut().ax, cpu.registers_mut().cx, cpu.registers_mut().di) = (0x8001, 0x0000, 0x0300);
+        assert_eq!(cpu.memory_mut().write(0x0300, 0x81), Ok(()));
+        cpu.registers_mut().flags.set_overflow(true);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8)));
+        assert_eq!(cpu.registers().ax, 0x8001);
+        assert!(cpu.registers().flags.overflow() && !cpu.registers().flags.carry());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(20 + 5)));
+        assert_eq!(cpu.memory().read(0x0300), Ok(0x81));
+        assert!(cpu.registers().flags.overflow() && !cpu.registers().flags.carry());
+    }
+
+    #[test]
+    fn test_push_pop_registers() {
+        // PUSH AX, PUSH DS, POP BX, POP ES
+        let mut cpu = cpu_with_program(&[0x50, 0x1E, 0x5B, 0x07]);
+        (cpu.registers_mut().ax, cpu.registers_mut().ds, cpu.registers_mut().sp) = (0x1234, 0x0040, 0x1000);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(11)));
+        assert_eq!(cpu.registers().sp, 0x0FFE);
+        assert_eq!(cpu.memory().read_region::<2>(0x0FFE), Ok([0x34, 0x12]));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.registers().sp, 0x0FFC);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8)));
+        assert_eq!((cpu.registers().bx, cpu.registers().sp), (0x0040, 0x0FFE));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8)));
+        assert_eq!((cpu.registers().es, cpu.registers().sp), (0x1234, 0x1000));
+    }
+
+    #[test]
+    fn test_push_pop_memory() {
+        // PUSH [0200h], POP [0300h]
+        let mut cpu = cpu_with_program(&[0xFF, 0x36, 0x00, 0x02, 0x8F, 0x06, 0x00, 0x03]);
+        (cpu.registers_mut().ss, cpu.registers_mut().sp) = (0x0100, 0x0100);
+        assert_eq!(cpu.memory_mut().write_region(0x0200, &[0xCD, 0xAB]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(16 + 6)));
+        assert_eq!(cpu.memory().read_region::<2>(0x10FE), Ok([0xCD, 0xAB]));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(17 + 6)));
+        assert_eq!(cpu.memory().read_region::<2>(0x0300), Ok([0xCD, 0xAB]));
+        assert_eq!(cpu.registers().sp, 0x0100);
+    }
+
+    #[test]
+    fn test_push_sp() {
+        // PUSH SP, POP SP
+        let mut cpu = cpu_with_program(&[0x54, 0x5C]);
+        cpu.registers_mut().sp = 0x1000;
+
+        // The 8086 pushes the value after the decrement
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(11)));
+        assert_eq!(cpu.registers().sp, 0x0FFE);
+        assert_eq!(cpu.memory().read_region::<2>(0x0FFE), Ok([0xFE, 0x0F]));
+
+        // The popped value replaces the incremented `SP`
+        assert_eq!(cpu.memory_mut().write_region(0x0FFE, &[0x00, 0x08]), Ok(()));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8)));
+        assert_eq!(cpu.registers().sp, 0x0800);
+    }
+
+    #[test]
+    fn test_stack_wraps_within_segment() {
+        // PUSH AX, POP BX, POP CX
+        let mut cpu = cpu_with_program(&[0x50, 0x5B, 0x59]);
+        (cpu.registers_mut().ax, cpu.registers_mut().ss, cpu.registers_mut().sp) = (0xBEEF, 0x0100, 0x0000);
+
+        // Pushing with an empty stack wraps around to the top of the segment
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(11)));
+        assert_eq!(cpu.registers().sp, 0xFFFE);
+        assert_eq!(cpu.memory().read_region::<2>(0x10FFE), Ok([0xEF, 0xBE]));
+
+        // Popping the last word wraps back around to the bottom
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8)));
+        assert_eq!((cpu.registers().bx, cpu.registers().sp), (0xBEEF, 0x0000));
+
+        // A word at an odd `SP` of `FFFFh` takes its high byte from the start of the segment
+        cpu.registers_mut().sp = 0xFFFF;
+        assert_eq!(cpu.memory_mut().write(0x10FFF, 0x34), Ok(()));
+        assert_eq!(cpu.memory_mut().write(0x01000, 0x12), Ok(()));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8)));
+        assert_eq!((cpu.registers().cx, cpu.registers().sp), (0x1234, 0x0001));
+    }
+
+    #[test]
+    fn test_push_pop_faults() {
+        // PUSH AX, POP AX
+        let mut cpu = cpu_with_program(&[0x50, 0x58]);
+        (cpu.registers_mut().ss, cpu.registers_mut().sp) = (0x3000, 0x0010);
+
+        // A stack in unmapped memory leaves `SP` untouched
+        assert_eq!(cpu.step(), Err(CpuFault::MemoryFault(BusDeviceError::AddressNotMapped { address: 0x3000E })));
+        assert_eq!(cpu.registers().sp, 0x0010);
+
+        assert_eq!(cpu.step(), Err(CpuFault::MemoryFault(BusDeviceError::AddressNotMapped { address: 0x30010 })));
+        assert_eq!(cpu.registers().sp, 0x0010);
+    }
+
+    #[test]
+    fn test_call_ret_near() {
+        let mut memory = Memory::<0x20000>::empty();
+
+        // CALL 0006h, then at 0006h ADD AX, 1 and RET
+        assert_eq!(memory.write_region(0x10000, &[0xE8, 0x03, 0x00, 0x90, 0x90, 0x90, 0x05, 0x01, 0x00, 0xC3]), Ok(()));
+
+        let mut cpu = cpu_with_memory(memory);
+        (cpu.registers_mut().ax, cpu.registers_mut().sp) = (0x0041, 0x1000);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(19)));
+        assert_eq!((cpu.registers().ip, cpu.registers().sp), (0x0006, 0x0FFE));
+        assert_eq!(cpu.memory().read_region::<2>(0x0FFE), Ok([0x03, 0x00]));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(8)));
+        assert_eq!((cpu.registers().ax, cpu.registers().ip, cpu.registers().sp), (0x0042, 0x0003, 0x1000));
+        assert_eq!(cpu.registers().cs, 0x1000);
+    }
+
+    #[test]
+    fn test_int_iret() {
+        let mut memory = Memory::<0x20000>::empty();
+
+        // INT 21h, with the handler at 1100:0010 loading AX with ABCDh before IRET
+        assert_eq!(memory.write_region(0x10000, &[0xCD, 0x21]), Ok(()));
+        assert_eq!(memory.write_region(0x11010, &[0xB8, 0xCD, 0xAB, 0xCF]), Ok(()));
+        assert_eq!(memory.write_region(0x21 * 4, &[0x10, 0x00, 0x00, 0x11]), Ok(()));
+
+        let mut cpu = cpu_with_memory(memory);
+        cpu.registers_mut().sp = 0x1000;
+        cpu.registers_mut().flags = Flags::from_u16(0x0301);
+        let flags = cpu.registers().flags;
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(51)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip, cpu.registers().sp), (0x1100, 0x0010, 0x0FFA));
+        assert_eq!(cpu.memory().read_region::<6>(0x0FFA), Ok([0x02, 0x00, 0x00, 0x10, 0x03, 0xF3]));
+
+        // The handler runs with interrupts and single stepping disabled
+        assert!(!cpu.registers().flags.interrupt() && !cpu.registers().flags.trap());
+        assert!(cpu.registers().flags.carry());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(24)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip, cpu.registers().sp), (0x1000, 0x0002, 0x1000));
+        assert_eq!(cpu.registers().ax, 0xABCD);
+        assert_eq!(cpu.registers().flags, flags);
+    }
+
+    #[test]
+    fn test_int3_into() {
+        let mut memory = Memory::<0x20000>::empty();
+
+        // INT 3, INTO, INTO, with both vectors pointing at 1100:0000
+        assert_eq!(memory.write_region(0x10000, &[0xCC, 0xCE, 0xCE]), Ok(()));
+        assert_eq!(memory.write_region(3 * 4, &[0x00, 0x00, 0x00, 0x11]), Ok(()));
+        assert_eq!(memory.write_region(4 * 4, &[0x00, 0x00, 0x00, 0x11]), Ok(()));
+
+        let mut cpu = cpu_with_memory(memory);
+        cpu.registers_mut().sp = 0x1000;
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(52)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip), (0x1100, 0x0000));
+        assert_eq!(cpu.memory().read_region::<2>(0x0FFA), Ok([0x01, 0x00]));
+
+        // INTO only traps with the overflow flag set
+        (cpu.registers_mut().cs, cpu.registers_mut().ip, cpu.registers_mut().sp) = (0x1000, 0x0001, 0x1000);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip, cpu.registers().sp), (0x1000, 0x0002, 0x1000));
+
+        cpu.registers_mut().flags.set_overflow(true);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(53)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip), (0x1100, 0x0000));
+        assert_eq!(cpu.memory().read_region::<2>(0x0FFA), Ok([0x03, 0x00]));
+    }
+
+    #[test]
+    fn test_int_host_handler() {
+        let mut memory = Memory::<0x20000>::empty();
+
+        // MOV AH, 02h, MOV DL, 'A', INT 21h, MOV AH, 09h, MOV DX, 0100h, INT 21h, INT 10h
+        assert_eq!(memory.write_region(0x10000, &[0xB4, 0x02, 0xB2, 0x41, 0xCD, 0x21, 0xB4, 0x09, 0xBA, 0x00, 0x01, 0xCD, 0x21, 0xCD, 0x10]), Ok(()));
+        assert_eq!(memory.write_region(0x0100, b"BC$"), Ok(()));
+        assert_eq!(memory.write_region(0x10 * 4, &[0x00, 0x00, 0x00, 0x11]), Ok(()));
+
+        let mut cpu = cpu_with_memory(memory);
+        cpu.registers_mut().sp = 0x1000;
+
+        // DOS character and string output, collected by the host
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&output);
+
+        cpu.register_interrupt_handler(0x21, Box::new(move |registers, memory| {
+            match registers.ah() {
+                0x02 => sink.borrow_mut().push(registers.dl()),
+                0x09 => {
+                    let address = SegmentedAddress::new(registers.ds, registers.dx).to_linear();
+                    sink.borrow_mut().extend(memory.read_terminated(address, b'$', 0x100)?.0);
+                }
+                _ => return Err(CpuFault::InvalidOpcode(0xCD))
+            }
+
+            Ok(())
+        }));
+
+        for _ in 0..3 {
+            cpu.step().unwrap();
+        }
+
+        // The handler replaces the routine in the interrupt vector table, so nothing is pushed
+        assert_eq!((cpu.registers().cs, cpu.registers().ip, cpu.registers().sp), (0x1000, 0x0006, 0x1000));
+
+        for _ in 0..3 {
+            cpu.step().unwrap();
+        }
+
+        assert_eq!(*output.borrow(), b"ABC");
+
+        // Other vectors still go through the interrupt vector table
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(51)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip, cpu.registers().sp), (0x1100, 0x0000, 0x0FFA));
+
+        // Faults raised by the handler are reported from the `INT` instruction, and removing it restores the table
+        (cpu.registers_mut().cs, cpu.registers_mut().ip, cpu.registers_mut().ax) = (0x1000, 0x000B, 0x4C00);
+        assert_eq!(cpu.step(), Err(CpuFault::InvalidOpcode(0xCD)));
+        assert_eq!(cpu.fault_address(), Some(SegmentedAddress::new(0x1000, 0x000B)));
+
+        assert!(cpu.unregister_interrupt_handler(0x21).is_some());
+        (cpu.registers_mut().ip, cpu.registers_mut().sp) = (0x000B, 0x1000);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(51)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip), (0x0000, 0x0000));
+    }
+
+    #[test]
+    fn test_in_out() {
+        // OUT 20h, AL, OUT DX, AX, IN AL, 21h, IN AX, DX, IN AL, 60h
+        let mut cpu = cpu_with_program(&[0xE6, 0x20, 0xEF, 0xE4, 0x21, 0xED, 0xE4, 0x60]);
+        *cpu.io_mut() = IoMap::new().with_range(0x20..=0x21, Box::new(Latch::new([0; 2])));
+        (cpu.registers_mut().ax, cpu.registers_mut().dx) = (0x1234, 0x0020);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.io().read_port(0x20), Ok(0x34));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(12)));
+        assert_eq!((cpu.io().read_port(0x20), cpu.io().read_port(0x21)), (Ok(0x34), Ok(0x12)));
+
+        cpu.registers_mut().ax = 0;
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.registers().ax, 0x0012);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(12)));
+        assert_eq!(cpu.registers().ax, 0x1234);
+
+        // Nothing is mapped at the keyboard controller, so the port floats high
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.registers().ax, 0x12FF);
+    }
+
+    #[test]
+    fn test_port_words_wrap() {
+        let mut cpu = cpu_with_program(&[]);
+        *cpu.io_mut() = IoMap::new()
+            .with_range(0x0000..=0x0000, Box::new(Latch::new([0; 1])))
+            .with_range(0xFFFF..=0xFFFF, Box::new(Latch::new([0; 1])));
+
+        assert_eq!(cpu.out_word(0xFFFF, 0xBEEF), Ok(()));
+        assert_eq!((cpu.io().read_port(0xFFFF), cpu.io().read_port(0x0000)), (Ok(0xEF), Ok(0xBE)));
+        assert_eq!(cpu.in_word(0xFFFF), Ok(0xBEEF));
+
+        // Unmapped ports ignore writes and read as open bus
+        assert_eq!(cpu.out_word(0x0001, 0x0000), Ok(()));
+        assert_eq!(cpu.in_word(0x0000), Ok(0xFFBE));
+    }
+
+    #[test]
+    fn test_call_ret_far() {
+        let mut memory = Memory::<0x20000>::empty();
+
+        // CALL 1100:0000, then at 1100:0000 ADD AX, 1 and RETF
+        assert_eq!(memory.write_region(0x10000, &[0x9A, 0x00, 0x00, 0x00, 0x11]), Ok(()));
+        assert_eq!(memory.write_region(0x11000, &[0x05, 0x01, 0x00, 0xCB]), Ok(()));
+
+        let mut cpu = cpu_with_memory(memory);
+        cpu.registers_mut().sp = 0x1000;
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(28)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip, cpu.registers().sp), (0x1100, 0x0000, 0x0FFC));
+        assert_eq!(cpu.memory().read_region::<4>(0x0FFC), Ok([0x05, 0x00, 0x00, 0x10]));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(18)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip, cpu.registers().sp), (0x1000, 0x0005, 0x1000));
+        assert_eq!(cpu.registers().ax, 0x0001);
+    }
+
+    #[test]
+    fn test_ret_releases_arguments() {
+        let mut memory = Memory::<0x20000>::empty();
+
+        // PUSH AX, PUSH BX, CALL 0010h, then PUSH AX and CALL 1100:0000
+        assert_eq!(memory.write_region(0x10000, &[0x50, 0x53, 0xE8, 0x0B, 0x00, 0x50, 0x9A, 0x00, 0x00, 0x00, 0x11]), Ok(()));
+
+        // ADD AX, 1 and RET 4 at 0010h, ADD AX, 1 and RETF 2 at 1100:0000
+        assert_eq!(memory.write_region(0x10010, &[0x05, 0x01, 0x00, 0xC2, 0x04, 0x00]), Ok(()));
+        assert_eq!(memory.write_region(0x11000, &[0x05, 0x01, 0x00, 0xCA, 0x02, 0x00]), Ok(()));
+
+        let mut cpu = cpu_with_memory(memory);
+        cpu.registers_mut().sp = 0x1000;
+
+        for _ in 0..4 {
+            assert!(cpu.step().is_ok());
+        }
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(12)));
+        assert_eq!((cpu.registers().ax, cpu.registers().ip, cpu.registers().sp), (0x0001, 0x0005, 0x1000));
+
+        for _ in 0..3 {
+            assert!(cpu.step().is_ok());
+        }
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(17)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip, cpu.registers().sp), (0x1000, 0x000B, 0x1000));
+        assert_eq!(cpu.registers().ax, 0x0002);
+    }
+
+    #[test]
+    fn test_call_indirect() {
+        // CALL BX, CALL FAR [0200h]
+        let mut cpu = cpu_with_program(&[0xFF, 0xD3]);
+        (cpu.registers_mut().bx, cpu.registers_mut().sp) = (0x0100, 0x1000);
+        assert_eq!(cpu.memory_mut().write_region(0x10100, &[0xFF, 0x1E, 0x00, 0x02]), Ok(()));
+        assert_eq!(cpu.memory_mut().write_region(0x0200, &[0x34, 0x12, 0x00, 0x11]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(16)));
+        assert_eq!((cpu.registers().ip, cpu.registers().sp), (0x0100, 0x0FFE));
+        assert_eq!(cpu.memory().read_region::<2>(0x0FFE), Ok([0x02, 0x00]));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(37 + 6)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip, cpu.registers().sp), (0x1100, 0x1234, 0x0FFA));
+        assert_eq!(cpu.memory().read_region::<4>(0x0FFA), Ok([0x04, 0x01, 0x00, 0x10]));
+    }
+
+    #[test]
+    fn test_conditional_jumps() {
+        const C: u16 = 1 << 0;
+        const P: u16 = 1 << 2;
+        const Z: u16 = 1 << 6;
+        const S: u16 = 1 << 7;
+        const O: u16 = 1 << 11;
+
+        // Each opcode with a set of flags under which it is taken and a set under which it is not
+        let cases = [
+            (0x70, O, 0), (0x71, 0, O),
+            (0x72, C, Z), (0x73, Z, C),
+            (0x74, Z, C), (0x75, C, Z),
+            (0x76, Z, S), (0x77, S, C),
+            (0x78, S, O), (0x79, O, S),
+            (0x7A, P, Z), (0x7B, Z, P),
+            (0x7C, O, S | O), (0x7D, S | O, S),
+            (0x7E, Z | S | O, S | O), (0x7F, S | O, Z)
+        ];
+
+        for (opcode, taken, not_taken) in cases {
+            for (flags, ip, cycles) in [(taken, 0x0012, 16), (not_taken, 0x0002, 4)] {
+                let mut cpu = cpu_with_program(&[opcode, 0x10]);
+                cpu.registers_mut().flags = Flags::from_u16(flags);
+
+                assert_eq!(cpu.step(), Ok(StepResult::Ok(cycles)), "opcode {opcode:02X} with flags {flags:04X}");
+                assert_eq!(cpu.registers().ip, ip, "opcode {opcode:02X} with flags {flags:04X}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_conditional_jump_backwards() {
+        // SUB CX, 1 then JNE back to it, leaving the loop once `CX` reaches zero
+        let mut cpu = cpu_with_program(&[0x83, 0xE9, 0x01, 0x75, 0xFB]);
+        cpu.registers_mut().cx = 3;
+
+        let mut cycles = 0;
+        while cpu.registers().ip != 5 {
+            let StepResult::Ok(taken) = cpu.step().unwrap() else { panic!() };
+            cycles += taken;
+        }
+
+        assert_eq!(cpu.registers().cx, 0);
+        assert_eq!(cycles, 3 * 4 + 2 * 16 + 4);
+    }
+
+    #[test]
+    fn test_loop_sum() {
+        // MOV AX, 0; MOV CX, 5; then ADD AX, CX and LOOP back to it
+        let mut cpu = cpu_with_program(&[0xB8, 0x00, 0x00, 0xB9, 0x05, 0x00, 0x01, 0xC8, 0xE2, 0xFC]);
+
+        let mut cycles = 0;
+        while cpu.registers().ip != 10 {
+            let StepResult::Ok(taken) = cpu.step().unwrap() else { panic!() };
+            cycles += taken;
+        }
+
+        assert_eq!((cpu.registers().ax, cpu.registers().cx), (5 + 4 + 3 + 2 + 1, 0));
+        assert_eq!(cycles, 4 + 4 + 5 * 3 + 4 * 17 + 5);
+    }
+
+    #[test]
+    fn test_loop_conditions() {
+        // LOOPE, LOOPNE and JCXZ, each branching forward by 0x10
+        for (opcode, cx, zero, taken, cx_after, cycles) in [
+            (0xE1, 2, true, true, 1, 18), (0xE1, 2, false, false, 1, 6), (0xE1, 1, true, false, 0, 6),
+            (0xE0, 2, false, true, 1, 19), (0xE0, 2, true, false, 1, 5), (0xE0, 1, false, false, 0, 5),
+            (0xE3, 0, false, true, 0, 18), (0xE3, 1, true, false, 1, 6),
+            (0xE2, 0, false, true, 0xFFFF, 17)
+        ] {
+            let mut cpu = cpu_with_program(&[opcode, 0x10]);
+            cpu.registers_mut().cx = cx;
+            cpu.registers_mut().flags.set_zero(zero);
+            let flags = cpu.registers().flags;
+
+            assert_eq!(cpu.step(), Ok(StepResult::Ok(cycles)), "opcode {opcode:02X} with CX {cx}");
+            assert_eq!(cpu.registers().ip, if taken { 0x12 } else { 0x02 }, "opcode {opcode:02X} with CX {cx}");
+            assert_eq!(cpu.registers().cx, cx_after);
+            assert_eq!(cpu.registers().flags, flags);
+        }
+    }
+
+    #[test]
+    fn test_lea_every_mode() {
+        // LEA AX with each `r/m` field under each `mod` field, without displacement, with -2 and with 8000h
+        let expected: [u16; 8] = [0x1030, 0x1004, 0x2030, 0x2004, 0x0030, 0x0004, 0x1234, 0x1000];
+        let cycles = [7, 8, 8, 7, 5, 5, 6, 5];
+
+        for rm in 0..8u8 {
+            for (mode, displacement, delta, extra_cycles) in [(0b00, &[][..], 0x0000, 0), (0b01, &[0xFE][..], 0xFFFE, 4), (0b10, &[0x00, 0x80][..], 0x8000, 4)] {
+                let mut program = vec![0x8D, mode << 6 | rm];
+
+                // Without a displacement, `r/m` 110 is a direct address rather than `BP`
+                let (offset, ea_cycles) = match (mode, rm) {
+                    (0b00, 6) => {
+                        program.extend([0x34, 0x12]);
+                        (0x1234, 6)
+                    }
+                    (_, 6) => (0x2000u16.wrapping_add(delta), 9),
+                    _ => (expected[usize::from(rm)].wrapping_add(delta), cycles[usize::from(rm)] + extra_cycles)
+                };
+                program.extend(displacement);
+
+                let mut cpu = cpu_with_program(&program);
+                let registers = cpu.registers_mut();
+                (registers.bx, registers.bp, registers.si, registers.di) = (0x1000, 0x2000, 0x0030, 0x0004);
+
+                // Every data and stack access would fault, so the address must not be dereferenced
+                (registers.ds, registers.ss) = (0x3000, 0x3000);
+
+                assert_eq!(cpu.step(), Ok(StepResult::Ok(2 + ea_cycles)), "mod {mode:02b} r/m {rm:03b}");
+                assert_eq!(cpu.registers().ax, offset, "mod {mode:02b} r/m {rm:03b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_lds_les() {
+        // LDS SI, [BX], then LES DI, [0200h] using the new `DS`
+        let mut cpu = cpu_with_program(&[0xC5, 0x37, 0xC4, 0x3E, 0x00, 0x02]);
+        cpu.registers_mut().bx = 0x0100;
+        assert_eq!(cpu.memory_mut().write_region(0x0100, &[0x78, 0x56, 0x40, 0x00]), Ok(()));
+        assert_eq!(cpu.memory_mut().write_region(0x0600, &[0xCD, 0xAB, 0x00, 0xB8]), Ok(()));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(16 + 5)));
+        assert_eq!((cpu.registers().si, cpu.registers().ds), (0x5678, 0x0040));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(16 + 6)));
+        assert_eq!((cpu.registers().di, cpu.registers().es), (0xABCD, 0xB800));
+        assert_eq!(cpu.registers().ds, 0x0040);
+    }
+
+    #[test]
+    fn test_decimal_adjust() {
+        // ADD AL, BL, DAA, SUB AL, BL, DAS
+        let mut cpu = cpu_with_program(&[0x00, 0xD8, 0x27, 0x28, 0xD8, 0x2F]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x0079, 0x0035);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(3)));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.registers().ax, 0x0014);
+        assert!(cpu.registers().flags.carry());
+
+        // 14 - 35 borrows, leaving 79
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(3)));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.registers().ax, 0x0079);
+        assert!(cpu.registers().flags.carry());
+    }
+
+    #[test]
+    fn test_ascii_adjust() {
+        // ADD AL, BL, AAA, then MUL BL, AAM, AAD, AAM 0
+        let mut cpu = cpu_with_program(&[0x00, 0xD8, 0x37, 0xF6, 0xE3, 0xD4, 0x0A, 0xD5, 0x0A, 0xD4, 0x00]);
+        (cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x0038, 0x0039);
+
+        // '8' + '9' is 17 in unpacked BCD
+        cpu.step().unwrap();
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(4)));
+        assert_eq!(cpu.registers().ax, 0x0107);
+        assert!(cpu.registers().flags.auxiliary() && cpu.registers().flags.carry());
+
+        // 7 * 9 is 63 in unpacked BCD, and 3Fh in binary again
+        (cpu.registers_mut().ax, cpu.registers_mut().bx) = (0x0007, 0x0009);
+        cpu.step().unwrap();
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(83)));
+        assert_eq!(cpu.registers().ax, 0x0603);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(60)));
+        assert_eq!(cpu.registers().ax, 0x003F);
+
+        assert_eq!(cpu.step(), Err(CpuFault::DivisionByZero));
+    }
+}
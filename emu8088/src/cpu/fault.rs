@@ -0,0 +1,63 @@
+use std::fmt::Display;
+
+use mem::{BusDeviceError, IoError};
+
+/// A condition which stops the processor from completing an instruction.
+///
+/// The processor records the address of the instruction which raised the fault, available from
+/// `Cpu::fault_address` until the next step, so that a debugger can report where it happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CpuFault {
+    /// The instruction starting with this opcode byte is not a valid instruction, or is not implemented.
+    InvalidOpcode(u8),
+    /// An access to memory failed.
+    MemoryFault(BusDeviceError),
+    /// A `DIV` or `IDIV` divided by zero, or its quotient did not fit in the destination register. On the processor
+    /// both raise interrupt 0.
+    DivisionByZero,
+    /// The processor was stepped while halted with interrupts disabled, so that only a reset can resume it.
+    Halted,
+    /// An access to an I/O port failed.
+    IoFault(IoError),
+    /// A push would have moved `SP` below the stack limit set with `Cpu::set_stack_limit`, or wrapped it around the
+    /// stack segment. The processor itself has no such check, so this is only raised when a limit is set.
+    StackOverflow
+}
+
+impl From<BusDeviceError> for CpuFault {
+    fn from(value: BusDeviceError) -> Self {
+        Self::MemoryFault(value)
+    }
+}
+
+impl From<IoError> for CpuFault {
+    fn from(value: IoError) -> Self {
+        Self::IoFault(value)
+    }
+}
+
+impl Display for CpuFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidOpcode(opcode) => write!(f, "invalid opcode {opcode:02X}h"),
+            Self::MemoryFault(error) => write!(f, "memory fault: {error:X?}"),
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::Halted => write!(f, "halted with interrupts disabled"),
+            Self::IoFault(error) => write!(f, "I/O fault: {error:X?}"),
+            Self::StackOverflow => write!(f, "stack overflow")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_fault_display() {
+        assert_eq!(CpuFault::InvalidOpcode(0x0F).to_string(), "invalid opcode 0Fh");
+        assert_eq!(CpuFault::from(BusDeviceError::AddressNotMapped { address: 0xA0000 }).to_string(),
+            "memory fault: AddressNotMapped { address: A0000 }");
+        assert_eq!(CpuFault::from(IoError::PortNotMapped { port: 0x60 }), CpuFault::IoFault(IoError::PortNotMapped { port: 0x60 }));
+    }
+}
@@ -0,0 +1,216 @@
+/// The 8086 FLAGS register.
+///
+/// Only the nine defined flag bits can be changed. On the 8086 bits 1 and 12 to 15 always read as 1 and bits 3 and 5
+/// always read as 0, so those reserved bits are fixed whenever a value is loaded into the register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Flags(u16);
+
+impl Flags {
+    /// Carry flag (`CF`)
+    pub const CARRY: u16 = 1 << 0;
+    /// Parity flag (`PF`)
+    pub const PARITY: u16 = 1 << 2;
+    /// Auxiliary carry flag (`AF`)
+    pub const AUXILIARY: u16 = 1 << 4;
+    /// Zero flag (`ZF`)
+    pub const ZERO: u16 = 1 << 6;
+    /// Sign flag (`SF`)
+    pub const SIGN: u16 = 1 << 7;
+    /// Trap flag (`TF`)
+    pub const TRAP: u16 = 1 << 8;
+    /// Interrupt enable flag (`IF`)
+    pub const INTERRUPT: u16 = 1 << 9;
+    /// Direction flag (`DF`)
+    pub const DIRECTION: u16 = 1 << 10;
+    /// Overflow flag (`OF`)
+    pub const OVERFLOW: u16 = 1 << 11;
+
+    /// Mask of every defined flag bit.
+    pub const DEFINED: u16 = Self::CARRY | Self::PARITY | Self::AUXILIARY | Self::ZERO | Self::SIGN | Self::TRAP | Self::INTERRUPT | Self::DIRECTION | Self::OVERFLOW;
+
+    /// Reserved bits which always read as 1 on the 8086.
+    pub const RESERVED_SET: u16 = 0xF002;
+
+    #[must_use]
+    /// Constructs a new FLAGS register with every flag cleared.
+    pub const fn new() -> Self {
+        Self(Self::RESERVED_SET)
+    }
+
+    #[must_use]
+    /// Constructs a FLAGS register from a raw value, such as one popped from the stack, fixing the reserved bits.
+    pub const fn from_u16(value: u16) -> Self {
+        Self(value & Self::DEFINED | Self::RESERVED_SET)
+    }
+
+    #[must_use]
+    /// Returns the raw value of the FLAGS register, as it would be pushed to the stack.
+    pub const fn to_u16(self) -> u16 {
+        self.0
+    }
+
+    /// Sets or clears the bits of `mask`.
+    const fn set(&mut self, mask: u16, value: bool) {
+        if value {
+            self.0 |= mask;
+        }
+        else {
+            self.0 &= !mask;
+        }
+    }
+
+    #[must_use]
+    /// Returns the state of the carry flag (`CF`).
+    pub const fn carry(self) -> bool {
+        self.0 & Self::CARRY != 0
+    }
+
+    /// Sets the state of the carry flag (`CF`).
+    pub const fn set_carry(&mut self, value: bool) {
+        self.set(Self::CARRY, value);
+    }
+
+    #[must_use]
+    /// Returns the state of the parity flag (`PF`).
+    pub const fn parity(self) -> bool {
+        self.0 & Self::PARITY != 0
+    }
+
+    /// Sets the state of the parity flag (`PF`).
+    pub const fn set_parity(&mut self, value: bool) {
+        self.set(Self::PARITY, value);
+    }
+
+    #[must_use]
+    /// Returns the state of the auxiliary carry flag (`AF`).
+    pub const fn auxiliary(self) -> bool {
+        self.0 & Self::AUXILIARY != 0
+    }
+
+    /// Sets the state of the auxiliary carry flag (`AF`).
+    pub const fn set_auxiliary(&mut self, value: bool) {
+        self.set(Self::AUXILIARY, value);
+    }
+
+    #[must_use]
+    /// Returns the state of the zero flag (`ZF`).
+    pub const fn zero(self) -> bool {
+        self.0 & Self::ZERO != 0
+    }
+
+    /// Sets the state of the zero flag (`ZF`).
+    pub const fn set_zero(&mut self, value: bool) {
+        self.set(Self::ZERO, value);
+    }
+
+    #[must_use]
+    /// Returns the state of the sign flag (`SF`).
+    pub const fn sign(self) -> bool {
+        self.0 & Self::SIGN != 0
+    }
+
+    /// Sets the state of the sign flag (`SF`).
+    pub const fn set_sign(&mut self, value: bool) {
+        self.set(Self::SIGN, value);
+    }
+
+    #[must_use]
+    /// Returns the state of the trap flag (`TF`).
+    pub const fn trap(self) -> bool {
+        self.0 & Self::TRAP != 0
+    }
+
+    /// Sets the state of the trap flag (`TF`).
+    pub const fn set_trap(&mut self, value: bool) {
+        self.set(Self::TRAP, value);
+    }
+
+    #[must_use]
+    /// Returns the state of the interrupt enable flag (`IF`).
+    pub const fn interrupt(self) -> bool {
+        self.0 & Self::INTERRUPT != 0
+    }
+
+    /// Sets the state of the interrupt enable flag (`IF`).
+    pub const fn set_interrupt(&mut self, value: bool) {
+        self.set(Self::INTERRUPT, value);
+    }
+
+    #[must_use]
+    /// Returns the state of the direction flag (`DF`).
+    pub const fn direction(self) -> bool {
+        self.0 & Self::DIRECTION != 0
+    }
+
+    /// Sets the state of the direction flag (`DF`).
+    pub const fn set_direction(&mut self, value: bool) {
+        self.set(Self::DIRECTION, value);
+    }
+
+    #[must_use]
+    /// Returns the state of the overflow flag (`OF`).
+    pub const fn overflow(self) -> bool {
+        self.0 & Self::OVERFLOW != 0
+    }
+
+    /// Sets the state of the overflow flag (`OF`).
+    pub const fn set_overflow(&mut self, value: bool) {
+        self.set(Self::OVERFLOW, value);
+    }
+}
+
+impl Default for Flags {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_reserved_bits() {
+        assert_eq!(Flags::new().to_u16(), 0xF002);
+        assert_eq!(Flags::default(), Flags::new());
+        assert_eq!(Flags::from_u16(0x0000).to_u16(), 0xF002);
+        assert_eq!(Flags::from_u16(0xFFFF).to_u16(), 0xFFD7);
+        assert_eq!(Flags::from_u16(0x0ED5).to_u16(), 0xFED7);
+    }
+
+    type Accessor = (fn(Flags) -> bool, fn(&mut Flags, bool), u16);
+
+    #[test]
+    fn test_flags_accessors() {
+        let accessors: [Accessor; 9] = [
+            (Flags::carry, Flags::set_carry, 0x0001),
+            (Flags::parity, Flags::set_parity, 0x0004),
+            (Flags::auxiliary, Flags::set_auxiliary, 0x0010),
+            (Flags::zero, Flags::set_zero, 0x0040),
+            (Flags::sign, Flags::set_sign, 0x0080),
+            (Flags::trap, Flags::set_trap, 0x0100),
+            (Flags::interrupt, Flags::set_interrupt, 0x0200),
+            (Flags::direction, Flags::set_direction, 0x0400),
+            (Flags::overflow, Flags::set_overflow, 0x0800),
+        ];
+
+        for (get, set, bit) in accessors {
+            let mut flags = Flags::new();
+            assert!(!get(flags));
+
+            set(&mut flags, true);
+            assert!(get(flags));
+            assert_eq!(flags.to_u16(), 0xF002 | bit);
+            assert_eq!(Flags::from_u16(bit), flags);
+
+            set(&mut flags, false);
+            assert!(!get(flags));
+            assert_eq!(flags, Flags::new());
+
+            let mut all = Flags::from_u16(0xFFFF);
+            assert!(get(all));
+            set(&mut all, false);
+            assert_eq!(all.to_u16(), 0xFFD7 & !bit);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+pub mod access;
+
+pub mod alu;
+
+pub mod address;
+pub use address::*;
+
+pub mod effective_address;
+pub use effective_address::*;
+
+pub mod execute;
+
+pub mod string;
+
+pub mod fault;
+pub use fault::*;
+
+pub mod flags;
+pub use flags::*;
+
+pub mod registers;
+pub use registers::*;
+
+pub mod processor;
+pub use processor::*;
+
+pub mod debugger;
+pub use debugger::*;
+
+#[cfg(test)]
+pub(crate) mod testing;
\ No newline at end of file
@@ -0,0 +1,553 @@
+use std::{cell::Cell, collections::HashMap};
+
+use mem::{BusDevice, BusDeviceError, IoDevice, IoError, IoMap, MemoryMap, Shared};
+
+use crate::{CpuFault, DecodeError, InstructionDecoder, Opcode, Registers, SegmentedAddress};
+
+/// The outcome of executing a single instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StepResult {
+    /// The instruction completed, taking the given number of clock cycles.
+    Ok(u32),
+    /// The instruction was `HLT`, and the processor is waiting for an interrupt.
+    Halted
+}
+
+/// Host side handler for a software interrupt, given the registers and memory to service a call made by the guest.
+pub type InterruptHandler = Box<dyn FnMut(&mut Registers, &mut MemoryMap) -> Result<(), CpuFault>>;
+
+/// A source of hardware interrupts on the `INTR` line, such as a `Pic8259`, polled by the processor between
+/// instructions while interrupts are enabled.
+pub trait InterruptController {
+    /// Acknowledges the highest priority pending request, returning its interrupt vector, or returns `None` if no
+    /// request is pending.
+    fn poll_interrupt(&mut self) -> Option<u8>;
+}
+
+impl<T: InterruptController> InterruptController for Shared<T> {
+    fn poll_interrupt(&mut self) -> Option<u8> {
+        self.borrow_mut().poll_interrupt()
+    }
+}
+
+/// A view of a single code segment as a bus device, addressed by offset, so that instructions running past the end of
+/// the segment wrap around to its start as they do on the processor.
+///
+/// As the instruction is read, the view notes its opcode byte, which is the first byte read that is not a prefix. If
+/// every byte read is a prefix, it notes the last prefix instead. This lets the processor report the opcode without
+/// reading the instruction a second time.
+struct CodeSegment<'a> {
+    memory: &'a MemoryMap,
+    segment: u16,
+    opcode: Cell<Option<u8>>
+}
+
+impl CodeSegment<'_> {
+    /// Returns the physical address of `offset` within the segment, wrapping offsets past the end of the segment.
+    const fn linear(&self, offset: usize) -> usize {
+        SegmentedAddress::new(self.segment, 0).to_linear() + (offset & 0xFFFF)
+    }
+}
+
+impl BusDevice for CodeSegment<'_> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        let byte = self.memory.read(self.linear(address))?;
+
+        if self.opcode.get().is_none_or(|opcode| Opcode::from_byte(opcode).is_prefix()) {
+            self.opcode.set(Some(byte));
+        }
+
+        Ok(byte)
+    }
+
+    fn write(&mut self, address: usize, _data: u8) -> Result<(), BusDeviceError> {
+        Err(BusDeviceError::AddressNotWritable { address: self.linear(address) })
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.memory.peek(self.linear(address))
+    }
+}
+
+/// The 8086 execution unit, owning the register file along with the memory and I/O address spaces it executes
+/// against.
+pub struct Cpu {
+    pub(super) registers: Registers,
+    pub(super) memory: MemoryMap,
+    pub(super) io: IoMap,
+    pub(super) halted: bool,
+    pub(super) stack_limit: Option<u16>,
+    pub(super) interrupt_handlers: HashMap<u8, InterruptHandler>,
+    interrupt_controller: Option<Box<dyn InterruptController>>,
+    fault_address: Option<SegmentedAddress>
+}
+
+impl Cpu {
+    #[must_use]
+    /// Constructs a processor attached to `memory` and `io`, with its registers in their power-on state.
+    pub fn new(memory: MemoryMap, io: IoMap) -> Self {
+        let mut registers = Registers::new();
+        registers.reset();
+
+        Self {
+            registers,
+            memory,
+            io,
+            halted: false,
+            stack_limit: None,
+            interrupt_handlers: HashMap::new(),
+            interrupt_controller: None,
+            fault_address: None
+        }
+    }
+
+    /// Puts the registers back into their power-on state and leaves any halt. The memory and I/O devices are left
+    /// unchanged.
+    pub const fn reset(&mut self) {
+        self.registers.reset();
+        self.halted = false;
+        self.fault_address = None;
+    }
+
+    #[must_use]
+    /// Returns `true` if the processor has executed `HLT` and is waiting for an interrupt.
+    pub const fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    #[must_use]
+    /// Returns the address of the instruction which raised the fault returned by the last step, or `None` if the last
+    /// step succeeded. For a fault while halted this is the address the processor is halted at.
+    pub const fn fault_address(&self) -> Option<SegmentedAddress> {
+        self.fault_address
+    }
+
+    #[must_use]
+    /// Returns the lowest value of `SP` a push may leave, if stack checking is enabled.
+    pub const fn stack_limit(&self) -> Option<u16> {
+        self.stack_limit
+    }
+
+    /// Enables checking pushes against a stack `limit`, so that a push leaving `SP` below it or wrapping `SP` around
+    /// the stack segment raises `CpuFault::StackOverflow`, or disables checking with `None`. The 8086 has no such
+    /// check, so it is disabled by default, but it catches runaway recursion before it overwrites data below the
+    /// stack.
+    pub const fn set_stack_limit(&mut self, limit: Option<u16>) {
+        self.stack_limit = limit;
+    }
+
+    /// Registers a host side `handler` for software interrupt `vector`, replacing any previous handler. When the guest
+    /// executes `INT` with that vector the handler is called in place of the routine in the interrupt vector table,
+    /// with `IP` already past the instruction, allowing BIOS and DOS services to be emulated without placing stubs in
+    /// memory. Hardware interrupts still go through the interrupt vector table.
+    pub fn register_interrupt_handler(&mut self, vector: u8, handler: InterruptHandler) {
+        self.interrupt_handlers.insert(vector, handler);
+    }
+
+    /// Removes the host side handler for software interrupt `vector`, returning it if there was one, so that the
+    /// interrupt goes through the interrupt vector table again.
+    pub fn unregister_interrupt_handler(&mut self, vector: u8) -> Option<InterruptHandler> {
+        self.interrupt_handlers.remove(&vector)
+    }
+
+    /// Attaches the `controller` driving the `INTR` line, replacing any previous one. Between instructions, while `IF`
+    /// is set, the controller is polled and any interrupt it acknowledges is entered through the interrupt vector
+    /// table, ending a halt.
+    pub fn set_interrupt_controller(&mut self, controller: Box<dyn InterruptController>) {
+        self.interrupt_controller = Some(controller);
+    }
+
+    /// Detaches the interrupt controller, returning it if there was one.
+    pub fn take_interrupt_controller(&mut self) -> Option<Box<dyn InterruptController>> {
+        self.interrupt_controller.take()
+    }
+
+    #[must_use]
+    /// Returns a reference to the register file.
+    pub const fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the register file.
+    pub const fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    #[must_use]
+    /// Returns a reference to the memory address space.
+    pub const fn memory(&self) -> &MemoryMap {
+        &self.memory
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the memory address space.
+    pub const fn memory_mut(&mut self) -> &mut MemoryMap {
+        &mut self.memory
+    }
+
+    #[must_use]
+    /// Returns a reference to the I/O address space.
+    pub const fn io(&self) -> &IoMap {
+        &self.io
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the I/O address space.
+    pub const fn io_mut(&mut self) -> &mut IoMap {
+        &mut self.io
+    }
+
+    #[must_use]
+    /// Returns the address of the next byte of the instruction stream, `CS:IP`.
+    pub const fn instruction_pointer(&self) -> SegmentedAddress {
+        SegmentedAddress::new(self.registers.cs, self.registers.ip)
+    }
+
+    /// Reads the byte at `CS:IP` and advances `IP` past it, wrapping within the code segment.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte cannot be read, in which case `IP` is left unchanged.
+    pub fn fetch_byte(&mut self) -> Result<u8, CpuFault> {
+        let byte = self.memory.read(self.instruction_pointer().to_linear())?;
+        self.registers.ip = self.registers.ip.wrapping_add(1);
+
+        Ok(byte)
+    }
+
+    /// Reads the little endian word at `CS:IP` and advances `IP` past it, wrapping within the code segment. At
+    /// `IP = FFFFh` the high byte is read from the start of the code segment.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if either byte cannot be read, in which case `IP` is left unchanged.
+    pub fn fetch_word(&mut self) -> Result<u16, CpuFault> {
+        let ip = self.registers.ip;
+        let low = self.fetch_byte()?;
+
+        match self.fetch_byte() {
+            Ok(high) => Ok(u16::from_le_bytes([low, high])),
+            Err(fault) => {
+                self.registers.ip = ip;
+                Err(fault)
+            }
+        }
+    }
+
+    /// Reads the byte from I/O `port`. Ports with nothing mapped to them read as `FFh`, as the data bus floats high,
+    /// so that programs probing for hardware see it missing rather than faulting.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the mapped device fails to read the port.
+    pub fn in_byte(&mut self, port: u16) -> Result<u8, CpuFault> {
+        match self.io.read_port(port) {
+            Err(IoError::PortNotMapped { .. }) => Ok(0xFF),
+            result => Ok(result?)
+        }
+    }
+
+    /// Writes `data` to I/O `port`. Writes to ports with nothing mapped to them are ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the mapped device fails to write the port.
+    pub fn out_byte(&mut self, port: u16, data: u8) -> Result<(), CpuFault> {
+        match self.io.write_port(port, data) {
+            Err(IoError::PortNotMapped { .. }) => Ok(()),
+            result => Ok(result?)
+        }
+    }
+
+    /// Reads a little endian word from I/O `port` and the port after it, wrapping at the top of the I/O address space.
+    /// Unmapped ports read as `FFh` as for `in_byte`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a mapped device fails to read either port.
+    pub fn in_word(&mut self, port: u16) -> Result<u16, CpuFault> {
+        let low = self.in_byte(port)?;
+        let high = self.in_byte(port.wrapping_add(1))?;
+
+        Ok(u16::from_le_bytes([low, high]))
+    }
+
+    /// Writes `data` as a little endian word to I/O `port` and the port after it, wrapping at the top of the I/O
+    /// address space. Writes to unmapped ports are ignored as for `out_byte`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a mapped device fails to write either port.
+    pub fn out_word(&mut self, port: u16, data: u16) -> Result<(), CpuFault> {
+        let [low, high] = data.to_le_bytes();
+
+        self.out_byte(port, low)?;
+        self.out_byte(port.wrapping_add(1), high)
+    }
+
+    /// Executes the instruction at `CS:IP`, leaving `IP` pointing at the following instruction. While halted no
+    /// instructions are executed and `StepResult::Halted` is returned, unless interrupts are disabled so that the
+    /// processor can never leave the halt.
+    ///
+    /// If interrupts are enabled and the interrupt controller has a request pending, the step instead enters the
+    /// interrupt handler, taking the 61 clock cycles of the interrupt acknowledge sequence.
+    ///
+    /// When a fault is returned, the address of the instruction which raised it is available from `fault_address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the instruction cannot be fetched, is not a valid or implemented
+    /// instruction, or faults during execution, or if the processor is halted with interrupts disabled.
+    pub fn step(&mut self) -> Result<StepResult, CpuFault> {
+        let address = self.instruction_pointer();
+        let result = self.step_instruction();
+
+        self.fault_address = result.is_err().then_some(address);
+
+        result
+    }
+
+    /// Executes the instruction at `CS:IP` for `step`.
+    fn step_instruction(&mut self) -> Result<StepResult, CpuFault> {
+        if self.registers.flags.interrupt() {
+            if let Some(vector) = self.interrupt_controller.as_mut().and_then(|controller| controller.poll_interrupt()) {
+                self.halted = false;
+                self.interrupt(vector)?;
+
+                return Ok(StepResult::Ok(61));
+            }
+        }
+
+        if self.halted {
+            return if self.registers.flags.interrupt() { Ok(StepResult::Halted) } else { Err(CpuFault::Halted) };
+        }
+
+        let start = self.registers.ip;
+
+        let code = CodeSegment { memory: &self.memory, segment: self.registers.cs, opcode: Cell::new(None) };
+        let decoded = InstructionDecoder::new().decode(&code, usize::from(start));
+
+        // The opcode byte is reported if the instruction is not implemented, and is only missing if nothing was read
+        let opcode = code.opcode.get().unwrap_or_default();
+
+        let instruction = decoded.map_err(|error| match error {
+            DecodeError::InvalidOpcode { opcode, .. } => CpuFault::InvalidOpcode(opcode),
+            DecodeError::UnexpectedPrefix { prefix, .. } => CpuFault::InvalidOpcode(prefix),
+            DecodeError::InstructionTooLong { .. } => CpuFault::InvalidOpcode(opcode),
+            DecodeError::Bus(error) => CpuFault::MemoryFault(error)
+        })?;
+
+        self.registers.ip = start.wrapping_add(u16::from(instruction.byte_length));
+
+        let result = self.execute(instruction, opcode);
+
+        // Leave an instruction which could not be executed to be reported from its first byte
+        if let Err(CpuFault::InvalidOpcode(_)) = result {
+            self.registers.ip = start;
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::{Counted, Memory, RegionBusDevice};
+
+    use crate::cpu::testing::{cpu_with_program, cpu_with_source};
+
+    use super::*;
+
+    #[test]
+    fn test_cpu_creation() {
+        let memory = MemoryMap::new().with_range(0x00000..=0x003FF, Box::new(Memory::<0x400>::empty()));
+        let mut cpu = Cpu::new(memory, IoMap::new());
+
+        assert_eq!((cpu.registers().cs, cpu.registers().ip), (0xFFFF, 0x0000));
+
+        cpu.registers_mut().ax = 0x1234;
+        cpu.registers_mut().cs = 0x0000;
+        assert_eq!(cpu.memory_mut().write(0x0010, 0x5A), Ok(()));
+
+        assert_eq!(cpu.registers().ax, 0x1234);
+        assert_eq!(cpu.memory().read(0x0010), Ok(0x5A));
+        assert!(cpu.io().mapping(0x60).is_none());
+
+        // Resetting only affects the registers
+        cpu.reset();
+        assert_eq!(*cpu.registers(), { let mut registers = Registers::new(); registers.reset(); registers });
+        assert_eq!(cpu.memory().read(0x0010), Ok(0x5A));
+    }
+
+    #[test]
+    fn test_cpu_fetch() {
+        let memory = MemoryMap::new().with_range(0x10000..=0x1FFFF, Box::new(Memory::<0x10000>::empty()));
+        let mut cpu = Cpu::new(memory, IoMap::new());
+
+        cpu.registers_mut().cs = 0x1000;
+        cpu.registers_mut().ip = 0x0100;
+        assert_eq!(cpu.memory_mut().write_region(0x10100, &[0xB8, 0x34, 0x12]), Ok(()));
+
+        assert_eq!(cpu.fetch_byte(), Ok(0xB8));
+        assert_eq!(cpu.registers().ip, 0x0101);
+        assert_eq!(cpu.fetch_word(), Ok(0x1234));
+        assert_eq!(cpu.registers().ip, 0x0103);
+    }
+
+    #[test]
+    fn test_cpu_fetch_wraps() {
+        let memory = MemoryMap::new().with_range(0x10000..=0x1FFFF, Box::new(Memory::<0x10000>::empty()));
+        let mut cpu = Cpu::new(memory, IoMap::new());
+
+        assert_eq!(cpu.memory_mut().write(0x1FFFF, 0xCD), Ok(()));
+        assert_eq!(cpu.memory_mut().write(0x10000, 0xAB), Ok(()));
+
+        cpu.registers_mut().cs = 0x1000;
+        cpu.registers_mut().ip = 0xFFFF;
+        assert_eq!(cpu.fetch_byte(), Ok(0xCD));
+        assert_eq!(cpu.registers().ip, 0x0000);
+
+        // The high byte of a word straddling the end of the segment comes from its start
+        cpu.registers_mut().ip = 0xFFFF;
+        assert_eq!(cpu.fetch_word(), Ok(0xABCD));
+        assert_eq!(cpu.registers().ip, 0x0001);
+    }
+
+    #[test]
+    fn test_cpu_step() {
+        let mut cpu = cpu_with_source("nop\nhlt\nnop");
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(3)));
+        assert_eq!(cpu.registers().ip, 1);
+        assert!(!cpu.is_halted());
+
+        assert_eq!(cpu.step(), Ok(StepResult::Halted));
+        assert_eq!(cpu.registers().ip, 2);
+        assert!(cpu.is_halted());
+
+        // Once halted, nothing more is executed until the processor is reset, and with interrupts disabled nothing
+        // else can end the halt
+        assert_eq!(cpu.step(), Err(CpuFault::Halted));
+        assert_eq!(cpu.fault_address(), Some(SegmentedAddress::new(0x1000, 2)));
+
+        cpu.registers_mut().flags.set_interrupt(true);
+        assert_eq!(cpu.step(), Ok(StepResult::Halted));
+        assert_eq!(cpu.registers().ip, 2);
+        assert_eq!(cpu.fault_address(), None);
+
+        cpu.reset();
+        assert!(!cpu.is_halted());
+    }
+
+    #[test]
+    fn test_cpu_step_invalid_opcode() {
+        let mut cpu = cpu_with_program(&[0x0F, 0x2E, 0xD6, 0xF3, 0x90, 0x26, 0xFF, 0xD8]);
+
+        // Invalid instructions leave `IP` at their first byte, and report the opcode after any prefixes
+        assert_eq!(cpu.step(), Err(CpuFault::InvalidOpcode(0x0F)));
+        assert_eq!(cpu.registers().ip, 0);
+        assert_eq!(cpu.fault_address(), Some(SegmentedAddress::new(0x1000, 0)));
+
+        cpu.registers_mut().ip = 1;
+        assert_eq!(cpu.step(), Err(CpuFault::InvalidOpcode(0xD6)));
+        assert_eq!(cpu.registers().ip, 1);
+
+        // A repeat prefix before an instruction which is not a string operation is reported as the prefix
+        cpu.registers_mut().ip = 3;
+        assert_eq!(cpu.step(), Err(CpuFault::InvalidOpcode(0xF3)));
+
+        cpu.registers_mut().ip = 5;
+        assert_eq!(cpu.step(), Err(CpuFault::InvalidOpcode(0xFF)));
+        assert_eq!(cpu.registers().ip, 5);
+        assert_eq!(cpu.fault_address(), Some(SegmentedAddress::new(0x1000, 5)));
+    }
+
+    #[test]
+    fn test_cpu_step_all_prefixes() {
+        // A code segment of nothing but prefixes is reported as too long to decode, rather than scanned forever
+        let mut cpu = cpu_with_program(&vec![0x26; 0x10000]);
+        cpu.registers_mut().ip = 0x1234;
+
+        assert!(matches!(cpu.step(), Err(CpuFault::InvalidOpcode(_))));
+        assert_eq!(cpu.registers().ip, 0x1234);
+    }
+
+    #[test]
+    fn test_cpu_step_reads_once() {
+        // ES: NOP, then a prefix at the last mapped byte
+        let mut program = [0x00; 0x100];
+        program[..2].copy_from_slice(&[0x26, 0x90]);
+        program[0xFF] = 0x26;
+
+        let code = Shared::new(Counted::new(Memory::<0x100>::populated(&program)));
+        let mut cpu = Cpu::new(MemoryMap::new().with_range(0x10000..=0x100FF, Box::new(code.clone())), IoMap::new());
+        (cpu.registers_mut().cs, cpu.registers_mut().ip) = (0x1000, 0x0000);
+
+        // Each byte of the instruction is read exactly once
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(3)));
+        assert_eq!(code.borrow().stats().reads, 2);
+
+        // A failed read leaves `IP` at the start of the instruction
+        cpu.registers_mut().ip = 0xFF;
+        assert_eq!(cpu.step(), Err(CpuFault::MemoryFault(BusDeviceError::AddressNotMapped { address: 0x10100 })));
+        assert_eq!(cpu.registers().ip, 0xFF);
+    }
+
+    #[test]
+    fn test_cpu_stack_limit() {
+        // PUSH AX, PUSH AX
+        let mut cpu = cpu_with_program(&[0x50, 0x50]);
+        (cpu.registers_mut().ss, cpu.registers_mut().sp) = (0x1000, 0x0102);
+        cpu.set_stack_limit(Some(0x0100));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(11)));
+        assert_eq!(cpu.step(), Err(CpuFault::StackOverflow));
+        assert_eq!(cpu.fault_address(), Some(SegmentedAddress::new(0x1000, 1)));
+        assert_eq!(cpu.registers().sp, 0x0100);
+
+        // A push wrapping `SP` always overflows a limit, but without a limit wraps around the stack segment as on the processor
+        (cpu.registers_mut().ip, cpu.registers_mut().sp) = (1, 0x0000);
+        cpu.set_stack_limit(Some(0));
+        assert_eq!(cpu.step(), Err(CpuFault::StackOverflow));
+
+        (cpu.registers_mut().ip, cpu.registers_mut().sp) = (1, 0x0000);
+        cpu.set_stack_limit(None);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(11)));
+        assert_eq!(cpu.registers().sp, 0xFFFE);
+    }
+
+    #[test]
+    fn test_cpu_step_wraps() {
+        let mut cpu = cpu_with_program(&[]);
+
+        // A prefixed `NOP` straddling the end of the code segment
+        assert_eq!(cpu.memory_mut().write(0x1FFFF, 0x2E), Ok(()));
+        assert_eq!(cpu.memory_mut().write(0x10000, 0x90), Ok(()));
+        cpu.registers_mut().ip = 0xFFFF;
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(3)));
+        assert_eq!(cpu.registers().ip, 0x0001);
+
+        assert_eq!(cpu.memory_mut().write(0x1FFFF, 0xF4), Ok(()));
+        cpu.registers_mut().ip = 0xFFFF;
+
+        assert_eq!(cpu.step(), Ok(StepResult::Halted));
+        assert_eq!(cpu.registers().ip, 0x0000);
+    }
+
+    #[test]
+    fn test_cpu_fetch_fault() {
+        let memory = MemoryMap::new().with_range(0x0000..=0xFFFF, Box::new(Memory::<0x10000>::empty()));
+        let mut cpu = Cpu::new(memory, IoMap::new());
+
+        // The reset vector at `FFFF:0000` is not mapped
+        assert_eq!(cpu.fetch_byte(), Err(CpuFault::MemoryFault(BusDeviceError::AddressNotMapped { address: 0xFFFF0 })));
+        assert_eq!(cpu.registers().ip, 0x0000);
+
+        cpu.registers_mut().cs = 0x0FFF;
+        cpu.registers_mut().ip = 0x000F;
+        assert_eq!(cpu.fetch_word(), Err(CpuFault::MemoryFault(BusDeviceError::AddressNotMapped { address: 0x10000 })));
+        assert_eq!(cpu.registers().ip, 0x000F);
+    }
+}
@@ -0,0 +1,413 @@
+use super::Flags;
+
+/// The 16-bit general purpose registers, in the order used by the `reg` and `r/m` fields of instruction encodings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Register16 {
+    Ax, Cx, Dx, Bx, Sp, Bp, Si, Di
+}
+
+/// The 8-bit general purpose registers, in the order used by the `reg` and `r/m` fields of instruction encodings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Register8 {
+    Al, Cl, Dl, Bl, Ah, Ch, Dh, Bh
+}
+
+/// The segment registers, in the order used by the `reg` field of instruction encodings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SegmentRegister {
+    Es, Cs, Ss, Ds
+}
+
+impl Register16 {
+    #[must_use]
+    /// Returns the register with the given 3-bit encoding, ignoring any higher bits.
+    pub const fn from_index(index: u8) -> Self {
+        match index & 0b111 {
+            0 => Self::Ax,
+            1 => Self::Cx,
+            2 => Self::Dx,
+            3 => Self::Bx,
+            4 => Self::Sp,
+            5 => Self::Bp,
+            6 => Self::Si,
+            _ => Self::Di
+        }
+    }
+
+    #[must_use]
+    /// Returns the Intel name of the register.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Ax => "AX",
+            Self::Cx => "CX",
+            Self::Dx => "DX",
+            Self::Bx => "BX",
+            Self::Sp => "SP",
+            Self::Bp => "BP",
+            Self::Si => "SI",
+            Self::Di => "DI"
+        }
+    }
+}
+
+impl Register8 {
+    #[must_use]
+    /// Returns the register with the given 3-bit encoding, ignoring any higher bits.
+    pub const fn from_index(index: u8) -> Self {
+        match index & 0b111 {
+            0 => Self::Al,
+            1 => Self::Cl,
+            2 => Self::Dl,
+            3 => Self::Bl,
+            4 => Self::Ah,
+            5 => Self::Ch,
+            6 => Self::Dh,
+            _ => Self::Bh
+        }
+    }
+
+    #[must_use]
+    /// Returns the Intel name of the register.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Al => "AL",
+            Self::Cl => "CL",
+            Self::Dl => "DL",
+            Self::Bl => "BL",
+            Self::Ah => "AH",
+            Self::Ch => "CH",
+            Self::Dh => "DH",
+            Self::Bh => "BH"
+        }
+    }
+}
+
+impl SegmentRegister {
+    #[must_use]
+    /// Returns the register with the given 2-bit encoding, ignoring any higher bits.
+    pub const fn from_index(index: u8) -> Self {
+        match index & 0b11 {
+            0 => Self::Es,
+            1 => Self::Cs,
+            2 => Self::Ss,
+            _ => Self::Ds
+        }
+    }
+
+    #[must_use]
+    /// Returns the Intel name of the register.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Es => "ES",
+            Self::Cs => "CS",
+            Self::Ss => "SS",
+            Self::Ds => "DS"
+        }
+    }
+}
+
+/// The register file of the 8086.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub ax: u16,
+    pub bx: u16,
+    pub cx: u16,
+    pub dx: u16,
+
+    pub si: u16,
+    pub di: u16,
+    pub sp: u16,
+    pub bp: u16,
+
+    pub cs: u16,
+    pub ds: u16,
+    pub es: u16,
+    pub ss: u16,
+
+    pub ip: u16,
+    pub flags: Flags
+}
+
+impl Registers {
+    #[must_use]
+    /// Constructs a new register file with every register zeroed.
+    pub const fn new() -> Self {
+        Self {
+            ax: 0, bx: 0, cx: 0, dx: 0,
+            si: 0, di: 0, sp: 0, bp: 0,
+            cs: 0, ds: 0, es: 0, ss: 0,
+            ip: 0, flags: Flags::new()
+        }
+    }
+
+    /// Puts the registers into their power-on state, with execution starting at `FFFF:0000` (physical address
+    /// `0xFFFF0`) and every other register cleared.
+    pub const fn reset(&mut self) {
+        *self = Self {
+            cs: 0xFFFF,
+            ip: 0x0000,
+            ..Self::new()
+        };
+    }
+
+    #[must_use]
+    /// Returns the value of a 16-bit general purpose register.
+    pub const fn get16(&self, register: Register16) -> u16 {
+        match register {
+            Register16::Ax => self.ax,
+            Register16::Cx => self.cx,
+            Register16::Dx => self.dx,
+            Register16::Bx => self.bx,
+            Register16::Sp => self.sp,
+            Register16::Bp => self.bp,
+            Register16::Si => self.si,
+            Register16::Di => self.di
+        }
+    }
+
+    /// Sets the value of a 16-bit general purpose register.
+    pub const fn set16(&mut self, register: Register16, value: u16) {
+        match register {
+            Register16::Ax => self.ax = value,
+            Register16::Cx => self.cx = value,
+            Register16::Dx => self.dx = value,
+            Register16::Bx => self.bx = value,
+            Register16::Sp => self.sp = value,
+            Register16::Bp => self.bp = value,
+            Register16::Si => self.si = value,
+            Register16::Di => self.di = value
+        }
+    }
+
+    #[must_use]
+    /// Returns the value of an 8-bit general purpose register.
+    pub const fn get8(&self, register: Register8) -> u8 {
+        match register {
+            Register8::Al => self.al(),
+            Register8::Cl => self.cl(),
+            Register8::Dl => self.dl(),
+            Register8::Bl => self.bl(),
+            Register8::Ah => self.ah(),
+            Register8::Ch => self.ch(),
+            Register8::Dh => self.dh(),
+            Register8::Bh => self.bh()
+        }
+    }
+
+    /// Sets the value of an 8-bit general purpose register.
+    pub const fn set8(&mut self, register: Register8, value: u8) {
+        match register {
+            Register8::Al => self.set_al(value),
+            Register8::Cl => self.set_cl(value),
+            Register8::Dl => self.set_dl(value),
+            Register8::Bl => self.set_bl(value),
+            Register8::Ah => self.set_ah(value),
+            Register8::Ch => self.set_ch(value),
+            Register8::Dh => self.set_dh(value),
+            Register8::Bh => self.set_bh(value)
+        }
+    }
+
+    #[must_use]
+    /// Returns the value of a segment register.
+    pub const fn segment(&self, register: SegmentRegister) -> u16 {
+        match register {
+            SegmentRegister::Es => self.es,
+            SegmentRegister::Cs => self.cs,
+            SegmentRegister::Ss => self.ss,
+            SegmentRegister::Ds => self.ds
+        }
+    }
+
+    /// Sets the value of a segment register.
+    pub const fn set_segment(&mut self, register: SegmentRegister, value: u16) {
+        match register {
+            SegmentRegister::Es => self.es = value,
+            SegmentRegister::Cs => self.cs = value,
+            SegmentRegister::Ss => self.ss = value,
+            SegmentRegister::Ds => self.ds = value
+        }
+    }
+
+    #[must_use]
+    /// Returns the high byte of `ax`.
+    pub const fn ah(&self) -> u8 {
+        self.ax.to_le_bytes()[1]
+    }
+
+    #[must_use]
+    /// Returns the low byte of `ax`.
+    pub const fn al(&self) -> u8 {
+        self.ax.to_le_bytes()[0]
+    }
+
+    /// Sets the high byte of `ax`, leaving the low byte unchanged.
+    pub const fn set_ah(&mut self, value: u8) {
+        self.ax = u16::from_le_bytes([self.al(), value]);
+    }
+
+    /// Sets the low byte of `ax`, leaving the high byte unchanged.
+    pub const fn set_al(&mut self, value: u8) {
+        self.ax = u16::from_le_bytes([value, self.ah()]);
+    }
+
+    #[must_use]
+    /// Returns the high byte of `bx`.
+    pub const fn bh(&self) -> u8 {
+        self.bx.to_le_bytes()[1]
+    }
+
+    #[must_use]
+    /// Returns the low byte of `bx`.
+    pub const fn bl(&self) -> u8 {
+        self.bx.to_le_bytes()[0]
+    }
+
+    /// Sets the high byte of `bx`, leaving the low byte unchanged.
+    pub const fn set_bh(&mut self, value: u8) {
+        self.bx = u16::from_le_bytes([self.bl(), value]);
+    }
+
+    /// Sets the low byte of `bx`, leaving the high byte unchanged.
+    pub const fn set_bl(&mut self, value: u8) {
+        self.bx = u16::from_le_bytes([value, self.bh()]);
+    }
+
+    #[must_use]
+    /// Returns the high byte of `cx`.
+    pub const fn ch(&self) -> u8 {
+        self.cx.to_le_bytes()[1]
+    }
+
+    #[must_use]
+    /// Returns the low byte of `cx`.
+    pub const fn cl(&self) -> u8 {
+        self.cx.to_le_bytes()[0]
+    }
+
+    /// Sets the high byte of `cx`, leaving the low byte unchanged.
+    pub const fn set_ch(&mut self, value: u8) {
+        self.cx = u16::from_le_bytes([self.cl(), value]);
+    }
+
+    /// Sets the low byte of `cx`, leaving the high byte unchanged.
+    pub const fn set_cl(&mut self, value: u8) {
+        self.cx = u16::from_le_bytes([value, self.ch()]);
+    }
+
+    #[must_use]
+    /// Returns the high byte of `dx`.
+    pub const fn dh(&self) -> u8 {
+        self.dx.to_le_bytes()[1]
+    }
+
+    #[must_use]
+    /// Returns the low byte of `dx`.
+    pub const fn dl(&self) -> u8 {
+        self.dx.to_le_bytes()[0]
+    }
+
+    /// Sets the high byte of `dx`, leaving the low byte unchanged.
+    pub const fn set_dh(&mut self, value: u8) {
+        self.dx = u16::from_le_bytes([self.dl(), value]);
+    }
+
+    /// Sets the low byte of `dx`, leaving the high byte unchanged.
+    pub const fn set_dl(&mut self, value: u8) {
+        self.dx = u16::from_le_bytes([value, self.dh()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_names() {
+        assert_eq!(Register16::from_index(5).name(), "BP");
+        assert_eq!(Register8::from_index(4).name(), "AH");
+        assert_eq!(SegmentRegister::from_index(1).name(), "CS");
+    }
+
+    #[test]
+    fn test_registers_creation() {
+        assert_eq!(Registers::new(), Registers::default());
+        assert_eq!(Registers::new().ax, 0);
+    }
+
+    #[test]
+    fn test_registers_reset() {
+        let mut registers = Registers {
+            ax: 1, bx: 2, cx: 3, dx: 4,
+            si: 5, di: 6, sp: 7, bp: 8,
+            cs: 9, ds: 10, es: 11, ss: 12,
+            ip: 13, flags: Flags::from_u16(0xFFFF)
+        };
+
+        registers.reset();
+
+        assert_eq!(registers, Registers { cs: 0xFFFF, ..Registers::new() });
+        assert_eq!(registers.ip, 0);
+        assert_eq!(registers.flags.to_u16(), 0xF002);
+        assert_eq!(usize::from(registers.cs) * 16 + usize::from(registers.ip), 0xFFFF0);
+    }
+
+    #[test]
+    fn test_registers_by_name() {
+        let mut registers = Registers::new();
+
+        for index in 0..8 {
+            registers.set16(Register16::from_index(index), u16::from(index) * 0x1111);
+        }
+
+        assert_eq!(registers, Registers { ax: 0x0000, cx: 0x1111, dx: 0x2222, bx: 0x3333, sp: 0x4444, bp: 0x5555, si: 0x6666, di: 0x7777, ..Registers::new() });
+
+        for index in 0..8 {
+            assert_eq!(registers.get16(Register16::from_index(index)), u16::from(index) * 0x1111);
+        }
+
+        for index in 0..8 {
+            registers.set8(Register8::from_index(index), index + 0xA0);
+        }
+
+        assert_eq!((registers.ax, registers.cx, registers.dx, registers.bx), (0xA4A0, 0xA5A1, 0xA6A2, 0xA7A3));
+
+        for index in 0..8 {
+            assert_eq!(registers.get8(Register8::from_index(index)), index + 0xA0);
+        }
+
+        for index in 0..4 {
+            registers.set_segment(SegmentRegister::from_index(index), u16::from(index) + 1);
+        }
+
+        assert_eq!((registers.es, registers.cs, registers.ss, registers.ds), (1, 2, 3, 4));
+        assert_eq!(registers.segment(SegmentRegister::Ds), 4);
+    }
+
+    #[test]
+    fn test_registers_byte_halves_read() {
+        let registers = Registers { ax: 0x1234, bx: 0x5678, cx: 0x9ABC, dx: 0xDEF0, ..Registers::new() };
+
+        assert_eq!((registers.ah(), registers.al()), (0x12, 0x34));
+        assert_eq!((registers.bh(), registers.bl()), (0x56, 0x78));
+        assert_eq!((registers.ch(), registers.cl()), (0x9A, 0xBC));
+        assert_eq!((registers.dh(), registers.dl()), (0xDE, 0xF0));
+    }
+
+    #[test]
+    fn test_registers_byte_halves_write() {
+        let mut registers = Registers { ax: 0x1234, bx: 0x5678, cx: 0x9ABC, dx: 0xDEF0, ..Registers::new() };
+
+        registers.set_ah(0xAA);
+        registers.set_bl(0xBB);
+        registers.set_ch(0xCC);
+        registers.set_cl(0xDD);
+        registers.set_dl(0xEE);
+
+        assert_eq!(registers.ax, 0xAA34);
+        assert_eq!(registers.bx, 0x56BB);
+        assert_eq!(registers.cx, 0xCCDD);
+        assert_eq!(registers.dx, 0xDEEE);
+        assert_eq!(registers, Registers { ax: 0xAA34, bx: 0x56BB, cx: 0xCCDD, dx: 0xDEEE, ..Registers::new() });
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{Cpu, CpuFault, Instruction, Opcode, OperandWidth, Register8, SegmentRegister, SegmentedAddress, StepResult};
+
+use super::alu;
+
+/// Clock cycles taken by a repeated string operation in addition to those of its repetitions.
+const REPEAT_CYCLES: u32 = 9;
+
+impl Cpu {
+    /// Executes one of the string operations, which read from `DS:SI`, or another segment given by an override prefix,
+    /// and write to or compare with `ES:DI`. Each index register used is then advanced by the operand size, downwards
+    /// if the direction flag is set.
+    ///
+    /// With a `REP` or `REPNE` prefix the operation is repeated `CX` times, with `CMPS` and `SCAS` also stopping once
+    /// the zero flag no longer matches the prefix. As on the processor, a single repetition is executed per step, with
+    /// `IP` left at the start of the instruction until the last, so that the repetitions can be interrupted. The cycles
+    /// common to every repeated operation are reported with its last repetition.
+    pub(super) fn string_operation(&mut self, instruction: Instruction) -> Result<StepResult, CpuFault> {
+        let operation = instruction.opcode;
+        let prefix = instruction.prefix;
+        let repeat = prefix.rep() || prefix.repne();
+
+        if repeat && self.registers.cx == 0 {
+            return Ok(StepResult::Ok(REPEAT_CYCLES));
+        }
+
+        let width = match operation {
+            Opcode::Movsw | Opcode::Cmpsw | Opcode::Scasw | Opcode::Lodsw | Opcode::Stosw => OperandWidth::Word,
+            _ => OperandWidth::Byte
+        };
+
+        let source_segment = self.registers.segment(prefix.segment_override().unwrap_or(SegmentRegister::Ds));
+        let source = SegmentedAddress::new(source_segment, self.registers.si);
+        let destination = SegmentedAddress::new(self.registers.es, self.registers.di);
+
+        let accumulator = match width {
+            OperandWidth::Byte => u16::from(self.registers.al()),
+            OperandWidth::Word => self.registers.ax
+        };
+
+        // Whether `SI` and `DI` are used and so advanced, and the cycles taken by a single and by a repeated operation
+        let (reads_source, uses_destination, single_cycles, repeated_cycles) = match operation {
+            Opcode::Movsb | Opcode::Movsw => {
+                let value = self.read_sized(source, width)?;
+                self.write_sized(destination, width, value)?;
+                (true, true, 18, 17)
+            }
+            Opcode::Cmpsb | Opcode::Cmpsw => {
+                let (a, b) = (self.read_sized(source, width)?, self.read_sized(destination, width)?);
+                alu::sub(&mut self.registers.flags, width, a, b, false);
+                (true, true, 22, 22)
+            }
+            Opcode::Scasb | Opcode::Scasw => {
+                let b = self.read_sized(destination, width)?;
+                alu::sub(&mut self.registers.flags, width, accumulator, b, false);
+                (false, true, 15, 15)
+            }
+            Opcode::Lodsb | Opcode::Lodsw => {
+                let value = self.read_sized(source, width)?;
+
+                match width {
+                    OperandWidth::Byte => self.registers.set8(Register8::Al, value.to_le_bytes()[0]),
+                    OperandWidth::Word => self.registers.ax = value
+                }
+
+                (true, false, 12, 13)
+            }
+            _ => {
+                self.write_sized(destination, width, accumulator)?;
+                (false, true, 11, 10)
+            }
+        };
+
+        let delta = match (width, self.registers.flags.direction()) {
+            (OperandWidth::Byte, false) => 1,
+            (OperandWidth::Byte, true) => 1u16.wrapping_neg(),
+            (OperandWidth::Word, false) => 2,
+            (OperandWidth::Word, true) => 2u16.wrapping_neg()
+        };
+
+        if reads_source {
+            self.registers.si = self.registers.si.wrapping_add(delta);
+        }
+
+        if uses_destination {
+            self.registers.di = self.registers.di.wrapping_add(delta);
+        }
+
+        if !repeat {
+            return Ok(StepResult::Ok(single_cycles));
+        }
+
+        self.registers.cx = self.registers.cx.wrapping_sub(1);
+
+        // `REPE` stops comparisons on the first mismatch and `REPNE` on the first match
+        let compares = matches!(operation, Opcode::Cmpsb | Opcode::Cmpsw | Opcode::Scasb | Opcode::Scasw);
+        let stop = self.registers.cx == 0 || (compares && self.registers.flags.zero() != prefix.rep());
+
+        if stop {
+            return Ok(StepResult::Ok(REPEAT_CYCLES + repeated_cycles));
+        }
+
+        self.registers.ip = self.registers.ip.wrapping_sub(u16::from(instruction.byte_length));
+
+        Ok(StepResult::Ok(repeated_cycles))
+    }
+
+    /// Reads a byte, zero extended, or a word at `address`.
+    fn read_sized(&self, address: SegmentedAddress, width: OperandWidth) -> Result<u16, CpuFault> {
+        match width {
+            OperandWidth::Byte => Ok(u16::from(self.read_byte(address)?)),
+            OperandWidth::Word => self.read_word(address)
+        }
+    }
+
+    /// Writes the low byte of `value`, or all of it, at `address`.
+    fn write_sized(&mut self, address: SegmentedAddress, width: OperandWidth, value: u16) -> Result<(), CpuFault> {
+        match width {
+            OperandWidth::Byte => self.write_byte(address, value.to_le_bytes()[0]),
+            OperandWidth::Word => self.write_word(address, value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::{BusDevice, RegionBusDevice};
+
+    use crate::cpu::testing::cpu_with_program;
+
+    use super::*;
+
+    /// Constructs a processor running `program` as `cpu_with_program` does, with the extra segment at `0100h`.
+    fn string_cpu(program: &[u8]) -> Cpu {
+        let mut cpu = cpu_with_program(program);
+        cpu.registers_mut().es = 0x0100;
+
+        cpu
+    }
+
+    /// Steps `cpu` until `IP` reaches `end`, returning the total cycles taken and the number of steps.
+    fn run_until(cpu: &mut Cpu, end: u16) -> (u32, u32) {
+        let (mut cycles, mut steps) = (0, 0);
+
+        while cpu.registers().ip != end {
+            let Ok(StepResult::Ok(taken)) = cpu.step() else { panic!("Step failed at {:04X}", cpu.registers().ip) };
+            (cycles, steps) = (cycles + taken, steps + 1);
+        }
+
+        (cycles, steps)
+    }
+
+    #[test]
+    fn test_rep_movsb_block_copy() {
+        // REP MOVSB
+        let mut cpu = string_cpu(&[0xF3, 0xA4]);
+        let block: Vec<u8> = (0..=255).collect();
+        assert_eq!(cpu.memory_mut().write_region(0x0200, &block), Ok(()));
+        (cpu.registers_mut().si, cpu.registers_mut().di, cpu.registers_mut().cx) = (0x0200, 0x0040, 256);
+
+        assert_eq!(run_until(&mut cpu, 2), (9 + 17 * 256, 256));
+
+        assert_eq!(cpu.memory().read_region::<256>(0x1040).unwrap().as_slice(), block);
+        assert_eq!(cpu.memory().read(0x1140), Ok(0));
+        assert_eq!((cpu.registers().si, cpu.registers().di, cpu.registers().cx), (0x0300, 0x0140, 0));
+    }
+
+    #[test]
+    fn test_movs_direction_and_override() {
+        // MOVSW, then ES: MOVSB
+        let mut cpu = string_cpu(&[0xA5, 0x26, 0xA4]);
+        assert_eq!(cpu.memory_mut().write_region(0x0010, &[0x34, 0x12]), Ok(()));
+        assert_eq!(cpu.memory_mut().write(0x1012, 0x56), Ok(()));
+        (cpu.registers_mut().si, cpu.registers_mut().di) = (0x0010, 0x0020);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(18)));
+        assert_eq!(cpu.memory().read_region::<2>(0x1020), Ok([0x34, 0x12]));
+        assert_eq!((cpu.registers().si, cpu.registers().di), (0x0012, 0x0022));
+
+        // Copying downwards, with the source in the extra segment
+        cpu.registers_mut().flags.set_direction(true);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(18)));
+        assert_eq!(cpu.memory().read(0x1022), Ok(0x56));
+        assert_eq!((cpu.registers().si, cpu.registers().di), (0x0011, 0x0021));
+    }
+
+    #[test]
+    fn test_lods_stos() {
+        // LODSW, STOSB, REP STOSW
+        let mut cpu = string_cpu(&[0xAD, 0xAA, 0xF3, 0xAB]);
+        assert_eq!(cpu.memory_mut().write_region(0x0000, &[0xCD, 0xAB]), Ok(()));
+        cpu.registers_mut().cx = 3;
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(12)));
+        assert_eq!((cpu.registers().ax, cpu.registers().si), (0xABCD, 2));
+
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(11)));
+        assert_eq!((cpu.memory().read(0x1000), cpu.registers().di), (Ok(0xCD), 1));
+
+        assert_eq!(run_until(&mut cpu, 4), (9 + 10 * 3, 3));
+        assert_eq!(cpu.memory().read_region(0x1000), Ok([0xCD, 0xCD, 0xAB, 0xCD, 0xAB, 0xCD, 0xAB, 0x00]));
+        assert_eq!(cpu.registers().di, 7);
+    }
+
+    #[test]
+    fn test_repne_scasb() {
+        // REPNE SCASB, as used to find the length of a string
+        let mut cpu = string_cpu(&[0xF2, 0xAE]);
+        assert_eq!(cpu.memory_mut().write_region(0x1000, b"HELLO\0"), Ok(()));
+        cpu.registers_mut().cx = 0xFFFF;
+
+        assert_eq!(run_until(&mut cpu, 2), (9 + 15 * 6, 6));
+        assert_eq!((cpu.registers().cx, cpu.registers().di), (0xFFF9, 6));
+        assert!(cpu.registers().flags.zero());
+
+        // Running out of `CX` stops the search without a match
+        (cpu.registers_mut().ip, cpu.registers_mut().di, cpu.registers_mut().cx) = (0, 0, 3);
+        assert_eq!(run_until(&mut cpu, 2), (9 + 15 * 3, 3));
+        assert!(!cpu.registers().flags.zero());
+    }
+
+    #[test]
+    fn test_repe_cmpsb() {
+        // REPE CMPSB
+        let mut cpu = string_cpu(&[0xF3, 0xA6]);
+        assert_eq!(cpu.memory_mut().write_region(0x0000, b"ABCDE"), Ok(()));
+        assert_eq!(cpu.memory_mut().write_region(0x1000, b"ABXDE"), Ok(()));
+        cpu.registers_mut().cx = 5;
+
+        // Stops after the first mismatch, with `SI` and `DI` past it
+        assert_eq!(run_until(&mut cpu, 2), (9 + 22 * 3, 3));
+        assert_eq!((cpu.registers().cx, cpu.registers().si, cpu.registers().di), (2, 3, 3));
+        assert!(!cpu.registers().flags.zero());
+
+        // 'C' - 'X' borrows
+        assert!(cpu.registers().flags.carry());
+
+        // A zero count does nothing at all
+        (cpu.registers_mut().ip, cpu.registers_mut().cx) = (0, 0);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(9)));
+        assert_eq!((cpu.registers().ip, cpu.registers().si), (2, 3));
+    }
+}
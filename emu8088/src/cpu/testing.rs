@@ -0,0 +1,28 @@
+use mem::{IoMap, Memory, MemoryMap, RegionBusDevice};
+
+use crate::{Assembler, Cpu};
+
+/// Constructs a processor with 128 KiB of memory holding `source` assembled at `1000:0000`, with the data, extra
+/// and stack segments at the bottom of memory.
+pub fn cpu_with_source(source: &str) -> Cpu {
+    cpu_with_program(&Assembler::assemble_str(source).unwrap())
+}
+
+/// As `cpu_with_source`, but with the program given as machine code.
+pub fn cpu_with_program(program: &[u8]) -> Cpu {
+    let mut memory = Memory::<0x20000>::empty();
+    assert_eq!(memory.write_region(0x10000, program), Ok(()));
+
+    cpu_with_memory(memory)
+}
+
+/// As `cpu_with_source`, but with the contents of memory given in full.
+pub fn cpu_with_memory(memory: Memory<0x20000>) -> Cpu {
+    let mut cpu = Cpu::new(MemoryMap::new().with_range(0x00000..=0x1FFFF, Box::new(memory)), IoMap::new());
+
+    let registers = cpu.registers_mut();
+    (registers.cs, registers.ip) = (0x1000, 0x0000);
+    (registers.ds, registers.es, registers.ss) = (0x0000, 0x0000, 0x0000);
+
+    cpu
+}
@@ -0,0 +1,308 @@
+use std::fmt::Write;
+
+use crate::{AddressBase, Instruction, MemoryAddress, Opcode, Operand, OperandWidth, SegmentRegister, SegmentedAddress};
+
+/// Number of instruction bytes the hex column of a listing is sized for. Longer instructions spill past the column.
+pub const LISTING_BYTES_WIDTH: usize = 6;
+
+/// Formats a number the way an Intel assembler would read it back, values below 10 in decimal and anything larger in
+/// hex with an `h` suffix, prefixed with `0` if it would otherwise start with a letter.
+fn format_number(value: u16) -> String {
+    if value < 10 {
+        return value.to_string();
+    }
+
+    let hex = format!("{value:X}h");
+
+    if hex.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        format!("0{hex}")
+    }
+    else {
+        hex
+    }
+}
+
+/// Formats a signed value with an explicit sign, as used for displacements and relative targets.
+fn format_signed(value: i16) -> String {
+    if value < 0 {
+        format!("-{}", format_number(value.unsigned_abs()))
+    }
+    else {
+        format!("+{}", format_number(value.cast_unsigned()))
+    }
+}
+
+/// Formats a memory operand as `seg:[base+disp]`, without any size specifier. The segment is shown if the operand
+/// carries an override, or failing that if `segment_override` is given.
+fn format_memory(address: MemoryAddress, segment_override: Option<SegmentRegister>) -> String {
+    let segment = address.segment_override.or(segment_override).map_or_else(String::new, |segment| format!("{}:", segment.name()));
+
+    let base = match address.base {
+        AddressBase::BxSi => "BX+SI",
+        AddressBase::BxDi => "BX+DI",
+        AddressBase::BpSi => "BP+SI",
+        AddressBase::BpDi => "BP+DI",
+        AddressBase::Si => "SI",
+        AddressBase::Di => "DI",
+        AddressBase::Bp => "BP",
+        AddressBase::Bx => "BX",
+        AddressBase::Direct => {
+            let offset = address.displacement.unwrap_or(0).cast_unsigned();
+            return format!("{segment}[{}]", format_number(offset));
+        }
+    };
+
+    match address.displacement {
+        Some(displacement) if displacement != 0 => format!("{segment}[{base}{}]", format_signed(displacement)),
+        _ => format!("{segment}[{base}]")
+    }
+}
+
+/// Produces Intel syntax assembly text from decoded instructions.
+///
+/// Numbers are written in hex with an `h` suffix (`MOV AX, 1234h`), except for values below 10 which read the same in
+/// decimal. Memory operands are given an explicit `BYTE PTR` or `WORD PTR` when their size is not implied by a
+/// register operand, relative jump targets are written relative to the start of the instruction (`JMP $+5`), and direct
+/// far targets as `segment:offset`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Disassembler;
+
+impl Disassembler {
+    #[must_use]
+    /// Constructs a new disassembler.
+    pub const fn new() -> Self {
+        Self
+    }
+
+    #[must_use]
+    /// Formats `instr` as a line of Intel syntax assembly, such as `"MOV AX, [BX+SI+4]"` or `"REP MOVSB"`.
+    pub fn format(&self, instr: &Instruction) -> String {
+        let mut text = String::new();
+
+        if instr.prefix.lock() {
+            text.push_str("LOCK ");
+        }
+
+        if instr.prefix.rep() {
+            // The same prefix byte repeats while equal for the comparing string operations
+            let repe = matches!(instr.opcode, Opcode::Cmpsb | Opcode::Cmpsw | Opcode::Scasb | Opcode::Scasw);
+            text.push_str(if repe { "REPE " } else { "REP " });
+        }
+        else if instr.prefix.repne() {
+            text.push_str("REPNE ");
+        }
+
+        // An override is shown within the memory operand, if there is one to show it in
+        let operands = [instr.dst, instr.src];
+        let has_memory = operands.iter().flatten().any(Operand::is_memory);
+
+        if let (Some(segment), false) = (instr.prefix.segment_override(), has_memory) {
+            text.push_str(segment.name());
+            text.push_str(": ");
+        }
+
+        text.push_str(instr.opcode.mnemonic().unwrap_or("??"));
+
+        for (index, operand) in operands.iter().enumerate() {
+            let Some(operand) = operand else {
+                continue;
+            };
+
+            text.push_str(if index == 0 { " " } else { ", " });
+            text.push_str(&Self::format_operand(instr, *operand, operands[1 - index]));
+        }
+
+        text
+    }
+
+    #[must_use]
+    /// Formats `instr` as a line of a listing, with the linear `address` of the instruction, its `raw_bytes` in hex
+    /// and its assembly text in aligned columns, such as `"01010  B8 34 12          MOV AX, 1234h"`.
+    pub fn format_listing(&self, address: usize, instr: &Instruction, raw_bytes: &[u8]) -> String {
+        self.listing(&format!("{address:05X}"), instr, raw_bytes)
+    }
+
+    #[must_use]
+    /// Formats `instr` as a line of a listing in the style of `DEBUG.COM`, with the `segment:offset` address of the
+    /// instruction, its `raw_bytes` in hex and its assembly text in aligned columns, such as
+    /// `"0100:0010  B8 34 12          MOV AX, 1234h"`.
+    pub fn format_segmented_listing(&self, address: SegmentedAddress, instr: &Instruction, raw_bytes: &[u8]) -> String {
+        self.listing(&address.to_string(), instr, raw_bytes)
+    }
+
+    /// Builds a listing line from an already formatted address.
+    fn listing(self, address: &str, instr: &Instruction, raw_bytes: &[u8]) -> String {
+        let mut bytes = String::with_capacity(LISTING_BYTES_WIDTH * 3);
+
+        for byte in raw_bytes {
+            let _ = write!(bytes, "{byte:02X} ");
+        }
+
+        format!("{address}  {bytes:<width$}{}", self.format(instr), width = LISTING_BYTES_WIDTH * 3)
+    }
+
+    /// Formats a single operand of `instr`, using the `other` operand to decide whether a memory operand needs a size
+    /// specifier.
+    fn format_operand(instr: &Instruction, operand: Operand, other: Option<Operand>) -> String {
+        match operand {
+            Operand::Register8(register) => register.name().to_string(),
+            Operand::Register16(register) => register.name().to_string(),
+            Operand::Segment(register) => register.name().to_string(),
+            Operand::Immediate8(value) => format_number(u16::from(value)),
+            Operand::Immediate16(value) => format_number(value),
+            Operand::Relative(displacement) => {
+                // The displacement is relative to the end of the instruction
+                let target = displacement.wrapping_add(i16::from(instr.byte_length));
+                format!("${}", format_signed(target))
+            }
+            Operand::FarPointer { segment, offset } => format!("{segment:04X}:{offset:04X}"),
+            Operand::Memory { address, width } => {
+                let implied = match other {
+                    Some(Operand::Register8(_)) => width == OperandWidth::Byte,
+                    Some(Operand::Register16(_) | Operand::Segment(_)) => width == OperandWidth::Word,
+                    _ => false
+                };
+
+                let size = if matches!(instr.opcode, Opcode::CallFar | Opcode::JmpFar) {
+                    "DWORD PTR "
+                }
+                else if implied {
+                    ""
+                }
+                else if width == OperandWidth::Byte {
+                    "BYTE PTR "
+                }
+                else {
+                    "WORD PTR "
+                };
+
+                format!("{size}{}", format_memory(address, instr.prefix.segment_override()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::Memory;
+
+    use crate::{InstructionDecoder, PrefixFlags, Register16, Register8};
+
+    use super::*;
+
+    fn disassemble(bytes: &[u8]) -> String {
+        let instruction = InstructionDecoder::new().decode(&Memory::<32>::populated(bytes), 0).unwrap();
+        Disassembler::new().format(&instruction)
+    }
+
+    #[test]
+    fn test_disassemble_registers_and_immediates() {
+        assert_eq!(disassemble(&[0xB8, 0x34, 0x12]), "MOV AX, 1234h");
+        assert_eq!(disassemble(&[0xB1, 0x05]), "MOV CL, 5");
+        assert_eq!(disassemble(&[0xB4, 0x4C]), "MOV AH, 4Ch");
+        assert_eq!(disassemble(&[0xBB, 0xFF, 0xFF]), "MOV BX, 0FFFFh");
+        assert_eq!(disassemble(&[0x01, 0xD8]), "ADD AX, BX");
+        assert_eq!(disassemble(&[0x8E, 0xD8]), "MOV DS, AX");
+        assert_eq!(disassemble(&[0x0E]), "PUSH CS");
+        assert_eq!(disassemble(&[0xCD, 0x21]), "INT 21h");
+        assert_eq!(disassemble(&[0xCC]), "INT 3");
+        assert_eq!(disassemble(&[0xEC]), "IN AL, DX");
+        assert_eq!(disassemble(&[0x90]), "NOP");
+    }
+
+    #[test]
+    fn test_disassemble_memory_operands() {
+        assert_eq!(disassemble(&[0x8B, 0x40, 0x04]), "MOV AX, [BX+SI+4]");
+        assert_eq!(disassemble(&[0x88, 0x56, 0xFE]), "MOV [BP-2], DL");
+        assert_eq!(disassemble(&[0x8B, 0x07]), "MOV AX, [BX]");
+        assert_eq!(disassemble(&[0x8B, 0x46, 0x00]), "MOV AX, [BP]");
+        assert_eq!(disassemble(&[0xA1, 0x34, 0x12]), "MOV AX, [1234h]");
+        assert_eq!(disassemble(&[0x8D, 0x81, 0x00, 0x01]), "LEA AX, [BX+DI+100h]");
+    }
+
+    #[test]
+    fn test_disassemble_size_specifiers() {
+        assert_eq!(disassemble(&[0xC6, 0x07, 0x41]), "MOV BYTE PTR [BX], 41h");
+        assert_eq!(disassemble(&[0xC7, 0x06, 0x00, 0x10, 0x78, 0x56]), "MOV WORD PTR [1000h], 5678h");
+        assert_eq!(disassemble(&[0x83, 0x2D, 0xFF]), "SUB WORD PTR [DI], 0FFFFh");
+        assert_eq!(disassemble(&[0xFE, 0x04]), "INC BYTE PTR [SI]");
+        assert_eq!(disassemble(&[0xD3, 0x27]), "SHL WORD PTR [BX], CL");
+        assert_eq!(disassemble(&[0xFF, 0x1F]), "CALL DWORD PTR [BX]");
+    }
+
+    #[test]
+    fn test_disassemble_prefixes() {
+        assert_eq!(disassemble(&[0xF3, 0xA4]), "REP MOVSB");
+        assert_eq!(disassemble(&[0xF3, 0xA6]), "REPE CMPSB");
+        assert_eq!(disassemble(&[0xF2, 0xAE]), "REPNE SCASB");
+        assert_eq!(disassemble(&[0x26, 0x8B, 0x07]), "MOV AX, ES:[BX]");
+        assert_eq!(disassemble(&[0x2E, 0xC6, 0x46, 0x02, 0x01]), "MOV BYTE PTR CS:[BP+2], 1");
+        assert_eq!(disassemble(&[0x26, 0xF3, 0xA5]), "REP ES: MOVSW");
+        assert_eq!(disassemble(&[0xF0, 0x87, 0x07]), "LOCK XCHG [BX], AX");
+    }
+
+    #[test]
+    fn test_disassemble_control_transfer() {
+        assert_eq!(disassemble(&[0xEB, 0xFE]), "JMP $+0");
+        assert_eq!(disassemble(&[0x74, 0x03]), "JE $+5");
+        assert_eq!(disassemble(&[0xE8, 0xF0, 0xFF]), "CALL $-0Dh");
+        assert_eq!(disassemble(&[0xEA, 0x5B, 0xE0, 0x00, 0xF0]), "JMP F000:E05B");
+        assert_eq!(disassemble(&[0xC2, 0x04, 0x00]), "RET 4");
+    }
+
+    #[test]
+    fn test_disassemble_listing() {
+        let disassembler = Disassembler::new();
+        let bytes = [0xB8, 0x34, 0x12];
+        let instruction = InstructionDecoder::new().decode(&Memory::<32>::populated(&bytes), 0).unwrap();
+
+        assert_eq!(disassembler.format_segmented_listing(SegmentedAddress::new(0x0100, 0x0010), &instruction, &bytes),
+            "0100:0010  B8 34 12          MOV AX, 1234h");
+        assert_eq!(disassembler.format_listing(0x1010, &instruction, &bytes),
+            "01010  B8 34 12          MOV AX, 1234h");
+    }
+
+    #[test]
+    fn test_disassemble_listing_alignment() {
+        let program = [0x90, 0xB8, 0x34, 0x12, 0xC7, 0x06, 0x00, 0x10, 0x78, 0x56, 0x26, 0xF3, 0xA5];
+        let memory = Memory::<32>::populated(&program);
+        let decoder = InstructionDecoder::new();
+        let disassembler = Disassembler::new();
+
+        let mut offset = 0;
+        let mut lines = Vec::new();
+
+        while offset < program.len() {
+            let instruction = decoder.decode(&memory, offset).unwrap();
+            let length = usize::from(instruction.byte_length);
+            let address = SegmentedAddress::new(0x0100, u16::try_from(offset).unwrap());
+
+            lines.push(disassembler.format_segmented_listing(address, &instruction, &program[offset..offset + length]));
+            offset += length;
+        }
+
+        assert_eq!(lines, [
+            "0100:0000  90                NOP",
+            "0100:0001  B8 34 12          MOV AX, 1234h",
+            "0100:0004  C7 06 00 10 78 56 MOV WORD PTR [1000h], 5678h",
+            "0100:000A  26 F3 A5          REP ES: MOVSW"
+        ]);
+
+        let mnemonic_column = lines[0].find("NOP").unwrap();
+        assert!(lines.iter().all(|line| line[mnemonic_column - 1..].starts_with(' ')));
+    }
+
+    #[test]
+    fn test_disassemble_constructed() {
+        let mut prefix = PrefixFlags::new();
+        prefix.set_segment_override(Some(SegmentRegister::Ss));
+
+        let instruction = Instruction::new(Opcode::Mov, 2)
+            .with_dst(Operand::Register8(Register8::Al))
+            .with_src(Operand::Memory { address: MemoryAddress::new(AddressBase::Si, Some(-0x80)), width: OperandWidth::Byte })
+            .with_prefix(prefix);
+
+        assert_eq!(Disassembler::new().format(&instruction), "MOV AL, SS:[SI-80h]");
+        assert_eq!(Disassembler::new().format(&Instruction::new(Opcode::Pop, 1).with_dst(Operand::Register16(Register16::Di))), "POP DI");
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{Opcode, Operand, PrefixFlags};
+
+/// A fully decoded instruction.
+///
+/// Instructions with a single operand store it in `dst`, while those with none leave both operands empty. The
+/// `byte_length` covers every byte of the instruction, including its prefixes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub dst: Option<Operand>,
+    pub src: Option<Operand>,
+    pub prefix: PrefixFlags,
+    pub byte_length: u8
+}
+
+impl Instruction {
+    #[must_use]
+    /// Constructs an instruction with no operands or prefixes.
+    pub const fn new(opcode: Opcode, byte_length: u8) -> Self {
+        Self { opcode, dst: None, src: None, prefix: PrefixFlags::new(), byte_length }
+    }
+
+    #[must_use]
+    /// Builder pattern for setting the destination operand.
+    pub const fn with_dst(mut self, dst: Operand) -> Self {
+        self.dst = Some(dst);
+        self
+    }
+
+    #[must_use]
+    /// Builder pattern for setting the source operand.
+    pub const fn with_src(mut self, src: Operand) -> Self {
+        self.src = Some(src);
+        self
+    }
+
+    #[must_use]
+    /// Builder pattern for setting the prefixes.
+    pub const fn with_prefix(mut self, prefix: PrefixFlags) -> Self {
+        self.prefix = prefix;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AddressBase, MemoryAddress, OperandWidth, Register16, SegmentRegister};
+
+    use super::*;
+
+    #[test]
+    fn test_instruction_builder() {
+        let instruction = Instruction::new(Opcode::Nop, 1);
+
+        assert_eq!(instruction.dst, None);
+        assert_eq!(instruction.src, None);
+        assert!(instruction.prefix.is_empty());
+
+        let mut prefix = PrefixFlags::new();
+        prefix.set_segment_override(Some(SegmentRegister::Es));
+
+        // ES: MOV [BX+SI+4], AX
+        let address = MemoryAddress::new(AddressBase::BxSi, Some(4));
+        let instruction = Instruction::new(Opcode::Mov, 4)
+            .with_dst(Operand::Memory { address, width: OperandWidth::Word })
+            .with_src(Operand::Register16(Register16::Ax))
+            .with_prefix(prefix);
+
+        assert_eq!(instruction, Instruction {
+            opcode: Opcode::Mov,
+            dst: Some(Operand::Memory { address, width: OperandWidth::Word }),
+            src: Some(Operand::Register16(Register16::Ax)),
+            prefix,
+            byte_length: 4
+        });
+    }
+}
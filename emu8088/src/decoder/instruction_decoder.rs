@@ -0,0 +1,532 @@
+use mem::{BusDevice, BusDeviceError};
+
+use crate::{Instruction, MemoryAddress, ModRM, Opcode, Operand, OperandWidth, PrefixFlags, Register16, Register8, SegmentRegister};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DecodeError {
+    /// The opcode byte, or the combination of a group opcode and its `ModRM` byte, is not a documented 8086
+    /// instruction.
+    InvalidOpcode{address: usize, opcode: u8},
+    /// A `REP` or `REPNE` prefix, at `address`, precedes an instruction which is not a string operation.
+    UnexpectedPrefix{address: usize, prefix: u8},
+    /// The instruction, including its prefixes, is longer than 255 bytes.
+    InstructionTooLong{address: usize},
+    Bus(BusDeviceError)
+}
+
+impl From<BusDeviceError> for DecodeError {
+    fn from(value: BusDeviceError) -> Self {
+        Self::Bus(value)
+    }
+}
+
+/// The operation of an instruction along with its destination and source operands.
+type DecodedOperands = (Opcode, Option<Operand>, Option<Operand>);
+
+/// Reads the bytes of a single instruction in order, keeping track of how many have been consumed.
+struct ByteReader<'a> {
+    bus: &'a dyn BusDevice,
+    address: usize,
+    length: usize
+}
+
+impl ByteReader<'_> {
+    fn next_u8(&mut self) -> Result<u8, DecodeError> {
+        if self.length >= usize::from(u8::MAX) {
+            return Err(DecodeError::InstructionTooLong { address: self.address });
+        }
+
+        let byte = self.bus.read(self.address.wrapping_add(self.length))?;
+        self.length += 1;
+
+        Ok(byte)
+    }
+
+    fn next_u16(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_le_bytes([self.next_u8()?, self.next_u8()?]))
+    }
+
+    /// Reads a signed byte, sign extending it to 16 bits.
+    fn next_i8(&mut self) -> Result<i16, DecodeError> {
+        Ok(i16::from(self.next_u8()?.cast_signed()))
+    }
+
+    fn next_modrm(&mut self) -> Result<ModRM, DecodeError> {
+        Ok(ModRM::decode(self.next_u8()?))
+    }
+
+    /// Reads the displacement of `modrm`, if any, and returns the operand selected by its `mod` and `r/m` fields.
+    fn rm_operand(&mut self, modrm: ModRM, width: OperandWidth) -> Result<Operand, DecodeError> {
+        let mut displacement = [0; 2];
+
+        for byte in displacement.iter_mut().take(modrm.displacement_length()) {
+            *byte = self.next_u8()?;
+        }
+
+        let (address, _) = modrm.to_effective_address(&displacement);
+
+        Ok(Operand::from_effective_address(address, width))
+    }
+
+    /// Reads an immediate of the given width.
+    fn immediate(&mut self, width: OperandWidth) -> Result<Operand, DecodeError> {
+        Ok(match width {
+            OperandWidth::Byte => Operand::Immediate8(self.next_u8()?),
+            OperandWidth::Word => Operand::Immediate16(self.next_u16()?)
+        })
+    }
+
+    /// Reads the remainder of an instruction from one of the single operand groups (`0xF6`, `0xF7`, `0xFE`, `0xFF`),
+    /// which take a register or memory operand selected by their `ModRM` byte.
+    fn unary_group(&mut self, byte: u8, opcode: Opcode, invalid: DecodeError) -> Result<DecodedOperands, DecodeError> {
+        let width = OperandWidth::from_w_bit(byte);
+        let modrm = self.next_modrm()?;
+        let rm = self.rm_operand(modrm, width)?;
+        let resolved = opcode.resolve_group(modrm.reg);
+
+        match resolved {
+            Opcode::Test => Ok((resolved, Some(rm), Some(self.immediate(width)?))),
+            // Far indirect transfers load a segment and offset from memory
+            Opcode::CallFar | Opcode::JmpFar if !rm.is_memory() => Err(invalid),
+            _ => Ok((resolved, Some(rm), None))
+        }
+    }
+
+    /// Reads the remainder of the instruction with the given primary opcode `byte`, returning its operation, resolved
+    /// for group opcodes, and its operands. `invalid` is returned for register operands where only memory is allowed.
+    fn operands(&mut self, byte: u8, opcode: Opcode, invalid: DecodeError) -> Result<DecodedOperands, DecodeError> {
+        let width = OperandWidth::from_w_bit(byte);
+
+        Ok(match byte {
+            // Arithmetic and MOV between a register and a register or memory operand
+            0x00..=0x3F | 0x88..=0x8B if byte & 0b110 != 0b110 && byte & 0b100 == 0 => {
+                let modrm = self.next_modrm()?;
+                let rm = self.rm_operand(modrm, width)?;
+                let reg = Operand::register(modrm.reg, width);
+
+                if byte & 0b10 == 0 { (opcode, Some(rm), Some(reg)) } else { (opcode, Some(reg), Some(rm)) }
+            }
+            // Arithmetic with the accumulator and an immediate
+            0x00..=0x3F if byte & 0b111 < 6 => (opcode, Some(Operand::register(0, width)), Some(self.immediate(width)?)),
+            0x06 | 0x07 | 0x0E | 0x16 | 0x17 | 0x1E | 0x1F => (opcode, Some(Operand::Segment(SegmentRegister::from_index(byte >> 3))), None),
+            0x40..=0x5F => (opcode, Some(Operand::register(byte, OperandWidth::Word)), None),
+            0x70..=0x7F | 0xE0..=0xE3 | 0xEB => (opcode, Some(Operand::Relative(self.next_i8()?)), None),
+            0x80..=0x83 => {
+                let modrm = self.next_modrm()?;
+                let rm = self.rm_operand(modrm, width)?;
+
+                let immediate = if byte == 0x83 {
+                    Operand::Immediate16(self.next_i8()?.cast_unsigned())
+                }
+                else {
+                    self.immediate(width)?
+                };
+
+                (opcode.resolve_group(modrm.reg), Some(rm), Some(immediate))
+            }
+            0x84..=0x87 => {
+                let modrm = self.next_modrm()?;
+                (opcode, Some(self.rm_operand(modrm, width)?), Some(Operand::register(modrm.reg, width)))
+            }
+            0x8C | 0x8E => {
+                let modrm = self.next_modrm()?;
+                let rm = self.rm_operand(modrm, OperandWidth::Word)?;
+                let segment = Operand::Segment(SegmentRegister::from_index(modrm.reg));
+
+                if byte == 0x8C { (opcode, Some(rm), Some(segment)) } else { (opcode, Some(segment), Some(rm)) }
+            }
+            0x8D | 0xC4 | 0xC5 => {
+                let modrm = self.next_modrm()?;
+                let rm = self.rm_operand(modrm, OperandWidth::Word)?;
+
+                if !rm.is_memory() {
+                    return Err(invalid);
+                }
+
+                (opcode, Some(Operand::register(modrm.reg, OperandWidth::Word)), Some(rm))
+            }
+            0x8F => {
+                let modrm = self.next_modrm()?;
+                (opcode, Some(self.rm_operand(modrm, OperandWidth::Word)?), None)
+            }
+            0x91..=0x97 => (opcode, Some(Operand::Register16(Register16::Ax)), Some(Operand::register(byte, OperandWidth::Word))),
+            0x9A | 0xEA => {
+                let offset = self.next_u16()?;
+                let segment = self.next_u16()?;
+                (opcode, Some(Operand::FarPointer { segment, offset }), None)
+            }
+            0xA0..=0xA3 => {
+                let memory = Operand::Memory { address: MemoryAddress::direct(self.next_u16()?), width };
+                let accumulator = Operand::register(0, width);
+
+                if byte & 0b10 == 0 { (opcode, Some(accumulator), Some(memory)) } else { (opcode, Some(memory), Some(accumulator)) }
+            }
+            0xA8 | 0xA9 => (opcode, Some(Operand::register(0, width)), Some(self.immediate(width)?)),
+            0xB0..=0xB7 => (opcode, Some(Operand::register(byte, OperandWidth::Byte)), Some(self.immediate(OperandWidth::Byte)?)),
+            0xB8..=0xBF => (opcode, Some(Operand::register(byte, OperandWidth::Word)), Some(self.immediate(OperandWidth::Word)?)),
+            0xC2 | 0xCA => (opcode, Some(self.immediate(OperandWidth::Word)?), None),
+            0xC6 | 0xC7 => {
+                let modrm = self.next_modrm()?;
+                let rm = self.rm_operand(modrm, width)?;
+                (opcode, Some(rm), Some(self.immediate(width)?))
+            }
+            0xCC => (opcode, Some(Operand::Immediate8(3)), None),
+            0xCD | 0xD4 | 0xD5 => (opcode, Some(self.immediate(OperandWidth::Byte)?), None),
+            0xD0..=0xD3 => {
+                let modrm = self.next_modrm()?;
+                let rm = self.rm_operand(modrm, width)?;
+                let count = if byte & 0b10 == 0 { Operand::Immediate8(1) } else { Operand::Register8(Register8::Cl) };
+
+                (opcode.resolve_group(modrm.reg), Some(rm), Some(count))
+            }
+            0xD8..=0xDF => {
+                // The escape opcode carries a 6-bit external opcode split between the opcode and the `reg` field
+                let modrm = self.next_modrm()?;
+                let rm = self.rm_operand(modrm, OperandWidth::Word)?;
+
+                (opcode, Some(Operand::Immediate8((byte & 0b111) << 3 | modrm.reg)), Some(rm))
+            }
+            0xE4 | 0xE5 => (opcode, Some(Operand::register(0, width)), Some(self.immediate(OperandWidth::Byte)?)),
+            0xE6 | 0xE7 => (opcode, Some(self.immediate(OperandWidth::Byte)?), Some(Operand::register(0, width))),
+            0xEC | 0xED => (opcode, Some(Operand::register(0, width)), Some(Operand::Register16(Register16::Dx))),
+            0xEE | 0xEF => (opcode, Some(Operand::Register16(Register16::Dx)), Some(Operand::register(0, width))),
+            0xE8 | 0xE9 => (opcode, Some(Operand::Relative(self.next_u16()?.cast_signed())), None),
+            0xF6 | 0xF7 | 0xFE | 0xFF => self.unary_group(byte, opcode, invalid)?,
+            _ => (opcode, None, None)
+        })
+    }
+}
+
+/// Decodes 8086 machine code read from a bus device into `Instruction`s.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionDecoder;
+
+impl InstructionDecoder {
+    #[must_use]
+    /// Constructs a new decoder.
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Decodes the instruction starting at `address` of `bus`, including any prefixes. The `byte_length` of the
+    /// returned instruction is the number of bytes consumed.
+    ///
+    /// A segment override prefix is recorded in the instruction's `prefix` and also applied to its memory operand, if
+    /// it has one. When several segment overrides are present the last one takes effect. A `REP` or `REPNE` prefix is
+    /// only accepted before a string operation, with the last of several repeat prefixes taking effect.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the bytes do not form a valid instruction, if a repeat prefix precedes an
+    /// instruction other than a string operation, or if the bytes cannot be read from the bus.
+    pub fn decode(&self, bus: &dyn BusDevice, address: usize) -> Result<Instruction, DecodeError> {
+        let mut reader = ByteReader { bus, address, length: 0 };
+        let mut prefix = PrefixFlags::new();
+
+        // The last repeat prefix, and its address, for reporting one which does not precede a string operation
+        let mut repeat = None;
+
+        let (byte, opcode) = loop {
+            let byte = reader.next_u8()?;
+            let byte_address = address.wrapping_add(reader.length - 1);
+
+            match Opcode::from_byte(byte) {
+                Opcode::Lock => prefix.set_lock(true),
+                Opcode::Rep => {
+                    prefix.set_rep(true);
+                    repeat = Some((byte_address, byte));
+                }
+                Opcode::Repne => {
+                    prefix.set_repne(true);
+                    repeat = Some((byte_address, byte));
+                }
+                Opcode::SegmentOverride(segment) => prefix.set_segment_override(Some(segment)),
+                opcode => break (byte, opcode)
+            }
+        };
+
+        let invalid = DecodeError::InvalidOpcode { address: address.wrapping_add(reader.length - 1), opcode: byte };
+        let (opcode, dst, src) = reader.operands(byte, opcode, invalid)?;
+
+        if matches!(opcode, Opcode::Invalid(_)) {
+            return Err(invalid);
+        }
+
+        if let Some((prefix_address, prefix_byte)) = repeat {
+            if !opcode.is_string_operation() {
+                return Err(DecodeError::UnexpectedPrefix { address: prefix_address, prefix: prefix_byte });
+            }
+        }
+
+        // A segment override applies to whichever operand refers to memory
+        let segment = prefix.segment_override();
+
+        Ok(Instruction {
+            opcode,
+            dst: dst.map(|operand| operand.with_segment_override(segment)),
+            src: src.map(|operand| operand.with_segment_override(segment)),
+            prefix,
+            byte_length: u8::try_from(reader.length).map_err(|_| DecodeError::InstructionTooLong { address })?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::{ConstantDevice, Memory, MemoryMap};
+
+    use crate::{AddressBase, EffectiveAddress, Registers, SegmentedAddress};
+
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Result<Instruction, DecodeError> {
+        InstructionDecoder::new().decode(&Memory::<32>::populated(bytes), 0)
+    }
+
+    fn memory(base: AddressBase, displacement: Option<i16>, width: OperandWidth) -> Operand {
+        Operand::Memory { address: MemoryAddress::new(base, displacement), width }
+    }
+
+    #[test]
+    fn test_decode_no_operands() {
+        assert_eq!(decode(&[0x90]), Ok(Instruction::new(Opcode::Nop, 1)));
+        assert_eq!(decode(&[0xF4]), Ok(Instruction::new(Opcode::Hlt, 1)));
+        assert_eq!(decode(&[0xA4]), Ok(Instruction::new(Opcode::Movsb, 1)));
+        assert_eq!(decode(&[0xC3]), Ok(Instruction::new(Opcode::Ret, 1)));
+    }
+
+    #[test]
+    fn test_decode_mov_forms() {
+        // MOV AX, 1234h
+        assert_eq!(decode(&[0xB8, 0x34, 0x12]), Ok(Instruction::new(Opcode::Mov, 3)
+            .with_dst(Operand::Register16(Register16::Ax)).with_src(Operand::Immediate16(0x1234))));
+
+        // MOV CH, 5h
+        assert_eq!(decode(&[0xB5, 0x05]), Ok(Instruction::new(Opcode::Mov, 2)
+            .with_dst(Operand::Register8(Register8::Ch)).with_src(Operand::Immediate8(5))));
+
+        // MOV [BX+SI+4], AX
+        assert_eq!(decode(&[0x89, 0x40, 0x04]), Ok(Instruction::new(Opcode::Mov, 3)
+            .with_dst(memory(AddressBase::BxSi, Some(4), OperandWidth::Word)).with_src(Operand::Register16(Register16::Ax))));
+
+        // MOV DL, [BP-2]
+        assert_eq!(decode(&[0x8A, 0x56, 0xFE]), Ok(Instruction::new(Opcode::Mov, 3)
+            .with_dst(Operand::Register8(Register8::Dl)).with_src(memory(AddressBase::Bp, Some(-2), OperandWidth::Byte))));
+
+        // MOV DS, AX
+        assert_eq!(decode(&[0x8E, 0xD8]), Ok(Instruction::new(Opcode::Mov, 2)
+            .with_dst(Operand::Segment(SegmentRegister::Ds)).with_src(Operand::Register16(Register16::Ax))));
+
+        // MOV AL, [1234h]
+        assert_eq!(decode(&[0xA0, 0x34, 0x12]), Ok(Instruction::new(Opcode::Mov, 3)
+            .with_dst(Operand::Register8(Register8::Al))
+            .with_src(Operand::Memory { address: MemoryAddress::direct(0x1234), width: OperandWidth::Byte })));
+
+        // MOV WORD PTR [1000h], 5678h
+        assert_eq!(decode(&[0xC7, 0x06, 0x00, 0x10, 0x78, 0x56]), Ok(Instruction::new(Opcode::Mov, 6)
+            .with_dst(Operand::Memory { address: MemoryAddress::direct(0x1000), width: OperandWidth::Word })
+            .with_src(Operand::Immediate16(0x5678))));
+    }
+
+    #[test]
+    fn test_decode_arithmetic_forms() {
+        // ADD AL, 7h
+        assert_eq!(decode(&[0x04, 0x07]), Ok(Instruction::new(Opcode::Add, 2)
+            .with_dst(Operand::Register8(Register8::Al)).with_src(Operand::Immediate8(7))));
+
+        // CMP AX, BX
+        assert_eq!(decode(&[0x39, 0xD8]), Ok(Instruction::new(Opcode::Cmp, 2)
+            .with_dst(Operand::Register16(Register16::Ax)).with_src(Operand::Register16(Register16::Bx))));
+
+        // SUB WORD PTR [DI], -1 (sign extended immediate)
+        assert_eq!(decode(&[0x83, 0x2D, 0xFF]), Ok(Instruction::new(Opcode::Sub, 3)
+            .with_dst(memory(AddressBase::Di, None, OperandWidth::Word)).with_src(Operand::Immediate16(0xFFFF))));
+
+        // XOR BYTE PTR [BX+1000h], 80h
+        assert_eq!(decode(&[0x80, 0xB7, 0x00, 0x10, 0x80]), Ok(Instruction::new(Opcode::Xor, 5)
+            .with_dst(memory(AddressBase::Bx, Some(0x1000), OperandWidth::Byte)).with_src(Operand::Immediate8(0x80))));
+
+        // SHL CX, CL
+        assert_eq!(decode(&[0xD3, 0xE1]), Ok(Instruction::new(Opcode::Shl, 2)
+            .with_dst(Operand::Register16(Register16::Cx)).with_src(Operand::Register8(Register8::Cl))));
+
+        // TEST BYTE PTR [SI], 1h and NOT BYTE PTR [SI]
+        assert_eq!(decode(&[0xF6, 0x04, 0x01]), Ok(Instruction::new(Opcode::Test, 3)
+            .with_dst(memory(AddressBase::Si, None, OperandWidth::Byte)).with_src(Operand::Immediate8(1))));
+        assert_eq!(decode(&[0xF6, 0x14]), Ok(Instruction::new(Opcode::Not, 2)
+            .with_dst(memory(AddressBase::Si, None, OperandWidth::Byte))));
+    }
+
+    #[test]
+    fn test_decode_control_transfer() {
+        assert_eq!(decode(&[0xEB, 0xFE]), Ok(Instruction::new(Opcode::Jmp, 2).with_dst(Operand::Relative(-2))));
+        assert_eq!(decode(&[0x74, 0x10]), Ok(Instruction::new(Opcode::Je, 2).with_dst(Operand::Relative(0x10))));
+        assert_eq!(decode(&[0xE8, 0x00, 0x80]), Ok(Instruction::new(Opcode::Call, 3).with_dst(Operand::Relative(i16::MIN))));
+        assert_eq!(decode(&[0xEA, 0x5B, 0xE0, 0x00, 0xF0]), Ok(Instruction::new(Opcode::JmpFar, 5)
+            .with_dst(Operand::FarPointer { segment: 0xF000, offset: 0xE05B })));
+        assert_eq!(decode(&[0xFF, 0x1F]), Ok(Instruction::new(Opcode::CallFar, 2)
+            .with_dst(memory(AddressBase::Bx, None, OperandWidth::Word))));
+        assert_eq!(decode(&[0xCD, 0x21]), Ok(Instruction::new(Opcode::Int, 2).with_dst(Operand::Immediate8(0x21))));
+        assert_eq!(decode(&[0xC2, 0x04, 0x00]), Ok(Instruction::new(Opcode::Ret, 3).with_dst(Operand::Immediate16(4))));
+    }
+
+    #[test]
+    fn test_decode_stack_and_io() {
+        assert_eq!(decode(&[0x0E]), Ok(Instruction::new(Opcode::Push, 1).with_dst(Operand::Segment(SegmentRegister::Cs))));
+        assert_eq!(decode(&[0x5F]), Ok(Instruction::new(Opcode::Pop, 1).with_dst(Operand::Register16(Register16::Di))));
+        assert_eq!(decode(&[0x93]), Ok(Instruction::new(Opcode::Xchg, 1)
+            .with_dst(Operand::Register16(Register16::Ax)).with_src(Operand::Register16(Register16::Bx))));
+        assert_eq!(decode(&[0xE6, 0x20]), Ok(Instruction::new(Opcode::Out, 2)
+            .with_dst(Operand::Immediate8(0x20)).with_src(Operand::Register8(Register8::Al))));
+        assert_eq!(decode(&[0xED]), Ok(Instruction::new(Opcode::In, 1)
+            .with_dst(Operand::Register16(Register16::Ax)).with_src(Operand::Register16(Register16::Dx))));
+    }
+
+    #[test]
+    fn test_decode_byte_length_with_prefixes() {
+        let mut prefix = PrefixFlags::new();
+        prefix.set_lock(true);
+
+        // LOCK INC WORD PTR [BX+SI+1234h]
+        let instruction = decode(&[0xF0, 0xFF, 0x80, 0x34, 0x12]).unwrap();
+        assert_eq!(instruction, Instruction::new(Opcode::Inc, 5)
+            .with_dst(memory(AddressBase::BxSi, Some(0x1234), OperandWidth::Word)).with_prefix(prefix));
+    }
+
+    #[test]
+    fn test_decode_segment_overrides() {
+        let regs = Registers { bx: 0x0010, cs: 0x1000, ds: 0x2000, es: 0x3000, ss: 0x4000, ..Registers::new() };
+
+        let expected = [
+            (0x26, SegmentRegister::Es, SegmentedAddress::new(0x3000, 0x0010)),
+            (0x2E, SegmentRegister::Cs, SegmentedAddress::new(0x1000, 0x0010)),
+            (0x36, SegmentRegister::Ss, SegmentedAddress::new(0x4000, 0x0010)),
+            (0x3E, SegmentRegister::Ds, SegmentedAddress::new(0x2000, 0x0010))
+        ];
+
+        for (prefix_byte, segment, address) in expected {
+            // MOV AX, seg:[BX]
+            let instruction = decode(&[prefix_byte, 0x8B, 0x07]).unwrap();
+
+            assert_eq!(instruction.opcode, Opcode::Mov);
+            assert_eq!(instruction.byte_length, 3);
+            assert_eq!(instruction.prefix.segment_override(), Some(segment));
+            assert_eq!(instruction.dst, Some(Operand::Register16(Register16::Ax)));
+
+            let Some(Operand::Memory { address: memory, width: OperandWidth::Word }) = instruction.src else {
+                panic!("expected a word memory operand, found {:?}", instruction.src);
+            };
+
+            assert_eq!(memory.segment_override, Some(segment));
+            assert_eq!(EffectiveAddress::Memory(memory).resolve(&regs), Some(address));
+        }
+
+        // Without a prefix the default segment is used
+        let Some(Operand::Memory { address: memory, .. }) = decode(&[0x8B, 0x07]).unwrap().src else { unreachable!() };
+        assert_eq!(memory.resolve(&regs), SegmentedAddress::new(0x2000, 0x0010));
+    }
+
+    #[test]
+    fn test_decode_segment_override_destination_and_repeats() {
+        // CS: MOV [BP+2], AL overrides the default SS
+        let instruction = decode(&[0x2E, 0x88, 0x46, 0x02]).unwrap();
+        assert_eq!(instruction.dst, Some(Operand::Memory {
+            address: MemoryAddress { segment_override: Some(SegmentRegister::Cs), ..MemoryAddress::new(AddressBase::Bp, Some(2)) },
+            width: OperandWidth::Byte
+        }));
+        assert_eq!(instruction.byte_length, 4);
+
+        // The last of several overrides wins
+        let instruction = decode(&[0x26, 0x36, 0xA1, 0x00, 0x01]).unwrap();
+        assert_eq!(instruction.prefix.segment_override(), Some(SegmentRegister::Ss));
+        assert_eq!(instruction.src, Some(Operand::Memory {
+            address: MemoryAddress { segment_override: Some(SegmentRegister::Ss), ..MemoryAddress::direct(0x100) },
+            width: OperandWidth::Word
+        }));
+        assert_eq!(instruction.byte_length, 5);
+
+        // Register operands are unaffected, the prefix is still recorded
+        let instruction = decode(&[0x26, 0x89, 0xD8]).unwrap();
+        assert_eq!(instruction.dst, Some(Operand::Register16(Register16::Ax)));
+        assert_eq!(instruction.prefix.segment_override(), Some(SegmentRegister::Es));
+    }
+
+    #[test]
+    fn test_decode_repeat_prefixes() {
+        // REP MOVSB
+        let instruction = decode(&[0xF3, 0xA4]).unwrap();
+        assert_eq!(instruction.opcode, Opcode::Movsb);
+        assert!(instruction.prefix.rep() && !instruction.prefix.repne());
+        assert_eq!(instruction.byte_length, 2);
+
+        // REPNE SCASB
+        let instruction = decode(&[0xF2, 0xAE]).unwrap();
+        assert_eq!(instruction.opcode, Opcode::Scasb);
+        assert!(instruction.prefix.repne() && !instruction.prefix.rep());
+        assert_eq!(instruction.byte_length, 2);
+
+        // REPE CMPSB shares its encoding with REP
+        let instruction = decode(&[0xF3, 0xA6]).unwrap();
+        assert_eq!(instruction.opcode, Opcode::Cmpsb);
+        assert!(instruction.prefix.rep());
+
+        // ES: REP STOSW keeps both prefixes
+        let instruction = decode(&[0x26, 0xF3, 0xAB]).unwrap();
+        assert_eq!(instruction.opcode, Opcode::Stosw);
+        assert!(instruction.prefix.rep());
+        assert_eq!(instruction.prefix.segment_override(), Some(SegmentRegister::Es));
+        assert_eq!(instruction.byte_length, 3);
+
+        // The last repeat prefix wins
+        let instruction = decode(&[0xF3, 0xF2, 0xA7]).unwrap();
+        assert!(instruction.prefix.repne() && !instruction.prefix.rep());
+    }
+
+    #[test]
+    fn test_decode_unexpected_repeat_prefix() {
+        assert_eq!(decode(&[0xF3, 0x90]), Err(DecodeError::UnexpectedPrefix { address: 0, prefix: 0xF3 }));
+        assert_eq!(decode(&[0x2E, 0xF2, 0x8B, 0x07]), Err(DecodeError::UnexpectedPrefix { address: 1, prefix: 0xF2 }));
+        assert_eq!(decode(&[0xF3, 0xF3, 0xC3]), Err(DecodeError::UnexpectedPrefix { address: 1, prefix: 0xF3 }));
+
+        // LOCK is not restricted to string operations
+        assert!(decode(&[0xF0, 0x87, 0x07]).unwrap().prefix.lock());
+    }
+
+    #[test]
+    fn test_decode_sequence() {
+        let mut map = MemoryMap::new().with_range(0x100..=0x1FF, Box::new(Memory::<0x100>::empty()));
+        mem::RegionBusDevice::write_region(&mut map, 0x100, &[0xB8, 0x34, 0x12, 0x01, 0xD8, 0x90, 0xF4]).unwrap();
+
+        let decoder = InstructionDecoder::new();
+        let mut address = 0x100;
+        let mut opcodes = Vec::new();
+
+        for _ in 0..4 {
+            let instruction = decoder.decode(&map, address).unwrap();
+            address += usize::from(instruction.byte_length);
+            opcodes.push(instruction.opcode);
+        }
+
+        assert_eq!(opcodes, [Opcode::Mov, Opcode::Add, Opcode::Nop, Opcode::Hlt]);
+        assert_eq!(address, 0x107);
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(decode(&[0x0F]), Err(DecodeError::InvalidOpcode { address: 0, opcode: 0x0F }));
+        assert_eq!(decode(&[0x2E, 0xD6]), Err(DecodeError::InvalidOpcode { address: 1, opcode: 0xD6 }));
+        assert_eq!(decode(&[0xFE, 0xD0]), Err(DecodeError::InvalidOpcode { address: 0, opcode: 0xFE }));
+        assert_eq!(decode(&[0xFF, 0xD8]), Err(DecodeError::InvalidOpcode { address: 0, opcode: 0xFF }));
+        assert_eq!(decode(&[0x8D, 0xC0]), Err(DecodeError::InvalidOpcode { address: 0, opcode: 0x8D }));
+    }
+
+    #[test]
+    fn test_decode_bus_errors() {
+        let mem = Memory::<2>::filled([0xB8, 0x34]);
+        assert_eq!(InstructionDecoder::new().decode(&mem, 0), Err(DecodeError::Bus(BusDeviceError::AddressOutOfBounds { address: 2, size: 2 })));
+
+        // An endless run of prefixes cannot form an instruction
+        let prefixes = ConstantDevice::new(0x26, true);
+        assert_eq!(InstructionDecoder::new().decode(&prefixes, 0x10), Err(DecodeError::InstructionTooLong { address: 0x10 }));
+    }
+}
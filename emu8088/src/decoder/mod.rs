@@ -0,0 +1,20 @@
+pub mod modrm;
+pub use modrm::*;
+
+pub mod opcode;
+pub use opcode::*;
+
+pub mod operand;
+pub use operand::*;
+
+pub mod prefix;
+pub use prefix::*;
+
+pub mod instruction;
+pub use instruction::*;
+
+pub mod instruction_decoder;
+pub use instruction_decoder::*;
+
+pub mod disassembler;
+pub use disassembler::*;
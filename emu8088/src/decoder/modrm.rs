@@ -0,0 +1,130 @@
+use crate::{AddressBase, EffectiveAddress, MemoryAddress};
+
+/// The fields of a `ModRM` byte, which follows the opcode of instructions taking a register or memory operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModRM {
+    /// Bits 6-7, selecting the displacement size or a register operand.
+    pub mod_field: u8,
+    /// Bits 3-5, selecting a register operand or an opcode extension.
+    pub reg: u8,
+    /// Bits 0-2, selecting the base of a memory operand or a register operand.
+    pub rm: u8
+}
+
+impl ModRM {
+    #[must_use]
+    /// Splits a `ModRM` byte into its fields.
+    pub const fn decode(byte: u8) -> Self {
+        Self {
+            mod_field: byte >> 6,
+            reg: (byte >> 3) & 0b111,
+            rm: byte & 0b111
+        }
+    }
+
+    #[must_use]
+    /// Reassembles the fields into a `ModRM` byte.
+    pub const fn encode(&self) -> u8 {
+        (self.mod_field & 0b11) << 6 | (self.reg & 0b111) << 3 | (self.rm & 0b111)
+    }
+
+    #[must_use]
+    /// Returns the number of displacement bytes which follow the `ModRM` byte.
+    pub const fn displacement_length(&self) -> usize {
+        match (self.mod_field, self.rm) {
+            (0b00, 0b110) | (0b10, _) => 2,
+            (0b01, _) => 1,
+            _ => 0
+        }
+    }
+
+    #[must_use]
+    /// Decodes the operand selected by the `mod` and `r/m` fields, taking any displacement from the start of
+    /// `extra_bytes`, and returns it along with the number of displacement bytes consumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `extra_bytes` is shorter than `displacement_length()`.
+    pub fn to_effective_address(&self, extra_bytes: &[u8]) -> (EffectiveAddress, usize) {
+        let base = AddressBase::from_rm(self.rm);
+
+        let address = match (self.mod_field, self.rm) {
+            (0b11, rm) => return (EffectiveAddress::Register(rm), 0),
+            (0b00, 0b110) => MemoryAddress::direct(u16::from_le_bytes([extra_bytes[0], extra_bytes[1]])),
+            (0b00, _) => MemoryAddress::new(base, None),
+            (0b01, _) => MemoryAddress::new(base, Some(i16::from(extra_bytes[0].cast_signed()))),
+            _ => MemoryAddress::new(base, Some(i16::from_le_bytes([extra_bytes[0], extra_bytes[1]])))
+        };
+
+        (EffectiveAddress::Memory(address), self.displacement_length())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modrm_decode() {
+        assert_eq!(ModRM::decode(0b11_010_001), ModRM { mod_field: 0b11, reg: 0b010, rm: 0b001 });
+        assert_eq!(ModRM::decode(0b00_111_110), ModRM { mod_field: 0b00, reg: 0b111, rm: 0b110 });
+        assert_eq!(ModRM::decode(0b01_000_111), ModRM { mod_field: 0b01, reg: 0b000, rm: 0b111 });
+
+        for byte in 0..=255 {
+            assert_eq!(ModRM::decode(byte).encode(), byte);
+        }
+    }
+
+    #[test]
+    fn test_modrm_register() {
+        for rm in 0..8 {
+            let modrm = ModRM { mod_field: 0b11, reg: 0, rm };
+            assert_eq!(modrm.displacement_length(), 0);
+            assert_eq!(modrm.to_effective_address(&[]), (EffectiveAddress::Register(rm), 0));
+        }
+    }
+
+    #[test]
+    fn test_modrm_no_displacement() {
+        let modrm = ModRM::decode(0b00_000_000);
+        assert_eq!(modrm.to_effective_address(&[0xFF, 0xFF]), (EffectiveAddress::Memory(MemoryAddress::new(AddressBase::BxSi, None)), 0));
+
+        let modrm = ModRM::decode(0b00_000_111);
+        assert_eq!(modrm.to_effective_address(&[]), (EffectiveAddress::Memory(MemoryAddress::new(AddressBase::Bx, None)), 0));
+    }
+
+    #[test]
+    fn test_modrm_direct() {
+        let modrm = ModRM::decode(0b00_011_110);
+        assert_eq!(modrm.displacement_length(), 2);
+        assert_eq!(modrm.to_effective_address(&[0x34, 0x12, 0x99]), (EffectiveAddress::Memory(MemoryAddress::direct(0x1234)), 2));
+    }
+
+    #[test]
+    fn test_modrm_byte_displacement() {
+        let modrm = ModRM::decode(0b01_000_110);
+        assert_eq!(modrm.displacement_length(), 1);
+        assert_eq!(modrm.to_effective_address(&[0x04]), (EffectiveAddress::Memory(MemoryAddress::new(AddressBase::Bp, Some(4))), 1));
+
+        // 8-bit displacements are sign extended
+        let modrm = ModRM::decode(0b01_000_100);
+        assert_eq!(modrm.to_effective_address(&[0xFE, 0x12]), (EffectiveAddress::Memory(MemoryAddress::new(AddressBase::Si, Some(-2))), 1));
+        assert_eq!(modrm.to_effective_address(&[0x80]), (EffectiveAddress::Memory(MemoryAddress::new(AddressBase::Si, Some(-128))), 1));
+    }
+
+    #[test]
+    fn test_modrm_word_displacement() {
+        let modrm = ModRM::decode(0b10_000_011);
+        assert_eq!(modrm.displacement_length(), 2);
+        assert_eq!(modrm.to_effective_address(&[0x00, 0x80]), (EffectiveAddress::Memory(MemoryAddress::new(AddressBase::BpDi, Some(i16::MIN))), 2));
+
+        let modrm = ModRM::decode(0b10_000_110);
+        assert_eq!(modrm.to_effective_address(&[0x10, 0x00]), (EffectiveAddress::Memory(MemoryAddress::new(AddressBase::Bp, Some(0x10))), 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_modrm_missing_displacement() {
+        let _ = ModRM::decode(0b10_000_000).to_effective_address(&[0x00]);
+    }
+}
@@ -0,0 +1,320 @@
+use crate::SegmentRegister;
+
+/// The operation named by the primary opcode byte of an 8086 instruction.
+///
+/// Most opcode bytes map directly onto a mnemonic, with the operand forms left to the decoder. The opcode bytes which
+/// are shared by a family of instructions are represented by the `Group` variants, carrying the opcode byte, and are
+/// resolved into their mnemonic by `resolve_group` once the `reg` field of the following `ModRM` byte is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Opcode {
+    // Arithmetic and logic
+    Add, Or, Adc, Sbb, And, Sub, Xor, Cmp,
+    Inc, Dec, Neg, Not, Test, Mul, Imul, Div, Idiv,
+    Daa, Das, Aaa, Aas, Aam, Aad, Cbw, Cwd,
+    Rol, Ror, Rcl, Rcr, Shl, Shr, Sar,
+
+    // Data transfer
+    Mov, Push, Pop, Xchg, Lea, Lds, Les, Lahf, Sahf, Pushf, Popf, Xlat, In, Out,
+
+    // String operations
+    Movsb, Movsw, Cmpsb, Cmpsw, Scasb, Scasw, Lodsb, Lodsw, Stosb, Stosw,
+
+    // Control transfer
+    Jmp, JmpFar, Call, CallFar, Ret, RetFar, Int, Int3, Into, Iret,
+    Jo, Jno, Jb, Jnb, Je, Jne, Jbe, Ja, Js, Jns, Jp, Jnp, Jl, Jge, Jle, Jg,
+    Loopne, Loope, Loop, Jcxz,
+
+    // Processor control
+    Clc, Stc, Cmc, Cld, Std, Cli, Sti, Hlt, Wait, Esc, Nop,
+
+    // Prefixes
+    Lock, Repne, Rep, SegmentOverride(SegmentRegister),
+
+    /// Immediate arithmetic group (`0x80`-`0x83`): `ADD`, `OR`, `ADC`, `SBB`, `AND`, `SUB`, `XOR`, `CMP`.
+    Group1(u8),
+    /// Shift and rotate group (`0xD0`-`0xD3`): `ROL`, `ROR`, `RCL`, `RCR`, `SHL`, `SHR`, `SAR`.
+    Group2(u8),
+    /// Unary arithmetic group (`0xF6`-`0xF7`): `TEST`, `NOT`, `NEG`, `MUL`, `IMUL`, `DIV`, `IDIV`.
+    Group3(u8),
+    /// Byte increment group (`0xFE`): `INC`, `DEC`.
+    Group4(u8),
+    /// Word increment and indirect control transfer group (`0xFF`): `INC`, `DEC`, `CALL`, `CALL FAR`, `JMP`,
+    /// `JMP FAR`, `PUSH`.
+    Group5(u8),
+
+    /// An opcode byte which is not documented for the 8086.
+    Invalid(u8)
+}
+
+impl Opcode {
+    #[must_use]
+    /// Returns the operation named by a primary opcode byte.
+    pub const fn from_byte(b: u8) -> Self {
+        // The eight arithmetic operations share a block of six opcodes each at 0x00-0x3F
+        const ARITHMETIC: [Opcode; 8] = [Opcode::Add, Opcode::Or, Opcode::Adc, Opcode::Sbb, Opcode::And, Opcode::Sub, Opcode::Xor, Opcode::Cmp];
+        const JUMPS: [Opcode; 16] = [
+            Opcode::Jo, Opcode::Jno, Opcode::Jb, Opcode::Jnb, Opcode::Je, Opcode::Jne, Opcode::Jbe, Opcode::Ja,
+            Opcode::Js, Opcode::Jns, Opcode::Jp, Opcode::Jnp, Opcode::Jl, Opcode::Jge, Opcode::Jle, Opcode::Jg
+        ];
+
+        match b {
+            0x00..=0x3F if b & 0b111 < 6 => ARITHMETIC[(b >> 3) as usize],
+            0x26 | 0x2E | 0x36 | 0x3E => Self::SegmentOverride(SegmentRegister::from_index(b >> 3)),
+            0x27 => Self::Daa,
+            0x2F => Self::Das,
+            0x37 => Self::Aaa,
+            0x3F => Self::Aas,
+            0x40..=0x47 => Self::Inc,
+            0x48..=0x4F => Self::Dec,
+            0x06 | 0x0E | 0x16 | 0x1E | 0x50..=0x57 => Self::Push,
+            0x07 | 0x17 | 0x1F | 0x58..=0x5F | 0x8F => Self::Pop,
+            0x70..=0x7F => JUMPS[(b & 0x0F) as usize],
+            0x80..=0x83 => Self::Group1(b),
+            0x84 | 0x85 | 0xA8 | 0xA9 => Self::Test,
+            0x86 | 0x87 | 0x91..=0x97 => Self::Xchg,
+            0x88..=0x8C | 0x8E | 0xA0..=0xA3 | 0xB0..=0xBF | 0xC6 | 0xC7 => Self::Mov,
+            0x8D => Self::Lea,
+            0x90 => Self::Nop,
+            0x98 => Self::Cbw,
+            0x99 => Self::Cwd,
+            0x9A => Self::CallFar,
+            0x9B => Self::Wait,
+            0x9C => Self::Pushf,
+            0x9D => Self::Popf,
+            0x9E => Self::Sahf,
+            0x9F => Self::Lahf,
+            0xA4 => Self::Movsb,
+            0xA5 => Self::Movsw,
+            0xA6 => Self::Cmpsb,
+            0xA7 => Self::Cmpsw,
+            0xAA => Self::Stosb,
+            0xAB => Self::Stosw,
+            0xAC => Self::Lodsb,
+            0xAD => Self::Lodsw,
+            0xAE => Self::Scasb,
+            0xAF => Self::Scasw,
+            0xC2 | 0xC3 => Self::Ret,
+            0xC4 => Self::Les,
+            0xC5 => Self::Lds,
+            0xCA | 0xCB => Self::RetFar,
+            0xCC => Self::Int3,
+            0xCD => Self::Int,
+            0xCE => Self::Into,
+            0xCF => Self::Iret,
+            0xD0..=0xD3 => Self::Group2(b),
+            0xD4 => Self::Aam,
+            0xD5 => Self::Aad,
+            0xD7 => Self::Xlat,
+            0xD8..=0xDF => Self::Esc,
+            0xE0 => Self::Loopne,
+            0xE1 => Self::Loope,
+            0xE2 => Self::Loop,
+            0xE3 => Self::Jcxz,
+            0xE4 | 0xE5 | 0xEC | 0xED => Self::In,
+            0xE6 | 0xE7 | 0xEE | 0xEF => Self::Out,
+            0xE8 => Self::Call,
+            0xE9 | 0xEB => Self::Jmp,
+            0xEA => Self::JmpFar,
+            0xF0 => Self::Lock,
+            0xF2 => Self::Repne,
+            0xF3 => Self::Rep,
+            0xF4 => Self::Hlt,
+            0xF5 => Self::Cmc,
+            0xF6 | 0xF7 => Self::Group3(b),
+            0xF8 => Self::Clc,
+            0xF9 => Self::Stc,
+            0xFA => Self::Cli,
+            0xFB => Self::Sti,
+            0xFC => Self::Cld,
+            0xFD => Self::Std,
+            0xFE => Self::Group4(b),
+            0xFF => Self::Group5(b),
+            _ => Self::Invalid(b)
+        }
+    }
+
+    #[must_use]
+    /// Resolves a group opcode into the operation selected by the `reg` field of its `ModRM` byte. Encodings which
+    /// are not documented for the 8086 resolve to `Invalid`, and opcodes which are not groups are returned unchanged.
+    pub const fn resolve_group(self, reg: u8) -> Self {
+        const GROUP1: [Opcode; 8] = [Opcode::Add, Opcode::Or, Opcode::Adc, Opcode::Sbb, Opcode::And, Opcode::Sub, Opcode::Xor, Opcode::Cmp];
+        const GROUP2: [Option<Opcode>; 8] = [Some(Opcode::Rol), Some(Opcode::Ror), Some(Opcode::Rcl), Some(Opcode::Rcr), Some(Opcode::Shl), Some(Opcode::Shr), None, Some(Opcode::Sar)];
+        const GROUP3: [Option<Opcode>; 8] = [Some(Opcode::Test), None, Some(Opcode::Not), Some(Opcode::Neg), Some(Opcode::Mul), Some(Opcode::Imul), Some(Opcode::Div), Some(Opcode::Idiv)];
+        const GROUP4: [Option<Opcode>; 8] = [Some(Opcode::Inc), Some(Opcode::Dec), None, None, None, None, None, None];
+        const GROUP5: [Option<Opcode>; 8] = [Some(Opcode::Inc), Some(Opcode::Dec), Some(Opcode::Call), Some(Opcode::CallFar), Some(Opcode::Jmp), Some(Opcode::JmpFar), Some(Opcode::Push), None];
+
+        let reg = (reg & 0b111) as usize;
+
+        let (byte, resolved) = match self {
+            Self::Group1(_) => return GROUP1[reg],
+            Self::Group2(byte) => (byte, GROUP2[reg]),
+            Self::Group3(byte) => (byte, GROUP3[reg]),
+            Self::Group4(byte) => (byte, GROUP4[reg]),
+            Self::Group5(byte) => (byte, GROUP5[reg]),
+            _ => return self
+        };
+
+        match resolved {
+            Some(opcode) => opcode,
+            None => Self::Invalid(byte)
+        }
+    }
+
+    #[must_use]
+    /// Returns `true` for the group opcodes which must be resolved with `resolve_group`.
+    pub const fn is_group(self) -> bool {
+        matches!(self, Self::Group1(_) | Self::Group2(_) | Self::Group3(_) | Self::Group4(_) | Self::Group5(_))
+    }
+
+    #[must_use]
+    /// Returns `true` for the prefix bytes which modify the following instruction.
+    pub const fn is_prefix(self) -> bool {
+        matches!(self, Self::Lock | Self::Repne | Self::Rep | Self::SegmentOverride(_))
+    }
+
+    #[must_use]
+    /// Returns `true` for the string operations, which may be repeated with a `REP` or `REPNE` prefix.
+    pub const fn is_string_operation(self) -> bool {
+        matches!(self, Self::Movsb | Self::Movsw | Self::Cmpsb | Self::Cmpsw | Self::Scasb | Self::Scasw | Self::Lodsb | Self::Lodsw | Self::Stosb | Self::Stosw)
+    }
+
+    #[must_use]
+    /// Returns the Intel mnemonic of the operation, or `None` for unresolved groups and invalid opcodes.
+    pub const fn mnemonic(self) -> Option<&'static str> {
+        Some(match self {
+            Self::Add => "ADD", Self::Or => "OR", Self::Adc => "ADC", Self::Sbb => "SBB",
+            Self::And => "AND", Self::Sub => "SUB", Self::Xor => "XOR", Self::Cmp => "CMP",
+            Self::Inc => "INC", Self::Dec => "DEC", Self::Neg => "NEG", Self::Not => "NOT",
+            Self::Test => "TEST", Self::Mul => "MUL", Self::Imul => "IMUL", Self::Div => "DIV", Self::Idiv => "IDIV",
+            Self::Daa => "DAA", Self::Das => "DAS", Self::Aaa => "AAA", Self::Aas => "AAS",
+            Self::Aam => "AAM", Self::Aad => "AAD", Self::Cbw => "CBW", Self::Cwd => "CWD",
+            Self::Rol => "ROL", Self::Ror => "ROR", Self::Rcl => "RCL", Self::Rcr => "RCR",
+            Self::Shl => "SHL", Self::Shr => "SHR", Self::Sar => "SAR",
+            Self::Mov => "MOV", Self::Push => "PUSH", Self::Pop => "POP", Self::Xchg => "XCHG",
+            Self::Lea => "LEA", Self::Lds => "LDS", Self::Les => "LES", Self::Lahf => "LAHF", Self::Sahf => "SAHF",
+            Self::Pushf => "PUSHF", Self::Popf => "POPF", Self::Xlat => "XLAT", Self::In => "IN", Self::Out => "OUT",
+            Self::Movsb => "MOVSB", Self::Movsw => "MOVSW", Self::Cmpsb => "CMPSB", Self::Cmpsw => "CMPSW",
+            Self::Scasb => "SCASB", Self::Scasw => "SCASW", Self::Lodsb => "LODSB", Self::Lodsw => "LODSW",
+            Self::Stosb => "STOSB", Self::Stosw => "STOSW",
+            Self::Jmp | Self::JmpFar => "JMP", Self::Call | Self::CallFar => "CALL",
+            Self::Ret => "RET", Self::RetFar => "RETF", Self::Int | Self::Int3 => "INT", Self::Into => "INTO", Self::Iret => "IRET",
+            Self::Jo => "JO", Self::Jno => "JNO", Self::Jb => "JB", Self::Jnb => "JNB",
+            Self::Je => "JE", Self::Jne => "JNE", Self::Jbe => "JBE", Self::Ja => "JA",
+            Self::Js => "JS", Self::Jns => "JNS", Self::Jp => "JP", Self::Jnp => "JNP",
+            Self::Jl => "JL", Self::Jge => "JGE", Self::Jle => "JLE", Self::Jg => "JG",
+            Self::Loopne => "LOOPNE", Self::Loope => "LOOPE", Self::Loop => "LOOP", Self::Jcxz => "JCXZ",
+            Self::Clc => "CLC", Self::Stc => "STC", Self::Cmc => "CMC", Self::Cld => "CLD", Self::Std => "STD",
+            Self::Cli => "CLI", Self::Sti => "STI", Self::Hlt => "HLT", Self::Wait => "WAIT", Self::Esc => "ESC",
+            Self::Nop => "NOP",
+            Self::Lock => "LOCK", Self::Repne => "REPNE", Self::Rep => "REP",
+            Self::SegmentOverride(SegmentRegister::Es) => "ES:",
+            Self::SegmentOverride(SegmentRegister::Cs) => "CS:",
+            Self::SegmentOverride(SegmentRegister::Ss) => "SS:",
+            Self::SegmentOverride(SegmentRegister::Ds) => "DS:",
+            Self::Group1(_) | Self::Group2(_) | Self::Group3(_) | Self::Group4(_) | Self::Group5(_) | Self::Invalid(_) => return None
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opcode_arithmetic_blocks() {
+        let expected = [Opcode::Add, Opcode::Or, Opcode::Adc, Opcode::Sbb, Opcode::And, Opcode::Sub, Opcode::Xor, Opcode::Cmp];
+
+        for (block, opcode) in expected.into_iter().enumerate() {
+            let base = u8::try_from(block).unwrap() << 3;
+
+            for offset in 0..6 {
+                assert_eq!(Opcode::from_byte(base + offset), opcode);
+            }
+        }
+    }
+
+    #[test]
+    fn test_opcode_single_bytes() {
+        let expected = [
+            (0x06, Opcode::Push), (0x07, Opcode::Pop), (0x0E, Opcode::Push), (0x0F, Opcode::Invalid(0x0F)),
+            (0x26, Opcode::SegmentOverride(SegmentRegister::Es)), (0x2E, Opcode::SegmentOverride(SegmentRegister::Cs)),
+            (0x36, Opcode::SegmentOverride(SegmentRegister::Ss)), (0x3E, Opcode::SegmentOverride(SegmentRegister::Ds)),
+            (0x27, Opcode::Daa), (0x2F, Opcode::Das), (0x37, Opcode::Aaa), (0x3F, Opcode::Aas),
+            (0x43, Opcode::Inc), (0x4B, Opcode::Dec), (0x55, Opcode::Push), (0x5D, Opcode::Pop),
+            (0x60, Opcode::Invalid(0x60)), (0x6F, Opcode::Invalid(0x6F)),
+            (0x70, Opcode::Jo), (0x74, Opcode::Je), (0x7F, Opcode::Jg),
+            (0x84, Opcode::Test), (0x87, Opcode::Xchg), (0x89, Opcode::Mov), (0x8C, Opcode::Mov), (0x8D, Opcode::Lea),
+            (0x8E, Opcode::Mov), (0x8F, Opcode::Pop), (0x90, Opcode::Nop), (0x97, Opcode::Xchg),
+            (0x9A, Opcode::CallFar), (0xA1, Opcode::Mov), (0xA4, Opcode::Movsb), (0xAF, Opcode::Scasw),
+            (0xB8, Opcode::Mov), (0xC0, Opcode::Invalid(0xC0)), (0xC3, Opcode::Ret), (0xCB, Opcode::RetFar),
+            (0xCC, Opcode::Int3), (0xCD, Opcode::Int), (0xCF, Opcode::Iret), (0xD6, Opcode::Invalid(0xD6)),
+            (0xD7, Opcode::Xlat), (0xDB, Opcode::Esc), (0xE2, Opcode::Loop), (0xE3, Opcode::Jcxz),
+            (0xE4, Opcode::In), (0xEF, Opcode::Out), (0xE8, Opcode::Call), (0xE9, Opcode::Jmp), (0xEA, Opcode::JmpFar),
+            (0xEB, Opcode::Jmp), (0xF0, Opcode::Lock), (0xF1, Opcode::Invalid(0xF1)), (0xF2, Opcode::Repne),
+            (0xF3, Opcode::Rep), (0xF4, Opcode::Hlt), (0xFD, Opcode::Std)
+        ];
+
+        for (byte, opcode) in expected {
+            assert_eq!(Opcode::from_byte(byte), opcode, "opcode {byte:#04x}");
+        }
+    }
+
+    #[test]
+    fn test_opcode_groups() {
+        assert_eq!(Opcode::from_byte(0x81), Opcode::Group1(0x81));
+        assert_eq!(Opcode::from_byte(0x81).resolve_group(0b101), Opcode::Sub);
+        assert_eq!(Opcode::from_byte(0x83).resolve_group(0b111), Opcode::Cmp);
+
+        assert_eq!(Opcode::from_byte(0xD2).resolve_group(0b100), Opcode::Shl);
+        assert_eq!(Opcode::from_byte(0xD0).resolve_group(0b110), Opcode::Invalid(0xD0));
+        assert_eq!(Opcode::from_byte(0xD3).resolve_group(0b111), Opcode::Sar);
+
+        assert_eq!(Opcode::from_byte(0xF6).resolve_group(0b000), Opcode::Test);
+        assert_eq!(Opcode::from_byte(0xF7).resolve_group(0b001), Opcode::Invalid(0xF7));
+        assert_eq!(Opcode::from_byte(0xF7).resolve_group(0b111), Opcode::Idiv);
+
+        assert_eq!(Opcode::from_byte(0xFE).resolve_group(0b001), Opcode::Dec);
+        assert_eq!(Opcode::from_byte(0xFE).resolve_group(0b010), Opcode::Invalid(0xFE));
+
+        assert_eq!(Opcode::from_byte(0xFF).resolve_group(0b011), Opcode::CallFar);
+        assert_eq!(Opcode::from_byte(0xFF).resolve_group(0b110), Opcode::Push);
+        assert_eq!(Opcode::from_byte(0xFF).resolve_group(0b111), Opcode::Invalid(0xFF));
+
+        assert_eq!(Opcode::Mov.resolve_group(3), Opcode::Mov);
+    }
+
+    #[test]
+    fn test_opcode_classification() {
+        let mut groups = 0;
+        let mut prefixes = 0;
+        let mut invalid = 0;
+        let mut strings = 0;
+
+        for byte in 0..=255 {
+            let opcode = Opcode::from_byte(byte);
+
+            groups += usize::from(opcode.is_group());
+            prefixes += usize::from(opcode.is_prefix());
+            invalid += usize::from(matches!(opcode, Opcode::Invalid(_)));
+            strings += usize::from(opcode.is_string_operation());
+
+            assert_eq!(opcode.mnemonic().is_none(), opcode.is_group() || matches!(opcode, Opcode::Invalid(_)));
+        }
+
+        assert_eq!(groups, 12);
+        assert_eq!(prefixes, 7);
+        // 0x0F, 0x60-0x6F, 0xC0, 0xC1, 0xC8, 0xC9, 0xD6 and 0xF1
+        assert_eq!(invalid, 23);
+        // 0xA4-0xA7 and 0xAA-0xAF
+        assert_eq!(strings, 10);
+    }
+
+    #[test]
+    fn test_opcode_mnemonic() {
+        assert_eq!(Opcode::Mov.mnemonic(), Some("MOV"));
+        assert_eq!(Opcode::RetFar.mnemonic(), Some("RETF"));
+        assert_eq!(Opcode::SegmentOverride(SegmentRegister::Es).mnemonic(), Some("ES:"));
+        assert_eq!(Opcode::Group1(0x80).mnemonic(), None);
+        assert_eq!(Opcode::Invalid(0x0F).mnemonic(), None);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{EffectiveAddress, MemoryAddress, Register16, Register8, SegmentRegister};
+
+/// The width of an operand, selected by the `w` bit of most opcodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OperandWidth {
+    Byte,
+    Word
+}
+
+impl OperandWidth {
+    #[must_use]
+    /// Returns the width selected by the `w` bit (bit 0) of an opcode byte.
+    pub const fn from_w_bit(opcode: u8) -> Self {
+        if opcode & 1 == 0 { Self::Byte } else { Self::Word }
+    }
+
+    #[must_use]
+    /// Returns the number of bytes in an operand of this width.
+    pub const fn bytes(self) -> usize {
+        match self {
+            Self::Byte => 1,
+            Self::Word => 2
+        }
+    }
+}
+
+/// A source or destination operand of a decoded instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Operand {
+    Register8(Register8),
+    Register16(Register16),
+    Segment(SegmentRegister),
+    Immediate8(u8),
+    Immediate16(u16),
+    /// A memory operand of the given width.
+    Memory { address: MemoryAddress, width: OperandWidth },
+    /// A signed displacement relative to the address of the following instruction, used by relative jumps and calls.
+    Relative(i16),
+    /// An absolute `segment:offset` target, used by direct far jumps and calls.
+    FarPointer { segment: u16, offset: u16 }
+}
+
+impl Operand {
+    #[must_use]
+    /// Converts the operand selected by a `ModRM` byte into an `Operand` of the given width, naming an 8-bit or 16-bit
+    /// register for register operands.
+    pub const fn from_effective_address(address: EffectiveAddress, width: OperandWidth) -> Self {
+        match (address, width) {
+            (EffectiveAddress::Register(index), OperandWidth::Byte) => Self::Register8(Register8::from_index(index)),
+            (EffectiveAddress::Register(index), OperandWidth::Word) => Self::Register16(Register16::from_index(index)),
+            (EffectiveAddress::Memory(address), width) => Self::Memory { address, width }
+        }
+    }
+
+    #[must_use]
+    /// Returns the register with the given 3-bit encoding as an operand of the given width.
+    pub const fn register(index: u8, width: OperandWidth) -> Self {
+        Self::from_effective_address(EffectiveAddress::Register(index), width)
+    }
+
+    #[must_use]
+    /// Returns the width of the operand, or `None` for relative and far pointer operands, which have no data width.
+    pub const fn width(&self) -> Option<OperandWidth> {
+        match self {
+            Self::Register8(_) | Self::Immediate8(_) => Some(OperandWidth::Byte),
+            Self::Register16(_) | Self::Segment(_) | Self::Immediate16(_) => Some(OperandWidth::Word),
+            Self::Memory { width, .. } => Some(*width),
+            Self::Relative(_) | Self::FarPointer { .. } => None
+        }
+    }
+
+    #[must_use]
+    /// Returns a copy of the operand using `segment` in place of the default segment. Operands which do not refer to
+    /// memory are returned unchanged.
+    pub const fn with_segment_override(self, segment: Option<SegmentRegister>) -> Self {
+        match self {
+            Self::Memory { address, width } if segment.is_some() => Self::Memory { address: MemoryAddress { segment_override: segment, ..address }, width },
+            _ => self
+        }
+    }
+
+    #[must_use]
+    /// Returns `true` if the operand refers to memory.
+    pub const fn is_memory(&self) -> bool {
+        matches!(self, Self::Memory { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::AddressBase;
+
+    use super::*;
+
+    #[test]
+    fn test_operand_from_effective_address() {
+        assert_eq!(Operand::from_effective_address(EffectiveAddress::Register(3), OperandWidth::Byte), Operand::Register8(Register8::Bl));
+        assert_eq!(Operand::from_effective_address(EffectiveAddress::Register(3), OperandWidth::Word), Operand::Register16(Register16::Bx));
+        assert_eq!(Operand::register(4, OperandWidth::Byte), Operand::Register8(Register8::Ah));
+
+        let address = MemoryAddress::new(AddressBase::BpSi, Some(-2));
+        let operand = Operand::from_effective_address(EffectiveAddress::Memory(address), OperandWidth::Word);
+
+        assert_eq!(operand, Operand::Memory { address, width: OperandWidth::Word });
+        assert!(operand.is_memory());
+    }
+
+    #[test]
+    fn test_operand_width() {
+        assert_eq!(OperandWidth::from_w_bit(0x88), OperandWidth::Byte);
+        assert_eq!(OperandWidth::from_w_bit(0x89), OperandWidth::Word);
+        assert_eq!(OperandWidth::Word.bytes(), 2);
+
+        assert_eq!(Operand::Immediate8(1).width(), Some(OperandWidth::Byte));
+        assert_eq!(Operand::Segment(SegmentRegister::Es).width(), Some(OperandWidth::Word));
+        assert_eq!(Operand::Memory { address: MemoryAddress::direct(0), width: OperandWidth::Byte }.width(), Some(OperandWidth::Byte));
+        assert_eq!(Operand::Relative(-2).width(), None);
+        assert_eq!(Operand::FarPointer { segment: 0xF000, offset: 0xFFF0 }.width(), None);
+    }
+
+    #[test]
+    fn test_operand_segment_override() {
+        let operand = Operand::Memory { address: MemoryAddress::direct(0x10), width: OperandWidth::Byte };
+
+        assert_eq!(operand.with_segment_override(Some(SegmentRegister::Cs)),
+            Operand::Memory { address: MemoryAddress { segment_override: Some(SegmentRegister::Cs), ..MemoryAddress::direct(0x10) }, width: OperandWidth::Byte });
+        assert_eq!(operand.with_segment_override(None), operand);
+        assert_eq!(Operand::Immediate8(1).with_segment_override(Some(SegmentRegister::Cs)), Operand::Immediate8(1));
+    }
+}
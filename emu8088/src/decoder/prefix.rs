@@ -0,0 +1,151 @@
+use crate::SegmentRegister;
+
+/// The prefixes applied to a decoded instruction, packed into a byte.
+///
+/// `REP` and `REPNE` are mutually exclusive, setting one clears the other, as only the last repeat prefix before an
+/// instruction takes effect. At most one segment override is recorded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PrefixFlags(u8);
+
+impl PrefixFlags {
+    /// `LOCK` prefix (`0xF0`)
+    pub const LOCK: u8 = 1 << 0;
+    /// `REP`/`REPE`/`REPZ` prefix (`0xF3`)
+    pub const REP: u8 = 1 << 1;
+    /// `REPNE`/`REPNZ` prefix (`0xF2`)
+    pub const REPNE: u8 = 1 << 2;
+    /// Set when a segment override prefix is present, with the segment register in the two bits above it.
+    pub const SEGMENT_OVERRIDE: u8 = 1 << 3;
+
+    /// Position of the 2-bit segment register encoding.
+    const SEGMENT_SHIFT: u8 = 4;
+
+    #[must_use]
+    /// Constructs an empty set of prefixes.
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    #[must_use]
+    /// Returns the raw bits of the prefixes.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    #[must_use]
+    /// Returns `true` if no prefixes are present.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Sets or clears the bits of `mask`.
+    const fn set(&mut self, mask: u8, value: bool) {
+        if value {
+            self.0 |= mask;
+        }
+        else {
+            self.0 &= !mask;
+        }
+    }
+
+    #[must_use]
+    /// Returns `true` if a `LOCK` prefix is present.
+    pub const fn lock(self) -> bool {
+        self.0 & Self::LOCK != 0
+    }
+
+    /// Sets the presence of a `LOCK` prefix.
+    pub const fn set_lock(&mut self, value: bool) {
+        self.set(Self::LOCK, value);
+    }
+
+    #[must_use]
+    /// Returns `true` if a `REP` prefix is present.
+    pub const fn rep(self) -> bool {
+        self.0 & Self::REP != 0
+    }
+
+    /// Sets the presence of a `REP` prefix, clearing any `REPNE` prefix.
+    pub const fn set_rep(&mut self, value: bool) {
+        self.set(Self::REPNE, false);
+        self.set(Self::REP, value);
+    }
+
+    #[must_use]
+    /// Returns `true` if a `REPNE` prefix is present.
+    pub const fn repne(self) -> bool {
+        self.0 & Self::REPNE != 0
+    }
+
+    /// Sets the presence of a `REPNE` prefix, clearing any `REP` prefix.
+    pub const fn set_repne(&mut self, value: bool) {
+        self.set(Self::REP, false);
+        self.set(Self::REPNE, value);
+    }
+
+    #[must_use]
+    /// Returns the segment register named by a segment override prefix, if one is present.
+    pub const fn segment_override(self) -> Option<SegmentRegister> {
+        if self.0 & Self::SEGMENT_OVERRIDE == 0 {
+            None
+        }
+        else {
+            Some(SegmentRegister::from_index(self.0 >> Self::SEGMENT_SHIFT))
+        }
+    }
+
+    /// Sets or clears the segment override.
+    pub const fn set_segment_override(&mut self, segment: Option<SegmentRegister>) {
+        self.0 &= !(Self::SEGMENT_OVERRIDE | 0b11 << Self::SEGMENT_SHIFT);
+
+        if let Some(segment) = segment {
+            self.0 |= Self::SEGMENT_OVERRIDE | (segment as u8) << Self::SEGMENT_SHIFT;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_flags_empty() {
+        let prefix = PrefixFlags::new();
+
+        assert!(prefix.is_empty());
+        assert!(!prefix.lock() && !prefix.rep() && !prefix.repne());
+        assert_eq!(prefix.segment_override(), None);
+        assert_eq!(prefix, PrefixFlags::default());
+    }
+
+    #[test]
+    fn test_prefix_flags_repeat_exclusive() {
+        let mut prefix = PrefixFlags::new();
+
+        prefix.set_rep(true);
+        assert!(prefix.rep() && !prefix.repne());
+
+        prefix.set_repne(true);
+        assert!(!prefix.rep() && prefix.repne());
+        assert_eq!(prefix.bits(), PrefixFlags::REPNE);
+
+        prefix.set_repne(false);
+        assert!(prefix.is_empty());
+    }
+
+    #[test]
+    fn test_prefix_flags_segment_override() {
+        let mut prefix = PrefixFlags::new();
+        prefix.set_lock(true);
+
+        for segment in [SegmentRegister::Es, SegmentRegister::Cs, SegmentRegister::Ss, SegmentRegister::Ds] {
+            prefix.set_segment_override(Some(segment));
+            assert_eq!(prefix.segment_override(), Some(segment));
+            assert!(prefix.lock());
+        }
+
+        prefix.set_segment_override(None);
+        assert_eq!(prefix.segment_override(), None);
+        assert_eq!(prefix.bits(), PrefixFlags::LOCK);
+    }
+}
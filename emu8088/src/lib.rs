@@ -1,7 +1,14 @@
+pub mod cpu;
+pub use cpu::*;
 
+pub mod decoder;
+pub use decoder::*;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod assembler;
+pub use assembler::*;
 
-}
+pub mod peripherals;
+pub use peripherals::*;
+
+pub mod loader;
+pub use loader::*;
\ No newline at end of file
@@ -0,0 +1,118 @@
+use mem::{segmented_linear, write_u16_seg, BusDevice, MemoryMap};
+
+use crate::{LoadError, Registers};
+
+/// The offset within its segment at which a COM program is loaded and started, just past its PSP.
+pub const COM_LOAD_OFFSET: u16 = 0x100;
+
+/// The largest COM program which can be loaded, filling its segment from `COM_LOAD_OFFSET` up to the word at the top
+/// of the stack.
+pub const COM_MAX_SIZE: usize = 0xFFFE - COM_LOAD_OFFSET as usize;
+
+/// The length in bytes of the program segment prefix.
+pub const PSP_SIZE: usize = 0x100;
+
+/// Builds the program segment prefix of a program loaded at `segment`.
+///
+/// Only the parts a program is likely to use are filled in. The program is given its whole segment, it has no
+/// environment, and its command tail is empty.
+fn program_segment_prefix(segment: u16) -> [u8; PSP_SIZE] {
+    let mut psp = [0; PSP_SIZE];
+
+    // INT 20h, so that jumping to offset 0 terminates the program
+    psp[0x00..0x02].copy_from_slice(&[0xCD, 0x20]);
+    // The segment just past the memory allocated to the program
+    psp[0x02..0x04].copy_from_slice(&segment.wrapping_add(0x1000).to_le_bytes());
+    // INT 21h, RETF, the far call entry to DOS
+    psp[0x50..0x53].copy_from_slice(&[0xCD, 0x21, 0xCB]);
+    // An empty command tail, terminated by a carriage return
+    psp[0x81] = b'\r';
+
+    psp
+}
+
+/// Loads flat COM programs into memory, as DOS does with `EXEC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ComLoader;
+
+impl ComLoader {
+    /// Copies the COM program `data` into `mem` at `segment:0100`, builds its PSP at `segment:0000`, and starts it at
+    /// `segment:0100` with every segment register pointing at `segment`.
+    ///
+    /// As under DOS a zero word is pushed onto the stack, leaving `SP` at `FFFEh`, so that a near return from the
+    /// program reaches the `INT 20h` at the start of its PSP.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `data` is longer than `COM_MAX_SIZE`, or if the program, its PSP or its
+    /// stack cannot be written to `mem`.
+    pub fn load(data: &[u8], mem: &mut MemoryMap, regs: &mut Registers, segment: u16) -> Result<(), LoadError> {
+        if data.len() > COM_MAX_SIZE {
+            return Err(LoadError::TooLarge { size: data.len(), limit: COM_MAX_SIZE });
+        }
+
+        mem.write_bytes(segmented_linear(segment, 0), &program_segment_prefix(segment))?;
+        mem.write_bytes(segmented_linear(segment, COM_LOAD_OFFSET), data)?;
+        write_u16_seg(mem, segment, 0xFFFE, 0x0000)?;
+
+        regs.cs = segment;
+        regs.ds = segment;
+        regs.es = segment;
+        regs.ss = segment;
+        regs.ip = COM_LOAD_OFFSET;
+        regs.sp = 0xFFFE;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::{BusDeviceError, IoMap, Memory, RegionBusDevice};
+
+    use crate::{Cpu, StepResult};
+
+    use super::*;
+
+    #[test]
+    fn test_com_loader() {
+        let mut map = MemoryMap::new().with_range(0x00000..=0x2FFFF, Box::new(Memory::<0x30000>::filled_with(0xFF)));
+        let mut regs = Registers::new();
+
+        // MOV AL, 'A'; RET
+        assert_eq!(ComLoader::load(&[0xB0, 0x41, 0xC3], &mut map, &mut regs, 0x1000), Ok(()));
+
+        assert_eq!((regs.cs, regs.ds, regs.es, regs.ss), (0x1000, 0x1000, 0x1000, 0x1000));
+        assert_eq!((regs.ip, regs.sp), (0x0100, 0xFFFE));
+
+        assert_eq!(map.read_region(0x10000), Ok([0xCD, 0x20, 0x00, 0x20]));
+        assert_eq!(map.read_region(0x10050), Ok([0xCD, 0x21, 0xCB]));
+        assert_eq!(map.read_region(0x10080), Ok([0x00, 0x0D, 0x00]));
+        assert_eq!(map.read_region(0x100FF), Ok([0x00, 0xB0, 0x41, 0xC3, 0xFF]));
+        assert_eq!(map.read_region(0x1FFFE), Ok([0x00, 0x00]));
+
+        // Returning from the program lands on the INT 20h at the start of the PSP
+        let mut cpu = Cpu::new(map, IoMap::new());
+        *cpu.registers_mut() = regs;
+
+        assert!(matches!(cpu.step(), Ok(StepResult::Ok(_))));
+        assert!(matches!(cpu.step(), Ok(StepResult::Ok(_))));
+        assert_eq!((cpu.registers().ip, cpu.registers().sp, cpu.registers().ax & 0xFF), (0x0000, 0x0000, 0x41));
+    }
+
+    #[test]
+    fn test_com_loader_errors() {
+        let mut map = MemoryMap::new().with_range(0x00000..=0x1FFFF, Box::new(Memory::<0x20000>::empty()));
+        let mut regs = Registers::new();
+
+        let data = vec![0x90; COM_MAX_SIZE + 1];
+        assert_eq!(ComLoader::load(&data, &mut map, &mut regs, 0x1000), Err(LoadError::TooLarge { size: 0xFEFF, limit: 0xFEFE }));
+        assert_eq!(ComLoader::load(&data[1..], &mut map, &mut regs, 0x1000), Ok(()));
+        assert_eq!(map.read_region(0x1FFFC), Ok([0x90, 0x90, 0x00, 0x00]));
+
+        // The stack at the top of the segment is not mapped
+        regs = Registers::new();
+        assert_eq!(ComLoader::load(&[0xC3], &mut map, &mut regs, 0x1800), Err(LoadError::Bus(BusDeviceError::AddressNotMapped { address: 0x27FFE })));
+        assert_eq!(regs, Registers::new());
+    }
+}
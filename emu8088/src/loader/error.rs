@@ -0,0 +1,41 @@
+use std::fmt::Display;
+
+use mem::BusDeviceError;
+
+use super::ParseError;
+
+/// An error raised while loading a program into memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LoadError {
+    /// The file ends before the end of the data its header describes.
+    Truncated{length: usize, required: usize},
+    /// The program is larger than the memory its format allows it.
+    TooLarge{size: usize, limit: usize},
+    /// The header of the file could not be parsed.
+    Parse(ParseError),
+    /// The program could not be written to memory.
+    Bus(BusDeviceError)
+}
+
+impl From<ParseError> for LoadError {
+    fn from(value: ParseError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+impl From<BusDeviceError> for LoadError {
+    fn from(value: BusDeviceError) -> Self {
+        Self::Bus(value)
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated { length, required } => write!(f, "file is {length} bytes long, but {required} bytes are required"),
+            Self::TooLarge { size, limit } => write!(f, "program is {size} bytes long, but at most {limit} bytes can be loaded"),
+            Self::Parse(error) => write!(f, "bad header: {error:X?}"),
+            Self::Bus(error) => write!(f, "bus error: {error:X?}")
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod mz;
+pub use mz::*;
+
+pub mod error;
+pub use error::*;
+
+pub mod com;
+pub use com::*;
\ No newline at end of file
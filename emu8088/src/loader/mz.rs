@@ -0,0 +1,257 @@
+use mem::{read_u16_seg, segmented_linear, write_u16_seg, BusDevice, MemoryMap};
+
+use crate::{LoadError, Registers};
+
+/// The signature opening every MZ executable, the bytes `"MZ"` read as a little endian word.
+pub const MZ_MAGIC: u16 = 0x5A4D;
+
+/// The length in bytes of the fixed part of the MZ header, before the relocation table.
+pub const MZ_HEADER_SIZE: usize = 28;
+
+/// An error raised while parsing an executable header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ParseError {
+    /// The data ends before the end of the header.
+    TooShort{length: usize, required: usize},
+    /// The data does not start with the signature of the format.
+    BadMagic{magic: u16}
+}
+
+/// The fixed header of a DOS MZ executable, describing the load module which follows it and the initial register
+/// values.
+///
+/// Every field is a little endian word, in the order they appear in the file. Sizes are in 512 byte pages or 16 byte
+/// paragraphs, as DOS uses them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MzHeader {
+    pub magic: u16,
+    /// The number of bytes used in the last page of the file, or zero if the whole page is used.
+    pub bytes_last_page: u16,
+    /// The number of pages in the file, including the last partial page.
+    pub pages: u16,
+    pub relocation_count: u16,
+    /// The size of the header, including the relocation table, in paragraphs.
+    pub header_paragraphs: u16,
+    /// The number of paragraphs the program needs beyond the load module.
+    pub min_alloc: u16,
+    /// The number of paragraphs the program would like beyond the load module.
+    pub max_alloc: u16,
+    /// The initial `SS`, relative to the start of the load module.
+    pub initial_ss: u16,
+    pub initial_sp: u16,
+    pub checksum: u16,
+    pub initial_ip: u16,
+    /// The initial `CS`, relative to the start of the load module.
+    pub initial_cs: u16,
+    /// The offset of the relocation table from the start of the file.
+    pub relocation_table_offset: u16,
+    pub overlay_number: u16
+}
+
+impl MzHeader {
+    /// Parses the header at the start of `data`, checking the signature.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `data` is shorter than the header, or does not start with `MZ_MAGIC`.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let header = data.get(..MZ_HEADER_SIZE).ok_or(ParseError::TooShort { length: data.len(), required: MZ_HEADER_SIZE })?;
+
+        let mut words = header.chunks_exact(2).map(|word| u16::from_le_bytes([word[0], word[1]]));
+        let mut next = || words.next().unwrap_or_default();
+
+        let magic = next();
+
+        if magic != MZ_MAGIC {
+            return Err(ParseError::BadMagic { magic });
+        }
+
+        Ok(Self {
+            magic,
+            bytes_last_page: next(),
+            pages: next(),
+            relocation_count: next(),
+            header_paragraphs: next(),
+            min_alloc: next(),
+            max_alloc: next(),
+            initial_ss: next(),
+            initial_sp: next(),
+            checksum: next(),
+            initial_ip: next(),
+            initial_cs: next(),
+            relocation_table_offset: next(),
+            overlay_number: next()
+        })
+    }
+
+    #[must_use]
+    /// Returns the size of the executable in bytes as given by the header, which may be shorter than the file if data
+    /// such as overlays is appended to it.
+    pub fn file_size(&self) -> usize {
+        let pages = usize::from(self.pages) * 512;
+
+        match self.bytes_last_page {
+            0 => pages,
+            bytes => pages.saturating_sub(512) + usize::from(bytes)
+        }
+    }
+
+    #[must_use]
+    /// Returns the size of the header, including the relocation table, in bytes.
+    pub fn header_size(&self) -> usize {
+        usize::from(self.header_paragraphs) * 16
+    }
+}
+
+/// Loads MZ executables into memory, as DOS does with `EXEC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct MzLoader;
+
+impl MzLoader {
+    /// Copies the load module of the executable `raw`, described by `header`, into `mem` at `load_segment:0000`,
+    /// relocates it, and points `CS:IP` and `SS:SP` at its entry point and stack.
+    ///
+    /// Each relocation names a word in the load module holding a segment relative to its start, to which
+    /// `load_segment` is added. As under DOS, `DS` and `ES` are set to the segment sixteen paragraphs below
+    /// `load_segment`, where the PSP of the program would be placed, though no PSP is written.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `raw` is shorter than the file described by `header` or does not contain
+    /// its relocation table, or if the load module cannot be written to `mem`.
+    pub fn load(header: &MzHeader, raw: &[u8], mem: &mut MemoryMap, regs: &mut Registers, load_segment: u16) -> Result<(), LoadError> {
+        let file_size = header.file_size();
+        let header_size = header.header_size();
+
+        let image = raw.get(header_size..file_size).ok_or_else(|| LoadError::Truncated { length: raw.len().min(file_size), required: file_size.max(header_size) })?;
+
+        let table_start = usize::from(header.relocation_table_offset);
+        let table_end = table_start + usize::from(header.relocation_count) * 4;
+        let table = raw.get(table_start..table_end).ok_or(LoadError::Truncated { length: raw.len(), required: table_end })?;
+
+        mem.write_bytes(segmented_linear(load_segment, 0), image)?;
+
+        for relocation in table.chunks_exact(4) {
+            let offset = u16::from_le_bytes([relocation[0], relocation[1]]);
+            let segment = load_segment.wrapping_add(u16::from_le_bytes([relocation[2], relocation[3]]));
+
+            let value = read_u16_seg(mem, segment, offset)?;
+            write_u16_seg(mem, segment, offset, value.wrapping_add(load_segment))?;
+        }
+
+        regs.cs = header.initial_cs.wrapping_add(load_segment);
+        regs.ip = header.initial_ip;
+        regs.ss = header.initial_ss.wrapping_add(load_segment);
+        regs.sp = header.initial_sp;
+        regs.ds = load_segment.wrapping_sub(0x10);
+        regs.es = regs.ds;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::{IoMap, Memory, RegionBusDevice};
+
+    use crate::{Cpu, StepResult};
+
+    use super::*;
+
+    /// The header of a small program, with one relocation and a 1000 byte load module.
+    const HEADER: [u8; 32] = [
+        0x4D, 0x5A, 0x08, 0x00, 0x03, 0x00, 0x01, 0x00, 0x02, 0x00, 0x10, 0x00, 0xFF, 0xFF, 0x3F, 0x00,
+        0x00, 0x01, 0x12, 0x34, 0x00, 0x00, 0x00, 0x00, 0x1C, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00
+    ];
+
+    #[test]
+    fn test_mz_header_parse() {
+        let header = MzHeader::parse(&HEADER).unwrap();
+
+        assert_eq!(header, MzHeader {
+            magic: MZ_MAGIC,
+            bytes_last_page: 8,
+            pages: 3,
+            relocation_count: 1,
+            header_paragraphs: 2,
+            min_alloc: 0x10,
+            max_alloc: 0xFFFF,
+            initial_ss: 0x3F,
+            initial_sp: 0x100,
+            checksum: 0x3412,
+            initial_ip: 0,
+            initial_cs: 0,
+            relocation_table_offset: 0x1C,
+            overlay_number: 0
+        });
+
+        assert_eq!(header.file_size(), 1032);
+        assert_eq!(header.header_size(), 32);
+
+        // A full last page is given as zero
+        let header = MzHeader { bytes_last_page: 0, ..header };
+        assert_eq!(header.file_size(), 1536);
+    }
+
+    #[test]
+    fn test_mz_header_errors() {
+        assert_eq!(MzHeader::parse(&HEADER[..27]), Err(ParseError::TooShort { length: 27, required: 28 }));
+        assert_eq!(MzHeader::parse(&[]), Err(ParseError::TooShort { length: 0, required: 28 }));
+        assert!(MzHeader::parse(&HEADER[..28]).is_ok());
+
+        let mut data = HEADER;
+        data[..2].copy_from_slice(b"ZM");
+        assert_eq!(MzHeader::parse(&data), Err(ParseError::BadMagic { magic: 0x4D5A }));
+    }
+
+    /// A program which loads the segment of its data through a relocation, then halts.
+    const PROGRAM: [u8; 50] = [
+        // 50 bytes in one page, a header of two paragraphs with one relocation, and the stack two paragraphs in
+        0x4D, 0x5A, 0x32, 0x00, 0x01, 0x00, 0x01, 0x00, 0x02, 0x00, 0x10, 0x00, 0xFF, 0xFF, 0x02, 0x00,
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1C, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        // mov ax, seg data; mov ds, ax; mov al, [0]; hlt
+        0xB8, 0x01, 0x00, 0x8E, 0xD8, 0xA0, 0x00, 0x00, 0xF4, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90,
+        // data
+        b'H', b'i'
+    ];
+
+    #[test]
+    fn test_mz_loader() {
+        let header = MzHeader::parse(&PROGRAM).unwrap();
+        let mut map = MemoryMap::new().with_range(0x00000..=0x1FFFF, Box::new(Memory::<0x20000>::empty()));
+        let mut regs = Registers::new();
+
+        assert_eq!(MzLoader::load(&header, &PROGRAM, &mut map, &mut regs, 0x1010), Ok(()));
+
+        assert_eq!((regs.cs, regs.ip), (0x1010, 0x0000));
+        assert_eq!((regs.ss, regs.sp), (0x1012, 0x0100));
+        assert_eq!((regs.ds, regs.es), (0x1000, 0x1000));
+
+        // The relocated segment of the data
+        assert_eq!(map.read_region(0x10100), Ok([0xB8, 0x11, 0x10]));
+        assert_eq!(map.read_region(0x10110), Ok(*b"Hi"));
+
+        let mut cpu = Cpu::new(map, IoMap::new());
+        *cpu.registers_mut() = regs;
+
+        while cpu.step() != Ok(StepResult::Halted) {}
+        assert_eq!((cpu.registers().ds, cpu.registers().ax), (0x1011, 0x1048));
+    }
+
+    #[test]
+    fn test_mz_loader_truncated() {
+        let header = MzHeader::parse(&PROGRAM).unwrap();
+        let mut map = MemoryMap::new().with_range(0x00000..=0x1FFFF, Box::new(Memory::<0x20000>::empty()));
+        let mut regs = Registers::new();
+
+        assert_eq!(MzLoader::load(&header, &PROGRAM[..40], &mut map, &mut regs, 0x1010), Err(LoadError::Truncated { length: 40, required: 50 }));
+
+        let header = MzHeader { relocation_count: 200, ..header };
+        assert_eq!(MzLoader::load(&header, &PROGRAM, &mut map, &mut regs, 0x1010), Err(LoadError::Truncated { length: 50, required: 828 }));
+        assert_eq!(regs, Registers::new());
+
+        // Memory outside the map is reported as is
+        let header = MzHeader::parse(&PROGRAM).unwrap();
+        assert_eq!(MzLoader::load(&header, &PROGRAM, &mut map, &mut regs, 0x2000), Err(LoadError::Bus(mem::BusDeviceError::AddressNotMapped { address: 0x20000 })));
+    }
+}
@@ -0,0 +1,368 @@
+use std::{cell::{Cell, RefCell}, collections::VecDeque};
+
+use mem::{IoDevice, IoError, Shared};
+
+use crate::Pic8259;
+
+/// The data port of the keyboard controller in the IBM PC/AT, from which scancodes are read.
+pub const KBD_DATA_PORT: u16 = 0x60;
+
+/// The status and command port of the keyboard controller in the IBM PC/AT.
+pub const KBD_STATUS_PORT: u16 = 0x64;
+
+/// The interrupt line the keyboard controller raises when a byte arrives in its output buffer.
+pub const KBD_IRQ: u8 = 1;
+
+/// Status register bit set while the output buffer holds a byte for the processor.
+const STATUS_OUTPUT_FULL: u8 = 0x01;
+
+/// Status register bit set once the controller has passed its self test.
+const STATUS_SYSTEM: u8 = 0x04;
+
+/// Status register bit set if the last byte written went to the command port rather than the data port.
+const STATUS_COMMAND: u8 = 0x08;
+
+/// Status register bit set while the keyboard is not locked by the keylock switch.
+const STATUS_UNLOCKED: u8 = 0x10;
+
+/// Command byte bit enabling `IRQ1` when the output buffer fills.
+const COMMAND_IRQ: u8 = 0x01;
+
+/// Command byte bit disabling the keyboard, holding key events back until it is enabled.
+const COMMAND_DISABLE: u8 = 0x10;
+
+/// The command byte set up by the BIOS, with `IRQ1` enabled, the system flag set and scancode translation on.
+const DEFAULT_COMMAND_BYTE: u8 = 0x45;
+
+/// A key being pressed or released, identified by its scancode in set 1, the set the processor sees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyEvent {
+    pub scancode: u8,
+    pub released: bool
+}
+
+impl KeyEvent {
+    #[must_use]
+    /// Constructs the event of the key with `scancode` being pressed.
+    pub const fn press(scancode: u8) -> Self {
+        Self { scancode, released: false }
+    }
+
+    #[must_use]
+    /// Constructs the event of the key with `scancode` being released.
+    pub const fn release(scancode: u8) -> Self {
+        Self { scancode, released: true }
+    }
+
+    #[must_use]
+    /// Returns the byte the processor reads for the event, the make code for a press or the break code, with the top
+    /// bit set, for a release.
+    pub const fn to_byte(self) -> u8 {
+        if self.released { self.scancode | 0x80 } else { self.scancode }
+    }
+}
+
+/// An Intel 8042 keyboard controller, as in the IBM PC/AT at ports `60h` and `64h`, mapped into an `IoMap` over
+/// `60h` to `64h`, with the ports in between left unmapped by the controller.
+///
+/// Key events from the host are queued with `push_key_event` and moved into the output buffer one at a time, each
+/// time it is emptied by the processor reading port `60h`. If the controller is connected to a PIC with `with_pic`,
+/// `IRQ1` is raised as each byte arrives in the output buffer, while enabled by the command byte.
+///
+/// Of the controller commands, reading and writing the command byte, the self and interface tests, and disabling and
+/// enabling the keyboard are emulated. Every byte written to port `60h` for the keyboard is acknowledged with `FAh`,
+/// with the reset command `FFh` also answered by a passed self test, `AAh`.
+#[derive(Debug, Clone)]
+pub struct Kbd8042 {
+    queue: RefCell<VecDeque<u8>>,
+    output: Cell<Option<u8>>,
+    /// The last byte read from the output buffer, read again if the buffer is empty.
+    last_output: Cell<u8>,
+    command_byte: u8,
+    /// Whether the next byte written to port `60h` is the command byte, following command `60h`.
+    awaiting_command_byte: bool,
+    last_write_command: bool,
+    pic: Option<Shared<Pic8259>>
+}
+
+impl Kbd8042 {
+    #[must_use]
+    /// Constructs a keyboard controller in the state the BIOS leaves it in, with nothing queued and no PIC connected.
+    pub const fn new() -> Self {
+        Self {
+            queue: RefCell::new(VecDeque::new()),
+            output: Cell::new(None),
+            last_output: Cell::new(0),
+            command_byte: DEFAULT_COMMAND_BYTE,
+            awaiting_command_byte: false,
+            last_write_command: false,
+            pic: None
+        }
+    }
+
+    #[must_use]
+    /// Builder pattern for connecting the controller to `pic`, raising `IRQ1` on it as bytes arrive in the output
+    /// buffer.
+    pub fn with_pic(mut self, pic: Shared<Pic8259>) -> Self {
+        self.pic = Some(pic);
+        self
+    }
+
+    /// Queues `event` to be read by the processor, moving it straight into the output buffer if that is empty.
+    pub fn push_key_event(&mut self, event: KeyEvent) {
+        self.queue.get_mut().push_back(event.to_byte());
+        self.fill_output();
+    }
+
+    #[must_use]
+    /// Returns the number of bytes waiting behind the output buffer.
+    pub fn queued(&self) -> usize {
+        self.queue.borrow().len()
+    }
+
+    #[must_use]
+    /// Returns `true` if the output buffer holds a byte for the processor.
+    pub const fn output_full(&self) -> bool {
+        self.output.get().is_some()
+    }
+
+    #[must_use]
+    /// Returns the command byte, as last written by the guest.
+    pub const fn command_byte(&self) -> u8 {
+        self.command_byte
+    }
+
+    /// Places `byte` in the output buffer, replacing anything already there, and raises `IRQ1` if it is enabled.
+    fn load_output(&self, byte: u8) {
+        self.output.set(Some(byte));
+
+        if self.command_byte & COMMAND_IRQ != 0 {
+            if let Some(pic) = &self.pic {
+                pic.borrow_mut().raise_irq(KBD_IRQ);
+            }
+        }
+    }
+
+    /// Moves the next queued byte into the output buffer if it is empty and the keyboard is enabled.
+    fn fill_output(&self) {
+        if self.output.get().is_some() || self.command_byte & COMMAND_DISABLE != 0 {
+            return;
+        }
+
+        let next = self.queue.borrow_mut().pop_front();
+
+        if let Some(byte) = next {
+            self.load_output(byte);
+        }
+    }
+
+    /// Returns the value of the status register.
+    const fn status(&self) -> u8 {
+        let full = if self.output_full() { STATUS_OUTPUT_FULL } else { 0 };
+        let command = if self.last_write_command { STATUS_COMMAND } else { 0 };
+
+        STATUS_SYSTEM | STATUS_UNLOCKED | full | command
+    }
+
+    /// Handles a controller command written to the command port.
+    fn write_command(&mut self, command: u8) {
+        match command {
+            0x20 => self.load_output(self.command_byte),
+            0x60 => self.awaiting_command_byte = true,
+            0xAA => self.load_output(0x55),
+            0xAB => self.load_output(0x00),
+            0xAD => self.command_byte |= COMMAND_DISABLE,
+            0xAE => {
+                self.command_byte &= !COMMAND_DISABLE;
+                self.fill_output();
+            }
+            _ => {}
+        }
+    }
+
+    /// Handles a byte written to the data port, either for a pending controller command or for the keyboard.
+    fn write_data(&mut self, data: u8) {
+        if std::mem::take(&mut self.awaiting_command_byte) {
+            self.command_byte = data;
+        }
+        else {
+            // The keyboard answers ahead of any keys still waiting to be read
+            let queue = self.queue.get_mut();
+
+            if data == 0xFF {
+                queue.push_front(0xAA);
+            }
+
+            queue.push_front(0xFA);
+        }
+
+        self.fill_output();
+    }
+}
+
+impl Default for Kbd8042 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IoDevice for Kbd8042 {
+    fn read_port(&self, port: u16) -> Result<u8, IoError> {
+        if port != 0 {
+            return self.peek_port(port);
+        }
+
+        if let Some(byte) = self.output.take() {
+            self.last_output.set(byte);
+            self.fill_output();
+        }
+
+        Ok(self.last_output.get())
+    }
+
+    fn write_port(&mut self, port: u16, data: u8) -> Result<(), IoError> {
+        match port {
+            0 => self.write_data(data),
+            4 => self.write_command(data),
+            _ => return Err(IoError::PortNotMapped { port })
+        }
+
+        self.last_write_command = port == 4;
+
+        Ok(())
+    }
+
+    fn peek_port(&self, port: u16) -> Result<u8, IoError> {
+        match port {
+            0 => Ok(self.output.get().unwrap_or_else(|| self.last_output.get())),
+            4 => Ok(self.status()),
+            _ => Err(IoError::PortNotMapped { port })
+        }
+    }
+
+    /// Returns the controller to the state the BIOS leaves it in, discarding anything queued but staying connected to
+    /// its PIC.
+    fn reset(&mut self) {
+        let pic = self.pic.take();
+
+        *self = Self::new();
+        self.pic = pic;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::{IoMap, Memory, RegionBusDevice};
+
+    use crate::{cpu::testing::cpu_with_memory, peripherals::pic::initialize, StepResult};
+
+    use super::*;
+
+    #[test]
+    fn test_kbd_key_events() {
+        let mut kbd = Kbd8042::new();
+        assert_eq!(kbd.read_port(4).map(|status| status & STATUS_OUTPUT_FULL), Ok(0));
+
+        // Pressing and releasing A, then pressing Enter
+        kbd.push_key_event(KeyEvent::press(0x1E));
+        kbd.push_key_event(KeyEvent::release(0x1E));
+        kbd.push_key_event(KeyEvent { scancode: 0x1C, released: false });
+        assert_eq!(kbd.read_port(4), Ok(0x15));
+        assert_eq!(kbd.queued(), 2);
+
+        assert_eq!(kbd.peek_port(0), Ok(0x1E));
+        assert_eq!(kbd.read_port(0), Ok(0x1E));
+        assert_eq!(kbd.read_port(0), Ok(0x9E));
+        assert_eq!(kbd.read_port(0), Ok(0x1C));
+        assert!(!kbd.output_full());
+
+        // An empty buffer reads back the last byte
+        assert_eq!(kbd.read_port(0), Ok(0x1C));
+        assert_eq!(kbd.read_port(4), Ok(0x14));
+        assert_eq!(kbd.read_port(2), Err(IoError::PortNotMapped { port: 2 }));
+    }
+
+    #[test]
+    fn test_kbd_commands() {
+        let mut kbd = Kbd8042::new();
+        kbd.push_key_event(KeyEvent::press(0x01));
+
+        // The self test answer replaces what was in the output buffer
+        assert_eq!(kbd.write_port(4, 0xAA), Ok(()));
+        assert_eq!(kbd.read_port(4), Ok(0x1D));
+        assert_eq!(kbd.read_port(0), Ok(0x55));
+
+        // Rewriting the command byte to disable the keyboard holds keys back
+        kbd.write_port(4, 0x60).unwrap();
+        kbd.write_port(0, DEFAULT_COMMAND_BYTE | COMMAND_DISABLE).unwrap();
+        kbd.push_key_event(KeyEvent::press(0x02));
+        assert!(!kbd.output_full());
+        kbd.write_port(4, 0x20).unwrap();
+        assert_eq!(kbd.read_port(0), Ok(0x55));
+        kbd.write_port(4, 0xAE).unwrap();
+        assert_eq!(kbd.read_port(0), Ok(0x02));
+
+        // Keyboard commands are acknowledged, and a reset also reports a passed self test
+        kbd.write_port(0, 0xFF).unwrap();
+        assert_eq!(kbd.read_port(4), Ok(0x15));
+        assert_eq!(kbd.read_port(0), Ok(0xFA));
+        assert_eq!(kbd.read_port(0), Ok(0xAA));
+        assert_eq!(kbd.command_byte(), DEFAULT_COMMAND_BYTE);
+    }
+
+    #[test]
+    fn test_kbd_raises_irq1() {
+        let pic = Shared::new(Pic8259::new());
+        initialize(&mut pic.clone());
+        let mut kbd = Kbd8042::new().with_pic(pic.clone());
+
+        kbd.push_key_event(KeyEvent::press(0x1E));
+        kbd.push_key_event(KeyEvent::release(0x1E));
+        assert_eq!(pic.borrow_mut().poll_interrupt(), Some(0x09));
+
+        // The next byte is loaded as the handler reads the first, and waits at the PIC for the end of interrupt
+        assert_eq!(kbd.read_port(0), Ok(0x1E));
+        assert_eq!(pic.borrow_mut().poll_interrupt(), None);
+        pic.borrow_mut().write_port(0, 0x20).unwrap();
+        assert_eq!(pic.borrow_mut().poll_interrupt(), Some(0x09));
+
+        // With IRQ1 disabled in the command byte nothing is raised
+        kbd.write_port(4, 0x60).unwrap();
+        kbd.write_port(0, DEFAULT_COMMAND_BYTE & !COMMAND_IRQ).unwrap();
+        assert_eq!(kbd.read_port(0), Ok(0x9E));
+        kbd.push_key_event(KeyEvent::press(0x1C));
+        pic.borrow_mut().write_port(0, 0x20).unwrap();
+        assert_eq!(pic.borrow().irr(), 0x00);
+    }
+
+    #[test]
+    fn test_kbd_cpu_reads_scancode() {
+        let mut memory = Memory::<0x20000>::empty();
+        // The program at 1000:0000 halts, and the IRQ1 handler at 1800:0000 reads the scancode with IN AL, 60h
+        memory.write_region(0x09 * 4, &[0x00, 0x00, 0x00, 0x18]).unwrap();
+        memory.write_region(0x1_0000, &[0xF4]).unwrap();
+        memory.write_region(0x1_8000, &[0xE4, 0x60]).unwrap();
+
+        let pic = Shared::new(Pic8259::new());
+        initialize(&mut pic.clone());
+        let kbd = Shared::new(Kbd8042::new().with_pic(pic.clone()));
+        let io = IoMap::new()
+            .with_range(0x20..=0x21, Box::new(pic.clone()))
+            .with_range(KBD_DATA_PORT..=KBD_STATUS_PORT, Box::new(kbd.clone()));
+
+        let mut cpu = cpu_with_memory(memory);
+        *cpu.io_mut() = io;
+        cpu.set_interrupt_controller(Box::new(pic));
+        cpu.registers_mut().sp = 0x0100;
+        cpu.registers_mut().flags.set_interrupt(true);
+
+        assert_eq!(cpu.step(), Ok(StepResult::Halted));
+        kbd.borrow_mut().push_key_event(KeyEvent::press(0x39));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(61)));
+
+        assert_eq!(cpu.in_byte(KBD_STATUS_PORT).map(|status| status & STATUS_OUTPUT_FULL), Ok(STATUS_OUTPUT_FULL));
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(10)));
+        assert_eq!(cpu.registers().ax & 0xFF, 0x39);
+        assert!(!kbd.borrow().output_full());
+    }
+}
@@ -0,0 +1,11 @@
+pub mod pic;
+pub use pic::*;
+
+pub mod pit;
+pub use pit::*;
+
+pub mod uart;
+pub use uart::*;
+
+pub mod keyboard;
+pub use keyboard::*;
\ No newline at end of file
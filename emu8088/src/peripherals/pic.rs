@@ -0,0 +1,400 @@
+use mem::{IoDevice, IoError};
+
+use crate::InterruptController;
+
+/// The port of the master PIC in the IBM PC, taking `ICW1`, `OCW2` and `OCW3` and reading back `IRR` or `ISR`.
+pub const PIC_COMMAND_PORT: u16 = 0x20;
+
+/// The port of the master PIC in the IBM PC, taking `ICW2` to `ICW4` and `OCW1`, and reading back `IMR`.
+pub const PIC_DATA_PORT: u16 = 0x21;
+
+/// The initialization command word the PIC expects next on its data port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Initialization {
+    /// Not within an initialization sequence, so writes to the data port set the interrupt mask.
+    Ready,
+    /// Waiting for `ICW2`, the vector base.
+    Icw2,
+    /// Waiting for `ICW3`, describing the cascade.
+    Icw3,
+    /// Waiting for `ICW4`, selecting the mode.
+    Icw4
+}
+
+/// An Intel 8259A programmable interrupt controller, as the single master PIC of the IBM PC at ports `20h` and `21h`,
+/// mapped into an `IoMap` relative to the command port.
+///
+/// Requests are raised by other devices with `raise_irq`, and delivered to the processor through `poll_interrupt`,
+/// which performs the `INTACK` cycle. Priorities are fixed, with `IRQ0` highest, and a request is only delivered while
+/// no request of the same or higher priority is in service. The PIC delivers nothing until it has been initialized by
+/// the guest with `ICW1` to `ICW4`.
+///
+/// Of the operation command words, `OCW1` sets the interrupt mask, `OCW2` supports non-specific and specific `EOI`, and
+/// `OCW3` selects whether `IRR` or `ISR` is read back from the command port. Cascading, rotating priorities and the
+/// special mask and poll modes are not emulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pic8259 {
+    irr: u8,
+    isr: u8,
+    imr: u8,
+    vector_base: u8,
+    initialization: Initialization,
+    initialized: bool,
+    icw1: u8,
+    icw4: u8,
+    read_isr: bool
+}
+
+impl Pic8259 {
+    #[must_use]
+    /// Constructs a PIC in its power-on state, waiting to be initialized.
+    pub const fn new() -> Self {
+        Self {
+            irr: 0,
+            isr: 0,
+            imr: 0,
+            vector_base: 0,
+            initialization: Initialization::Ready,
+            initialized: false,
+            icw1: 0,
+            icw4: 0,
+            read_isr: false
+        }
+    }
+
+    /// Queues a request on interrupt line `irq`, as a rising edge on that input does. The request is held in `IRR`
+    /// until it is delivered, so raising it again before then has no further effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `irq` is not one of the eight lines `0` to `7`.
+    pub fn raise_irq(&mut self, irq: u8) {
+        assert!(irq < 8, "IRQ{irq} is not an input of the 8259");
+
+        self.irr |= 1 << irq;
+    }
+
+    /// Delivers the highest priority request which is pending, unmasked and not blocked by a request in service,
+    /// moving it from `IRR` to `ISR` and returning its interrupt vector, as the `INTACK` cycle does. In automatic
+    /// `EOI` mode the request is not held in service. Returns `None` if no request can be delivered.
+    pub const fn poll_interrupt(&mut self) -> Option<u8> {
+        let Some(irq) = self.pending_irq() else {
+            return None;
+        };
+
+        self.irr &= !(1 << irq);
+
+        // Outside automatic EOI mode the request is held in service until it is ended by an EOI
+        if self.icw4 & 0x02 == 0 {
+            self.isr |= 1 << irq;
+        }
+
+        Some(self.vector_base | irq)
+    }
+
+    #[must_use]
+    /// Returns `true` if the guest has completed the initialization sequence.
+    pub const fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    #[must_use]
+    /// Returns the interrupt request register, holding the requests waiting to be delivered.
+    pub const fn irr(&self) -> u8 {
+        self.irr
+    }
+
+    #[must_use]
+    /// Returns the in-service register, holding the delivered requests which have not yet been ended by an `EOI`.
+    pub const fn isr(&self) -> u8 {
+        self.isr
+    }
+
+    #[must_use]
+    /// Returns the interrupt mask register, in which a set bit masks the corresponding request.
+    pub const fn imr(&self) -> u8 {
+        self.imr
+    }
+
+    /// Returns the request `poll_interrupt` would deliver, if any.
+    const fn pending_irq(self) -> Option<u8> {
+        if !self.is_initialized() {
+            return None;
+        }
+
+        let requests = self.irr & !self.imr;
+
+        if requests == 0 {
+            return None;
+        }
+
+        // Fixed priority, so the lowest numbered request wins unless it is blocked by one of equal or higher priority
+        #[allow(clippy::cast_possible_truncation)]
+        let irq = requests.trailing_zeros() as u8;
+        let blocking = u8::MAX >> (7 - irq);
+
+        if self.isr & blocking == 0 { Some(irq) } else { None }
+    }
+
+    /// Handles a write of `data` to the command port.
+    const fn write_command(&mut self, data: u8) {
+        if data & 0x10 != 0 {
+            // ICW1 restarts initialization, clearing the mask and any requests in service
+            self.initialization = Initialization::Icw2;
+            self.initialized = false;
+            self.icw1 = data;
+            self.icw4 = 0;
+            self.imr = 0;
+            self.isr = 0;
+            self.read_isr = false;
+        }
+        else if data & 0x08 != 0 {
+            // OCW3, of which only the register select is emulated
+            if data & 0x02 != 0 {
+                self.read_isr = data & 0x01 != 0;
+            }
+        }
+        else {
+            // OCW2, of which only the end of interrupt commands are emulated
+            match data >> 5 {
+                0b001 => self.isr &= self.isr.wrapping_sub(1),
+                0b011 => self.isr &= !(1 << (data & 0x07)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Handles a write of `data` to the data port.
+    const fn write_data(&mut self, data: u8) {
+        let initialization = match self.initialization {
+            Initialization::Ready => {
+                self.imr = data;
+                Initialization::Ready
+            }
+            Initialization::Icw2 => {
+                self.vector_base = data & 0xF8;
+
+                if self.icw1 & 0x02 == 0 {
+                    Initialization::Icw3
+                }
+                else if self.icw1 & 0x01 != 0 {
+                    Initialization::Icw4
+                }
+                else {
+                    Initialization::Ready
+                }
+            }
+            Initialization::Icw3 => if self.icw1 & 0x01 != 0 { Initialization::Icw4 } else { Initialization::Ready },
+            Initialization::Icw4 => {
+                self.icw4 = data;
+                Initialization::Ready
+            }
+        };
+
+        // Completing the sequence started by ICW1 initializes the PIC
+        if !matches!(self.initialization, Initialization::Ready) && matches!(initialization, Initialization::Ready) {
+            self.initialized = true;
+        }
+
+        self.initialization = initialization;
+    }
+}
+
+impl Default for Pic8259 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IoDevice for Pic8259 {
+    fn read_port(&self, port: u16) -> Result<u8, IoError> {
+        match port {
+            0 if self.read_isr => Ok(self.isr),
+            0 => Ok(self.irr),
+            1 => Ok(self.imr),
+            _ => Err(IoError::PortNotMapped { port })
+        }
+    }
+
+    fn write_port(&mut self, port: u16, data: u8) -> Result<(), IoError> {
+        match port {
+            0 => self.write_command(data),
+            1 => self.write_data(data),
+            _ => return Err(IoError::PortNotMapped { port })
+        }
+
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl InterruptController for Pic8259 {
+    fn poll_interrupt(&mut self) -> Option<u8> {
+        Self::poll_interrupt(self)
+    }
+}
+
+/// Initializes `pic` as the IBM PC BIOS does, as a single edge triggered PIC with vectors from `08h`, in 8086 mode.
+#[cfg(test)]
+pub(crate) fn initialize(pic: &mut impl IoDevice) {
+    pic.write_port(0, 0x13).unwrap();
+    pic.write_port(1, 0x08).unwrap();
+    pic.write_port(1, 0x01).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::{IoMap, Memory, RegionBusDevice, Shared};
+
+    use crate::{cpu::testing::cpu_with_memory, StepResult};
+
+    use super::*;
+
+    #[test]
+    fn test_pic_initialization() {
+        let mut pic = Pic8259::new();
+        pic.raise_irq(0);
+
+        // Nothing is delivered until the initialization sequence completes
+        assert_eq!(pic.poll_interrupt(), None);
+        pic.write_port(0, 0x13).unwrap();
+        pic.write_port(1, 0x08).unwrap();
+        assert!(!pic.is_initialized());
+        assert_eq!(pic.poll_interrupt(), None);
+        pic.write_port(1, 0x01).unwrap();
+        assert!(pic.is_initialized());
+        assert_eq!(pic.poll_interrupt(), Some(0x08));
+
+        // With cascading, ICW3 is expected before ICW4, and the low bits of the vector base are ignored
+        let mut pic = Pic8259::new();
+        for (port, data) in [(0, 0x11), (1, 0x77), (1, 0x04), (1, 0x01)] {
+            pic.write_port(port, data).unwrap();
+        }
+        pic.raise_irq(3);
+        assert_eq!(pic.poll_interrupt(), Some(0x73));
+
+        assert_eq!(pic.read_port(2), Err(IoError::PortNotMapped { port: 2 }));
+    }
+
+    #[test]
+    fn test_pic_mask_before_initialization() {
+        let mut pic = Pic8259::new();
+
+        // Before ICW1 the data port takes OCW1 rather than ICW2
+        pic.write_port(1, 0xFE).unwrap();
+        assert_eq!(pic.imr(), 0xFE);
+        assert!(!pic.is_initialized());
+
+        pic.raise_irq(1);
+        assert_eq!(pic.poll_interrupt(), None);
+
+        initialize(&mut pic);
+        assert_eq!(pic.poll_interrupt(), Some(0x09));
+    }
+
+    #[test]
+    fn test_pic_priority_and_eoi() {
+        let mut pic = Pic8259::new();
+        initialize(&mut pic);
+
+        pic.raise_irq(4);
+        pic.raise_irq(1);
+        assert_eq!(pic.irr(), 0x12);
+
+        // The higher priority request goes first, and blocks the lower one until it is ended
+        assert_eq!(pic.poll_interrupt(), Some(0x09));
+        assert_eq!(pic.poll_interrupt(), None);
+        assert_eq!(pic.isr(), 0x02);
+
+        // A higher priority request still nests within it
+        pic.raise_irq(0);
+        assert_eq!(pic.poll_interrupt(), Some(0x08));
+        assert_eq!(pic.isr(), 0x03);
+
+        // A non-specific EOI ends the highest priority request in service
+        pic.write_port(0, 0x20).unwrap();
+        assert_eq!(pic.isr(), 0x02);
+        assert_eq!(pic.poll_interrupt(), None);
+        pic.write_port(0, 0x61).unwrap();
+        assert_eq!(pic.poll_interrupt(), Some(0x0C));
+
+        // OCW3 selects which register the command port reads back
+        pic.raise_irq(7);
+        assert_eq!(pic.read_port(0), Ok(0x80));
+        pic.write_port(0, 0x0B).unwrap();
+        assert_eq!(pic.read_port(0), Ok(0x10));
+        pic.write_port(0, 0x0A).unwrap();
+        assert_eq!(pic.read_port(0), Ok(0x80));
+    }
+
+    #[test]
+    fn test_pic_mask() {
+        let mut pic = Pic8259::new();
+        initialize(&mut pic);
+
+        pic.write_port(1, 0xFE).unwrap();
+        assert_eq!(pic.read_port(1), Ok(0xFE));
+
+        // Masked requests are held until they are unmasked
+        pic.raise_irq(1);
+        assert_eq!(pic.poll_interrupt(), None);
+        pic.write_port(1, 0x00).unwrap();
+        assert_eq!(pic.poll_interrupt(), Some(0x09));
+
+        // Reinitializing clears the mask and anything in service
+        pic.write_port(1, 0xFF).unwrap();
+        initialize(&mut pic);
+        assert_eq!((pic.imr(), pic.isr()), (0x00, 0x00));
+    }
+
+    #[test]
+    #[should_panic(expected = "IRQ8 is not an input")]
+    fn test_pic_invalid_irq() {
+        Pic8259::new().raise_irq(8);
+    }
+
+    #[test]
+    fn test_pic_cpu_delivery() {
+        let mut memory = Memory::<0x20000>::empty();
+        // The vector for IRQ0 points at 2000:0000, and the program at 1000:0000 is a NOP followed by a HLT
+        memory.write_region(0x08 * 4, &[0x00, 0x00, 0x00, 0x20]).unwrap();
+        memory.write_region(0x10000, &[0x90, 0xF4]).unwrap();
+
+        let pic = Shared::new(Pic8259::new());
+        let io = IoMap::new().with_range(PIC_COMMAND_PORT..=PIC_DATA_PORT, Box::new(pic.clone()));
+
+        let mut cpu = cpu_with_memory(memory);
+        *cpu.io_mut() = io;
+        cpu.set_interrupt_controller(Box::new(pic.clone()));
+        cpu.registers_mut().sp = 0x0100;
+
+        initialize(&mut pic.clone());
+        pic.borrow_mut().raise_irq(0);
+
+        // Requests wait while interrupts are disabled
+        assert!(matches!(cpu.step(), Ok(StepResult::Ok(_))));
+        assert_eq!(pic.borrow().irr(), 0x01);
+
+        // With IF set the request is delivered before the next instruction
+        cpu.registers_mut().flags.set_interrupt(true);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(61)));
+        assert_eq!((cpu.registers().cs, cpu.registers().ip), (0x2000, 0x0000));
+        assert_eq!(pic.borrow().isr(), 0x01);
+        assert!(!cpu.registers().flags.interrupt());
+
+        // A request also ends a halt
+        cpu.registers_mut().flags.set_interrupt(true);
+        (cpu.registers_mut().cs, cpu.registers_mut().ip) = (0x1000, 0x0001);
+        assert_eq!(cpu.step(), Ok(StepResult::Halted));
+        assert_eq!(cpu.step(), Ok(StepResult::Halted));
+
+        assert_eq!(cpu.out_byte(PIC_COMMAND_PORT, 0x20), Ok(()));
+        pic.borrow_mut().raise_irq(0);
+        assert_eq!(cpu.step(), Ok(StepResult::Ok(61)));
+        assert!(!cpu.is_halted());
+        assert_eq!((cpu.registers().cs, cpu.registers().ip), (0x2000, 0x0000));
+    }
+}
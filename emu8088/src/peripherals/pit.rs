@@ -0,0 +1,545 @@
+use std::cell::Cell;
+
+use mem::{IoDevice, IoError};
+
+/// The first port of the PIT in the IBM PC, holding the data port of counter 0.
+pub const PIT_BASE_PORT: u16 = 0x40;
+
+/// The port of the PIT in the IBM PC taking control words.
+pub const PIT_CONTROL_PORT: u16 = 0x43;
+
+/// The frequency in hertz of the clock driving the counters of the PIT in the IBM PC.
+pub const PIT_FREQUENCY: u32 = 1_193_182;
+
+/// Decodes a four digit BCD count.
+const fn from_bcd(value: u16) -> u32 {
+    let mut result = 0;
+    let mut digit = 4;
+
+    while digit > 0 {
+        digit -= 1;
+        result = result * 10 + ((value >> (digit * 4)) & 0xF) as u32;
+    }
+
+    result
+}
+
+/// Encodes a count below 10000 as four BCD digits.
+#[allow(clippy::cast_possible_truncation)]
+const fn to_bcd(mut value: u32) -> u16 {
+    let mut result = 0;
+    let mut shift = 0;
+
+    while shift < 16 {
+        result |= ((value % 10) as u16) << shift;
+        value /= 10;
+        shift += 4;
+    }
+
+    result
+}
+
+/// A single counter of the PIT.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Counter {
+    /// The last control word written for the counter, selecting its access mode, counter mode and BCD counting.
+    control: u8,
+    /// The count last written, or `None` if no count has been written since the control word.
+    reload: Option<u16>,
+    /// The value of the counting element, in binary.
+    count: u32,
+    /// The clocks left in the current half of the square wave, in mode 3.
+    phase: u32,
+    running: bool,
+    output: bool,
+    gate: bool,
+    /// The low byte of a two byte count which has been written without its high byte.
+    pending_low: Option<u8>,
+    /// Whether the next read of a two byte count returns the high byte.
+    read_high: Cell<bool>,
+    latch: Cell<Option<u16>>
+}
+
+impl Counter {
+    /// Constructs a counter in its power-on state, with no count, two byte access, mode 0 and the gate high.
+    const fn new() -> Self {
+        Self {
+            control: 0x30,
+            reload: None,
+            count: 0,
+            phase: 0,
+            running: false,
+            output: false,
+            gate: true,
+            pending_low: None,
+            read_high: Cell::new(false),
+            latch: Cell::new(None)
+        }
+    }
+
+    /// Returns the counter mode, with the duplicate encodings of modes 2 and 3 folded.
+    const fn mode(&self) -> u8 {
+        match (self.control >> 1) & 0x07 {
+            6 => 2,
+            7 => 3,
+            mode => mode
+        }
+    }
+
+    /// Returns the number of distinct values of the counting element, which wraps around from zero to one below it.
+    const fn modulus(&self) -> u32 {
+        if self.control & 0x01 != 0 { 10_000 } else { 0x1_0000 }
+    }
+
+    /// Returns the number of clocks in a full count, with a count of zero standing for the modulus.
+    const fn period(&self) -> u32 {
+        let reload = match self.reload {
+            Some(reload) => reload,
+            None => 0
+        };
+        let value = if self.control & 0x01 != 0 { from_bcd(reload) } else { reload as u32 };
+
+        if value == 0 { self.modulus() } else { value }
+    }
+
+    /// Returns the value a read of the counter would give, encoded as programmed.
+    #[allow(clippy::cast_possible_truncation)]
+    const fn current(&self) -> u16 {
+        let value = if self.running && self.mode() == 3 { (self.phase * 2) % self.modulus() } else { self.count };
+
+        if self.control & 0x01 != 0 { to_bcd(value) } else { value as u16 }
+    }
+
+    /// Programs the counter with a new control word, stopping it until a count is written.
+    fn set_control(&mut self, control: u8) {
+        self.control = control;
+        self.reload = None;
+        self.running = false;
+        self.output = self.mode() != 0;
+        self.pending_low = None;
+        self.read_high.set(false);
+        self.latch.set(None);
+    }
+
+    /// Latches the current count for reading, unless a count is already latched.
+    fn latch_count(&self) {
+        if self.latch.get().is_none() {
+            self.latch.set(Some(self.current()));
+        }
+    }
+
+    /// Loads the counting element from the count and starts counting.
+    const fn start(&mut self) {
+        self.count = self.period() % self.modulus();
+        self.phase = self.period().div_ceil(2);
+        self.running = true;
+        self.output = !matches!(self.mode(), 0 | 1);
+    }
+
+    /// Handles a write of a byte of the count.
+    const fn write(&mut self, data: u8) {
+        let reload = match (self.control >> 4) & 0x03 {
+            0x01 => u16::from_le_bytes([data, 0]),
+            0x02 => u16::from_le_bytes([0, data]),
+            _ => {
+                let Some(low) = self.pending_low.take() else {
+                    self.pending_low = Some(data);
+                    return;
+                };
+
+                u16::from_le_bytes([low, data])
+            }
+        };
+
+        self.reload = Some(reload);
+
+        match self.mode() {
+            // Modes 1 and 5 wait for a trigger on the gate, and modes 2 and 3 pick up a new count at the end of the
+            // current period
+            1 | 5 => {}
+            2 | 3 if self.running => {}
+            _ => self.start()
+        }
+    }
+
+    /// Handles a read of a byte of the count, advancing through the bytes of a two byte count.
+    fn read(&self) -> u8 {
+        let [low, high] = self.latch.get().unwrap_or_else(|| self.current()).to_le_bytes();
+
+        let (data, done) = match (self.control >> 4) & 0x03 {
+            0x01 => (low, true),
+            0x02 => (high, true),
+            _ if self.read_high.get() => (high, true),
+            _ => (low, false)
+        };
+
+        self.read_high.set(!done);
+
+        if done {
+            self.latch.set(None);
+        }
+
+        data
+    }
+
+    /// Returns the byte a read of the count would give, without advancing.
+    fn peek(&self) -> u8 {
+        let [low, high] = self.latch.get().unwrap_or_else(|| self.current()).to_le_bytes();
+
+        match (self.control >> 4) & 0x03 {
+            0x01 => low,
+            0x02 => high,
+            _ if self.read_high.get() => high,
+            _ => low
+        }
+    }
+
+    /// Sets the level of the gate input, triggering modes 1 and 5 and restarting modes 2 and 3 on a rising edge.
+    const fn set_gate(&mut self, level: bool) {
+        let rising = level && !self.gate;
+        self.gate = level;
+
+        match self.mode() {
+            1 | 5 if rising && self.reload.is_some() => self.start(),
+            2 | 3 if !level => self.output = true,
+            2 | 3 if rising && self.running => self.start(),
+            _ => {}
+        }
+    }
+
+    /// Decrements the counting element, wrapping from zero.
+    const fn decrement(&mut self) {
+        self.count = if self.count == 0 { self.modulus() - 1 } else { self.count - 1 };
+    }
+
+    /// Advances the counter by a single clock, returning `true` if the output rose.
+    const fn clock(&mut self) -> bool {
+        let mode = self.mode();
+
+        if !self.running || (!self.gate && !matches!(mode, 1 | 5)) {
+            return false;
+        }
+
+        match mode {
+            // Interrupt on terminal count and the hardware retriggerable one-shot, with the output rising at zero
+            0 | 1 => {
+                self.decrement();
+
+                if self.count == 0 && !self.output {
+                    self.output = true;
+                    return true;
+                }
+            }
+            // Rate generator, with the output low for the last clock of each period
+            2 => {
+                self.decrement();
+
+                if self.count == 1 {
+                    self.output = false;
+                }
+                else if self.count == 0 {
+                    let rose = !self.output;
+
+                    self.output = true;
+                    self.count = self.period() % self.modulus();
+                    return rose;
+                }
+            }
+            // Square wave, high for the longer half of odd counts
+            3 => {
+                self.phase -= 1;
+
+                if self.phase == 0 {
+                    self.output = !self.output;
+                    self.phase = if self.output { self.period().div_ceil(2) } else { self.period() / 2 };
+
+                    if self.phase == 0 {
+                        self.phase = 1;
+                    }
+
+                    return self.output;
+                }
+            }
+            // Software and hardware triggered strobes, with the output low for one clock after reaching zero
+            _ => {
+                if !self.output {
+                    self.output = true;
+                    self.running = false;
+                    return true;
+                }
+
+                self.decrement();
+
+                if self.count == 0 {
+                    self.output = false;
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// An Intel 8253 programmable interval timer, as in the IBM PC at ports `40h` to `43h`, mapped into an `IoMap`
+/// relative to the data port of counter 0.
+///
+/// Each of the three counters supports all six modes, in binary or BCD, with one or two byte counts and the counter
+/// latch command. The 8254 read-back command is recognized, latching the counts it selects, but status latching is
+/// not emulated. The counters are driven by `tick`, whose result tells the caller when to raise an interrupt, as the
+/// IBM PC wires the output of counter 0 to `IRQ0`.
+///
+/// All three gates start high, as counters 0 and 1 of the IBM PC are wired; the gate of counter 2 is driven by port
+/// `61h` through `set_gate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pit8253 {
+    counters: [Counter; 3]
+}
+
+impl Pit8253 {
+    #[must_use]
+    /// Constructs a PIT in its power-on state, with no counter running.
+    pub const fn new() -> Self {
+        Self { counters: [Counter::new(), Counter::new(), Counter::new()] }
+    }
+
+    /// Advances every counter by `cycles` clocks of the PIT's own input clock, returning for each counter whether its
+    /// output rose during the period, which is the edge the PIC latches an interrupt request on.
+    pub fn tick(&mut self, cycles: u64) -> [bool; 3] {
+        self.counters.each_mut().map(|counter| {
+            let mut rose = false;
+
+            for _ in 0..cycles {
+                if !counter.running {
+                    break;
+                }
+
+                rose |= counter.clock();
+            }
+
+            rose
+        })
+    }
+
+    /// Sets the level of the gate input of counter `channel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not one of the three counters `0` to `2`.
+    pub const fn set_gate(&mut self, channel: usize, level: bool) {
+        self.counters[channel].set_gate(level);
+    }
+
+    #[must_use]
+    /// Returns the level of the output of counter `channel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not one of the three counters `0` to `2`.
+    pub const fn output(&self, channel: usize) -> bool {
+        self.counters[channel].output
+    }
+
+    /// Handles a write of a control word.
+    fn write_control(&mut self, data: u8) {
+        match (data >> 6, (data >> 4) & 0x03) {
+            // 8254 read-back, of which only the count latch is emulated
+            (3, _) => {
+                if data & 0x20 == 0 {
+                    for (index, counter) in self.counters.iter().enumerate() {
+                        if data & (2 << index) != 0 {
+                            counter.latch_count();
+                        }
+                    }
+                }
+            }
+            (channel, 0) => self.counters[usize::from(channel)].latch_count(),
+            (channel, _) => self.counters[usize::from(channel)].set_control(data)
+        }
+    }
+}
+
+impl Default for Pit8253 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IoDevice for Pit8253 {
+    fn read_port(&self, port: u16) -> Result<u8, IoError> {
+        match port {
+            0..=2 => Ok(self.counters[usize::from(port)].read()),
+            // The control port cannot be read back
+            3 => Ok(0xFF),
+            _ => Err(IoError::PortNotMapped { port })
+        }
+    }
+
+    fn write_port(&mut self, port: u16, data: u8) -> Result<(), IoError> {
+        match port {
+            0..=2 => self.counters[usize::from(port)].write(data),
+            3 => self.write_control(data),
+            _ => return Err(IoError::PortNotMapped { port })
+        }
+
+        Ok(())
+    }
+
+    fn peek_port(&self, port: u16) -> Result<u8, IoError> {
+        match port {
+            0..=2 => Ok(self.counters[usize::from(port)].peek()),
+            _ => self.read_port(port)
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{peripherals::pic::initialize, Pic8259};
+
+    use super::*;
+
+    /// Programs `channel` with `mode` and a two byte binary `count`.
+    fn program(pit: &mut Pit8253, channel: u8, mode: u8, count: u16) {
+        let [low, high] = count.to_le_bytes();
+
+        pit.write_port(3, (channel << 6) | 0x30 | (mode << 1)).unwrap();
+        pit.write_port(u16::from(channel), low).unwrap();
+        pit.write_port(u16::from(channel), high).unwrap();
+    }
+
+    /// Returns the clocks after which the output of `channel` rose within `clocks` clocks, ticking one at a time.
+    fn rising_edges(pit: &mut Pit8253, channel: usize, clocks: u64) -> Vec<u64> {
+        (1..=clocks).filter(|_| pit.tick(1)[channel]).collect()
+    }
+
+    #[test]
+    fn test_pit_terminal_count() {
+        let mut pit = Pit8253::new();
+        program(&mut pit, 0, 0, 5);
+        assert!(!pit.output(0));
+
+        assert_eq!(pit.tick(4), [false; 3]);
+        assert!(!pit.output(0));
+        assert_eq!(pit.tick(1), [true, false, false]);
+        assert!(pit.output(0));
+
+        // The counter wraps around and keeps counting, but the output stays high
+        assert_eq!(pit.tick(0x1_0000), [false; 3]);
+        assert_eq!(pit.read_port(0), Ok(0x00));
+        assert_eq!(pit.read_port(0), Ok(0x00));
+
+        // A low gate holds the count
+        program(&mut pit, 0, 0, 5);
+        pit.set_gate(0, false);
+        assert_eq!(pit.tick(10), [false; 3]);
+        pit.set_gate(0, true);
+        assert_eq!(pit.tick(5), [true, false, false]);
+    }
+
+    #[test]
+    fn test_pit_periodic_modes() {
+        let mut pit = Pit8253::new();
+
+        // The rate generator rises every period, after a single low clock
+        program(&mut pit, 0, 2, 4);
+        assert_eq!(rising_edges(&mut pit, 0, 12), [4, 8, 12]);
+        pit.tick(3);
+        assert!(!pit.output(0));
+
+        // The square wave of an odd count is high for the longer half
+        program(&mut pit, 2, 3, 5);
+        let levels: Vec<bool> = (0..10).map(|_| { pit.tick(1); pit.output(2) }).collect();
+        assert_eq!(levels, [true, true, false, false, true, true, true, false, false, true]);
+
+        // The control word encodings with bit 3 set select the same modes
+        pit.write_port(3, 0x7C).unwrap();
+        pit.write_port(1, 0x03).unwrap();
+        pit.write_port(1, 0x00).unwrap();
+        assert_eq!(rising_edges(&mut pit, 1, 9), [3, 6, 9]);
+    }
+
+    #[test]
+    fn test_pit_triggered_modes() {
+        let mut pit = Pit8253::new();
+
+        // The one-shot waits for the gate, then holds its output low for the count
+        program(&mut pit, 2, 1, 3);
+        assert_eq!(pit.tick(10), [false; 3]);
+        assert!(pit.output(2));
+        pit.set_gate(2, false);
+        pit.set_gate(2, true);
+        assert!(!pit.output(2));
+        assert_eq!(rising_edges(&mut pit, 2, 10), [3]);
+
+        // The software strobe pulses low once, the clock after the count runs out
+        program(&mut pit, 1, 4, 3);
+        assert!(pit.output(1));
+        assert_eq!(rising_edges(&mut pit, 1, 10), [4]);
+
+        // The hardware strobe does the same from a rising edge of the gate
+        program(&mut pit, 0, 5, 2);
+        assert_eq!(pit.tick(10), [false; 3]);
+        pit.set_gate(0, false);
+        pit.set_gate(0, true);
+        assert_eq!(rising_edges(&mut pit, 0, 10), [3]);
+    }
+
+    #[test]
+    fn test_pit_reading_counts() {
+        let mut pit = Pit8253::new();
+        program(&mut pit, 0, 0, 0x1234);
+        pit.tick(0x34);
+
+        // A latched count is held across further clocks until it has been read
+        pit.write_port(3, 0x00).unwrap();
+        pit.tick(0x100);
+        assert_eq!(pit.peek_port(0), Ok(0x00));
+        assert_eq!(pit.read_port(0), Ok(0x00));
+        assert_eq!(pit.read_port(0), Ok(0x12));
+        assert_eq!(pit.read_port(0), Ok(0x00));
+        assert_eq!(pit.read_port(0), Ok(0x11));
+
+        // Single byte access and BCD counting
+        pit.write_port(3, 0x51).unwrap();
+        pit.write_port(1, 0x20).unwrap();
+        pit.tick(11);
+        assert_eq!(pit.read_port(1), Ok(0x09));
+
+        // The read-back command latches the counts it selects
+        pit.write_port(3, 0xD2).unwrap();
+        pit.tick(1);
+        assert_eq!(pit.read_port(0), Ok(0xF5));
+        assert_eq!(pit.read_port(0), Ok(0x10));
+
+        assert_eq!(pit.read_port(3), Ok(0xFF));
+        assert_eq!(pit.write_port(4, 0), Err(IoError::PortNotMapped { port: 4 }));
+    }
+
+    #[test]
+    fn test_pit_raises_irq0() {
+        let mut pic = Pic8259::new();
+        initialize(&mut pic);
+
+        // The BIOS programs counter 0 as a rate generator, interrupting on every rising edge of its output
+        let mut pit = Pit8253::new();
+        program(&mut pit, 0, 2, 100);
+
+        let mut interrupts = 0;
+        for _ in 0..10 {
+            if pit.tick(50)[0] {
+                pic.raise_irq(0);
+            }
+
+            if pic.poll_interrupt() == Some(0x08) {
+                interrupts += 1;
+                pic.write_port(0, 0x20).unwrap();
+            }
+        }
+
+        assert_eq!(interrupts, 5);
+    }
+}
@@ -0,0 +1,304 @@
+use std::{cell::{Cell, RefCell}, collections::VecDeque, ops::RangeInclusive};
+
+use mem::{IoDevice, IoError};
+
+/// The base port of `COM1` in the IBM PC.
+pub const COM1_BASE_PORT: u16 = 0x3F8;
+
+/// The base port of `COM2` in the IBM PC.
+pub const COM2_BASE_PORT: u16 = 0x2F8;
+
+/// The frequency in hertz the baud rate divisor of the UART in the IBM PC divides, a sixteenth of its 1.8432 MHz
+/// crystal.
+pub const UART_BASE_BAUD: u32 = 115_200;
+
+/// Line status register bit set while a received byte is waiting to be read.
+const LSR_DATA_READY: u8 = 0x01;
+
+/// Line status register bits set while the transmitter holding and shift registers are empty.
+const LSR_TRANSMITTER_EMPTY: u8 = 0x60;
+
+/// Line control register bit switching offsets 0 and 1 over to the divisor latch.
+const LCR_DLAB: u8 = 0x80;
+
+/// Modem control register bit gating the interrupt output onto the bus in the IBM PC.
+const MCR_OUT2: u8 = 0x08;
+
+/// Modem control register bit looping the transmitter back into the receiver.
+const MCR_LOOPBACK: u8 = 0x10;
+
+/// An Intel 8250 UART, mapped into an `IoMap` over the eight ports from its base port.
+///
+/// Transmission is instantaneous, so the transmitter is always empty and bytes written by the guest are collected for
+/// the host to take with `drain_output`. Bytes from the host are queued with `receive_byte`, and read by the guest one
+/// at a time through the receiver buffer register, so the queue stands in for the far end of the line rather than
+/// overrunning as the single byte buffer of the 8250 would.
+///
+/// The received data and transmitter empty interrupts are emulated, as is loopback. The modem status register reads
+/// as zero, with no line errors ever reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Uart8250 {
+    base: u16,
+    received: RefCell<VecDeque<u8>>,
+    transmitted: Vec<u8>,
+    divisor: u16,
+    ier: u8,
+    lcr: u8,
+    mcr: u8,
+    scratch: u8,
+    /// Whether the transmitter empty interrupt is waiting to be acknowledged.
+    transmitter_empty: Cell<bool>
+}
+
+impl Uart8250 {
+    #[must_use]
+    /// Constructs a UART in its power-on state, to be mapped at `base`.
+    pub const fn new(base: u16) -> Self {
+        Self {
+            base,
+            received: RefCell::new(VecDeque::new()),
+            transmitted: Vec::new(),
+            divisor: 0,
+            ier: 0,
+            lcr: 0,
+            mcr: 0,
+            scratch: 0,
+            transmitter_empty: Cell::new(false)
+        }
+    }
+
+    #[must_use]
+    /// Returns the base port the UART is to be mapped at.
+    pub const fn base(&self) -> u16 {
+        self.base
+    }
+
+    #[must_use]
+    /// Returns the range of ports to map the UART over.
+    pub const fn ports(&self) -> RangeInclusive<u16> {
+        self.base..=self.base + 7
+    }
+
+    /// Queues `b` as received on the line, to be read by the guest.
+    pub fn receive_byte(&mut self, b: u8) {
+        self.received.get_mut().push_back(b);
+    }
+
+    /// Takes every byte the guest has transmitted since the last call, in order.
+    pub fn drain_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.transmitted)
+    }
+
+    #[must_use]
+    /// Returns the baud rate divisor last programmed by the guest.
+    pub const fn divisor(&self) -> u16 {
+        self.divisor
+    }
+
+    #[must_use]
+    /// Returns the baud rate programmed by the guest, or `None` if the divisor is zero.
+    pub fn baud_rate(&self) -> Option<u32> {
+        UART_BASE_BAUD.checked_div(u32::from(self.divisor))
+    }
+
+    #[must_use]
+    /// Returns `true` if the UART is requesting an interrupt and the guest has enabled its interrupt output with
+    /// `OUT2`, as the IBM PC requires before the request reaches the PIC.
+    pub fn interrupt_pending(&self) -> bool {
+        self.mcr & MCR_OUT2 != 0 && self.interrupt_identification() & 0x01 == 0
+    }
+
+    /// Returns the value of the interrupt identification register, with the highest priority pending interrupt.
+    fn interrupt_identification(&self) -> u8 {
+        if self.ier & 0x01 != 0 && !self.received.borrow().is_empty() {
+            0x04
+        }
+        else if self.ier & 0x02 != 0 && self.transmitter_empty.get() {
+            0x02
+        }
+        else {
+            0x01
+        }
+    }
+
+    /// Returns the value of the line status register.
+    fn line_status(&self) -> u8 {
+        let ready = if self.received.borrow().is_empty() { 0 } else { LSR_DATA_READY };
+
+        LSR_TRANSMITTER_EMPTY | ready
+    }
+
+    /// Returns the value of the register at `port` without side effects.
+    fn register(&self, port: u16) -> u8 {
+        let dlab = self.lcr & LCR_DLAB != 0;
+        let [low, high] = self.divisor.to_le_bytes();
+
+        match port {
+            0 if dlab => low,
+            0 => self.received.borrow().front().copied().unwrap_or(0),
+            1 if dlab => high,
+            1 => self.ier,
+            2 => self.interrupt_identification(),
+            3 => self.lcr,
+            4 => self.mcr,
+            5 => self.line_status(),
+            6 => 0x00,
+            _ => self.scratch
+        }
+    }
+}
+
+impl IoDevice for Uart8250 {
+    fn read_port(&self, port: u16) -> Result<u8, IoError> {
+        let value = self.peek_port(port)?;
+
+        match port {
+            0 if self.lcr & LCR_DLAB == 0 => {
+                self.received.borrow_mut().pop_front();
+            }
+            // Identifying the transmitter empty interrupt acknowledges it
+            2 if value == 0x02 => self.transmitter_empty.set(false),
+            _ => {}
+        }
+
+        Ok(value)
+    }
+
+    fn write_port(&mut self, port: u16, data: u8) -> Result<(), IoError> {
+        let dlab = self.lcr & LCR_DLAB != 0;
+
+        match port {
+            0 if dlab => self.divisor = (self.divisor & 0xFF00) | u16::from(data),
+            0 => {
+                if self.mcr & MCR_LOOPBACK == 0 {
+                    self.transmitted.push(data);
+                }
+                else {
+                    self.received.get_mut().push_back(data);
+                }
+
+                self.transmitter_empty.set(true);
+            }
+            1 if dlab => self.divisor = (self.divisor & 0x00FF) | (u16::from(data) << 8),
+            1 => {
+                // Enabling the transmitter empty interrupt raises it at once, as the transmitter is always empty
+                if data & 0x02 != 0 && self.ier & 0x02 == 0 {
+                    self.transmitter_empty.set(true);
+                }
+
+                self.ier = data & 0x0F;
+            }
+            3 => self.lcr = data,
+            4 => self.mcr = data & 0x1F,
+            7 => self.scratch = data,
+            // The 8250 has no FIFO control register, and the line and modem status registers are read only
+            2 | 5 | 6 => {}
+            _ => return Err(IoError::PortNotMapped { port })
+        }
+
+        Ok(())
+    }
+
+    fn peek_port(&self, port: u16) -> Result<u8, IoError> {
+        if port > 7 {
+            return Err(IoError::PortNotMapped { port });
+        }
+
+        Ok(self.register(port))
+    }
+
+    /// Returns the registers to their power-on state and discards any bytes waiting to be read by the guest. Bytes
+    /// already transmitted are left for the host to drain.
+    fn reset(&mut self) {
+        let transmitted = std::mem::take(&mut self.transmitted);
+
+        *self = Self::new(self.base);
+        self.transmitted = transmitted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mem::{IoMap, Shared};
+
+    use super::*;
+
+    #[test]
+    fn test_uart_transmit() {
+        let uart = Shared::new(Uart8250::new(COM1_BASE_PORT));
+        let mut io = IoMap::new().with_range(uart.borrow().ports(), Box::new(uart.clone()));
+
+        // 9600 baud, 8N1
+        for (port, data) in [(0x3FB, 0x80), (0x3F8, 0x0C), (0x3F9, 0x00), (0x3FB, 0x03)] {
+            assert_eq!(io.write_port(port, data), Ok(()));
+        }
+        assert_eq!(uart.borrow().baud_rate(), Some(9600));
+        assert_eq!(io.read_port(0x3FB), Ok(0x03));
+
+        for byte in b"OK\r\n" {
+            assert_eq!(io.read_port(0x3FD).map(|lsr| lsr & 0x20), Ok(0x20));
+            assert_eq!(io.write_port(0x3F8, *byte), Ok(()));
+        }
+
+        assert_eq!(uart.borrow_mut().drain_output(), b"OK\r\n");
+        assert_eq!(uart.borrow_mut().drain_output(), b"");
+
+        // The scratch register holds whatever is written to it
+        assert_eq!(io.write_port(0x3FF, 0x5A), Ok(()));
+        assert_eq!(io.read_port(0x3FF), Ok(0x5A));
+        assert_eq!(io.read_port(0x400), Err(IoError::PortNotMapped { port: 0x400 }));
+    }
+
+    #[test]
+    fn test_uart_receive() {
+        let mut uart = Uart8250::new(COM2_BASE_PORT);
+        assert_eq!(uart.read_port(5), Ok(0x60));
+
+        uart.receive_byte(b'A');
+        uart.receive_byte(b'B');
+        assert_eq!(uart.read_port(5), Ok(0x61));
+
+        // Peeking leaves the byte to be read
+        assert_eq!(uart.peek_port(0), Ok(b'A'));
+        assert_eq!(uart.read_port(0), Ok(b'A'));
+        assert_eq!(uart.read_port(0), Ok(b'B'));
+        assert_eq!(uart.read_port(5), Ok(0x60));
+
+        // In loopback transmitted bytes come straight back
+        uart.write_port(4, MCR_LOOPBACK).unwrap();
+        uart.write_port(0, b'C').unwrap();
+        assert_eq!(uart.drain_output(), b"");
+        assert_eq!(uart.read_port(0), Ok(b'C'));
+    }
+
+    #[test]
+    fn test_uart_interrupts() {
+        let mut uart = Uart8250::new(COM1_BASE_PORT);
+        uart.write_port(4, MCR_OUT2).unwrap();
+        assert_eq!(uart.read_port(2), Ok(0x01));
+
+        // Enabling the transmitter empty interrupt raises it, and identifying it acknowledges it
+        uart.write_port(1, 0x02).unwrap();
+        assert!(uart.interrupt_pending());
+        assert_eq!(uart.read_port(2), Ok(0x02));
+        assert_eq!(uart.read_port(2), Ok(0x01));
+        assert!(!uart.interrupt_pending());
+
+        // Received data takes priority, and is cleared by reading the byte
+        uart.write_port(1, 0x03).unwrap();
+        uart.write_port(0, b'x').unwrap();
+        uart.receive_byte(b'y');
+        assert_eq!(uart.read_port(2), Ok(0x04));
+        assert_eq!(uart.read_port(0), Ok(b'y'));
+        assert_eq!(uart.read_port(2), Ok(0x02));
+
+        // Without OUT2 the request does not leave the UART
+        uart.write_port(4, 0x00).unwrap();
+        uart.receive_byte(b'z');
+        assert!(!uart.interrupt_pending());
+
+        uart.reset();
+        assert_eq!((uart.read_port(1), uart.read_port(5)), (Ok(0x00), Ok(0x60)));
+        assert_eq!(uart.drain_output(), b"x");
+    }
+}
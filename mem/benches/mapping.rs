@@ -0,0 +1,54 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use mem::{BusDevice, Memory, MemoryMap};
+
+/// The number of mappings in the benchmarked map, about as many as a fully populated PC has.
+const MAPPINGS: usize = 30;
+
+/// Constructs a map of `MAPPINGS` 1 KiB memories spaced 4 KiB apart, added in an order other than that of their
+/// addresses.
+fn map() -> MemoryMap {
+    let mut map = MemoryMap::new();
+
+    for i in (0..MAPPINGS).map(|i| (i * 7) % MAPPINGS) {
+        map.add_range(i * 0x1000..=i * 0x1000 + 0x3FF, Box::new(Memory::<0x400>::empty()));
+    }
+
+    map
+}
+
+fn bench_lookup(c: &mut Criterion) {
+    let map = map();
+
+    c.bench_function("memory_map_read_first", |b| b.iter(|| map.read(black_box(0x0010))));
+    c.bench_function("memory_map_read_last", |b| b.iter(|| map.read(black_box((MAPPINGS - 1) * 0x1000 + 0x10))));
+
+    // Every mapping in turn, so that no lookup hits the same mapping as the one before
+    c.bench_function("memory_map_read_each", |b| b.iter(|| {
+        (0..MAPPINGS).try_fold(0u32, |sum, i| map.read(black_box(i * 0x1000 + 0x10)).map(|data| sum + u32::from(data)))
+    }));
+
+    c.bench_function("memory_map_read_unmapped", |b| b.iter(|| map.read(black_box(0x0800))));
+}
+
+fn bench_lookup_cache(c: &mut Criterion) {
+    let map = map();
+    let start = 0x0F * 0x1000;
+
+    // A tight loop over every byte of one device, which after its first read only hits the cached mapping
+    let mut group = c.benchmark_group("memory_map_sequential");
+    group.bench_function("cached", |b| b.iter(|| {
+        (start..start + 0x400).try_fold(0u32, |sum, address| map.read(black_box(address)).map(|data| sum + u32::from(data)))
+    }));
+    group.bench_function("uncached", |b| b.iter(|| {
+        (start..start + 0x400).try_fold(0u32, |sum, address| {
+            map.clear_lookup_cache();
+            map.read(black_box(address)).map(|data| sum + u32::from(data))
+        })
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, bench_lookup, bench_lookup_cache);
+criterion_main!(benches);
@@ -0,0 +1,162 @@
+use crate::{BusDevice, BusDeviceError, MemoryMap};
+
+/// Mask applied to addresses while the A20 line is disabled, wrapping everything above 1 MiB back to the bottom of the
+/// address space.
+pub const A20_DISABLED_MASK: usize = 0xF_FFFF;
+
+/// Models the A20 gate, forwarding accesses to an inner device (by default a `MemoryMap`) with address bit 20 and
+/// above masked off while the gate is disabled.
+///
+/// The gate starts disabled, which matches the 8086 where `FFFF:0010` wraps around to physical address `0`.
+pub struct A20Gate<T: BusDevice = MemoryMap> {
+    inner: T,
+    a20_enabled: bool
+}
+
+impl<T: BusDevice> A20Gate<T> {
+    #[must_use]
+    /// Constructs a new `A20Gate` wrapping `inner`, with the A20 line disabled.
+    pub const fn new(inner: T) -> Self {
+        Self { inner, a20_enabled: false }
+    }
+
+    /// Enables or disables the A20 line.
+    pub const fn set_a20(&mut self, enabled: bool) {
+        self.a20_enabled = enabled;
+    }
+
+    #[must_use]
+    /// Returns `true` if the A20 line is enabled, so addresses are passed through unmodified.
+    pub const fn a20_enabled(&self) -> bool {
+        self.a20_enabled
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the gate, returning the wrapped device.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Applies the gate to an `address`.
+    const fn gate(&self, address: usize) -> usize {
+        if self.a20_enabled {
+            address
+        }
+        else {
+            address & A20_DISABLED_MASK
+        }
+    }
+}
+
+impl<T: BusDevice> BusDevice for A20Gate<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.read(self.gate(address))
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.write(self.gate(address), data)
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(self.gate(address))
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.poke(self.gate(address), data)
+    }
+
+    /// Disables the A20 line again, as on the processor, and resets the wrapped device.
+    fn reset(&mut self) {
+        self.a20_enabled = false;
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, RegionBusDevice};
+
+    use super::*;
+
+    fn test_map() -> MemoryMap {
+        MemoryMap::new()
+            .with_range(0x00000..=0x0000F, Box::new(Memory::filled([0x11; 16])))
+            .with_range(0x10_0000..=0x10_000F, Box::new(Memory::filled([0x22; 16])))
+    }
+
+    #[test]
+    fn test_a20_disabled_wraps() {
+        let mut gate = A20Gate::new(test_map());
+        assert!(!gate.a20_enabled());
+
+        assert_eq!(gate.read(0x10_0000), Ok(0x11));
+        assert_eq!(gate.read(0x10_000F), Ok(0x11));
+        assert_eq!(gate.read(0x10_0010), Err(BusDeviceError::AddressNotMapped { address: 0x10 }));
+
+        assert_eq!(gate.write(0x10_0004, 0x33), Ok(()));
+        assert_eq!(gate.read(0x4), Ok(0x33));
+        assert_eq!(gate.inner().read(0x10_0004), Ok(0x22));
+    }
+
+    #[test]
+    fn test_a20_enabled_passes_through() {
+        let mut gate = A20Gate::new(test_map());
+        gate.set_a20(true);
+        assert!(gate.a20_enabled());
+
+        assert_eq!(gate.read(0x10_0000), Ok(0x22));
+        assert_eq!(gate.write(0x10_0004, 0x33), Ok(()));
+        assert_eq!(gate.read_region(0x10_0003), Ok([0x22, 0x33, 0x22]));
+        assert_eq!(gate.read(0x4), Ok(0x11));
+        assert_eq!(gate.read(0x10_0010), Err(BusDeviceError::AddressNotMapped { address: 0x10_0010 }));
+    }
+
+    #[test]
+    fn test_a20_reset_disables() {
+        let mut gate = A20Gate::new(test_map());
+        gate.set_a20(true);
+        assert_eq!(gate.write(0x10_0004, 0x33), Ok(()));
+
+        gate.reset();
+        assert!(!gate.a20_enabled());
+        assert_eq!(gate.inner().read(0x10_0004), Ok(0x33));
+    }
+
+    #[test]
+    fn test_a20_toggle() {
+        let mut gate = A20Gate::new(test_map());
+
+        // FFFF:0010 is the classic wrap around address
+        let address = 0xFFFF * 16 + 0x10;
+
+        assert_eq!(gate.read(address), Ok(0x11));
+        gate.set_a20(true);
+        assert_eq!(gate.read(address), Ok(0x22));
+        gate.set_a20(false);
+        assert_eq!(gate.read(address), Ok(0x11));
+
+        let map = gate.into_inner();
+        assert_eq!(map.read(address), Ok(0x22));
+    }
+
+    #[test]
+    fn test_a20_generic_device() {
+        let mut gate = A20Gate::new(Memory::<16>::empty());
+
+        assert_eq!(gate.write(0x10_0002, 5), Ok(()));
+        assert_eq!(gate.inner_mut().read(2), Ok(5));
+        assert_eq!(gate.read(0x20_0002), Ok(5));
+    }
+}
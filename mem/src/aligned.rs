@@ -0,0 +1,216 @@
+use std::{cell::Cell, fmt::Debug};
+
+use crate::{AccessKind, BusDevice, BusDeviceError};
+
+/// Callback invoked by an `Aligned` device with the address and direction of each misaligned access.
+pub type MisalignedFn = Box<dyn Fn(usize, AccessKind)>;
+
+/// What an `Aligned` device does about a misaligned access, beyond counting it.
+pub enum AlignmentPolicy {
+    /// Pass the access on, as the 8086 does at the cost of an extra bus cycle.
+    Allow,
+    /// Pass the access on after invoking the callback.
+    Warn(MisalignedFn),
+    /// Refuse the access with `AddressMisaligned`, without passing it on.
+    Fault
+}
+
+impl Debug for AlignmentPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Allow => write!(f, "Allow"),
+            Self::Warn(_) => write!(f, "Warn(..)"),
+            Self::Fault => write!(f, "Fault")
+        }
+    }
+}
+
+/// Checks the alignment of the accesses made to an inner device, counting those which are misaligned and handling
+/// them according to an `AlignmentPolicy`.
+///
+/// An access is misaligned if it is more than one byte wide, such as a word read with `read_region::<2>`, and starts
+/// at an address which is not a multiple of the alignment. Single byte accesses are never misaligned. The width of an
+/// access is only known to the device if it arrives through `read_bytes` or `write_bytes`, which `MemoryMap` passes on
+/// for accesses lying within a single mapping. Peeks and pokes are not checked.
+pub struct Aligned<T: BusDevice> {
+    inner: T,
+    alignment: usize,
+    policy: AlignmentPolicy,
+    misaligned: Cell<usize>
+}
+
+impl<T: BusDevice> Aligned<T> {
+    #[must_use]
+    /// Wraps `inner`, checking accesses against `alignment` bytes and handling misaligned accesses by `policy`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alignment` is zero.
+    pub fn new(inner: T, alignment: usize, policy: AlignmentPolicy) -> Self {
+        assert!(alignment > 0, "Alignment must be at least one byte");
+
+        Self { inner, alignment, policy, misaligned: Cell::new(0) }
+    }
+
+    #[must_use]
+    /// Returns the alignment accesses are checked against.
+    pub const fn alignment(&self) -> usize {
+        self.alignment
+    }
+
+    #[must_use]
+    /// Returns the number of misaligned accesses made, whether or not they were passed on.
+    pub const fn misaligned_count(&self) -> usize {
+        self.misaligned.get()
+    }
+
+    /// Resets the count of misaligned accesses to zero.
+    pub fn clear_misaligned_count(&self) {
+        self.misaligned.set(0);
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device, returning the wrapped device.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Checks an access of `length` bytes at `address`, returning an error if the policy refuses it.
+    fn check(&self, address: usize, length: usize, kind: AccessKind) -> Result<(), BusDeviceError> {
+        if length <= 1 || address.is_multiple_of(self.alignment) {
+            return Ok(());
+        }
+
+        self.misaligned.set(self.misaligned.get() + 1);
+
+        match &self.policy {
+            AlignmentPolicy::Allow => Ok(()),
+            AlignmentPolicy::Warn(callback) => {
+                callback(address, kind);
+                Ok(())
+            }
+            AlignmentPolicy::Fault => Err(BusDeviceError::AddressMisaligned { address, alignment: self.alignment })
+        }
+    }
+}
+
+impl<T: BusDevice + Debug> Debug for Aligned<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Aligned")
+            .field("inner", &self.inner)
+            .field("alignment", &self.alignment)
+            .field("policy", &self.policy)
+            .field("misaligned", &self.misaligned.get())
+            .finish()
+    }
+}
+
+impl<T: BusDevice> BusDevice for Aligned<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.read(address)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.write(address, data)
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.poke(address, data)
+    }
+
+    fn read_bytes(&self, address: usize, buffer: &mut [u8]) -> Result<(), BusDeviceError> {
+        self.check(address, buffer.len(), AccessKind::Read)?;
+        self.inner.read_bytes(address, buffer)
+    }
+
+    fn write_bytes(&mut self, address: usize, data: &[u8]) -> Result<(), BusDeviceError> {
+        self.check(address, data.len(), AccessKind::Write)?;
+        self.inner.write_bytes(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{Memory, MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_aligned_allow() {
+        let mut device = Aligned::new(Memory::<8>::incrementing(), 2, AlignmentPolicy::Allow);
+
+        assert_eq!(device.read_region(1), Ok([1, 2]));
+        assert_eq!(device.read_region(2), Ok([2, 3]));
+        assert_eq!(device.write_region(5, &[0xAA, 0xBB]), Ok(()));
+        assert_eq!(device.misaligned_count(), 2);
+
+        // Single bytes are never misaligned
+        assert_eq!(device.read(3), Ok(3));
+        assert_eq!(device.read_region(3), Ok([3]));
+        assert_eq!(device.misaligned_count(), 2);
+
+        device.clear_misaligned_count();
+        assert_eq!(device.misaligned_count(), 0);
+        assert_eq!(device.inner().as_slice(), [0, 1, 2, 3, 4, 0xAA, 0xBB, 7]);
+    }
+
+    #[test]
+    fn test_aligned_warn() {
+        let warnings = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&warnings);
+        let policy = AlignmentPolicy::Warn(Box::new(move |address, kind| sink.borrow_mut().push((address, kind))));
+
+        let mut map = MemoryMap::new().with_range(0x100..=0x10F, Box::new(Aligned::new(Memory::<16>::incrementing(), 2, policy)));
+
+        assert_eq!(map.read_region(0x103), Ok([3, 4]));
+        assert_eq!(map.read_region(0x104), Ok([4, 5]));
+        assert_eq!(map.write_region(0x107, &[0, 0]), Ok(()));
+
+        // Addresses are reported relative to the device, as with errors
+        assert_eq!(*warnings.borrow(), [(3, AccessKind::Read), (7, AccessKind::Write)]);
+    }
+
+    #[test]
+    fn test_aligned_fault() {
+        let mut device = Aligned::new(Memory::<8>::incrementing(), 4, AlignmentPolicy::Fault);
+
+        assert_eq!(device.read_region::<2>(1), Err(BusDeviceError::AddressMisaligned { address: 1, alignment: 4 }));
+        assert_eq!(device.read_region::<2>(2), Err(BusDeviceError::AddressMisaligned { address: 2, alignment: 4 }));
+        assert_eq!(device.read_region(4), Ok([4, 5, 6, 7]));
+
+        // Refused writes are not passed on
+        assert_eq!(device.write_region(3, &[0xFF, 0xFF]), Err(BusDeviceError::AddressMisaligned { address: 3, alignment: 4 }));
+        assert_eq!(device.inner().as_slice(), Memory::<8>::incrementing().as_slice());
+        assert_eq!(device.misaligned_count(), 3);
+
+        // Host access is not checked
+        assert_eq!(device.poke(3, 0xFF), Ok(()));
+        assert_eq!(device.peek(3), Ok(0xFF));
+    }
+}
@@ -0,0 +1,121 @@
+use std::ops::RangeInclusive;
+
+use crate::{BusDevice, BusDeviceError};
+
+/// Lookup table for the reflected CRC-32 (IEEE 802.3) polynomial.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut crc = i as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 == 0 { crc >> 1 } else { (crc >> 1) ^ 0xEDB8_8320 };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+};
+
+/// Computes the 8-bit sum of every byte in `range` of `device`. BIOS option ROMs are valid when this sum is zero.
+///
+/// # Errors
+///
+/// This function will return an error if any byte in the range cannot be read.
+pub fn checksum8(device: &(impl BusDevice + ?Sized), range: RangeInclusive<usize>) -> Result<u8, BusDeviceError> {
+    range.into_iter().try_fold(0u8, |sum, address| Ok(sum.wrapping_add(device.read(address)?)))
+}
+
+/// Computes the CRC-32 (IEEE 802.3, as used by zip and PNG) of every byte in `range` of `device`.
+///
+/// # Errors
+///
+/// This function will return an error if any byte in the range cannot be read.
+pub fn crc32(device: &(impl BusDevice + ?Sized), range: RangeInclusive<usize>) -> Result<u32, BusDeviceError> {
+    let crc = range.into_iter().try_fold(0xFFFF_FFFFu32, |crc, address| {
+        let byte = device.read(address)?;
+        Ok(CRC32_TABLE[usize::from(crc.to_le_bytes()[0] ^ byte)] ^ (crc >> 8))
+    })?;
+
+    Ok(!crc)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, ReadOnlyMemory};
+
+    use super::*;
+
+    #[test]
+    fn test_checksum8() {
+        let mem = Memory::<8>::populated(&[0x55, 0xAA, 0x01, 0x02]);
+
+        assert_eq!(checksum8(&mem, 0..=7), Ok(0x02));
+        assert_eq!(checksum8(&mem, 2..=3), Ok(0x03));
+        assert_eq!(checksum8(&mem, 0..=0), Ok(0x55));
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 1..=0;
+        assert_eq!(checksum8(&mem, empty), Ok(0));
+    }
+
+    #[test]
+    fn test_checksum8_option_rom() {
+        // Option ROM header: signature, length in 512 byte blocks and a far return entry point, with the final byte used
+        // to bring the checksum to zero
+        let mut rom = Memory::<512>::populated(&[0x55, 0xAA, 0x01, 0xCB]);
+        let fixup = checksum8(&rom, 0..=511).unwrap().wrapping_neg();
+        rom.write(511, fixup).unwrap();
+
+        assert_eq!(checksum8(&rom, 0..=511), Ok(0));
+    }
+
+    #[test]
+    fn test_crc32_known_answers() {
+        let mem = ReadOnlyMemory::<9>::filled(*b"123456789");
+        assert_eq!(crc32(&mem, 0..=8), Ok(0xCBF4_3926));
+
+        let fox = b"The quick brown fox jumps over the lazy dog";
+        let mem = Memory::<43>::populated(fox);
+        assert_eq!(crc32(&mem, 0..=42), Ok(0x414F_A339));
+
+        let zeroes = Memory::<32>::empty();
+        assert_eq!(crc32(&zeroes, 0..=31), Ok(0x190A_55AD));
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 1..=0;
+        assert_eq!(crc32(&zeroes, empty), Ok(0));
+    }
+
+    #[test]
+    fn test_checksums_across_memory_map() {
+        let map = MemoryMap::new()
+            .with_range(0..=3, Box::new(ReadOnlyMemory::filled(*b"1234")))
+            .with_range(4..=8, Box::new(Memory::filled(*b"56789")));
+
+        assert_eq!(crc32(&map, 0..=8), Ok(0xCBF4_3926));
+        assert_eq!(checksum8(&map, 0..=8), Ok(0xDD));
+
+        let device: &dyn BusDevice = &map;
+        assert_eq!(crc32(device, 0..=8), Ok(0xCBF4_3926));
+    }
+
+    #[test]
+    fn test_checksums_unmapped_hole() {
+        let map = MemoryMap::new()
+            .with_range(0..=3, Box::new(Memory::filled([1, 2, 3, 4])))
+            .with_range(6..=7, Box::new(Memory::filled([5, 6])));
+
+        assert_eq!(checksum8(&map, 0..=7), Err(BusDeviceError::AddressNotMapped { address: 4 }));
+        assert_eq!(crc32(&map, 0..=7), Err(BusDeviceError::AddressNotMapped { address: 4 }));
+        assert_eq!(crc32(&map, 2..=9), Err(BusDeviceError::AddressNotMapped { address: 4 }));
+        assert_eq!(checksum8(&map, 6..=8), Err(BusDeviceError::AddressNotMapped { address: 8 }));
+    }
+}
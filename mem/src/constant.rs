@@ -0,0 +1,113 @@
+use crate::{BusDevice, BusDeviceError, TimedBusDevice};
+
+/// Value read from an undriven data bus on a PC.
+pub const OPEN_BUS_VALUE: u8 = 0xFF;
+
+/// A device with no backing storage whose reads always return the same byte, such as an undriven (open) bus.
+///
+/// Writes are either silently discarded or rejected with `AddressNotWritable`, depending on how the device was
+/// constructed. As it holds no data it can be mapped over ranges of any size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConstantDevice {
+    value: u8,
+    ignore_writes: bool
+}
+
+impl ConstantDevice {
+    #[must_use]
+    /// Constructs a device which reads as `value`, silently discarding writes if `ignore_writes` is set and rejecting
+    /// them otherwise.
+    pub const fn new(value: u8, ignore_writes: bool) -> Self {
+        Self { value, ignore_writes }
+    }
+
+    #[must_use]
+    /// Constructs a device behaving like an open bus, reading as `0xFF` and discarding writes.
+    pub const fn open_bus() -> Self {
+        Self::new(OPEN_BUS_VALUE, true)
+    }
+
+    #[must_use]
+    /// Returns the byte returned by every read.
+    pub const fn value(&self) -> u8 {
+        self.value
+    }
+
+    #[must_use]
+    /// Returns `true` if writes are silently discarded rather than rejected.
+    pub const fn ignores_writes(&self) -> bool {
+        self.ignore_writes
+    }
+}
+
+impl Default for ConstantDevice {
+    fn default() -> Self {
+        Self::open_bus()
+    }
+}
+
+impl BusDevice for ConstantDevice {
+    fn read(&self, _address: usize) -> Result<u8, BusDeviceError> {
+        Ok(self.value)
+    }
+
+    fn write(&mut self, address: usize, _data: u8) -> Result<(), BusDeviceError> {
+        if self.ignore_writes {
+            Ok(())
+        }
+        else {
+            Err(BusDeviceError::AddressNotWritable { address })
+        }
+    }
+}
+
+impl TimedBusDevice for ConstantDevice {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_constant_device_open_bus_filler() {
+        let mut map = MemoryMap::new()
+            .with_range(0x00000..=0x003FF, Box::new(Memory::<0x400>::empty()))
+            .with_range(0x00400..=0xEFFFF, Box::new(ConstantDevice::open_bus()))
+            .with_range(0xF0000..=0xF0003, Box::new(Memory::filled([1, 2, 3, 4])));
+
+        for address in [0x400, 0x401, 0x1_0000, 0x9_FFFF, 0xE_FFFF] {
+            assert_eq!(map.read(address), Ok(0xFF));
+            assert_eq!(map.write(address, 0x12), Ok(()));
+            assert_eq!(map.read(address), Ok(0xFF));
+        }
+
+        assert_eq!(map.write(0x3FF, 0x12), Ok(()));
+        assert_eq!(map.read_region(0x3FE), Ok([0x00, 0x12, 0xFF, 0xFF]));
+        assert_eq!(map.read_region(0xEFFFE), Ok([0xFF, 0xFF, 1, 2]));
+    }
+
+    #[test]
+    fn test_constant_device_rejects_writes() {
+        let mut map = MemoryMap::new()
+            .with_range(0x0000..=0x0003, Box::new(Memory::<4>::empty()))
+            .with_range(0x0004..=0xFFFF, Box::new(ConstantDevice::new(0x00, false)));
+
+        for address in [0x4, 0x5, 0x1000, 0xFFFF] {
+            assert_eq!(map.read(address), Ok(0x00));
+            assert_eq!(map.write(address, 0x12), Err(BusDeviceError::AddressNotWritable { address: address - 4 }));
+        }
+
+        assert_eq!(map.write_region(0x2, &[1, 2, 3]), Err(BusDeviceError::AddressNotWritable { address: 0 }));
+        assert_eq!(map.read_region(0x0), Ok([0, 0, 1, 2, 0]));
+    }
+
+    #[test]
+    fn test_constant_device_any_address() {
+        let device = ConstantDevice::default();
+
+        assert_eq!(device.value(), OPEN_BUS_VALUE);
+        assert!(device.ignores_writes());
+        assert_eq!(device.read(usize::MAX), Ok(0xFF));
+    }
+}
@@ -0,0 +1,224 @@
+use std::cell::Cell;
+
+use crate::{BusDevice, BusDeviceError};
+
+/// Access counts gathered by a `Counted` device.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AccessStats {
+    /// Number of successful reads.
+    pub reads: u64,
+    /// Number of successful writes.
+    pub writes: u64,
+    /// Number of reads and writes which returned an error.
+    pub errors: u64
+}
+
+/// Wraps a device, counting the reads and writes made to it for performance analysis.
+///
+/// Optionally a per-address histogram of successful accesses can be kept for the addresses below a threshold, which
+/// allows the hottest addresses to be found. Without the histogram the wrapper only increments a counter per access.
+/// Peeks and pokes are forwarded without being counted.
+#[derive(Debug, Clone)]
+pub struct Counted<T: BusDevice> {
+    inner: T,
+    reads: Cell<u64>,
+    writes: Cell<u64>,
+    errors: Cell<u64>,
+    histogram: Option<Box<[Cell<u64>]>>
+}
+
+impl<T: BusDevice> Counted<T> {
+    #[must_use]
+    /// Wraps `inner`, counting accesses without a per-address histogram.
+    pub const fn new(inner: T) -> Self {
+        Self { inner, reads: Cell::new(0), writes: Cell::new(0), errors: Cell::new(0), histogram: None }
+    }
+
+    #[must_use]
+    /// Wraps `inner`, counting accesses and keeping a per-address histogram of the accesses to addresses below
+    /// `histogram_size`.
+    pub fn with_histogram(inner: T, histogram_size: usize) -> Self {
+        Self { histogram: Some((0..histogram_size).map(|_| Cell::new(0)).collect()), ..Self::new(inner) }
+    }
+
+    #[must_use]
+    /// Returns the access counts gathered so far.
+    pub const fn stats(&self) -> AccessStats {
+        AccessStats { reads: self.reads.get(), writes: self.writes.get(), errors: self.errors.get() }
+    }
+
+    /// Resets every counter, including the histogram, to zero.
+    pub fn reset_stats(&mut self) {
+        self.reads.set(0);
+        self.writes.set(0);
+        self.errors.set(0);
+
+        for count in self.histogram.iter().flatten() {
+            count.set(0);
+        }
+    }
+
+    #[must_use]
+    /// Returns the number of successful accesses to `address`, or `None` if it is not covered by the histogram.
+    pub fn access_count(&self, address: usize) -> Option<u64> {
+        self.histogram.as_ref()?.get(address).map(Cell::get)
+    }
+
+    #[must_use]
+    /// Returns up to `count` of the most accessed addresses in the histogram along with their access counts, most
+    /// accessed first and ties broken by address. Addresses which were never accessed are not included.
+    pub fn hottest(&self, count: usize) -> Vec<(usize, u64)> {
+        let mut hottest: Vec<(usize, u64)> = self.histogram.iter()
+            .flat_map(|histogram| histogram.iter().map(Cell::get).enumerate())
+            .filter(|(_, accesses)| *accesses > 0)
+            .collect();
+
+        hottest.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hottest.truncate(count);
+        hottest
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device, discarding the counts.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Records the outcome of an access to `address` in `counter`.
+    fn record<R>(&self, counter: &Cell<u64>, address: usize, result: &Result<R, BusDeviceError>) {
+        if result.is_err() {
+            self.errors.set(self.errors.get() + 1);
+            return;
+        }
+
+        counter.set(counter.get() + 1);
+
+        if let Some(count) = self.histogram.as_ref().and_then(|histogram| histogram.get(address)) {
+            count.set(count.get() + 1);
+        }
+    }
+}
+
+impl<T: BusDevice> BusDevice for Counted<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        let result = self.inner.read(address);
+        self.record(&self.reads, address, &result);
+        result
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        let result = self.inner.write(address, data);
+        self.record(&self.writes, address, &result);
+        result
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.poke(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, ReadOnlyMemory, Shared};
+
+    use super::*;
+
+    #[test]
+    fn test_counted_in_memory_map() {
+        let ram = Shared::new(Counted::new(Memory::<0x100>::empty()));
+        let rom = Shared::new(Counted::new(ReadOnlyMemory::<0x10>::empty()));
+
+        let mut map = MemoryMap::new()
+            .with_range(0x000..=0x0FF, Box::new(ram.clone()))
+            .with_range(0x100..=0x10F, Box::new(rom.clone()));
+
+        for i in 0..2000 {
+            let address = i % 0x110;
+
+            assert_eq!(map.read(address), Ok(0));
+            let _ = map.write(address, 0);
+        }
+
+        // Unmapped accesses never reach either device
+        assert!(map.read(0x110).is_err());
+
+        // 2000 accesses cover 0x000-0x10F seven times (1904 accesses), with the remaining 96 landing in 0x000-0x05F
+        assert_eq!(ram.borrow().stats(), AccessStats { reads: 7 * 0x100 + 96, writes: 7 * 0x100 + 96, errors: 0 });
+        assert_eq!(rom.borrow().stats(), AccessStats { reads: 7 * 0x10, writes: 0, errors: 7 * 0x10 });
+
+        ram.borrow_mut().reset_stats();
+        assert_eq!(ram.borrow().stats(), AccessStats::default());
+        assert_eq!(map.read(0x00), Ok(0));
+        assert_eq!(ram.borrow().stats().reads, 1);
+    }
+
+    #[test]
+    fn test_counted_errors() {
+        let mut device = Counted::new(Memory::<4>::empty());
+
+        for address in 0..8 {
+            let _ = device.read(address);
+            let _ = device.write(address, 1);
+        }
+
+        assert_eq!(device.stats(), AccessStats { reads: 4, writes: 4, errors: 8 });
+        assert_eq!(device.access_count(0), None);
+        assert!(device.hottest(4).is_empty());
+    }
+
+    #[test]
+    fn test_counted_histogram() {
+        let mut device = Counted::with_histogram(Memory::<0x100>::empty(), 0x10);
+
+        for i in 0..3000 {
+            // Address 3 is accessed on every iteration, address 7 on every other and 0x80 is beyond the histogram
+            device.read(3).unwrap();
+            if i % 2 == 0 {
+                device.write(7, 0).unwrap();
+            }
+            device.read(0x80).unwrap();
+        }
+
+        assert_eq!(device.stats(), AccessStats { reads: 6000, writes: 1500, errors: 0 });
+        assert_eq!(device.access_count(3), Some(3000));
+        assert_eq!(device.access_count(7), Some(1500));
+        assert_eq!(device.access_count(8), Some(0));
+        assert_eq!(device.access_count(0x80), None);
+        assert_eq!(device.hottest(5), [(3, 3000), (7, 1500)]);
+        assert_eq!(device.hottest(1), [(3, 3000)]);
+
+        // Peeks and pokes are not counted
+        assert_eq!(device.peek(3), Ok(0));
+        assert_eq!(device.poke(3, 1), Ok(()));
+        assert_eq!(device.access_count(3), Some(3000));
+
+        device.reset_stats();
+        assert_eq!(device.access_count(3), Some(0));
+        assert!(device.hottest(5).is_empty());
+        assert_eq!(device.into_inner()[3], 1);
+    }
+}
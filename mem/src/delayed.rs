@@ -0,0 +1,139 @@
+use crate::{BusDevice, BusDeviceError, TimedBusDevice};
+
+/// Adds a fixed number of wait states to every access to an inner device, as a slow ROM or an I/O card on the
+/// expansion bus inserts through the `READY` line.
+///
+/// The wait states are added to those the inner device reports, so delays applied at several levels of nested
+/// `MemoryMap`s add up. Untimed accesses through `BusDevice` are passed on unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Delayed<T: BusDevice> {
+    inner: T,
+    wait_states: u32
+}
+
+impl<T: BusDevice> Delayed<T> {
+    #[must_use]
+    /// Wraps `inner`, adding `wait_states` to every access.
+    pub const fn new(inner: T, wait_states: u32) -> Self {
+        Self { inner, wait_states }
+    }
+
+    #[must_use]
+    /// Returns the number of wait states added to every access.
+    pub const fn wait_states(&self) -> u32 {
+        self.wait_states
+    }
+
+    /// Sets the number of wait states added to every access.
+    pub const fn set_wait_states(&mut self, wait_states: u32) {
+        self.wait_states = wait_states;
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device, returning the wrapped device.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: BusDevice> BusDevice for Delayed<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.read(address)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.write(address, data)
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.poke(address, data)
+    }
+
+    fn read_bytes(&self, address: usize, buffer: &mut [u8]) -> Result<(), BusDeviceError> {
+        self.inner.read_bytes(address, buffer)
+    }
+
+    fn write_bytes(&mut self, address: usize, data: &[u8]) -> Result<(), BusDeviceError> {
+        self.inner.write_bytes(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+impl<T: TimedBusDevice> TimedBusDevice for Delayed<T> {
+    fn read_timed(&self, address: usize) -> Result<(u8, u32), BusDeviceError> {
+        let (value, wait_states) = self.inner.read_timed(address)?;
+        Ok((value, wait_states + self.wait_states))
+    }
+
+    fn write_timed(&mut self, address: usize, data: u8) -> Result<u32, BusDeviceError> {
+        Ok(self.inner.write_timed(address, data)? + self.wait_states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Latch, Memory, MemoryMap, ReadOnlyMemory, DEFAULT_WAIT_STATES};
+
+    use super::*;
+
+    #[test]
+    fn test_delayed_nested_maps() {
+        // An expansion card adding a wait state to everything on it, with a slow ROM adding two more
+        let card = MemoryMap::timed()
+            .with_range(0x0000..=0x00FF, Box::new(Delayed::new(ReadOnlyMemory::<0x100>::incrementing(), 2)))
+            .with_range(0x0100..=0x01FF, Box::new(Memory::<0x100>::empty()));
+
+        let mut map = MemoryMap::timed()
+            .with_range(0x00000..=0x003FF, Box::new(Memory::<0x400>::empty()))
+            .with_range(0xC8000..=0xC81FF, Box::new(Delayed::new(card, 1)));
+
+        assert_eq!(map.read_timed(0xC8010), Ok((0x10, 3)));
+        assert_eq!(map.write_timed(0xC8110, 0xAA), Ok(1));
+        assert_eq!(map.read_timed(0xC8110), Ok((0xAA, 1)));
+
+        // Errors are passed on without a cost
+        assert_eq!(map.write_timed(0xC8010, 0xAA), Err(BusDeviceError::AddressNotWritable { address: 0x10 }));
+        assert_eq!(map.read_timed(0xC8200), Err(BusDeviceError::AddressNotMapped { address: 0xC8200 }));
+    }
+
+    #[test]
+    fn test_delayed_untimed_default() {
+        let mut map = MemoryMap::timed()
+            .with_range(0x000..=0x0FF, Box::new(Memory::<0x100>::empty()))
+            .with_range(0x100..=0x100, Box::new(Latch::new([0x00])));
+
+        assert_eq!(map.write_timed(0x010, 0x12), Ok(DEFAULT_WAIT_STATES));
+        assert_eq!(map.read_timed(0x010), Ok((0x12, DEFAULT_WAIT_STATES)));
+        assert_eq!(map.write_timed(0x100, 0x34), Ok(DEFAULT_WAIT_STATES));
+        assert_eq!(map.read_timed(0x100), Ok((0x34, DEFAULT_WAIT_STATES)));
+
+        // Untimed accesses pass straight through a delay
+        let mut delayed = Delayed::new(Memory::<4>::empty(), 4);
+        assert_eq!(delayed.write(1, 0x56), Ok(()));
+        assert_eq!(delayed.read(1), Ok(0x56));
+        assert_eq!(delayed.read_timed(1), Ok((0x56, DEFAULT_WAIT_STATES + 4)));
+    }
+}
@@ -0,0 +1,275 @@
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::{BusDeviceError, RegionBusDevice, HEXDUMP_LINE_WIDTH};
+
+/// Default number of differing bytes from each side kept in a `DiffRange` by `diff`.
+pub const DIFF_PREVIEW_LENGTH: usize = 16;
+
+/// A run of consecutive addresses at which two devices differ.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DiffRange {
+    /// Address of the first differing byte.
+    pub start: usize,
+    /// Number of consecutive differing bytes.
+    pub length: usize,
+    /// Bytes from the first device, truncated to the preview length.
+    pub a: Vec<u8>,
+    /// Bytes from the second device, truncated to the preview length.
+    pub b: Vec<u8>
+}
+
+impl DiffRange {
+    #[must_use]
+    /// Returns the range of addresses covered by this difference.
+    pub const fn range(&self) -> RangeInclusive<usize> {
+        self.start..=self.start + self.length - 1
+    }
+}
+
+/// Compares `range` of two devices, returning the runs of differing bytes with up to `DIFF_PREVIEW_LENGTH` bytes of
+/// each run kept from both sides.
+///
+/// # Errors
+///
+/// This function will return an error if any byte in the range cannot be read from either device.
+pub fn diff(a: &impl RegionBusDevice, b: &impl RegionBusDevice, range: RangeInclusive<usize>) -> Result<Vec<DiffRange>, BusDeviceError> {
+    diff_with_preview(a, b, range, DIFF_PREVIEW_LENGTH)
+}
+
+/// Compares `range` of two devices, returning the runs of differing bytes with up to `preview_length` bytes of each
+/// run kept from both sides.
+///
+/// # Errors
+///
+/// This function will return an error if any byte in the range cannot be read from either device.
+pub fn diff_with_preview(a: &impl RegionBusDevice, b: &impl RegionBusDevice, range: RangeInclusive<usize>, preview_length: usize) -> Result<Vec<DiffRange>, BusDeviceError> {
+    let mut result: Vec<DiffRange> = Vec::new();
+    let mut current: Option<DiffRange> = None;
+
+    for address in range {
+        let byte_a = a.read(address)?;
+        let byte_b = b.read(address)?;
+
+        if byte_a == byte_b {
+            result.extend(current.take());
+            continue;
+        }
+
+        let run = current.get_or_insert_with(|| DiffRange { start: address, length: 0, a: Vec::new(), b: Vec::new() });
+
+        if run.length < preview_length {
+            run.a.push(byte_a);
+            run.b.push(byte_b);
+        }
+
+        run.length += 1;
+    }
+
+    result.extend(current);
+
+    Ok(result)
+}
+
+/// The first difference found by `compare_region`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompareMismatch {
+    /// The byte at `address` differs from the expected byte.
+    Byte{address: usize, expected: u8, actual: u8},
+    /// A byte could not be read before any difference was found.
+    Bus(BusDeviceError)
+}
+
+impl CompareMismatch {
+    #[must_use]
+    /// Returns the address of the differing byte, or of the access which failed.
+    pub const fn address(self) -> usize {
+        match self {
+            Self::Byte { address, .. } => address,
+            Self::Bus(error) => error.address()
+        }
+    }
+
+    #[must_use]
+    /// Returns the range of whole hexdump lines around the mismatch, from the line before it to the line after it,
+    /// for showing the mismatch in context.
+    pub const fn window(self) -> RangeInclusive<usize> {
+        let start = (self.address() / HEXDUMP_LINE_WIDTH).saturating_sub(1) * HEXDUMP_LINE_WIDTH;
+        start..=start + 3 * HEXDUMP_LINE_WIDTH - 1
+    }
+}
+
+impl Display for CompareMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Byte { address, expected, actual } => write!(f, "Byte at {address:#X} is {actual:02X}h, expected {expected:02X}h"),
+            Self::Bus(error) => write!(f, "Bus error while comparing: {error:X?}")
+        }
+    }
+}
+
+/// Compares the bytes of `device` starting at `address` against `expected`, returning the first difference.
+///
+/// # Errors
+///
+/// This function will return the first byte which differs, with its absolute address, or the error from the first
+/// byte which cannot be read, whichever comes first.
+pub fn compare_region(device: &impl RegionBusDevice, address: usize, expected: &[u8]) -> Result<(), CompareMismatch> {
+    for (i, expected) in expected.iter().enumerate() {
+        let address = address + i;
+        let actual = device.read(address).map_err(CompareMismatch::Bus)?;
+
+        if actual != *expected {
+            return Err(CompareMismatch::Byte { address, expected: *expected, actual });
+        }
+    }
+
+    Ok(())
+}
+
+/// Asserts that the bytes of a device starting at an address match the expected bytes, as with `compare_region`.
+///
+/// The device is borrowed by the macro, and the expected bytes may be anything which can be viewed as a byte slice. On
+/// failure the panic message names the first differing byte and includes a hexdump of the lines around it.
+///
+/// ```
+/// # use mem::{assert_region_eq, Memory};
+/// let memory = Memory::<4>::filled([0xEB, 0xFE, 0x90, 0x90]);
+/// assert_region_eq!(memory, 0, b"\xEB\xFE");
+/// assert_region_eq!(memory, 2, [0x90, 0x90]);
+/// ```
+#[macro_export]
+macro_rules! assert_region_eq {
+    ($device:expr, $address:expr, $expected:expr $(,)?) => {{
+        let device = &$device;
+        let expected = &$expected;
+        let expected: &[u8] = ::core::convert::AsRef::as_ref(expected);
+
+        if let Err(mismatch) = $crate::compare_region(device, $address, expected) {
+            panic!("{}\n{}", mismatch, $crate::hexdump(device, mismatch.window()));
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BusDevice, Memory, MemoryMap};
+
+    use super::*;
+
+    #[test]
+    fn test_diff_identical() {
+        let a = Memory::<64>::populated(&[1, 2, 3, 4]);
+        let b = a.clone();
+
+        assert_eq!(diff(&a, &b, 0..=63), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_diff_single_byte() {
+        let a = Memory::<64>::empty();
+        let mut b = a.clone();
+        b.write(10, 0xAA).unwrap();
+
+        let expected = DiffRange { start: 10, length: 1, a: vec![0], b: vec![0xAA] };
+        assert_eq!(expected.range(), 10..=10);
+        assert_eq!(diff(&a, &b, 0..=63), Ok(vec![expected]));
+        assert_eq!(diff(&a, &b, 11..=63), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_diff_separated_runs() {
+        let a = Memory::<64>::empty();
+        let mut b = a.clone();
+        b.write_region(4, &[1, 2, 3]).unwrap();
+        b.write_region(8, &[4, 5]).unwrap();
+
+        assert_eq!(diff(&a, &b, 0..=63), Ok(vec![
+            DiffRange { start: 4, length: 3, a: vec![0, 0, 0], b: vec![1, 2, 3] },
+            DiffRange { start: 8, length: 2, a: vec![0, 0], b: vec![4, 5] },
+        ]));
+
+        // A range starting part way through a run only reports the part inside the range
+        assert_eq!(diff(&a, &b, 5..=8), Ok(vec![
+            DiffRange { start: 5, length: 2, a: vec![0, 0], b: vec![2, 3] },
+            DiffRange { start: 8, length: 1, a: vec![0], b: vec![4] },
+        ]));
+    }
+
+    #[test]
+    fn test_diff_last_address() {
+        let a = Memory::<64>::empty();
+        let mut b = a.clone();
+        b.write(63, 1).unwrap();
+
+        assert_eq!(diff(&a, &b, 0..=63), Ok(vec![DiffRange { start: 63, length: 1, a: vec![0], b: vec![1] }]));
+        assert_eq!(diff(&a, &b, 63..=63), Ok(vec![DiffRange { start: 63, length: 1, a: vec![0], b: vec![1] }]));
+    }
+
+    #[test]
+    fn test_diff_preview_length() {
+        let a = Memory::<64>::empty();
+        let b = Memory::<64>::filled([0xFF; 64]);
+
+        let result = diff(&a, &b, 0..=63).unwrap();
+        assert_eq!(result, vec![DiffRange { start: 0, length: 64, a: vec![0; DIFF_PREVIEW_LENGTH], b: vec![0xFF; DIFF_PREVIEW_LENGTH] }]);
+        assert_eq!(result[0].range(), 0..=63);
+
+        assert_eq!(diff_with_preview(&a, &b, 2..=9, 2), Ok(vec![DiffRange { start: 2, length: 8, a: vec![0, 0], b: vec![0xFF, 0xFF] }]));
+        assert_eq!(diff_with_preview(&a, &b, 2..=9, 0), Ok(vec![DiffRange { start: 2, length: 8, a: vec![], b: vec![] }]));
+    }
+
+    #[test]
+    fn test_diff_errors() {
+        let a = Memory::<8>::empty();
+        let b = MemoryMap::new().with_range(0..=3, Box::new(Memory::<4>::empty()));
+
+        assert_eq!(diff(&a, &b, 0..=3), Ok(vec![]));
+        assert_eq!(diff(&a, &b, 0..=7), Err(BusDeviceError::AddressNotMapped { address: 4 }));
+        assert_eq!(diff(&b, &a, 0..=8), Err(BusDeviceError::AddressNotMapped { address: 4 }));
+        assert_eq!(diff(&a, &a, 0..=8), Err(BusDeviceError::AddressOutOfBounds { address: 8, size: 8 }));
+    }
+
+    #[test]
+    fn test_compare_region() {
+        let memory = Memory::<8>::incrementing();
+
+        assert_eq!(compare_region(&memory, 2, &[2, 3, 4]), Ok(()));
+        assert_eq!(compare_region(&memory, 0, &[]), Ok(()));
+        assert_eq!(compare_region(&memory, 0, &[9, 1, 2]), Err(CompareMismatch::Byte { address: 0, expected: 9, actual: 0 }));
+        assert_eq!(compare_region(&memory, 5, &[5, 6, 0]), Err(CompareMismatch::Byte { address: 7, expected: 0, actual: 7 }));
+    }
+
+    #[test]
+    fn test_compare_region_unmapped_hole() {
+        let map = MemoryMap::new()
+            .with_range(0x10..=0x13, Box::new(Memory::filled([0, 1, 2, 3])))
+            .with_range(0x16..=0x17, Box::new(Memory::filled([6, 7])));
+
+        let mismatch = compare_region(&map, 0x10, &[0, 1, 2, 3, 4, 5, 6, 7]).unwrap_err();
+        assert_eq!(mismatch, CompareMismatch::Bus(BusDeviceError::AddressNotMapped { address: 0x14 }));
+        assert_eq!(mismatch.address(), 0x14);
+        assert_eq!(mismatch.window(), 0x00..=0x2F);
+
+        // A difference before the hole is reported first
+        assert_eq!(compare_region(&map, 0x12, &[2, 4, 4]), Err(CompareMismatch::Byte { address: 0x13, expected: 4, actual: 3 }));
+    }
+
+    #[test]
+    fn test_assert_region_eq_expression() {
+        let memory = Memory::<8>::incrementing();
+
+        // The assertion can stand anywhere an expression can, such as a match arm or closure body
+        match memory.size() {
+            Some(8) => crate::assert_region_eq!(memory, 0, [0, 1, 2]),
+            _ => panic!("Unexpected size")
+        }
+
+        (0..4).for_each(|byte: u8| crate::assert_region_eq!(memory, usize::from(byte), [byte]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Byte at 0x7 is 07h, expected 00h")]
+    fn test_assert_region_eq_panics() {
+        crate::assert_region_eq!(Memory::<8>::incrementing(), 4, [4, 5, 6, 0]);
+    }
+}
@@ -0,0 +1,185 @@
+use std::ops::RangeInclusive;
+
+use crate::{BusDevice, BusDeviceError};
+
+/// Wraps a device, recording which addresses have been written so that a consumer such as a video renderer only needs
+/// to process what has changed.
+///
+/// Written addresses are kept as a sorted list of disjoint ranges, with adjacent addresses coalesced into a single
+/// range. Only successful writes and pokes mark an address as dirty, reads never do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirtyTracking<T: BusDevice> {
+    inner: T,
+    dirty: Vec<RangeInclusive<usize>>
+}
+
+impl<T: BusDevice> DirtyTracking<T> {
+    #[must_use]
+    /// Wraps `inner`, initially with nothing dirty.
+    pub const fn new(inner: T) -> Self {
+        Self { inner, dirty: Vec::new() }
+    }
+
+    #[must_use]
+    /// Returns the ranges written since the dirty state was last taken, in ascending order, without clearing them.
+    pub fn peek_dirty(&self) -> &[RangeInclusive<usize>] {
+        &self.dirty
+    }
+
+    /// Returns the ranges written since the dirty state was last taken, in ascending order, and clears them.
+    pub fn take_dirty(&mut self) -> Vec<RangeInclusive<usize>> {
+        std::mem::take(&mut self.dirty)
+    }
+
+    #[must_use]
+    /// Returns `true` if anything has been written since the dirty state was last taken.
+    pub const fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device. Writes made through it are not tracked.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device, discarding the dirty state.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Marks `address` as dirty, merging it into any range it touches.
+    fn mark(&mut self, address: usize) {
+        // The first range which contains or ends immediately before the address
+        let index = self.dirty.partition_point(|range| range.end().saturating_add(1) < address);
+
+        let Some(range) = self.dirty.get(index) else {
+            self.dirty.push(address..=address);
+            return;
+        };
+
+        if range.contains(&address) {
+            return;
+        }
+
+        if range.end().saturating_add(1) == address {
+            let start = *range.start();
+
+            // The address may also close the gap to the following range
+            if let Some(next) = self.dirty.get(index + 1).filter(|next| *next.start() == address + 1) {
+                self.dirty[index] = start..=*next.end();
+                self.dirty.remove(index + 1);
+            }
+            else {
+                self.dirty[index] = start..=address;
+            }
+        }
+        else if *range.start() == address + 1 {
+            self.dirty[index] = address..=*range.end();
+        }
+        else {
+            self.dirty.insert(index, address..=address);
+        }
+    }
+}
+
+impl<T: BusDevice> BusDevice for DirtyTracking<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.read(address)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.write(address, data)?;
+        self.mark(address);
+        Ok(())
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.poke(address, data)?;
+        self.mark(address);
+        Ok(())
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_dirty_tracking_writes() {
+        let mut device = DirtyTracking::new(Memory::<4000>::empty());
+        assert!(!device.is_dirty());
+
+        assert_eq!(device.write(10, 1), Ok(()));
+        assert_eq!(device.write_region(160, b"Hello"), Ok(()));
+        assert_eq!(device.write(3999, 1), Ok(()));
+
+        assert_eq!(device.peek_dirty(), &[10..=10, 160..=164, 3999..=3999]);
+        assert_eq!(device.peek_dirty(), &[10..=10, 160..=164, 3999..=3999]);
+        assert!(device.is_dirty());
+
+        assert_eq!(device.take_dirty(), vec![10..=10, 160..=164, 3999..=3999]);
+        assert!(!device.is_dirty());
+        assert_eq!(device.take_dirty(), vec![]);
+    }
+
+    #[test]
+    fn test_dirty_tracking_coalescing() {
+        let mut device = DirtyTracking::new(Memory::<64>::empty());
+
+        // Out of order writes which grow ranges at both ends and bridge the gap between them
+        for address in [20, 22, 21, 19, 24, 10, 23, 9, 11, 21] {
+            assert_eq!(device.write(address, 0xFF), Ok(()));
+        }
+
+        assert_eq!(device.peek_dirty(), &[9..=11, 19..=24]);
+
+        assert_eq!(device.write_region(12, &[0; 7]), Ok(()));
+        assert_eq!(device.take_dirty(), vec![9..=24]);
+
+        assert_eq!(device.write(0, 0), Ok(()));
+        assert_eq!(device.write(63, 0), Ok(()));
+        assert_eq!(device.write(2, 0), Ok(()));
+        assert_eq!(device.take_dirty(), vec![0..=0, 2..=2, 63..=63]);
+    }
+
+    #[test]
+    fn test_dirty_tracking_reads_and_errors() {
+        let mut device = DirtyTracking::new(Memory::<8>::filled([1; 8]));
+
+        assert_eq!(device.read_region(0), Ok([1; 8]));
+        assert_eq!(device.peek(3), Ok(1));
+        assert!(!device.is_dirty());
+
+        // Only the bytes written before the failing write are marked
+        assert_eq!(device.write_region(6, &[2, 2, 2]), Err(BusDeviceError::AddressOutOfBounds { address: 8, size: 8 }));
+        assert_eq!(device.take_dirty(), vec![6..=7]);
+
+        // Pokes change the contents, so are tracked
+        assert_eq!(device.poke(4, 3), Ok(()));
+        assert_eq!(device.take_dirty(), vec![4..=4]);
+
+        assert_eq!(device.into_inner().as_slice(), &[1, 1, 1, 1, 3, 1, 2, 2]);
+    }
+}
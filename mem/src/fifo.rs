@@ -0,0 +1,338 @@
+use std::cell::RefCell;
+
+use crate::{BusDevice, BusDeviceError, TimedBusDevice};
+
+/// Offset of the data register of a `FifoDevice`, whose reads pop from the front of the FIFO and whose writes push to
+/// the back.
+pub const FIFO_DATA_OFFSET: usize = 0;
+
+/// Offset of the read only status register of a `FifoDevice`, holding the `FIFO_STATUS_EMPTY` and `FIFO_STATUS_FULL`
+/// bits.
+pub const FIFO_STATUS_OFFSET: usize = 1;
+
+/// Offset of the read only count register of a `FifoDevice`, holding the number of bytes queued, saturating at `FFh`.
+pub const FIFO_COUNT_OFFSET: usize = 2;
+
+/// Number of addresses occupied by a `FifoDevice`.
+const FIFO_DEVICE_SIZE: usize = 3;
+
+/// Status register bit set while a `FifoDevice` holds no bytes.
+pub const FIFO_STATUS_EMPTY: u8 = 0x01;
+
+/// Status register bit set while a `FifoDevice` is at capacity.
+pub const FIFO_STATUS_FULL: u8 = 0x02;
+
+/// What a read of the data register of an empty `FifoDevice` returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FifoEmptyRead {
+    /// Read as zero.
+    Zero,
+    /// Read the byte most recently popped again, or zero if none has been.
+    Last,
+    /// Fail the read with `FifoEmpty`.
+    Error
+}
+
+/// Which byte is lost when pushing to a full `FifoDevice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FifoOverflow {
+    /// Discard the byte being pushed, keeping the queued bytes.
+    DropNewest,
+    /// Discard the byte at the front of the FIFO to make room for the byte being pushed.
+    DropOldest
+}
+
+/// A ring buffer of bytes, with the byte most recently popped.
+#[derive(Debug, Clone)]
+struct Ring {
+    buffer: Box<[u8]>,
+    head: usize,
+    len: usize,
+    last: u8
+}
+
+impl Ring {
+    fn push(&mut self, data: u8, overflow: FifoOverflow) -> bool {
+        let capacity = self.buffer.len();
+        let full = self.len == capacity;
+
+        if full {
+            if overflow == FifoOverflow::DropNewest {
+                return false;
+            }
+
+            self.head = (self.head + 1) % capacity;
+            self.len -= 1;
+        }
+
+        self.buffer[(self.head + self.len) % capacity] = data;
+        self.len += 1;
+
+        !full
+    }
+
+    fn front(&self) -> Option<u8> {
+        (self.len > 0).then(|| self.buffer[self.head])
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        let data = self.front()?;
+
+        self.head = (self.head + 1) % self.buffer.len();
+        self.len -= 1;
+        self.last = data;
+
+        Some(data)
+    }
+}
+
+/// A byte FIFO visible on the bus, as found in keyboard controllers and UARTs.
+///
+/// The device occupies three addresses: the data register at `FIFO_DATA_OFFSET`, and the read only status and count
+/// registers at `FIFO_STATUS_OFFSET` and `FIFO_COUNT_OFFSET`. Guest reads of the data register pop from the front of the
+/// FIFO and guest writes push to the back, and the host can do the same with `pop` and `push`, so the FIFO can carry
+/// bytes in either direction. Peeking the data register returns the byte at the front without popping it.
+///
+/// The FIFO is held in a fixed size ring buffer. How reads of an empty FIFO and pushes to a full one behave are chosen
+/// at construction.
+#[derive(Debug, Clone)]
+pub struct FifoDevice {
+    ring: RefCell<Ring>,
+    empty_read: FifoEmptyRead,
+    overflow: FifoOverflow
+}
+
+impl FifoDevice {
+    #[must_use]
+    /// Constructs an empty FIFO holding at most `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize, empty_read: FifoEmptyRead, overflow: FifoOverflow) -> Self {
+        assert!(capacity > 0, "FIFO capacity must not be zero");
+
+        Self { ring: RefCell::new(Ring { buffer: vec![0; capacity].into_boxed_slice(), head: 0, len: 0, last: 0 }), empty_read, overflow }
+    }
+
+    #[must_use]
+    /// Returns the maximum number of bytes the FIFO holds.
+    pub fn capacity(&self) -> usize {
+        self.ring.borrow().buffer.len()
+    }
+
+    #[must_use]
+    /// Returns the number of bytes queued.
+    pub fn len(&self) -> usize {
+        self.ring.borrow().len
+    }
+
+    #[must_use]
+    /// Returns `true` if no bytes are queued.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[must_use]
+    /// Returns `true` if the FIFO is at capacity.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    #[must_use]
+    /// Returns the value of the status register.
+    pub fn status(&self) -> u8 {
+        let mut status = 0;
+
+        if self.is_empty() {
+            status |= FIFO_STATUS_EMPTY;
+        }
+
+        if self.is_full() {
+            status |= FIFO_STATUS_FULL;
+        }
+
+        status
+    }
+
+    /// Pushes `data` to the back of the FIFO, returning `false` if a byte was lost to the overflow policy.
+    pub fn push(&mut self, data: u8) -> bool {
+        self.ring.get_mut().push(data, self.overflow)
+    }
+
+    /// Pops the byte at the front of the FIFO, or returns `None` if it is empty.
+    pub fn pop(&mut self) -> Option<u8> {
+        self.ring.get_mut().pop()
+    }
+
+    /// Discards every queued byte.
+    pub fn clear(&mut self) {
+        let ring = self.ring.get_mut();
+        (ring.head, ring.len) = (0, 0);
+    }
+
+    /// Returns the byte a read of the data register of the empty FIFO returns.
+    fn read_empty(&self, address: usize) -> Result<u8, BusDeviceError> {
+        match self.empty_read {
+            FifoEmptyRead::Zero => Ok(0),
+            FifoEmptyRead::Last => Ok(self.ring.borrow().last),
+            FifoEmptyRead::Error => Err(BusDeviceError::FifoEmpty { address })
+        }
+    }
+}
+
+impl BusDevice for FifoDevice {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        if address != FIFO_DATA_OFFSET {
+            return self.peek(address);
+        }
+
+        let popped = self.ring.borrow_mut().pop();
+
+        popped.map_or_else(|| self.read_empty(address), Ok)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        match address {
+            FIFO_DATA_OFFSET => {
+                self.push(data);
+                Ok(())
+            }
+            FIFO_STATUS_OFFSET | FIFO_COUNT_OFFSET => Err(BusDeviceError::AddressNotWritable { address }),
+            _ => Err(BusDeviceError::AddressOutOfBounds { address, size: FIFO_DEVICE_SIZE })
+        }
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        match address {
+            FIFO_DATA_OFFSET => {
+                let front = self.ring.borrow().front();
+                front.map_or_else(|| self.read_empty(address), Ok)
+            }
+            FIFO_STATUS_OFFSET => Ok(self.status()),
+            FIFO_COUNT_OFFSET => Ok(u8::try_from(self.len()).unwrap_or(u8::MAX)),
+            _ => Err(BusDeviceError::AddressOutOfBounds { address, size: FIFO_DEVICE_SIZE })
+        }
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(FIFO_DEVICE_SIZE)
+    }
+
+    /// Discards any queued bytes.
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+impl TimedBusDevice for FifoDevice {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_fifo_wraparound() {
+        let mut fifo = FifoDevice::new(4, FifoEmptyRead::Zero, FifoOverflow::DropNewest);
+
+        // Each round moves the start of the queue further around the ring
+        for round in 0..5u8 {
+            for i in 0..3 {
+                assert!(fifo.push(round * 10 + i));
+            }
+
+            assert_eq!(fifo.read(FIFO_DATA_OFFSET), Ok(round * 10));
+            assert_eq!(fifo.pop(), Some(round * 10 + 1));
+            assert_eq!(fifo.read(FIFO_DATA_OFFSET), Ok(round * 10 + 2));
+            assert!(fifo.is_empty());
+        }
+
+        assert_eq!(fifo.pop(), None);
+        assert_eq!(fifo.capacity(), 4);
+    }
+
+    #[test]
+    fn test_fifo_overflow() {
+        let mut fifo = FifoDevice::new(3, FifoEmptyRead::Zero, FifoOverflow::DropNewest);
+        assert_eq!(fifo.write_region(FIFO_DATA_OFFSET, &[1]), Ok(()));
+        assert!(fifo.push(2));
+        assert!(fifo.push(3));
+        assert!(!fifo.push(4));
+        assert_eq!([fifo.pop(), fifo.pop(), fifo.pop(), fifo.pop()], [Some(1), Some(2), Some(3), None]);
+
+        let mut fifo = FifoDevice::new(3, FifoEmptyRead::Zero, FifoOverflow::DropOldest);
+        for data in 1..=5 {
+            assert_eq!(fifo.push(data), data <= 3);
+        }
+        assert_eq!([fifo.pop(), fifo.pop(), fifo.pop(), fifo.pop()], [Some(3), Some(4), Some(5), None]);
+
+        // A single byte FIFO always keeps the newest byte
+        let mut fifo = FifoDevice::new(1, FifoEmptyRead::Zero, FifoOverflow::DropOldest);
+        assert!(fifo.push(1));
+        assert!(!fifo.push(2));
+        assert_eq!(fifo.pop(), Some(2));
+    }
+
+    #[test]
+    fn test_fifo_status() {
+        let mut fifo = FifoDevice::new(2, FifoEmptyRead::Zero, FifoOverflow::DropNewest);
+        assert_eq!(fifo.read_region(FIFO_STATUS_OFFSET), Ok([FIFO_STATUS_EMPTY, 0]));
+
+        fifo.push(0xAA);
+        assert_eq!(fifo.read_region(FIFO_STATUS_OFFSET), Ok([0, 1]));
+
+        fifo.push(0xBB);
+        assert_eq!(fifo.read_region(FIFO_STATUS_OFFSET), Ok([FIFO_STATUS_FULL, 2]));
+
+        fifo.clear();
+        assert_eq!(fifo.read_region(FIFO_STATUS_OFFSET), Ok([FIFO_STATUS_EMPTY, 0]));
+
+        assert_eq!(fifo.write(FIFO_STATUS_OFFSET, 0), Err(BusDeviceError::AddressNotWritable { address: 1 }));
+        assert_eq!(fifo.read(3), Err(BusDeviceError::AddressOutOfBounds { address: 3, size: FIFO_DEVICE_SIZE }));
+
+        // The count saturates rather than wrapping
+        let mut fifo = FifoDevice::new(300, FifoEmptyRead::Zero, FifoOverflow::DropNewest);
+        for _ in 0..300 {
+            fifo.push(0);
+        }
+        assert_eq!(fifo.read(FIFO_COUNT_OFFSET), Ok(0xFF));
+    }
+
+    #[test]
+    fn test_fifo_empty_read() {
+        let mut fifo = FifoDevice::new(2, FifoEmptyRead::Zero, FifoOverflow::DropNewest);
+        fifo.push(0x41);
+        assert_eq!(fifo.read_region(FIFO_DATA_OFFSET), Ok([0x41]));
+        assert_eq!(fifo.read(FIFO_DATA_OFFSET), Ok(0x00));
+
+        let mut fifo = FifoDevice::new(2, FifoEmptyRead::Last, FifoOverflow::DropNewest);
+        assert_eq!(fifo.read(FIFO_DATA_OFFSET), Ok(0x00));
+        fifo.push(0x41);
+        assert_eq!(fifo.read(FIFO_DATA_OFFSET), Ok(0x41));
+        assert_eq!(fifo.read(FIFO_DATA_OFFSET), Ok(0x41));
+
+        let fifo = FifoDevice::new(2, FifoEmptyRead::Error, FifoOverflow::DropNewest);
+        assert_eq!(fifo.read(FIFO_DATA_OFFSET), Err(BusDeviceError::FifoEmpty { address: 0 }));
+        assert_eq!(fifo.peek(FIFO_DATA_OFFSET), Err(BusDeviceError::FifoEmpty { address: 0 }));
+    }
+
+    #[test]
+    fn test_fifo_peek() {
+        let mut map = MemoryMap::new()
+            .with_range(0x60..=0x62, Box::new(FifoDevice::new(16, FifoEmptyRead::Last, FifoOverflow::DropNewest)));
+
+        assert_eq!(map.write_region(0x60, &[0x1C, 0x9C]), Err(BusDeviceError::AddressNotWritable { address: 1 }));
+        assert_eq!(map.write(0x60, 0x9C), Ok(()));
+
+        // Peeking the data register does not consume the byte
+        assert_eq!(map.peek(0x60), Ok(0x1C));
+        assert_eq!(map.peek(0x62), Ok(2));
+        assert_eq!(map.read(0x60), Ok(0x1C));
+        assert_eq!(map.peek(0x60), Ok(0x9C));
+        assert_eq!(map.read(0x60), Ok(0x9C));
+        assert_eq!(map.peek(0x60), Ok(0x9C));
+        assert_eq!(map.read(0x61), Ok(FIFO_STATUS_EMPTY));
+    }
+}
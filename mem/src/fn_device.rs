@@ -0,0 +1,116 @@
+use std::fmt::Debug;
+
+use crate::{BusDevice, BusDeviceError};
+
+/// Closure handling reads for a `FnDevice`.
+pub type ReadFn = Box<dyn Fn(usize) -> Result<u8, BusDeviceError>>;
+
+/// Closure handling writes for a `FnDevice`.
+pub type WriteFn = Box<dyn FnMut(usize, u8) -> Result<(), BusDeviceError>>;
+
+/// A device whose reads and writes are handled by a pair of closures, convenient for prototyping memory mapped
+/// peripherals.
+///
+/// The closures are given the address relative to the start of the device, as with any other `BusDevice`.
+pub struct FnDevice {
+    read_fn: ReadFn,
+    write_fn: WriteFn
+}
+
+impl FnDevice {
+    #[must_use]
+    /// Constructs a device from a closure handling reads and a closure handling writes.
+    pub fn new(read_fn: impl Fn(usize) -> Result<u8, BusDeviceError> + 'static, write_fn: impl FnMut(usize, u8) -> Result<(), BusDeviceError> + 'static) -> Self {
+        Self { read_fn: Box::new(read_fn), write_fn: Box::new(write_fn) }
+    }
+
+    #[must_use]
+    /// Constructs a device from a closure handling reads, rejecting every write with `AddressNotWritable`.
+    pub fn read_only(read_fn: impl Fn(usize) -> Result<u8, BusDeviceError> + 'static) -> Self {
+        Self::new(read_fn, |address, _| Err(BusDeviceError::AddressNotWritable { address }))
+    }
+
+    #[must_use]
+    /// Constructs a device from a closure handling writes, rejecting every read with `AddressNotMapped`.
+    pub fn write_only(write_fn: impl FnMut(usize, u8) -> Result<(), BusDeviceError> + 'static) -> Self {
+        Self::new(|address| Err(BusDeviceError::AddressNotMapped { address }), write_fn)
+    }
+}
+
+impl Debug for FnDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnDevice").finish_non_exhaustive()
+    }
+}
+
+impl BusDevice for FnDevice {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        (self.read_fn)(address)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        (self.write_fn)(address, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::{Memory, MemoryMap};
+
+    use super::*;
+
+    #[test]
+    fn test_fn_device_status_register() {
+        // A status register whose busy bit clears after it has been polled three times
+        let polls = Cell::new(0u8);
+        let status = FnDevice::read_only(move |_| {
+            polls.set(polls.get() + 1);
+            Ok(if polls.get() < 3 { 0x80 } else { 0x00 })
+        });
+
+        let mut map = MemoryMap::new()
+            .with_range(0x0000..=0x00FF, Box::new(Memory::<0x100>::empty()))
+            .with_range(0x0100..=0x0100, Box::new(status));
+
+        assert_eq!(map.read(0x100), Ok(0x80));
+        assert_eq!(map.read(0x100), Ok(0x80));
+        assert_eq!(map.read(0x100), Ok(0x00));
+        assert_eq!(map.write(0x100, 0), Err(BusDeviceError::AddressNotWritable { address: 0 }));
+    }
+
+    #[test]
+    fn test_fn_device_write_side_effect() {
+        let last_write = Rc::new(Cell::new(None));
+
+        let captured = Rc::clone(&last_write);
+        let port = FnDevice::write_only(move |address, data| {
+            captured.set(Some((address, data)));
+            Ok(())
+        });
+
+        let mut map = MemoryMap::new().with_range(0x3F8..=0x3FF, Box::new(port));
+
+        assert_eq!(last_write.get(), None);
+        assert_eq!(map.write(0x3F9, b'A'), Ok(()));
+        assert_eq!(last_write.get(), Some((1, b'A')));
+        assert_eq!(map.read(0x3F9), Err(BusDeviceError::AddressNotMapped { address: 1 }));
+    }
+
+    #[test]
+    fn test_fn_device_read_write() {
+        let register = Rc::new(Cell::new(0u8));
+
+        let read_register = Rc::clone(&register);
+        let write_register = Rc::clone(&register);
+        let mut device = FnDevice::new(
+            move |address| if address == 0 { Ok(read_register.get()) } else { Err(BusDeviceError::AddressOutOfBounds { address, size: 1 }) },
+            move |_, data| { write_register.set(!data); Ok(()) });
+
+        assert_eq!(device.write(0, 0x0F), Ok(()));
+        assert_eq!(device.read(0), Ok(0xF0));
+        assert_eq!(device.read(1), Err(BusDeviceError::AddressOutOfBounds { address: 1, size: 1 }));
+        assert_eq!(format!("{device:?}"), "FnDevice { .. }");
+    }
+}
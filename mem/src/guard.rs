@@ -0,0 +1,51 @@
+use crate::{AccessKind, BusDevice, BusDeviceError};
+
+/// A device which fails every access with `GuardViolation`.
+///
+/// Guards are placed in the gaps between other devices so that stray accesses are caught as soon as they happen,
+/// rather than silently landing in a neighbouring device. The device knows the address it is mapped at, so that the
+/// error reports the address as seen on the bus rather than relative to the start of the guard. Peeks and pokes fail
+/// in the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GuardDevice {
+    base: usize
+}
+
+impl GuardDevice {
+    #[must_use]
+    /// Constructs a guard mapped starting at `base`.
+    pub const fn new(base: usize) -> Self {
+        Self { base }
+    }
+
+    #[must_use]
+    /// Returns the address the guard is mapped at.
+    pub const fn base(&self) -> usize {
+        self.base
+    }
+}
+
+impl BusDevice for GuardDevice {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        Err(BusDeviceError::GuardViolation { address: self.base + address, kind: AccessKind::Read })
+    }
+
+    fn write(&mut self, address: usize, _data: u8) -> Result<(), BusDeviceError> {
+        Err(BusDeviceError::GuardViolation { address: self.base + address, kind: AccessKind::Write })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_device() {
+        let mut guard = GuardDevice::new(0x100);
+
+        assert_eq!(guard.read(4), Err(BusDeviceError::GuardViolation { address: 0x104, kind: AccessKind::Read }));
+        assert_eq!(guard.write(0, 1), Err(BusDeviceError::GuardViolation { address: 0x100, kind: AccessKind::Write }));
+        assert_eq!(guard.peek(1), Err(BusDeviceError::GuardViolation { address: 0x101, kind: AccessKind::Read }));
+        assert_eq!(guard.size(), None);
+    }
+}
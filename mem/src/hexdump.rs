@@ -0,0 +1,181 @@
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::RegionBusDevice;
+
+/// Number of bytes displayed on each line of a hexdump.
+pub const HEXDUMP_LINE_WIDTH: usize = 16;
+
+/// Default number of lines shown by the `Debug` implementations of the memory types, which can be overridden with the
+/// precision of the format specifier (`{:.4?}`).
+pub const DEBUG_HEXDUMP_LINES: usize = 16;
+
+/// Display adapter producing a classic hexdump of a region of a bus device.
+///
+/// Each line contains the address of its first byte, up to 16 bytes in hex and an ASCII gutter. Bytes are read with
+/// `peek`, so dumping a device does not trigger its read side effects. Bytes which cannot be read (for example
+/// unmapped addresses in a `MemoryMap`) are rendered as `..`.
+pub struct HexDump<'a, T: RegionBusDevice> {
+    device: &'a T,
+    range: RangeInclusive<usize>,
+    max_lines: Option<usize>
+}
+
+/// Constructs a `HexDump` display adapter for the given `range` of `device`.
+#[must_use]
+pub const fn hexdump<T: RegionBusDevice>(device: &T, range: RangeInclusive<usize>) -> HexDump<'_, T> {
+    HexDump { device, range, max_lines: None }
+}
+
+impl<T: RegionBusDevice> HexDump<'_, T> {
+    /// Limits the dump to at most `max_lines` lines, replacing the remainder with a summary line.
+    #[must_use]
+    pub const fn max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+}
+
+impl<T: RegionBusDevice> Display for HexDump<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.range.is_empty() {
+            return Ok(());
+        }
+
+        let start = *self.range.start();
+        let end = *self.range.end();
+
+        for (line, line_start) in (start..=end).step_by(HEXDUMP_LINE_WIDTH).enumerate() {
+            if line > 0 {
+                writeln!(f)?;
+            }
+
+            if self.max_lines.is_some_and(|max| line >= max) {
+                return write!(f, "... {} more bytes", end - line_start + 1);
+            }
+
+            write!(f, "{line_start:08X} ")?;
+
+            let mut gutter = String::with_capacity(HEXDUMP_LINE_WIDTH);
+
+            for column in 0..HEXDUMP_LINE_WIDTH {
+                if column % 8 == 0 {
+                    write!(f, " ")?;
+                }
+
+                let address = line_start + column;
+
+                if address > end {
+                    write!(f, "   ")?;
+                    continue;
+                }
+
+                if let Ok(byte) = self.device.peek(address) {
+                    write!(f, "{byte:02X} ")?;
+                    gutter.push(if byte.is_ascii_graphic() || byte == b' ' { char::from(byte) } else { '.' });
+                }
+                else {
+                    write!(f, ".. ")?;
+                    gutter.push(' ');
+                }
+            }
+
+            write!(f, " |{gutter}|")?;
+
+            if line_start.checked_add(HEXDUMP_LINE_WIDTH).is_none() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, ReadOnlyMemory};
+
+    use super::*;
+
+    #[test]
+    fn test_hexdump_single_line() {
+        let mem = Memory::<16>::populated(b"Hello, world!\0\x01\x7F");
+
+        assert_eq!(hexdump(&mem, 0..=15).to_string(),
+            "00000000  48 65 6C 6C 6F 2C 20 77  6F 72 6C 64 21 00 01 7F  |Hello, world!...|");
+    }
+
+    #[test]
+    fn test_hexdump_partial_lines() {
+        let mem = Memory::<32>::from_fn(|i| u8::try_from(i).unwrap() + b'A');
+
+        assert_eq!(hexdump(&mem, 4..=24).to_string(),
+            "00000004  45 46 47 48 49 4A 4B 4C  4D 4E 4F 50 51 52 53 54  |EFGHIJKLMNOPQRST|\n\
+             00000014  55 56 57 58 59                                    |UVWXY|");
+
+        assert_eq!(hexdump(&mem, 0..=0).to_string(),
+            "00000000  41                                                |A|");
+    }
+
+    #[test]
+    fn test_hexdump_empty_range() {
+        let mem = Memory::<16>::empty();
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 8..=7;
+        assert_eq!(hexdump(&mem, empty).to_string(), "");
+    }
+
+    #[test]
+    fn test_hexdump_unmapped() {
+        let map = MemoryMap::new()
+            .with_range(0..=3, Box::new(Memory::filled(*b"ABCD")))
+            .with_range(12..=13, Box::new(ReadOnlyMemory::filled(*b"EF")));
+
+        assert_eq!(hexdump(&map, 0..=19).to_string(),
+            "00000000  41 42 43 44 .. .. .. ..  .. .. .. .. 45 46 .. ..  |ABCD        EF  |\n\
+             00000010  .. .. .. ..                                       |    |");
+    }
+
+    #[test]
+    fn test_hexdump_max_lines() {
+        let mem = Memory::<64>::empty();
+
+        assert_eq!(hexdump(&mem, 0..=63).max_lines(2).to_string(),
+            "00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+             00000010  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+             ... 32 more bytes");
+
+        assert_eq!(hexdump(&mem, 0..=31).max_lines(2).to_string(), hexdump(&mem, 0..=31).to_string());
+        assert_eq!(hexdump(&mem, 0..=63).max_lines(0).to_string(), "... 64 more bytes");
+    }
+
+    #[test]
+    fn test_memory_debug() {
+        let mem = Memory::<20>::populated(&[0xEB, 0xFE]);
+
+        assert_eq!(format!("{mem:?}"),
+            "Memory<20>\n\
+             00000000  EB FE 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+             00000010  00 00 00 00                                       |....|");
+
+        assert_eq!(format!("{mem:.1?}"),
+            "Memory<20>\n\
+             00000000  EB FE 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+             ... 4 more bytes");
+
+        assert_eq!(format!("{:?}", Memory::<0>::empty()), "Memory<0>");
+    }
+
+    #[test]
+    fn test_read_only_memory_debug() {
+        let mem = ReadOnlyMemory::<1024>::populated(b"ROM");
+
+        let debug = format!("{mem:?}");
+        let lines: Vec<&str> = debug.lines().collect();
+
+        assert_eq!(lines.len(), DEBUG_HEXDUMP_LINES + 2);
+        assert_eq!(lines[0], "ReadOnlyMemory<1024>");
+        assert_eq!(lines[1], "00000000  52 4F 4D 00 00 00 00 00  00 00 00 00 00 00 00 00  |ROM.............|");
+        assert_eq!(lines[DEBUG_HEXDUMP_LINES + 1], "... 768 more bytes");
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{segmented_linear, srec::decode_hex, BusDeviceError, RegionBusDevice};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IhexError {
+    MissingRecordMark{line: usize},
+    UnknownRecordType{line: usize, record_type: u8},
+    InvalidHex{line: usize},
+    /// The record has an odd number of digits, its length does not match its byte count, or it has the wrong amount
+    /// of data for its type.
+    MalformedRecord{line: usize},
+    BadChecksum{line: usize, expected: u8, actual: u8},
+    Bus(BusDeviceError)
+}
+
+impl From<BusDeviceError> for IhexError {
+    fn from(value: BusDeviceError) -> Self {
+        Self::Bus(value)
+    }
+}
+
+/// Loads the Intel HEX formatted `text` into `device`, returning the `CS:IP` entry point given by a start segment
+/// address record, if one is present.
+///
+/// Type 00 data records are written to the device at their offset within the segment set by the last type 02
+/// extended segment address record, wrapping within that segment as the 8086 would. A type 01 record ends the load.
+/// Blank lines are skipped.
+///
+/// # Errors
+///
+/// This function will return an error if a record is malformed or of a type other than 00 to 03, if a checksum does
+/// not match, or if the data cannot be written to the device.
+pub fn load_ihex(device: &mut impl RegionBusDevice, text: &str) -> Result<Option<(u16, u16)>, IhexError> {
+    let mut segment = 0;
+    let mut start = None;
+
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = index + 1;
+        let record = raw_line.trim();
+
+        if record.is_empty() {
+            continue;
+        }
+
+        let hex = record.strip_prefix(':').ok_or(IhexError::MissingRecordMark { line })?;
+
+        if hex.len() % 2 != 0 {
+            return Err(IhexError::MalformedRecord { line });
+        }
+
+        let bytes = decode_hex(hex).ok_or(IhexError::InvalidHex { line })?;
+
+        // The count byte covers only the data, between the four bytes of count, offset and type and the checksum
+        if bytes.len() < 5 || bytes.len() != usize::from(bytes[0]) + 5 {
+            return Err(IhexError::MalformedRecord { line });
+        }
+
+        let (body, checksum) = bytes.split_at(bytes.len() - 1);
+        let expected = body.iter().fold(0u8, |acc, byte| acc.wrapping_add(*byte)).wrapping_neg();
+
+        if checksum[0] != expected {
+            return Err(IhexError::BadChecksum { line, expected, actual: checksum[0] });
+        }
+
+        let offset = u16::from_be_bytes([body[1], body[2]]);
+        let record_type = body[3];
+        let data = &body[4..];
+
+        match (record_type, data.len()) {
+            (0x00, _) => {
+                // Data running past the end of the segment wraps to its start
+                let (before, after) = data.split_at(data.len().min(0x1_0000 - usize::from(offset)));
+
+                device.write_region(segmented_linear(segment, offset), before)?;
+                device.write_region(segmented_linear(segment, 0), after)?;
+            }
+            (0x01, 0) => return Ok(start),
+            (0x02, 2) => segment = u16::from_be_bytes([data[0], data[1]]),
+            (0x03, 4) => start = Some((u16::from_be_bytes([data[0], data[1]]), u16::from_be_bytes([data[2], data[3]]))),
+            (0x01..=0x03, _) => return Err(IhexError::MalformedRecord { line }),
+            _ => return Err(IhexError::UnknownRecordType { line, record_type })
+        }
+    }
+
+    Ok(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap};
+
+    use super::*;
+
+    #[test]
+    fn test_ihex_data_records() {
+        let mut mem = Memory::<16>::empty();
+
+        let text = ":03000000010203F7\n\
+                    \n\
+                    :02000800AABB91\n\
+                    :00000001FF\n";
+
+        assert_eq!(load_ihex(&mut mem, text), Ok(None));
+        assert_eq!(mem.read_region(0), Ok([1, 2, 3, 0, 0, 0, 0, 0, 0xAA, 0xBB, 0]));
+    }
+
+    #[test]
+    fn test_ihex_segments_in_memory_map() {
+        let mut map = MemoryMap::new()
+            .with_range(0x10000..=0x1000F, Box::new(Memory::<16>::empty()))
+            .with_range(0xFFFF0..=0xFFFFF, Box::new(Memory::<16>::empty()));
+
+        let text = ":020000021000EC\n\
+                    :020002001122C9\n\
+                    :02000002F000 0C\n";
+
+        // Digits must be contiguous
+        assert_eq!(load_ihex(&mut map, text), Err(IhexError::MalformedRecord { line: 3 }));
+        assert_eq!(map.read_region(0x10002), Ok([0x11, 0x22]));
+
+        // The reset vector at F000:FFF0, with the start of the ROM at F000:0000
+        let text = ":02000002F0000C\n\
+                    :05FFF000EA00E000F052\n\
+                    :04000003F000FFF01A\n\
+                    :00000001FF\n";
+
+        assert_eq!(load_ihex(&mut map, text), Ok(Some((0xF000, 0xFFF0))));
+        assert_eq!(map.read_region(0xFFFF0), Ok([0xEA, 0x00, 0xE0, 0x00, 0xF0]));
+    }
+
+    #[test]
+    fn test_ihex_wraps_within_segment() {
+        let mut mem = Memory::<0x20000>::empty();
+
+        assert_eq!(load_ihex(&mut mem, ":020000021000EC\n:03FFFF00AABBCCCE\n"), Ok(None));
+        assert_eq!(mem.read_region(0x1FFFF), Ok([0xAA]));
+        assert_eq!(mem.read_region(0x10000), Ok([0xBB, 0xCC]));
+    }
+
+    #[test]
+    fn test_ihex_stops_at_end_of_file() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_ihex(&mut mem, ":00000001FF\n:02000000FFFF00\n"), Ok(None));
+        assert_eq!(mem.read_region(0), Ok([0, 0]));
+    }
+
+    #[test]
+    fn test_ihex_errors() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_ihex(&mut mem, ":03000000010203F7\n:03000000010203F8\n"), Err(IhexError::BadChecksum { line: 2, expected: 0xF7, actual: 0xF8 }));
+        assert_eq!(load_ihex(&mut mem, "03000000010203F7"), Err(IhexError::MissingRecordMark { line: 1 }));
+        assert_eq!(load_ihex(&mut mem, ":0300000001020G3F7"), Err(IhexError::MalformedRecord { line: 1 }));
+        assert_eq!(load_ihex(&mut mem, ":030000000102G3F7"), Err(IhexError::InvalidHex { line: 1 }));
+        assert_eq!(load_ihex(&mut mem, ":04000000010203F6"), Err(IhexError::MalformedRecord { line: 1 }));
+        assert_eq!(load_ihex(&mut mem, ":000000"), Err(IhexError::MalformedRecord { line: 1 }));
+        assert_eq!(load_ihex(&mut mem, ":0100000200FD"), Err(IhexError::MalformedRecord { line: 1 }));
+        assert_eq!(load_ihex(&mut mem, ":00000004FC"), Err(IhexError::UnknownRecordType { line: 1, record_type: 4 }));
+        assert_eq!(load_ihex(&mut mem, ":02000F00AABB8A"), Err(IhexError::Bus(BusDeviceError::AddressOutOfBounds { address: 16, size: 16 })));
+    }
+}
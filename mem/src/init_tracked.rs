@@ -0,0 +1,213 @@
+use std::{cell::RefCell, fmt::Debug};
+
+use crate::{BusDevice, BusDeviceError};
+
+/// Callback invoked by an `InitTracked` device with the address of each read of a byte which was never written.
+pub type UninitFn = Box<dyn FnMut(usize)>;
+
+/// What an `InitTracked` device does when a byte is read before ever being written.
+pub enum UninitPolicy {
+    /// Read the stored byte as plain memory would, zero unless the device was constructed with data.
+    Ignore,
+    /// Read the given poison value in place of the stored byte, making the bad read easy to spot in the results.
+    Poison(u8),
+    /// Fail the read with `UninitializedRead`.
+    Error,
+    /// Invoke the callback with the address, then read the stored byte.
+    Callback(RefCell<UninitFn>)
+}
+
+impl UninitPolicy {
+    #[must_use]
+    /// Constructs a `Callback` policy invoking `callback` for each uninitialized read.
+    pub fn callback(callback: impl FnMut(usize) + 'static) -> Self {
+        Self::Callback(RefCell::new(Box::new(callback)))
+    }
+}
+
+impl Debug for UninitPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ignore => write!(f, "Ignore"),
+            Self::Poison(value) => f.debug_tuple("Poison").field(value).finish(),
+            Self::Error => write!(f, "Error"),
+            Self::Callback(_) => f.debug_tuple("Callback").finish_non_exhaustive()
+        }
+    }
+}
+
+/// A memory region which tracks which bytes have been written, to catch programs reading memory they never
+/// initialized.
+///
+/// Every successful write, including those made by `write_region` and pokes, marks the byte as initialized. Reads of
+/// bytes which were never written are handled according to the `UninitPolicy` given at construction. Peeks always
+/// return the stored byte without applying the policy.
+#[derive(Debug)]
+pub struct InitTracked<const SIZE: usize> {
+    data: [u8; SIZE],
+    written: [bool; SIZE],
+    policy: UninitPolicy
+}
+
+impl<const SIZE: usize> InitTracked<SIZE> {
+    #[must_use]
+    /// Constructs a new, zeroed and entirely uninitialized memory region.
+    pub const fn new(policy: UninitPolicy) -> Self {
+        Self::filled([0; SIZE], policy)
+    }
+
+    #[must_use]
+    /// Constructs a new, entirely uninitialized memory region holding the given data, such as the random contents of
+    /// RAM at power on, which is read under the `Ignore` and `Callback` policies.
+    pub const fn filled(data: [u8; SIZE], policy: UninitPolicy) -> Self {
+        Self { data, written: [false; SIZE], policy }
+    }
+
+    #[must_use]
+    /// Returns the policy applied to uninitialized reads.
+    pub const fn policy(&self) -> &UninitPolicy {
+        &self.policy
+    }
+
+    /// Replaces the policy applied to uninitialized reads.
+    pub fn set_policy(&mut self, policy: UninitPolicy) {
+        self.policy = policy;
+    }
+
+    #[must_use]
+    /// Returns `true` if the byte at `address` has been written. Addresses outside of the region are never
+    /// initialized.
+    pub fn is_initialized(&self, address: usize) -> bool {
+        self.written.get(address).copied().unwrap_or(false)
+    }
+
+    #[must_use]
+    /// Returns a mask with one entry per byte, `true` for the bytes which have been written.
+    pub const fn initialized_mask(&self) -> &[bool; SIZE] {
+        &self.written
+    }
+
+    /// Marks every byte as uninitialized again, leaving the stored data unchanged.
+    pub const fn clear_initialized(&mut self) {
+        self.written = [false; SIZE];
+    }
+}
+
+impl<const SIZE: usize> BusDevice for InitTracked<SIZE> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        let value = self.peek(address)?;
+
+        if self.written[address] {
+            return Ok(value);
+        }
+
+        match &self.policy {
+            UninitPolicy::Ignore => Ok(value),
+            UninitPolicy::Poison(poison) => Ok(*poison),
+            UninitPolicy::Error => Err(BusDeviceError::UninitializedRead { address }),
+            UninitPolicy::Callback(callback) => {
+                (callback.borrow_mut())(address);
+                Ok(value)
+            }
+        }
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        let byte = self.data.get_mut(address).ok_or(BusDeviceError::AddressOutOfBounds { address, size: SIZE })?;
+
+        *byte = data;
+        self.written[address] = true;
+
+        Ok(())
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.data.get(address).copied().ok_or(BusDeviceError::AddressOutOfBounds { address, size: SIZE })
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::{Memory, MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    /// Runs a short program of writes followed by reads against `device`, as a simple checksum routine would,
+    /// returning the bytes read.
+    fn run_program(device: &mut dyn BusDevice) -> Result<Vec<u8>, BusDeviceError> {
+        for (i, address) in (0x10..0x18).enumerate() {
+            device.write(address, u8::try_from(i * 3).unwrap())?;
+        }
+
+        (0x10..0x18).map(|address| device.read(address)).collect()
+    }
+
+    #[test]
+    fn test_init_tracked_ignore_matches_memory() {
+        let mut plain = Memory::<0x20>::empty();
+        let mut tracked = InitTracked::<0x20>::new(UninitPolicy::Ignore);
+
+        assert_eq!(run_program(&mut tracked), run_program(&mut plain));
+
+        // Uninitialized reads behave as plain memory too
+        assert_eq!(tracked.read(0x1F), plain.read(0x1F));
+        assert_eq!(tracked.read(0x20), plain.read(0x20));
+        assert!(tracked.is_initialized(0x17));
+        assert!(!tracked.is_initialized(0x18));
+    }
+
+    #[test]
+    fn test_init_tracked_error() {
+        let mut tracked = InitTracked::<0x20>::new(UninitPolicy::Error);
+
+        assert_eq!(run_program(&mut tracked), Ok(vec![0, 3, 6, 9, 12, 15, 18, 21]));
+
+        // A region read stops at the first byte which was never written
+        assert_eq!(tracked.write_region(0x00, &[1, 2, 3]), Ok(()));
+        assert_eq!(tracked.write(0x04, 5), Ok(()));
+        assert_eq!(tracked.read_region::<5>(0x00), Err(BusDeviceError::UninitializedRead { address: 0x03 }));
+
+        assert_eq!(tracked.peek(0x03), Ok(0));
+        assert_eq!(tracked.poke(0x03, 4), Ok(()));
+        assert_eq!(tracked.read_region::<5>(0x00), Ok([1, 2, 3, 4, 5]));
+
+        tracked.clear_initialized();
+        assert_eq!(tracked.read(0x00), Err(BusDeviceError::UninitializedRead { address: 0x00 }));
+    }
+
+    #[test]
+    fn test_init_tracked_poison() {
+        let mut tracked = InitTracked::<8>::filled([0x11; 8], UninitPolicy::Poison(0xCC));
+
+        assert_eq!(tracked.write_region(2, &[0xAA, 0xBB]), Ok(()));
+        assert_eq!(tracked.read_region::<5>(0), Ok([0xCC, 0xCC, 0xAA, 0xBB, 0xCC]));
+        assert_eq!(tracked.initialized_mask(), &[false, false, true, true, false, false, false, false]);
+
+        tracked.set_policy(UninitPolicy::Ignore);
+        assert_eq!(tracked.read(0), Ok(0x11));
+    }
+
+    #[test]
+    fn test_init_tracked_callback() {
+        let reads = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&reads);
+
+        let policy = UninitPolicy::callback(move |address| sink.borrow_mut().push(address));
+        let mut map = MemoryMap::new()
+            .with_range(0x400..=0x4FF, Box::new(InitTracked::<0x100>::new(policy)));
+
+        assert_eq!(map.write(0x410, 7), Ok(()));
+        assert_eq!(map.read(0x410), Ok(7));
+        assert_eq!(map.read(0x411), Ok(0));
+        assert_eq!(map.read_region::<2>(0x4FE), Ok([0, 0]));
+
+        // Addresses are relative to the start of the device
+        assert_eq!(*reads.borrow(), [0x11, 0xFE, 0xFF]);
+    }
+}
@@ -1,8 +1,64 @@
+use std::ops::{Index, IndexMut, Range};
+
+use crate::{hexdump, AccessKind, DEBUG_HEXDUMP_LINES};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BusDeviceError {
     AddressOutOfBounds{address: usize, size: usize},
     AddressNotWritable{address: usize},
-    AddressNotMapped{address: usize}
+    /// The address can only be written, as with a write only hardware register.
+    AddressNotReadable{address: usize},
+    AddressNotMapped{address: usize},
+    /// The device is behind a lock which was poisoned by a panic while it was held.
+    LockPoisoned{address: usize},
+    /// The byte was read before ever being written.
+    UninitializedRead{address: usize},
+    /// The address lies within a guard region, which should never be accessed.
+    GuardViolation{address: usize, kind: AccessKind},
+    /// The data register of a FIFO was read while the FIFO was empty.
+    FifoEmpty{address: usize},
+    /// An access of more than one byte started at an address which is not a multiple of `alignment`.
+    AddressMisaligned{address: usize, alignment: usize},
+    /// The address lies within a mapping which has been removed, labelled with a description of what was mapped there.
+    Poisoned{address: usize, label: &'static str}
+}
+
+impl BusDeviceError {
+    #[must_use]
+    /// Returns the address of the access which failed.
+    pub const fn address(self) -> usize {
+        match self {
+            Self::AddressOutOfBounds { address, .. } | Self::AddressNotWritable { address } |
+            Self::AddressNotReadable { address } | Self::AddressNotMapped { address } | Self::LockPoisoned { address } |
+            Self::UninitializedRead { address } | Self::GuardViolation { address, .. } | Self::FifoEmpty { address } |
+            Self::AddressMisaligned { address, .. } | Self::Poisoned { address, .. } => address
+        }
+    }
+
+    #[must_use]
+    /// Returns the same error reported at `address` instead, for devices which pass accesses on to another device at
+    /// a different address and must report errors in their own address space.
+    pub const fn with_address(self, address: usize) -> Self {
+        match self {
+            Self::AddressOutOfBounds { size, .. } => Self::AddressOutOfBounds { address, size },
+            Self::AddressNotWritable { .. } => Self::AddressNotWritable { address },
+            Self::AddressNotReadable { .. } => Self::AddressNotReadable { address },
+            Self::AddressNotMapped { .. } => Self::AddressNotMapped { address },
+            Self::LockPoisoned { .. } => Self::LockPoisoned { address },
+            Self::UninitializedRead { .. } => Self::UninitializedRead { address },
+            Self::GuardViolation { kind, .. } => Self::GuardViolation { address, kind },
+            Self::FifoEmpty { .. } => Self::FifoEmpty { address },
+            Self::AddressMisaligned { alignment, .. } => Self::AddressMisaligned { address, alignment },
+            Self::Poisoned { label, .. } => Self::Poisoned { address, label }
+        }
+    }
+}
+
+/// An error raised while building a device from an image of its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImageError {
+    /// The image is `length` bytes long, more than the `size` bytes the device holds.
+    LengthMismatch{length: usize, size: usize}
 }
 
 pub trait BusDevice {
@@ -20,41 +76,374 @@ pub trait BusDevice {
     ///
     /// This function will return an error if the byte cannot be written.
     fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError>;
+
+    /// Reads the byte at the given `address` without triggering any side effects, for use by debuggers and other
+    /// host tools. Devices whose reads change their state, such as a FIFO data register, should override this to
+    /// return the value which would be read without consuming it. Defaults to `read`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte cannot be read.
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.read(address)
+    }
+
+    /// Writes `data` to the byte at the given `address` without triggering any side effects, for use by debuggers
+    /// and other host tools. Defaults to `write`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte cannot be written.
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.write(address, data)
+    }
+
+    /// Reads `buffer.len()` consecutive bytes starting at `address` as a single access, such as a word. Devices which
+    /// care about the width of an access, such as an alignment check, should override this. Defaults to reading each
+    /// byte in turn.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the bytes cannot be read.
+    fn read_bytes(&self, address: usize, buffer: &mut [u8]) -> Result<(), BusDeviceError> {
+        for (i, slot) in buffer.iter_mut().enumerate() {
+            *slot = self.read(address + i)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `data` to consecutive bytes starting at `address` as a single access, such as a word. Devices which care
+    /// about the width of an access, such as an alignment check, should override this. Defaults to writing each byte
+    /// in turn.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the bytes cannot be written.
+    fn write_bytes(&mut self, address: usize, data: &[u8]) -> Result<(), BusDeviceError> {
+        for (i, byte) in data.iter().enumerate() {
+            self.write(address + i, *byte)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of addresses the device responds to, starting from zero, or `None` if the device is
+    /// unbounded, such as a procedural device or one which aliases every address. Defaults to `None`.
+    fn size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the device to its power-on state, as on a machine reset, leaving it mapped where it is. Devices holding
+    /// registers, such as latches and FIFOs, should override this, while memory keeps its contents as RAM does across
+    /// a reset of the original machine. Wrappers forward the reset to the devices they wrap. Defaults to doing nothing.
+    fn reset(&mut self) {}
+}
+
+/// Forwards every access to the boxed device, so that a `Box<dyn BusDevice>` can be passed to code generic over
+/// `T: BusDevice`.
+impl<T: ?Sized + BusDevice> BusDevice for Box<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        (**self).read(address)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        (**self).write(address, data)
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        (**self).peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        (**self).poke(address, data)
+    }
+
+    fn read_bytes(&self, address: usize, buffer: &mut [u8]) -> Result<(), BusDeviceError> {
+        (**self).read_bytes(address, buffer)
+    }
+
+    fn write_bytes(&mut self, address: usize, data: &[u8]) -> Result<(), BusDeviceError> {
+        (**self).write_bytes(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        (**self).size()
+    }
+
+    fn reset(&mut self) {
+        (**self).reset();
+    }
+}
+
+/// Forwards every access to the borrowed device, so that a device can be lent to code generic over `T: BusDevice`
+/// which takes it by value. Devices shared through `Rc<RefCell<T>>` are covered by `Shared` instead.
+impl<T: ?Sized + BusDevice> BusDevice for &mut T {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        (**self).read(address)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        (**self).write(address, data)
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        (**self).peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        (**self).poke(address, data)
+    }
+
+    fn read_bytes(&self, address: usize, buffer: &mut [u8]) -> Result<(), BusDeviceError> {
+        (**self).read_bytes(address, buffer)
+    }
+
+    fn write_bytes(&mut self, address: usize, data: &[u8]) -> Result<(), BusDeviceError> {
+        (**self).write_bytes(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        (**self).size()
+    }
+
+    fn reset(&mut self) {
+        (**self).reset();
+    }
 }
 
 pub trait RegionBusDevice : BusDevice {
-    /// Reads a region of memory with the given starting `address`.
+    /// Reads a region of memory with the given starting `address`, as a single access.
     ///
     /// # Errors
     ///
     /// This function will return an error if any of the required bytes cannot be read.
     fn read_region<const SIZE: usize>(&self, address: usize) -> Result<[u8; SIZE], BusDeviceError> {
         let mut result = [0; SIZE];
-
-        for (i, slot) in result.iter_mut().enumerate() {
-            *slot = self.read(address + i)?;
-        }
+        self.read_bytes(address, &mut result)?;
 
         Ok(result)
     }
 
-    /// Writes to a region of memory with the given starting `address`.
+    /// Writes to a region of memory with the given starting `address`, as a single access.
     ///
     /// # Errors
     ///
     /// This function will return an error if any of the required bytes cannot be written to.
     fn write_region(&mut self, address: usize, data: &[u8]) -> Result<(), BusDeviceError> {
-        for (i, byte) in data.iter().enumerate() {
-            self.write(address + i, *byte)?;
+        self.write_bytes(address, data)
+    }
+
+    /// Reads a little endian word starting at `address`, as the 8086 stores them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if either byte cannot be read.
+    fn read_u16_le(&self, address: usize) -> Result<u16, BusDeviceError> {
+        self.read_region(address).map(u16::from_le_bytes)
+    }
+
+    /// Reads a big endian word starting at `address`, as many peripheral datasheets describe their registers.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if either byte cannot be read.
+    fn read_u16_be(&self, address: usize) -> Result<u16, BusDeviceError> {
+        self.read_region(address).map(u16::from_be_bytes)
+    }
+
+    /// Reads a little endian double word starting at `address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the bytes cannot be read.
+    fn read_u32_le(&self, address: usize) -> Result<u32, BusDeviceError> {
+        self.read_region(address).map(u32::from_le_bytes)
+    }
+
+    /// Reads a big endian double word starting at `address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the bytes cannot be read.
+    fn read_u32_be(&self, address: usize) -> Result<u32, BusDeviceError> {
+        self.read_region(address).map(u32::from_be_bytes)
+    }
+
+    /// Writes `value` as a little endian word starting at `address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if either byte cannot be written to.
+    fn write_u16_le(&mut self, address: usize, value: u16) -> Result<(), BusDeviceError> {
+        self.write_region(address, &value.to_le_bytes())
+    }
+
+    /// Writes `value` as a big endian word starting at `address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if either byte cannot be written to.
+    fn write_u16_be(&mut self, address: usize, value: u16) -> Result<(), BusDeviceError> {
+        self.write_region(address, &value.to_be_bytes())
+    }
+
+    /// Writes `value` as a little endian double word starting at `address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the bytes cannot be written to.
+    fn write_u32_le(&mut self, address: usize, value: u32) -> Result<(), BusDeviceError> {
+        self.write_region(address, &value.to_le_bytes())
+    }
+
+    /// Writes `value` as a big endian double word starting at `address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the bytes cannot be written to.
+    fn write_u32_be(&mut self, address: usize, value: u32) -> Result<(), BusDeviceError> {
+        self.write_region(address, &value.to_be_bytes())
+    }
+
+    /// Reads a null terminated (ASCIIZ) string starting at `address`, reading at most `max_len` bytes including the
+    /// terminator. The terminator is not included in the result, which is paired with `true` if the terminator was
+    /// found and `false` if `max_len` bytes were read without finding it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any byte before the terminator cannot be read, or if the string would
+    /// run past the top of the address space.
+    fn read_cstr(&self, address: usize, max_len: usize) -> Result<(Vec<u8>, bool), BusDeviceError> {
+        self.read_terminated(address, 0, max_len)
+    }
+
+    /// Reads a string ending in `terminator` starting at `address`, such as the `'$'` terminated strings used by DOS,
+    /// reading at most `max_len` bytes including the terminator. The terminator is not included in the result, which
+    /// is paired with `true` if the terminator was found and `false` if `max_len` bytes were read without finding it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any byte before the terminator cannot be read, or if the string would
+    /// run past the top of the address space.
+    fn read_terminated(&self, address: usize, terminator: u8, max_len: usize) -> Result<(Vec<u8>, bool), BusDeviceError> {
+        let mut result = Vec::new();
+
+        for i in 0..max_len {
+            match self.read(offset_address(address, i)?)? {
+                byte if byte == terminator => return Ok((result, true)),
+                byte => result.push(byte)
+            }
         }
 
-        Ok(())
+        Ok((result, false))
+    }
+
+    /// Writes `data` followed by a null terminator starting at `address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the required bytes cannot be written to.
+    fn write_cstr(&mut self, address: usize, data: &[u8]) -> Result<(), BusDeviceError> {
+        self.write_terminated(address, data, 0)
+    }
+
+    /// Writes `data` followed by `terminator` starting at `address`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the required bytes cannot be written to, or if the terminator
+    /// would fall past the top of the address space.
+    fn write_terminated(&mut self, address: usize, data: &[u8], terminator: u8) -> Result<(), BusDeviceError> {
+        let end = offset_address(address, data.len())?;
+
+        self.write_region(address, data)?;
+        self.write(end, terminator)
+    }
+}
+
+/// Returns the address `offset` bytes past `address`, or `AddressOutOfBounds` at the top of the address space if it
+/// cannot be represented.
+const fn offset_address(address: usize, offset: usize) -> Result<usize, BusDeviceError> {
+    match address.checked_add(offset) {
+        Some(address) => Ok(address),
+        None => Err(BusDeviceError::AddressOutOfBounds { address: usize::MAX, size: usize::MAX })
     }
 }
 
 impl<T: BusDevice> RegionBusDevice for T {}
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// Number of wait states reported by a `TimedBusDevice` which does not override its timing, as for RAM which keeps up
+/// with the processor.
+pub const DEFAULT_WAIT_STATES: u32 = 0;
+
+/// A `BusDevice` which reports the number of wait states each access took.
+///
+/// Wait states are inserted on top of the processor's own bus cycle, so that slow devices such as ROM and memory
+/// mapped I/O can be timed. Devices are mapped into a `MemoryMap<dyn TimedBusDevice>` to be timed through it. Devices
+/// without wait states only need an empty implementation, taking `DEFAULT_WAIT_STATES` for every access.
+pub trait TimedBusDevice : BusDevice {
+    /// Reads the byte at the given `address`, returning it along with the number of wait states taken.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte cannot be read.
+    fn read_timed(&self, address: usize) -> Result<(u8, u32), BusDeviceError> {
+        Ok((self.read(address)?, DEFAULT_WAIT_STATES))
+    }
+
+    /// Writes `data` to the byte at the given `address`, returning the number of wait states taken.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte cannot be written.
+    fn write_timed(&mut self, address: usize, data: u8) -> Result<u32, BusDeviceError> {
+        self.write(address, data)?;
+        Ok(DEFAULT_WAIT_STATES)
+    }
+}
+
+impl<T: ?Sized + TimedBusDevice> TimedBusDevice for Box<T> {
+    fn read_timed(&self, address: usize) -> Result<(u8, u32), BusDeviceError> {
+        (**self).read_timed(address)
+    }
+
+    fn write_timed(&mut self, address: usize, data: u8) -> Result<u32, BusDeviceError> {
+        (**self).write_timed(address, data)
+    }
+}
+
+impl<T: ?Sized + TimedBusDevice> TimedBusDevice for &mut T {
+    fn read_timed(&self, address: usize) -> Result<(u8, u32), BusDeviceError> {
+        (**self).read_timed(address)
+    }
+
+    fn write_timed(&mut self, address: usize, data: u8) -> Result<u32, BusDeviceError> {
+        (**self).write_timed(address, data)
+    }
+}
+
+/// A `BusDevice` which can be cloned behind a `Box`, allowing a `MemoryMap<dyn CloneBusDevice>` to be cloned.
+pub trait CloneBusDevice : BusDevice {
+    /// Clones the device into a new box.
+    fn clone_boxed(&self) -> Box<dyn CloneBusDevice>;
+}
+
+impl<T: BusDevice + Clone + 'static> CloneBusDevice for T {
+    fn clone_boxed(&self) -> Box<dyn CloneBusDevice> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn CloneBusDevice> {
+    fn clone(&self) -> Self {
+        (**self).clone_boxed()
+    }
+}
+
+// `Copy` is deliberately not derived: a `Memory<0x100000>` is a megabyte, and an implicit copy on every move or
+// by-value use would be both easy to miss and expensive. Copies must go through an explicit `clone`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Memory<const SIZE: usize> ([u8; SIZE]);
 
 impl<const SIZE: usize> Memory<SIZE> {
@@ -70,6 +459,34 @@ impl<const SIZE: usize> Memory<SIZE> {
         Self (data)
     }
 
+    #[must_use]
+    /// Constructs a new memory region with every byte set to `byte`.
+    pub const fn filled_with(byte: u8) -> Self {
+        Self ([byte; SIZE])
+    }
+
+    #[must_use]
+    /// Constructs a new memory region where each byte holds the low byte of its own offset, a pattern which makes the
+    /// source of any byte read back easy to identify.
+    pub const fn incrementing() -> Self {
+        let mut data = [0; SIZE];
+        let mut offset = 0;
+
+        while offset < SIZE {
+            data[offset] = offset.to_le_bytes()[0];
+            offset += 1;
+        }
+
+        Self (data)
+    }
+
+    #[must_use]
+    /// Constructs a new memory region with each byte generated by calling `f` with its offset. `f` is called exactly once
+    /// for each offset, in increasing order.
+    pub fn from_fn(f: impl FnMut(usize) -> u8) -> Self {
+        Self (core::array::from_fn(f))
+    }
+
     #[must_use]
     /// Constructs a new memory region populated with the given data, padded with zeros.
     ///
@@ -82,6 +499,160 @@ impl<const SIZE: usize> Memory<SIZE> {
 
         Self(inner)
     }
+
+    #[must_use]
+    /// Constructs a new memory region populated with `data` starting at `offset`, padded with zeros, such as a BIOS image
+    /// placed so that it ends at the reset vector.
+    ///
+    /// # Panics
+    /// This function will panic if `data` extends past the end of the memory region, including if `offset` is so large
+    /// that the end overflows.
+    pub fn populated_at(offset: usize, data: &[u8]) -> Self {
+        Self::empty().with_data_at(offset, data)
+    }
+
+    #[must_use]
+    /// Builder pattern for copying `data` into the memory region starting at `offset`, replacing whatever was there, so that
+    /// several blobs can be layered into one region.
+    ///
+    /// # Panics
+    /// This function will panic if `data` extends past the end of the memory region, including if `offset` is so large
+    /// that the end overflows.
+    pub fn with_data_at(mut self, offset: usize, data: &[u8]) -> Self {
+        let end = offset.checked_add(data.len()).filter(|end| *end <= SIZE);
+        let end = end.unwrap_or_else(|| panic!("{} bytes at offset {offset} do not fit in {SIZE} bytes", data.len()));
+
+        self.0[offset..end].copy_from_slice(data);
+        self
+    }
+
+    #[must_use]
+    /// Returns the contents of the memory region as a slice.
+    pub const fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    #[must_use]
+    /// Returns the contents of the memory region as a mutable slice.
+    pub const fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+
+    #[must_use]
+    /// Freezes the memory region into a read only memory region with the same contents.
+    pub const fn into_read_only(self) -> ReadOnlyMemory<SIZE> {
+        ReadOnlyMemory(self.0)
+    }
+
+    /// Returns an iterator over the bytes of the memory region.
+    pub fn iter(&self) -> std::iter::Copied<std::slice::Iter<'_, u8>> {
+        self.0.iter().copied()
+    }
+
+    /// Returns an iterator over the bytes of the memory region starting at `address`, which is empty if `address` is past
+    /// the end of the region.
+    pub fn iter_from(&self, address: usize) -> std::iter::Copied<std::slice::Iter<'_, u8>> {
+        self.0.get(address..).unwrap_or_default().iter().copied()
+    }
+
+    /// Returns an iterator over the addresses and values of every non-zero byte in the memory region.
+    pub fn enumerate_nonzero(&self) -> impl Iterator<Item = (usize, u8)> + '_ {
+        self.iter().enumerate().filter(|(_, byte)| *byte != 0)
+    }
+}
+
+impl<'a, const SIZE: usize> IntoIterator for &'a Memory<SIZE> {
+    type Item = u8;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const SIZE: usize> From<[u8; SIZE]> for Memory<SIZE> {
+    fn from(value: [u8; SIZE]) -> Self {
+        Self::filled(value)
+    }
+}
+
+/// Builds a memory region from a byte sequence, padded with zeros, as when restoring saved contents. Unlike `populated`
+/// this fails rather than panicking if there are more than `SIZE` bytes.
+impl<const SIZE: usize> TryFrom<&[u8]> for Memory<SIZE> {
+    type Error = ImageError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() > SIZE {
+            return Err(ImageError::LengthMismatch { length: value.len(), size: SIZE });
+        }
+
+        Ok(Self::populated(value))
+    }
+}
+
+impl<const SIZE: usize> From<ReadOnlyMemory<SIZE>> for Memory<SIZE> {
+    fn from(value: ReadOnlyMemory<SIZE>) -> Self {
+        Self(value.0)
+    }
+}
+
+impl<const SIZE: usize> AsRef<[u8]> for Memory<SIZE> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// Host-side convenience indexing, panicking on out of bounds accesses like a slice would. Accesses made on behalf of
+/// the emulated machine should use the fallible `BusDevice` methods instead.
+impl<const SIZE: usize> Index<usize> for Memory<SIZE> {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+/// Host-side convenience indexing of a range of bytes, panicking on out of bounds accesses like a slice would.
+impl<const SIZE: usize> Index<Range<usize>> for Memory<SIZE> {
+    type Output = [u8];
+
+    fn index(&self, index: Range<usize>) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+/// Host-side convenience patching, panicking on out of bounds accesses like a slice would. Accesses made on behalf of
+/// the emulated machine should use the fallible `BusDevice` methods instead.
+impl<const SIZE: usize> IndexMut<usize> for Memory<SIZE> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+/// Host-side convenience patching of a range of bytes, panicking on out of bounds accesses like a slice would.
+impl<const SIZE: usize> IndexMut<Range<usize>> for Memory<SIZE> {
+    fn index_mut(&mut self, index: Range<usize>) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl<const SIZE: usize> std::fmt::Debug for Memory<SIZE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Memory<{SIZE}>")?;
+
+        if SIZE > 0 {
+            let lines = f.precision().unwrap_or(DEBUG_HEXDUMP_LINES);
+            write!(f, "\n{}", hexdump(self, 0..=SIZE - 1).max_lines(lines))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const SIZE: usize> Default for Memory<SIZE> {
+    fn default() -> Self {
+        Self::empty()
+    }
 }
 
 impl<const SIZE: usize> BusDevice for Memory<SIZE> {
@@ -95,9 +666,20 @@ impl<const SIZE: usize> BusDevice for Memory<SIZE> {
         *(self.0.get_mut(address).ok_or(BusDeviceError::AddressOutOfBounds { address, size })?) = data;
         Ok(())
     }
+
+    fn size(&self) -> Option<usize> {
+        Some(SIZE)
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+impl<const SIZE: usize> TimedBusDevice for Memory<SIZE> {}
+
+/// A fixed size memory region which rejects every write with `AddressNotWritable`.
+///
+/// This is equivalent to a `ReadOnly<Memory<SIZE>>`, but additionally offers the same host-side construction, indexing
+/// and iteration as `Memory`. Devices other than plain memory can be made read only with `ReadOnly` instead.
+// Not `Copy` for the same reason as `Memory`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ReadOnlyMemory<const SIZE: usize> ([u8; SIZE]);
 
 impl<const SIZE: usize> ReadOnlyMemory<SIZE> {
@@ -113,6 +695,34 @@ impl<const SIZE: usize> ReadOnlyMemory<SIZE> {
         Self (data)
     }
 
+    #[must_use]
+    /// Constructs a new read only memory region with every byte set to `byte`.
+    pub const fn filled_with(byte: u8) -> Self {
+        Self ([byte; SIZE])
+    }
+
+    #[must_use]
+    /// Constructs a new read only memory region where each byte holds the low byte of its own offset, a pattern which makes the
+    /// source of any byte read back easy to identify.
+    pub const fn incrementing() -> Self {
+        let mut data = [0; SIZE];
+        let mut offset = 0;
+
+        while offset < SIZE {
+            data[offset] = offset.to_le_bytes()[0];
+            offset += 1;
+        }
+
+        Self (data)
+    }
+
+    #[must_use]
+    /// Constructs a new read only memory region with each byte generated by calling `f` with its offset. `f` is called exactly once
+    /// for each offset, in increasing order.
+    pub fn from_fn(f: impl FnMut(usize) -> u8) -> Self {
+        Self (core::array::from_fn(f))
+    }
+
     #[must_use]
     /// Constructs a new read only memory region populated with the given data, padded with zeros.
     ///
@@ -125,6 +735,140 @@ impl<const SIZE: usize> ReadOnlyMemory<SIZE> {
 
         Self(inner)
     }
+
+    #[must_use]
+    /// Constructs a new read only memory region populated with `data` starting at `offset`, padded with zeros, such as a BIOS image
+    /// placed so that it ends at the reset vector.
+    ///
+    /// # Panics
+    /// This function will panic if `data` extends past the end of the memory region, including if `offset` is so large
+    /// that the end overflows.
+    pub fn populated_at(offset: usize, data: &[u8]) -> Self {
+        Self::empty().with_data_at(offset, data)
+    }
+
+    #[must_use]
+    /// Builder pattern for copying `data` into the read only memory region starting at `offset`, replacing whatever was there, so that
+    /// several blobs can be layered into one region.
+    ///
+    /// # Panics
+    /// This function will panic if `data` extends past the end of the memory region, including if `offset` is so large
+    /// that the end overflows.
+    pub fn with_data_at(mut self, offset: usize, data: &[u8]) -> Self {
+        let end = offset.checked_add(data.len()).filter(|end| *end <= SIZE);
+        let end = end.unwrap_or_else(|| panic!("{} bytes at offset {offset} do not fit in {SIZE} bytes", data.len()));
+
+        self.0[offset..end].copy_from_slice(data);
+        self
+    }
+
+    #[must_use]
+    /// Returns the contents of the read only memory region as a slice.
+    ///
+    /// There is deliberately no mutable counterpart, the contents of a `ReadOnlyMemory` cannot be changed once constructed.
+    ///
+    /// ```compile_fail
+    /// let mut rom = mem::ReadOnlyMemory::<4>::empty();
+    /// rom.as_mut_slice()[0] = 1;
+    /// ```
+    pub const fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns an iterator over the bytes of the read only memory region.
+    pub fn iter(&self) -> std::iter::Copied<std::slice::Iter<'_, u8>> {
+        self.0.iter().copied()
+    }
+
+    /// Returns an iterator over the bytes of the read only memory region starting at `address`, which is empty if `address` is past
+    /// the end of the region.
+    pub fn iter_from(&self, address: usize) -> std::iter::Copied<std::slice::Iter<'_, u8>> {
+        self.0.get(address..).unwrap_or_default().iter().copied()
+    }
+
+    /// Returns an iterator over the addresses and values of every non-zero byte in the read only memory region.
+    pub fn enumerate_nonzero(&self) -> impl Iterator<Item = (usize, u8)> + '_ {
+        self.iter().enumerate().filter(|(_, byte)| *byte != 0)
+    }
+}
+
+impl<'a, const SIZE: usize> IntoIterator for &'a ReadOnlyMemory<SIZE> {
+    type Item = u8;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const SIZE: usize> From<[u8; SIZE]> for ReadOnlyMemory<SIZE> {
+    fn from(value: [u8; SIZE]) -> Self {
+        Self::filled(value)
+    }
+}
+
+/// Builds a read only memory region from a byte sequence, padded with zeros, as when restoring saved contents. Unlike `populated`
+/// this fails rather than panicking if there are more than `SIZE` bytes.
+impl<const SIZE: usize> TryFrom<&[u8]> for ReadOnlyMemory<SIZE> {
+    type Error = ImageError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() > SIZE {
+            return Err(ImageError::LengthMismatch { length: value.len(), size: SIZE });
+        }
+
+        Ok(Self::populated(value))
+    }
+}
+
+impl<const SIZE: usize> From<Memory<SIZE>> for ReadOnlyMemory<SIZE> {
+    fn from(value: Memory<SIZE>) -> Self {
+        value.into_read_only()
+    }
+}
+
+impl<const SIZE: usize> AsRef<[u8]> for ReadOnlyMemory<SIZE> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// Host-side convenience indexing, panicking on out of bounds accesses like a slice would. Accesses made on behalf of
+/// the emulated machine should use the fallible `BusDevice` methods instead.
+impl<const SIZE: usize> Index<usize> for ReadOnlyMemory<SIZE> {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+/// Host-side convenience indexing of a range of bytes, panicking on out of bounds accesses like a slice would.
+impl<const SIZE: usize> Index<Range<usize>> for ReadOnlyMemory<SIZE> {
+    type Output = [u8];
+
+    fn index(&self, index: Range<usize>) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl<const SIZE: usize> std::fmt::Debug for ReadOnlyMemory<SIZE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReadOnlyMemory<{SIZE}>")?;
+
+        if SIZE > 0 {
+            let lines = f.precision().unwrap_or(DEBUG_HEXDUMP_LINES);
+            write!(f, "\n{}", hexdump(self, 0..=SIZE - 1).max_lines(lines))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const SIZE: usize> Default for ReadOnlyMemory<SIZE> {
+    fn default() -> Self {
+        Self::empty()
+    }
 }
 
 impl<const SIZE: usize> BusDevice for ReadOnlyMemory<SIZE> {
@@ -136,11 +880,22 @@ impl<const SIZE: usize> BusDevice for ReadOnlyMemory<SIZE> {
     fn write(&mut self, address: usize, _data: u8) -> Result<(), BusDeviceError> {
         Err(BusDeviceError::AddressNotWritable { address })
     }
+
+    fn size(&self) -> Option<usize> {
+        Some(SIZE)
+    }
 }
 
+impl<const SIZE: usize> TimedBusDevice for ReadOnlyMemory<SIZE> {}
+
 
 #[cfg(test)]
+#[allow(clippy::cast_possible_truncation)]
 mod tests {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use crate::{Latch, MemoryMap};
+
     use super::*;
 
     fn test_memory_creation_size<const SIZE: usize>() {
@@ -204,17 +959,136 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "data.len() <= SIZE")]
     fn test_memory_populate_panic() {
         let _mem: Memory<2> = Memory::populated(&[0, 1, 2, 3]);
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "data.len() <= SIZE")]
     fn test_read_only_memory_populate_panic() {
         let _mem: ReadOnlyMemory<2> = ReadOnlyMemory::populated(&[0, 1, 2, 3]);
     }
 
+    #[test]
+    fn test_memory_populate_at() {
+        // A reset vector in the last 16 bytes of a 64 KiB BIOS image
+        let bios = ReadOnlyMemory::<0x10000>::populated_at(0xFFF0, &[0xEA, 0x5B, 0xE0, 0x00, 0xF0]);
+        assert_eq!(bios.read_region(0xFFEF), Ok([0x00, 0xEA, 0x5B, 0xE0, 0x00, 0xF0]));
+
+        let mem = Memory::<8>::populated_at(4, &[1, 2, 3, 4]);
+        assert_eq!(mem.0, [0, 0, 0, 0, 1, 2, 3, 4]);
+        assert_eq!(Memory::<8>::populated_at(8, &[]), Memory::empty());
+    }
+
+    #[test]
+    fn test_memory_with_data_at_layers() {
+        // Later blobs win where they overlap
+        let mem = Memory::<8>::empty()
+            .with_data_at(0, &[1, 1, 1, 1])
+            .with_data_at(2, &[2, 2, 2])
+            .with_data_at(7, &[3]);
+
+        assert_eq!(mem.0, [1, 1, 2, 2, 2, 0, 0, 3]);
+
+        let rom = ReadOnlyMemory::<4>::populated(&[0xFF; 4]).with_data_at(1, &[0x00, 0x00]);
+        assert_eq!(rom.0, [0xFF, 0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    #[should_panic(expected = "4 bytes at offset 5 do not fit in 8 bytes")]
+    fn test_memory_populate_at_panic() {
+        let _mem = Memory::<8>::populated_at(5, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "do not fit in 8 bytes")]
+    fn test_read_only_memory_populate_at_overflow_panic() {
+        let _rom = ReadOnlyMemory::<8>::empty().with_data_at(usize::MAX, &[1, 2]);
+    }
+
+    #[test]
+    fn test_memory_from_fn_order() {
+        let mut offsets = Vec::new();
+        let mem = Memory::<8>::from_fn(|offset| {
+            offsets.push(offset);
+            u8::try_from(offset).unwrap() * 2
+        });
+
+        assert_eq!(offsets, (0..8).collect::<Vec<_>>());
+        assert_eq!(mem.0, [0, 2, 4, 6, 8, 10, 12, 14]);
+
+        let mut offsets = Vec::new();
+        let rom = ReadOnlyMemory::<4>::from_fn(|offset| {
+            offsets.push(offset);
+            0xFF
+        });
+
+        assert_eq!(offsets, [0, 1, 2, 3]);
+        assert_eq!(rom, ReadOnlyMemory::filled_with(0xFF));
+    }
+
+    #[test]
+    fn test_memory_canned_patterns() {
+        const PATTERN: Memory<4> = Memory::filled_with(0xAA);
+        const INCREMENTING: ReadOnlyMemory<0x102> = ReadOnlyMemory::incrementing();
+
+        assert_eq!(PATTERN.0, [0xAA; 4]);
+        assert_eq!(INCREMENTING.0[0..3], [0, 1, 2]);
+        assert_eq!(INCREMENTING.0[0xFE..], [0xFE, 0xFF, 0x00, 0x01]);
+        assert_eq!(Memory::<300>::incrementing(), Memory::from_fn(|offset| (offset % 256) as u8));
+    }
+
+    /// The word and double word accessors of one endianness, with the conversions they should agree with.
+    struct WordAccess {
+        read_u16: fn(&Memory<16>, usize) -> Result<u16, BusDeviceError>,
+        read_u32: fn(&Memory<16>, usize) -> Result<u32, BusDeviceError>,
+        write_u16: fn(&mut Memory<16>, usize, u16) -> Result<(), BusDeviceError>,
+        write_u32: fn(&mut Memory<16>, usize, u32) -> Result<(), BusDeviceError>,
+        from_u16: fn([u8; 2]) -> u16,
+        from_u32: fn([u8; 4]) -> u32
+    }
+
+    #[test]
+    fn test_memory_word_access_endianness() {
+        let little = WordAccess {
+            read_u16: Memory::read_u16_le, read_u32: Memory::read_u32_le,
+            write_u16: Memory::write_u16_le, write_u32: Memory::write_u32_le,
+            from_u16: u16::from_le_bytes, from_u32: u32::from_le_bytes
+        };
+
+        let big = WordAccess {
+            read_u16: Memory::read_u16_be, read_u32: Memory::read_u32_be,
+            write_u16: Memory::write_u16_be, write_u32: Memory::write_u32_be,
+            from_u16: u16::from_be_bytes, from_u32: u32::from_be_bytes
+        };
+
+        for access in [little, big] {
+            let mut mem = Memory::<16>::incrementing();
+
+            assert_eq!((access.read_u16)(&mem, 3), Ok((access.from_u16)([3, 4])));
+            assert_eq!((access.read_u32)(&mem, 12), Ok((access.from_u32)([12, 13, 14, 15])));
+
+            // Both endiannesses fail at the same byte, without a partial result
+            assert_eq!((access.read_u16)(&mem, 15), Err(BusDeviceError::AddressOutOfBounds { address: 16, size: 16 }));
+            assert_eq!((access.read_u32)(&mem, 13), Err(BusDeviceError::AddressOutOfBounds { address: 16, size: 16 }));
+
+            assert_eq!((access.write_u16)(&mut mem, 0, 0x1234), Ok(()));
+            assert_eq!((access.read_u16)(&mem, 0), Ok(0x1234));
+            assert_eq!((access.write_u32)(&mut mem, 4, 0x89AB_CDEF), Ok(()));
+            assert_eq!((access.from_u32)(mem.read_region(4).unwrap()), 0x89AB_CDEF);
+
+            // A write running off the end stores the bytes before it, as `write_region` does
+            assert_eq!((access.write_u16)(&mut mem, 15, 0xFFFF), Err(BusDeviceError::AddressOutOfBounds { address: 16, size: 16 }));
+            assert_eq!(mem.read(15), Ok(0xFF));
+        }
+
+        let mem = Memory::<16>::incrementing();
+        assert_eq!(mem.read_u16_le(0), Ok(0x0100));
+        assert_eq!(mem.read_u16_be(0), Ok(0x0001));
+        assert_eq!(mem.read_u32_be(0), Ok(0x0001_0203));
+    }
+
     #[test]
     fn test_memory_single_byte_read() {
         let empty = Memory::<0>::empty();
@@ -223,11 +1097,10 @@ mod tests {
             assert_eq!(empty.read(*i), Err(BusDeviceError::AddressOutOfBounds { address: *i, size: 0 }));
         }
 
-        let data: [u8; 512] = core::array::from_fn(|index| (index % 256) as u8);
-        let mem = Memory::filled(data);
+        let mem = Memory::<512>::incrementing();
 
         for i in &[0, 16, 64, 512, 1024, 1, 2, 3, 4, 5, 256, 257, 258] {
-            if *i < data.len() {
+            if *i < 512 {
                 assert_eq!(mem.read(*i), Ok((*i % 256) as u8));    
             }
             else {
@@ -244,11 +1117,10 @@ mod tests {
             assert_eq!(empty.read(*i), Err(BusDeviceError::AddressOutOfBounds { address: *i, size: 0 }));
         }
 
-        let data: [u8; 512] = core::array::from_fn(|index| (index % 256) as u8);
-        let mem = ReadOnlyMemory::filled(data);
+        let mem = ReadOnlyMemory::<512>::incrementing();
 
         for i in &[0, 16, 64, 512, 1024, 1, 2, 3, 4, 5, 256, 257, 258] {
-            if *i < data.len() {
+            if *i < 512 {
                 assert_eq!(mem.read(*i), Ok((*i % 256) as u8));    
             }
             else {
@@ -265,13 +1137,12 @@ mod tests {
             assert_eq!(empty.write(*i, 0), Err(BusDeviceError::AddressOutOfBounds { address: *i, size: 0 }));
         }
 
-        let data: [u8; 512] = core::array::from_fn(|index| (index % 256) as u8);
-        let mut mem = Memory::filled(data);
+        let mut mem = Memory::<512>::incrementing();
 
         let indexes = &[0, 16, 64, 512, 1024, 1, 2, 3, 4, 5, 256, 257, 258];
 
         for i in &[0, 16, 64, 512, 1024, 1, 2, 3, 4, 5, 256, 257, 258] {
-            if *i < data.len() {
+            if *i < 512 {
                 assert_eq!(mem.write(*i, 255 - ((*i % 256) as u8)), Ok(()));    
             }
             else {
@@ -297,14 +1168,254 @@ mod tests {
             assert_eq!(empty.write(*i, 0), Err(BusDeviceError::AddressNotWritable { address: *i }));
         }
 
-        let data: [u8; 512] = core::array::from_fn(|index| (index % 256) as u8);
-        let mut mem = ReadOnlyMemory::filled(data);
+        let mut mem = ReadOnlyMemory::<512>::incrementing();
 
         for i in &[0, 16, 64, 512, 1024, 1, 2, 3, 4, 5, 256, 257, 258] {
             assert_eq!(mem.write(*i, 255 - ((*i % 256) as u8)), Err(BusDeviceError::AddressNotWritable { address: *i }));    
         }
     }
 
+    #[test]
+    fn test_memory_slice_access() {
+        let mut mem = Memory::<8>::populated(&[0, 1, 2, 3]);
+
+        assert_eq!(mem.as_slice(), &[0, 1, 2, 3, 0, 0, 0, 0]);
+
+        mem.as_mut_slice()[5] = 42;
+        mem.as_mut_slice()[0..2].copy_from_slice(&[0xEB, 0xFE]);
+
+        assert_eq!(mem.read(5), Ok(42));
+        assert_eq!(mem.read_region(0), Ok([0xEB, 0xFE, 2, 3]));
+        assert_eq!(mem.as_ref(), &[0xEB, 0xFE, 2, 3, 0, 42, 0, 0]);
+
+        assert_eq!(mem.write(7, 43), Ok(()));
+        assert_eq!(mem.as_slice()[7], 43);
+    }
+
+    #[test]
+    fn test_read_only_memory_slice_access() {
+        let rom = ReadOnlyMemory::<4>::filled([4, 3, 2, 1]);
+
+        assert_eq!(rom.as_slice(), &[4, 3, 2, 1]);
+        assert_eq!(rom.as_ref(), &[4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_memory_iteration() {
+        let mem = Memory::<512>::incrementing();
+
+        let expected: Vec<u8> = (0..512).map(|i| mem.read(i).unwrap()).collect();
+        assert_eq!(mem.iter().collect::<Vec<_>>(), expected);
+        assert_eq!((&mem).into_iter().collect::<Vec<_>>(), expected);
+
+        let mut count = 0;
+        for (i, byte) in (&mem).into_iter().enumerate() {
+            assert_eq!(Ok(byte), mem.read(i));
+            count += 1;
+        }
+        assert_eq!(count, 512);
+
+        assert_eq!(mem.iter_from(300).collect::<Vec<_>>(), expected[300..]);
+        assert_eq!(mem.iter_from(512).count(), 0);
+        assert_eq!(mem.iter_from(1024).count(), 0);
+    }
+
+    #[test]
+    fn test_read_only_memory_iteration() {
+        let rom = ReadOnlyMemory::<512>::incrementing();
+
+        let expected: Vec<u8> = (0..512).map(|i| rom.read(i).unwrap()).collect();
+        assert_eq!(rom.iter().collect::<Vec<_>>(), expected);
+        assert_eq!((&rom).into_iter().collect::<Vec<_>>(), expected);
+        assert_eq!(rom.iter_from(511).collect::<Vec<_>>(), [255]);
+        assert_eq!(rom.iter_from(513).count(), 0);
+    }
+
+    #[test]
+    fn test_memory_enumerate_nonzero() {
+        let mut mem = Memory::<16>::empty();
+        assert_eq!(mem.enumerate_nonzero().count(), 0);
+
+        mem.write(0, 1).unwrap();
+        mem.write(7, 0xFF).unwrap();
+        mem.write(15, 2).unwrap();
+        assert_eq!(mem.enumerate_nonzero().collect::<Vec<_>>(), [(0, 1), (7, 0xFF), (15, 2)]);
+
+        let rom = ReadOnlyMemory::<4>::filled([0, 3, 0, 4]);
+        assert_eq!(rom.enumerate_nonzero().collect::<Vec<_>>(), [(1, 3), (3, 4)]);
+    }
+
+    #[test]
+    fn test_memory_freeze() {
+        let mut mem = Memory::<8>::empty();
+        assert_eq!(mem.write_region(2, &[0xEA, 0x5B, 0xE0]), Ok(()));
+
+        let mut rom = mem.into_read_only();
+        assert_eq!(rom.read_region(0), Ok([0, 0, 0xEA, 0x5B, 0xE0, 0, 0, 0]));
+        assert_eq!(rom.write(2, 0), Err(BusDeviceError::AddressNotWritable { address: 2 }));
+        assert_eq!(rom.write_region(0, &[1, 2]), Err(BusDeviceError::AddressNotWritable { address: 0 }));
+        assert_eq!(rom.read_region(0), Ok([0, 0, 0xEA, 0x5B, 0xE0, 0, 0, 0]));
+
+        let rom: ReadOnlyMemory<8> = Memory::filled([1; 8]).into();
+        assert_eq!(rom, ReadOnlyMemory::filled([1; 8]));
+    }
+
+    #[test]
+    fn test_memory_default() {
+        assert_eq!(Memory::<16>::default(), Memory::empty());
+        assert_eq!(ReadOnlyMemory::<16>::default(), ReadOnlyMemory::empty());
+        assert_eq!(Memory::<4>::default().read_region(0), Ok([0; 4]));
+        assert!(<MemoryMap>::default().mapping(0).is_none());
+    }
+
+    #[test]
+    fn test_memory_hash() {
+        fn hash(value: &impl Hash) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let mut a = Memory::<16>::empty();
+        let mut b = Memory::<16>::default();
+        assert_eq!(hash(&a), hash(&b));
+
+        assert_eq!(a.write(3, 7), Ok(()));
+        assert_ne!(hash(&a), hash(&b));
+
+        assert_eq!(b.write(3, 7), Ok(()));
+        assert_eq!(hash(&a), hash(&b));
+
+        assert_eq!(hash(&ReadOnlyMemory::filled([1, 2, 3])), hash(&ReadOnlyMemory::from(Memory::filled([1, 2, 3]))));
+    }
+
+    #[test]
+    fn test_bus_device_error_address() {
+        let error = BusDeviceError::AddressOutOfBounds { address: 4, size: 8 };
+        assert_eq!(error.address(), 4);
+        assert_eq!(error.with_address(0x10), BusDeviceError::AddressOutOfBounds { address: 0x10, size: 8 });
+
+        let error = BusDeviceError::GuardViolation { address: 1, kind: AccessKind::Write };
+        assert_eq!(error.with_address(2), BusDeviceError::GuardViolation { address: 2, kind: AccessKind::Write });
+        assert_eq!(BusDeviceError::AddressNotWritable { address: 3 }.address(), 3);
+    }
+
+    /// Loads `program` at `address`, in the style of the loaders which take any device by value.
+    fn load_program<T: BusDevice>(mut device: T, address: usize, program: &[u8]) -> Result<T, BusDeviceError> {
+        device.write_region(address, program)?;
+        Ok(device)
+    }
+
+    #[test]
+    fn test_bus_device_blanket_impls() {
+        // A boxed trait object, with the region helpers resolving through the box
+        let boxed: Box<dyn BusDevice> = Box::new(Memory::<8>::empty());
+        let boxed = load_program(boxed, 2, &[0xEB, 0xFE]).unwrap();
+        assert_eq!(boxed.read_region(2), Ok([0xEB, 0xFE]));
+        assert_eq!(boxed.read_u16_le(2), Ok(0xFEEB));
+        assert_eq!(boxed.size(), Some(8));
+
+        // A borrowed device, which is still usable afterwards
+        let mut memory = Memory::<8>::empty();
+        assert!(load_program(&mut memory, 6, &[0x90, 0xF4]).is_ok());
+        assert_eq!(memory.as_slice(), [0, 0, 0, 0, 0, 0, 0x90, 0xF4]);
+        assert_eq!(load_program(&mut memory, 7, &[0x90, 0xF4]).err(), Some(BusDeviceError::AddressOutOfBounds { address: 8, size: 8 }));
+
+        // Both together, lending a boxed map, with resets forwarded through the box
+        let mut map: Box<MemoryMap> = Box::new(MemoryMap::new().with_range(0x100..=0x107, Box::new(Latch::new([0; 8]))));
+        assert!(load_program(&mut map, 0x100, &[1, 2, 3]).is_ok());
+        assert_eq!(map.peek(0x101), Ok(2));
+        map.reset();
+        assert_eq!(map.read_region(0x100), Ok([0, 0, 0]));
+    }
+
+    #[test]
+    fn test_memory_try_from_bytes() {
+        assert_eq!(Memory::<4>::try_from(&[1, 2][..]), Ok(Memory::filled([1, 2, 0, 0])));
+        assert_eq!(ReadOnlyMemory::<4>::try_from(&[1, 2, 3, 4][..]), Ok(ReadOnlyMemory::filled([1, 2, 3, 4])));
+
+        // Round trip through the byte representation
+        let memory = Memory::<16>::populated(&[0xDE, 0xAD, 0xBE, 0xEF]);
+        assert_eq!(Memory::<16>::try_from(memory.as_slice()), Ok(memory));
+
+        assert_eq!(Memory::<4>::try_from(&[0; 5][..]), Err(ImageError::LengthMismatch { length: 5, size: 4 }));
+        assert_eq!(ReadOnlyMemory::<0>::try_from(&[0][..]), Err(ImageError::LengthMismatch { length: 1, size: 0 }));
+    }
+
+    #[test]
+    fn test_read_only_memory_unlock() {
+        let rom = ReadOnlyMemory::<4>::filled([1, 2, 3, 4]);
+
+        let mut mem = Memory::from(rom.clone());
+        assert_eq!(mem.write(0, 42), Ok(()));
+        assert_eq!(mem.read_region(0), Ok([42, 2, 3, 4]));
+        assert_eq!(rom.read(0), Ok(1));
+
+        let round_trip: ReadOnlyMemory<4> = Memory::from(rom.clone()).into();
+        assert_eq!(round_trip, rom);
+    }
+
+    #[test]
+    fn test_memory_indexing() {
+        let mut mem = Memory::<0x8000>::empty();
+
+        mem[0x7C00] = 0xEB;
+        mem[0x7C01] = 0xFE;
+        mem[0x7DFE..0x7E00].copy_from_slice(&[0x55, 0xAA]);
+
+        assert_eq!(mem[0x7C00], 0xEB);
+        assert_eq!(mem.read(0x7C01), Ok(0xFE));
+        assert_eq!(mem[0x7C00..0x7C02], [0xEB, 0xFE]);
+        assert_eq!(mem.read_region(0x7DFE), Ok([0x55, 0xAA]));
+        assert_eq!(mem[0x8000..0x8000], [0u8; 0]);
+    }
+
+    #[test]
+    fn test_read_only_memory_indexing() {
+        let rom = ReadOnlyMemory::<8>::populated(&[1, 2, 3]);
+
+        assert_eq!(rom[0], 1);
+        assert_eq!(rom[7], 0);
+        assert_eq!(rom[1..4], [2, 3, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_memory_index_panic() {
+        let mem = Memory::<8>::empty();
+        let _ = mem[8];
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_memory_index_mut_panic() {
+        let mut mem = Memory::<8>::empty();
+        mem[8] = 0;
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_memory_range_index_panic() {
+        let mem = Memory::<8>::empty();
+        let _ = &mem[4..9];
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_read_only_memory_index_panic() {
+        let rom = ReadOnlyMemory::<8>::empty();
+        let _ = rom[1024];
+    }
+
+    #[test]
+    fn test_memory_from_array() {
+        let mem: Memory<4> = [1, 2, 3, 4].into();
+        assert_eq!(mem, Memory::filled([1, 2, 3, 4]));
+
+        let rom = ReadOnlyMemory::from([5, 6]);
+        assert_eq!(rom, ReadOnlyMemory::filled([5, 6]));
+    }
+
     #[test]
     fn test_memory_region_read_interaction() {
         let empty = Memory::<0>::empty();
@@ -0,0 +1,136 @@
+use crate::{BusDevice, BusDeviceError};
+
+/// Combines two devices into one contiguous region by splitting bytes between them by address, as the planar RAM of
+/// the original IBM PC and some video cards do with their even and odd banks.
+///
+/// Even addresses are passed to the `even` device and odd addresses to the `odd` device, both at half the address.
+/// Errors are reported at the address the access was made at rather than the halved address, with the size of any
+/// `AddressOutOfBounds` error being the size of the combined region if it is bounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interleaved<A: BusDevice, B: BusDevice> {
+    even: A,
+    odd: B
+}
+
+impl<A: BusDevice, B: BusDevice> Interleaved<A, B> {
+    #[must_use]
+    /// Combines the `even` and `odd` banks.
+    pub const fn new(even: A, odd: B) -> Self {
+        Self { even, odd }
+    }
+
+    #[must_use]
+    /// Returns a reference to the bank holding the even addresses.
+    pub const fn even(&self) -> &A {
+        &self.even
+    }
+
+    #[must_use]
+    /// Returns a reference to the bank holding the odd addresses.
+    pub const fn odd(&self) -> &B {
+        &self.odd
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the bank holding the even addresses.
+    pub const fn even_mut(&mut self) -> &mut A {
+        &mut self.even
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the bank holding the odd addresses.
+    pub const fn odd_mut(&mut self) -> &mut B {
+        &mut self.odd
+    }
+
+    #[must_use]
+    /// Separates the banks again, even first.
+    pub fn into_inner(self) -> (A, B) {
+        (self.even, self.odd)
+    }
+
+    /// Translates an error from one of the banks back to the combined address space.
+    fn translate(&self, error: BusDeviceError, address: usize) -> BusDeviceError {
+        match (error, self.size()) {
+            (BusDeviceError::AddressOutOfBounds { .. }, Some(size)) => BusDeviceError::AddressOutOfBounds { address, size },
+            _ => error.with_address(address)
+        }
+    }
+}
+
+impl<A: BusDevice, B: BusDevice> BusDevice for Interleaved<A, B> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        let result = if address.is_multiple_of(2) { self.even.read(address / 2) } else { self.odd.read(address / 2) };
+        result.map_err(|error| self.translate(error, address))
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        let result = if address.is_multiple_of(2) { self.even.write(address / 2, data) } else { self.odd.write(address / 2, data) };
+        result.map_err(|error| self.translate(error, address))
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        let result = if address.is_multiple_of(2) { self.even.peek(address / 2) } else { self.odd.peek(address / 2) };
+        result.map_err(|error| self.translate(error, address))
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        let result = if address.is_multiple_of(2) { self.even.poke(address / 2, data) } else { self.odd.poke(address / 2, data) };
+        result.map_err(|error| self.translate(error, address))
+    }
+
+    /// Returns the length of the contiguous run of addresses both banks respond to, if both are bounded.
+    fn size(&self) -> Option<usize> {
+        Some((2 * self.even.size()?).min(2 * self.odd.size()? + 1))
+    }
+
+    fn reset(&mut self) {
+        self.even.reset();
+        self.odd.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, ReadOnlyMemory, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_interleaved_write_region() {
+        let mut device = Interleaved::new(Memory::<8>::empty(), Memory::<8>::empty());
+        let data: Vec<u8> = (0..16).collect();
+
+        assert_eq!(device.write_region(0, &data), Ok(()));
+        assert_eq!(device.read_region::<16>(0).unwrap().as_slice(), data);
+        assert_eq!(device.size(), Some(16));
+
+        let (even, odd) = device.into_inner();
+        assert_eq!(even, Memory::filled([0, 2, 4, 6, 8, 10, 12, 14]));
+        assert_eq!(odd, Memory::filled([1, 3, 5, 7, 9, 11, 13, 15]));
+    }
+
+    #[test]
+    fn test_interleaved_errors() {
+        let mut device = Interleaved::new(Memory::<8>::empty(), ReadOnlyMemory::<8>::filled([0xAA; 8]));
+
+        // The odd bank reports the original odd address, not the halved one
+        assert_eq!(device.write(5, 0), Err(BusDeviceError::AddressNotWritable { address: 5 }));
+        assert_eq!(device.write_region(2, &[1, 2]), Err(BusDeviceError::AddressNotWritable { address: 3 }));
+        assert_eq!(device.read_region(2), Ok([1, 0xAA]));
+
+        assert_eq!(device.read(16), Err(BusDeviceError::AddressOutOfBounds { address: 16, size: 16 }));
+        assert_eq!(device.peek(17), Err(BusDeviceError::AddressOutOfBounds { address: 17, size: 16 }));
+    }
+
+    #[test]
+    fn test_interleaved_uneven_banks() {
+        // With a shorter odd bank the combined region ends after the last odd byte
+        let mut map = MemoryMap::new()
+            .with_range(0xB8000..=0xB8006, Box::new(Interleaved::new(Memory::<4>::empty(), Memory::<3>::empty())));
+
+        assert_eq!(map.write_region(0xB8000, b"A\x07B\x07C\x07D"), Ok(()));
+        assert_eq!(map.read_region(0xB8000), Ok(*b"A\x07B\x07C\x07D"));
+        assert_eq!(Interleaved::new(Memory::<4>::empty(), Memory::<3>::empty()).size(), Some(7));
+    }
+}
@@ -0,0 +1,209 @@
+use std::ops::RangeInclusive;
+
+use crate::mapping::unmapped_ranges;
+
+/// An error raised by an access to the I/O address space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IoError {
+    /// No device is mapped to the port.
+    PortNotMapped{port: u16}
+}
+
+/// A device attached to the 64 KiB I/O address space reached by `IN` and `OUT`, separate from memory.
+pub trait IoDevice {
+    /// Reads the byte from the given `port`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the port cannot be read.
+    fn read_port(&self, port: u16) -> Result<u8, IoError>;
+
+    /// Writes `data` to the given `port`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the port cannot be written.
+    fn write_port(&mut self, port: u16, data: u8) -> Result<(), IoError>;
+
+    /// Reads the byte from the given `port` without triggering any side effects, for use by debuggers and other host
+    /// tools, as `BusDevice::peek` does for memory. Defaults to `read_port`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the port cannot be read.
+    fn peek_port(&self, port: u16) -> Result<u8, IoError> {
+        self.read_port(port)
+    }
+
+    /// Returns the device to its power-on state, as on a machine reset. Defaults to doing nothing.
+    fn reset(&mut self) {}
+}
+
+/// Maps port ranges of the I/O address space onto devices, in the same way as a `MemoryMap` does for memory.
+///
+/// Devices are given the port relative to the start of their range. As with `MemoryMap` they are stored as `Box<D>`,
+/// which defaults to `Box<dyn IoDevice>`.
+pub struct IoMap<D: ?Sized + IoDevice = dyn IoDevice> {
+    entries: Vec<(RangeInclusive<u16>, Box<D>)>
+}
+
+impl IoMap {
+    /// Construct a new, empty `IoMap`
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<D: ?Sized + IoDevice> IoMap<D> {
+    /// Builder pattern for adding a `range` of ports mapped to a `device` to the `IoMap`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is already mapped.
+    #[must_use]
+    pub fn with_range(mut self, range: RangeInclusive<u16>, device: Box<D>) -> Self {
+        self.add_range(range, device);
+        self
+    }
+
+    /// Adds a `range` of ports mapped to a `device` to the `IoMap`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is already mapped.
+    pub fn add_range(&mut self, range: RangeInclusive<u16>, device: Box<D>) {
+        for (r, _) in &self.entries {
+            assert!(!(range.start() <= r.end() && r.start() <= range.end()), "Port Range {range:#x?} overlaps already mapped {r:#x?}");
+        }
+
+        self.entries.push((range, device));
+    }
+
+    /// Get a reference to the device mapped to the given port
+    #[must_use]
+    pub fn mapping(&self, port: u16) -> Option<(&RangeInclusive<u16>, &D)> {
+        self.entries.iter()
+            .find(|(range, _)| range.contains(&port))
+            .map(|(range, device)| (range, device.as_ref()))
+    }
+
+    /// Get a mutable reference to the device mapped to the given port
+    #[must_use]
+    pub fn mut_mapping(&mut self, port: u16) -> Option<(&RangeInclusive<u16>, &mut D)> {
+        self.entries.iter_mut()
+            .find(|(range, _)| range.contains(&port))
+            .map(|(range, device)| (&*range, device.as_mut()))
+    }
+
+    /// Returns the holes within `range` which are not covered by any mapped device, in ascending order.
+    #[must_use]
+    pub fn unmapped_ranges(&self, range: RangeInclusive<u16>) -> Vec<RangeInclusive<u16>> {
+        let mut ranges: Vec<&RangeInclusive<u16>> = self.entries.iter().map(|(r, _)| r).collect();
+        ranges.sort_by_key(|r| r.start());
+
+        unmapped_ranges(ranges, range)
+    }
+
+    /// Returns `true` if every port in `range` is covered by some mapped device.
+    #[must_use]
+    pub fn is_range_fully_mapped(&self, range: RangeInclusive<u16>) -> bool {
+        range.is_empty() || self.unmapped_ranges(range).is_empty()
+    }
+}
+
+impl<D: ?Sized + IoDevice> Default for IoMap<D> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new()
+        }
+    }
+}
+
+impl<D: ?Sized + IoDevice> IoDevice for IoMap<D> {
+    fn read_port(&self, port: u16) -> Result<u8, IoError> {
+        let (range, device) = self.mapping(port).ok_or(IoError::PortNotMapped { port })?;
+        device.read_port(port - range.start())
+    }
+
+    fn write_port(&mut self, port: u16, data: u8) -> Result<(), IoError> {
+        let (range, device) = self.mut_mapping(port).ok_or(IoError::PortNotMapped { port })?;
+        device.write_port(port - range.start(), data)
+    }
+
+    fn peek_port(&self, port: u16) -> Result<u8, IoError> {
+        let (range, device) = self.mapping(port).ok_or(IoError::PortNotMapped { port })?;
+        device.peek_port(port - range.start())
+    }
+
+    /// Resets every mapped device, leaving the mappings themselves in place.
+    fn reset(&mut self) {
+        for (_, device) in &mut self.entries {
+            device.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Latch;
+
+    use super::*;
+
+    #[test]
+    fn test_io_map_ports() {
+        let mut io = IoMap::new()
+            .with_range(0x20..=0x21, Box::new(Latch::new([0; 4])))
+            .with_range(0x60..=0x63, Box::new(Latch::new([1, 2, 3, 4])));
+
+        assert_eq!(io.read_port(0x62), Ok(3));
+        assert_eq!(io.write_port(0x21, 0xFF), Ok(()));
+        assert_eq!(io.read_port(0x21), Ok(0xFF));
+        assert_eq!(io.read_port(0x20), Ok(0));
+
+        assert_eq!(io.read_port(0x22), Err(IoError::PortNotMapped { port: 0x22 }));
+        assert_eq!(io.write_port(0x3F8, 0), Err(IoError::PortNotMapped { port: 0x3F8 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps already mapped")]
+    fn test_io_map_overlap() {
+        let _ = IoMap::new()
+            .with_range(0x20..=0x21, Box::new(Latch::new([0; 4])))
+            .with_range(0x21..=0x22, Box::new(Latch::new([0; 4])));
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps already mapped")]
+    fn test_io_map_overlap_enclosing() {
+        let _ = IoMap::new()
+            .with_range(0x21..=0x22, Box::new(Latch::new([0; 4])))
+            .with_range(0x20..=0x23, Box::new(Latch::new([0; 4])));
+    }
+
+    #[test]
+    fn test_io_map_unmapped_ranges() {
+        let io = IoMap::new()
+            .with_range(0x60..=0x63, Box::new(Latch::new([0; 4])))
+            .with_range(0x20..=0x21, Box::new(Latch::new([0; 4])))
+            .with_range(0xFFFC..=0xFFFF, Box::new(Latch::new([0; 4])));
+
+        assert_eq!(io.unmapped_ranges(0x00..=0xFF), [0x00..=0x1F, 0x22..=0x5F, 0x64..=0xFF]);
+        assert_eq!(io.unmapped_ranges(0xFFF0..=0xFFFF), [0xFFF0..=0xFFFB]);
+        assert!(io.is_range_fully_mapped(0x60..=0x63));
+        assert!(!io.is_range_fully_mapped(0x21..=0x22));
+    }
+
+    #[test]
+    fn test_io_map_peek_reset() {
+        let mut io = IoMap::new().with_range(0x40..=0x43, Box::new(Latch::new([1, 2, 3, 4])));
+
+        assert_eq!(io.peek_port(0x41), Ok(2));
+        assert_eq!(io.peek_port(0x44), Err(IoError::PortNotMapped { port: 0x44 }));
+
+        assert_eq!(io.write_port(0x41, 0xFF), Ok(()));
+        io.reset();
+        assert_eq!(io.read_port(0x41), Ok(2));
+        assert!(io.mapping(0x41).is_some());
+    }
+}
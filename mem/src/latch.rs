@@ -0,0 +1,184 @@
+use std::fmt::Debug;
+
+use crate::{BusDevice, BusDeviceError, IoDevice, IoError, TimedBusDevice};
+
+/// Callback invoked by a `Latch` with each byte written by the guest.
+pub type LatchFn = Box<dyn FnMut(u8)>;
+
+/// A small register file of `SIZE` latched bytes, one by default, such as the POST code port at `80h`.
+///
+/// Writes store the byte, and reads return the last byte written, or the reset value if there has been no write
+/// since construction or the last `reset`. An optional callback is invoked with each byte written, after it has been
+/// stored, so the callback observes writes in the order the guest made them. Pokes store the byte without invoking
+/// the callback.
+pub struct Latch<const SIZE: usize = 1> {
+    value: [u8; SIZE],
+    reset_value: [u8; SIZE],
+    on_write: Option<LatchFn>
+}
+
+impl<const SIZE: usize> Latch<SIZE> {
+    #[must_use]
+    /// Constructs a latch holding `reset_value`, without a write callback.
+    pub const fn new(reset_value: [u8; SIZE]) -> Self {
+        Self { value: reset_value, reset_value, on_write: None }
+    }
+
+    #[must_use]
+    /// Sets the callback invoked with each byte written by the guest, replacing any previous callback.
+    pub fn with_on_write(mut self, on_write: impl FnMut(u8) + 'static) -> Self {
+        self.on_write = Some(Box::new(on_write));
+        self
+    }
+
+    #[must_use]
+    /// Returns the bytes currently latched.
+    pub const fn value(&self) -> [u8; SIZE] {
+        self.value
+    }
+
+    /// Replaces the bytes currently latched without invoking the write callback, as seen by the guest's next read.
+    pub const fn set_value(&mut self, value: [u8; SIZE]) {
+        self.value = value;
+    }
+
+    #[must_use]
+    /// Returns the bytes latched at construction and after a reset.
+    pub const fn reset_value(&self) -> [u8; SIZE] {
+        self.reset_value
+    }
+
+    /// Restores the reset value.
+    pub const fn reset(&mut self) {
+        self.value = self.reset_value;
+    }
+}
+
+impl<const SIZE: usize> Debug for Latch<SIZE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Latch")
+            .field("value", &self.value)
+            .field("reset_value", &self.reset_value)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<const SIZE: usize> BusDevice for Latch<SIZE> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.value.get(address).copied().ok_or(BusDeviceError::AddressOutOfBounds { address, size: SIZE })
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.poke(address, data)?;
+
+        if let Some(on_write) = &mut self.on_write {
+            on_write(data);
+        }
+
+        Ok(())
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        *self.value.get_mut(address).ok_or(BusDeviceError::AddressOutOfBounds { address, size: SIZE })? = data;
+        Ok(())
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(SIZE)
+    }
+
+    fn reset(&mut self) {
+        Self::reset(self);
+    }
+}
+
+impl<const SIZE: usize> TimedBusDevice for Latch<SIZE> {}
+
+/// Maps the latched bytes onto consecutive ports, relative to the start of the range the latch is mapped to.
+impl<const SIZE: usize> IoDevice for Latch<SIZE> {
+    fn read_port(&self, port: u16) -> Result<u8, IoError> {
+        self.read(usize::from(port)).map_err(|_| IoError::PortNotMapped { port })
+    }
+
+    fn write_port(&mut self, port: u16, data: u8) -> Result<(), IoError> {
+        self.write(usize::from(port), data).map_err(|_| IoError::PortNotMapped { port })
+    }
+
+    fn reset(&mut self) {
+        Self::reset(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_latch_callback_order() {
+        let codes = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&codes);
+
+        let mut latch = Latch::new([0x00]).with_on_write(move |code| sink.borrow_mut().push(code));
+
+        assert_eq!(latch.write(0, 0x01), Ok(()));
+        assert_eq!(latch.write(0, 0x2A), Ok(()));
+        assert_eq!(latch.write(0, 0x01), Ok(()));
+        assert_eq!(*codes.borrow(), [0x01, 0x2A, 0x01]);
+
+        // Failed writes and pokes do not fire
+        assert_eq!(latch.write(1, 0x03), Err(BusDeviceError::AddressOutOfBounds { address: 1, size: 1 }));
+        assert_eq!(latch.poke(0, 0x04), Ok(()));
+        assert_eq!(codes.borrow().len(), 3);
+        assert_eq!(latch.read(0), Ok(0x04));
+    }
+
+    #[test]
+    fn test_latch_host_access() {
+        let mut latch = Latch::<2>::new([0xFF, 0x00]);
+        assert_eq!(latch.read_region(0), Ok([0xFF, 0x00]));
+
+        // The guest sees values set by the host, and the host sees values written by the guest
+        latch.set_value([0x12, 0x34]);
+        assert_eq!(latch.read_region(0), Ok([0x12, 0x34]));
+
+        assert_eq!(latch.write(1, 0x56), Ok(()));
+        assert_eq!(latch.value(), [0x12, 0x56]);
+        assert_eq!(latch.read(2), Err(BusDeviceError::AddressOutOfBounds { address: 2, size: 2 }));
+
+        latch.reset();
+        assert_eq!(latch.value(), latch.reset_value());
+        assert_eq!(latch.read(0), Ok(0xFF));
+    }
+
+    #[test]
+    fn test_latch_ports() {
+        let mut latch = Latch::new([0x00, 0x00]);
+
+        assert_eq!(latch.write_port(1, 0x42), Ok(()));
+        assert_eq!(latch.read_port(1), Ok(0x42));
+        assert_eq!(latch.read_port(2), Err(IoError::PortNotMapped { port: 2 }));
+        assert_eq!(latch.write_port(2, 0x00), Err(IoError::PortNotMapped { port: 2 }));
+
+        IoDevice::reset(&mut latch);
+        assert_eq!(latch.read_port(1), Ok(0x00));
+    }
+
+    #[test]
+    fn test_latch_post_code_port() {
+        let codes = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&codes);
+
+        let mut map = MemoryMap::new()
+            .with_range(0x80..=0x80, Box::new(Latch::new([0x00]).with_on_write(move |code| sink.borrow_mut().push(code))));
+
+        assert_eq!(map.write(0x80, 0x11), Ok(()));
+        assert_eq!(map.write(0x80, 0x12), Ok(()));
+        assert_eq!(map.read(0x80), Ok(0x12));
+        assert_eq!(map.write(0x81, 0x13), Err(BusDeviceError::AddressNotMapped { address: 0x81 }));
+        assert_eq!(*codes.borrow(), [0x11, 0x12]);
+    }
+}
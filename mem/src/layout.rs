@@ -0,0 +1,344 @@
+use std::{fmt::Formatter, ops::RangeInclusive};
+
+use serde::{de::{Error, SeqAccess, Visitor}, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{BusDevice, ConstantDevice, GuardDevice, ImageError, Memory, MemoryMap, ReadOnlyMemory};
+
+/// Visits a byte sequence, given either as bytes or as a sequence of integers as JSON gives them, rejecting sequences
+/// longer than `limit` before reading them in full.
+struct BytesVisitor {
+    limit: usize
+}
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a sequence of at most {} bytes", self.limit)
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        if v.len() > self.limit {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+
+        Ok(v.to_vec())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default().min(self.limit));
+
+        while let Some(byte) = seq.next_element()? {
+            if bytes.len() == self.limit {
+                return Err(A::Error::invalid_length(self.limit + 1 + seq.size_hint().unwrap_or_default(), &self));
+            }
+
+            bytes.push(byte);
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// Deserializes a byte sequence of at most `limit` bytes.
+fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D, limit: usize) -> Result<Vec<u8>, D::Error> {
+    deserializer.deserialize_bytes(BytesVisitor { limit })
+}
+
+/// Converts an error building a memory from its contents into a deserialization error.
+fn image_error<E: Error>(error: ImageError) -> E {
+    match error {
+        ImageError::LengthMismatch { length, size } => E::invalid_length(length, &format!("at most {size} bytes").as_str())
+    }
+}
+
+/// Serializes the contents of the memory as a byte sequence.
+impl<const SIZE: usize> Serialize for Memory<SIZE> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+/// Deserializes a memory from a byte sequence, padded with zeros as by `TryFrom<&[u8]>`. A sequence longer than
+/// `SIZE` is an error.
+impl<'de, const SIZE: usize> Deserialize<'de> for Memory<SIZE> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::try_from(deserialize_bytes(deserializer, SIZE)?.as_slice()).map_err(image_error)
+    }
+}
+
+/// Serializes the contents of the memory as a byte sequence.
+impl<const SIZE: usize> Serialize for ReadOnlyMemory<SIZE> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+/// Deserializes a read only memory from a byte sequence, padded with zeros as by `TryFrom<&[u8]>`. A sequence longer
+/// than `SIZE` is an error.
+impl<'de, const SIZE: usize> Deserialize<'de> for ReadOnlyMemory<SIZE> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::try_from(deserialize_bytes(deserializer, SIZE)?.as_slice()).map_err(image_error)
+    }
+}
+
+/// Serializes the contents of a memory mapping as a byte sequence, rather than as a sequence of integers.
+mod contents {
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(data)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        super::deserialize_bytes(deserializer, usize::MAX)
+    }
+}
+
+/// The kind of a standard device in a `MemoryMapLayout`, with what is needed to rebuild it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DeviceKind {
+    /// A `Memory` holding the given contents, padded with zeros to the length of its range.
+    Ram(#[serde(with = "contents")] Vec<u8>),
+    /// A `ReadOnlyMemory` holding the given contents, padded with zeros to the length of its range.
+    Rom(#[serde(with = "contents")] Vec<u8>),
+    /// A `ConstantDevice`.
+    Constant{value: u8, ignore_writes: bool},
+    /// A `GuardDevice`.
+    Guard
+}
+
+/// A single range of a `MemoryMapLayout`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MappingLayout {
+    pub range: RangeInclusive<usize>,
+    pub kind: DeviceKind
+}
+
+/// An error raised while building a `MemoryMap` from a `MemoryMapLayout`, identifying the mapping by the start of its
+/// range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LayoutError {
+    /// The range ends before it starts, or overlaps another range of the layout.
+    BadRange{start: usize, end: usize},
+    /// The length of the range of a memory is not a multiple of `LAYOUT_MEMORY_GRANULARITY`.
+    UnsupportedSize{start: usize, length: usize},
+    /// The contents of a memory do not fit in its range.
+    Image{start: usize, error: ImageError}
+}
+
+/// The granularity of the ranges of `Ram` and `Rom` devices in a `MemoryMapLayout`, whose lengths must be a multiple of
+/// it.
+pub const LAYOUT_MEMORY_GRANULARITY: usize = 0x400;
+
+/// Constructs a memory of `SIZE` bytes holding `data`, which must fit in it.
+fn memory_chunk<const SIZE: usize>(data: &[u8], read_only: bool) -> Box<dyn BusDevice> {
+    let memory = Memory::<SIZE>::populated(data);
+
+    if read_only {
+        Box::new(memory.into_read_only())
+    }
+    else {
+        Box::new(memory)
+    }
+}
+
+/// Maps a memory over `range` holding `data` into `map`, as a run of memories of at most 64 KiB so that large memories
+/// are never built on the stack.
+fn map_memory(map: &mut MemoryMap, range: &RangeInclusive<usize>, data: &[u8], read_only: bool) -> Result<(), LayoutError> {
+    let start = *range.start();
+    let length = range.end() - start + 1;
+
+    if !length.is_multiple_of(LAYOUT_MEMORY_GRANULARITY) {
+        return Err(LayoutError::UnsupportedSize { start, length });
+    }
+
+    if data.len() > length {
+        return Err(LayoutError::Image { start, error: ImageError::LengthMismatch { length: data.len(), size: length } });
+    }
+
+    let mut offset = 0;
+
+    while offset < length {
+        let size = [0x1_0000, 0x8000, 0x4000, 0x2000, 0x1000, 0x800, 0x400].into_iter()
+            .find(|size| *size <= length - offset)
+            .unwrap_or(LAYOUT_MEMORY_GRANULARITY);
+        let chunk = data.get(offset..data.len().min(offset + size)).unwrap_or_default();
+
+        let device = match size {
+            0x1_0000 => memory_chunk::<0x1_0000>(chunk, read_only),
+            0x8000 => memory_chunk::<0x8000>(chunk, read_only),
+            0x4000 => memory_chunk::<0x4000>(chunk, read_only),
+            0x2000 => memory_chunk::<0x2000>(chunk, read_only),
+            0x1000 => memory_chunk::<0x1000>(chunk, read_only),
+            0x800 => memory_chunk::<0x800>(chunk, read_only),
+            _ => memory_chunk::<0x400>(chunk, read_only)
+        };
+
+        map.add_range(start + offset..=start + offset + size - 1, device);
+        offset += size;
+    }
+
+    Ok(())
+}
+
+/// A serializable description of the devices in a `MemoryMap`, from which an equivalent map of standard devices can be
+/// rebuilt, as when saving and restoring the state of a machine.
+///
+/// As the devices in a `MemoryMap` cannot be inspected, a layout is built up alongside the map it describes rather than
+/// taken from it. Memories are rebuilt as runs of memories of at most 64 KiB, which behave as one memory over their
+/// range but appear as several mappings of the map.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MemoryMapLayout {
+    pub mappings: Vec<MappingLayout>
+}
+
+impl MemoryMapLayout {
+    #[must_use]
+    /// Constructs an empty layout.
+    pub const fn new() -> Self {
+        Self { mappings: Vec::new() }
+    }
+
+    #[must_use]
+    /// Builder pattern for adding a device of the given `kind` mapped over `range`.
+    pub fn with_mapping(mut self, range: RangeInclusive<usize>, kind: DeviceKind) -> Self {
+        self.mappings.push(MappingLayout { range, kind });
+        self
+    }
+
+    /// Builds a `MemoryMap` with a new device for each mapping of the layout.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a range is inverted or overlaps another, if a memory has a range whose
+    /// length is not a multiple of `LAYOUT_MEMORY_GRANULARITY`, or if its contents are longer than its range.
+    pub fn build(&self) -> Result<MemoryMap, LayoutError> {
+        let mut ranges: Vec<_> = self.mappings.iter().map(|mapping| &mapping.range).collect();
+        ranges.sort_by_key(|range| range.start());
+
+        for (index, range) in ranges.iter().enumerate() {
+            let overlaps = index > 0 && ranges[index - 1].end() >= range.start();
+
+            if range.start() > range.end() || overlaps {
+                return Err(LayoutError::BadRange { start: *range.start(), end: *range.end() });
+            }
+        }
+
+        let mut map = MemoryMap::new();
+
+        for MappingLayout { range, kind } in &self.mappings {
+            match kind {
+                DeviceKind::Ram(data) => map_memory(&mut map, range, data, false)?,
+                DeviceKind::Rom(data) => map_memory(&mut map, range, data, true)?,
+                DeviceKind::Constant { value, ignore_writes } => map.add_range(range.clone(), Box::new(ConstantDevice::new(*value, *ignore_writes))),
+                DeviceKind::Guard => map.add_range(range.clone(), Box::new(GuardDevice::new(*range.start())))
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BusDeviceError, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_memory_serde_round_trip() {
+        let memory = Memory::<1024>::populated(b"Hello, world!");
+
+        // Stored as a length followed by the bytes themselves
+        let encoded = bincode::serialize(&memory).unwrap();
+        assert_eq!(encoded.len(), 8 + 1024);
+        assert_eq!(bincode::deserialize::<Memory<1024>>(&encoded).unwrap(), memory);
+
+        let json = serde_json::to_string(&Memory::<4>::filled([1, 2, 3, 4])).unwrap();
+        assert_eq!(json, "[1,2,3,4]");
+        assert_eq!(serde_json::from_str::<Memory<4>>(&json).unwrap(), Memory::filled([1, 2, 3, 4]));
+
+        let rom = ReadOnlyMemory::<16>::populated(&[0xEA, 0x5B, 0xE0, 0x00, 0xF0]);
+        assert_eq!(bincode::deserialize::<ReadOnlyMemory<16>>(&bincode::serialize(&rom).unwrap()).unwrap(), rom);
+        assert_eq!(serde_json::from_str::<ReadOnlyMemory<16>>(&serde_json::to_string(&rom).unwrap()).unwrap(), rom);
+    }
+
+    #[test]
+    fn test_memory_deserialize_length() {
+        // Shorter data is padded with zeros
+        assert_eq!(serde_json::from_str::<Memory<4>>("[1, 2]").unwrap(), Memory::filled([1, 2, 0, 0]));
+
+        // Longer data is an error rather than a panic
+        assert!(serde_json::from_str::<Memory<4>>("[1, 2, 3, 4, 5]").is_err());
+        assert!(serde_json::from_str::<ReadOnlyMemory<0>>("[0]").is_err());
+
+        let encoded = bincode::serialize(&Memory::<8>::incrementing()).unwrap();
+        assert!(bincode::deserialize::<Memory<4>>(&encoded).is_err());
+        assert!(bincode::deserialize::<ReadOnlyMemory<7>>(&encoded).is_err());
+        assert!(bincode::deserialize::<Memory<8>>(&encoded).is_ok());
+    }
+
+    fn layout() -> MemoryMapLayout {
+        MemoryMapLayout::new()
+            .with_mapping(0x0000..=0x07FF, DeviceKind::Ram(vec![0xCD, 0x19]))
+            .with_mapping(0x0800..=0x0BFF, DeviceKind::Guard)
+            .with_mapping(0x0C00..=0x0FFF, DeviceKind::Constant { value: 0xFF, ignore_writes: true })
+            .with_mapping(0xF000..=0xFFFF, DeviceKind::Rom(vec![0xEA; 16]))
+    }
+
+    #[test]
+    fn test_memory_map_layout_round_trip() {
+        let layout = layout();
+
+        let json = serde_json::to_string(&layout).unwrap();
+        assert_eq!(serde_json::from_str::<MemoryMapLayout>(&json).unwrap(), layout);
+
+        let encoded = bincode::serialize(&layout).unwrap();
+        assert_eq!(bincode::deserialize::<MemoryMapLayout>(&encoded).unwrap(), layout);
+
+        let mut map = layout.build().unwrap();
+        assert_eq!(map.read_region(0x0000), Ok([0xCD, 0x19, 0x00]));
+        assert_eq!(map.write(0x0002, 0x42), Ok(()));
+        assert_eq!(map.read(0x0900), Err(BusDeviceError::GuardViolation { address: 0x0900, kind: crate::AccessKind::Read }));
+        assert_eq!(map.read(0x0C00), Ok(0xFF));
+        assert_eq!(map.read_region(0xF00F), Ok([0xEA, 0x00]));
+        assert_eq!(map.write(0xF000, 0x00), Err(BusDeviceError::AddressNotWritable { address: 0x0000 }));
+        assert_eq!(map.read(0x1000), Err(BusDeviceError::AddressNotMapped { address: 0x1000 }));
+    }
+
+    #[test]
+    fn test_memory_map_layout_large_memory() {
+        let mut data = vec![0; 0x9_0001];
+        data[0x9_0000] = 0x5A;
+
+        // 640 KiB of conventional memory is rebuilt as ten memories of 64 KiB
+        let layout = MemoryMapLayout::new().with_mapping(0x0_0000..=0x9_FFFF, DeviceKind::Ram(data));
+        let map = layout.build().unwrap();
+
+        assert_eq!(map.len(), 10);
+        assert!(map.is_range_fully_mapped(0x0_0000..=0x9_FFFF));
+        assert_eq!(map.read_region(0x8_FFFF), Ok([0x00, 0x5A, 0x00]));
+
+        // 3 KiB is rebuilt as 2 KiB followed by 1 KiB
+        let layout = MemoryMapLayout::new().with_mapping(0x400..=0xFFF, DeviceKind::Rom(vec![0x11; 0xC00]));
+        let map = layout.build().unwrap();
+        assert_eq!(map.iter().map(|(range, _)| range.clone()).collect::<Vec<_>>(), [0x400..=0xBFF, 0xC00..=0xFFF]);
+        assert_eq!(map.read_region(0xBFF), Ok([0x11, 0x11]));
+    }
+
+    #[test]
+    fn test_memory_map_layout_errors() {
+        let overlapping = layout().with_mapping(0x0BF0..=0x0C0F, DeviceKind::Guard);
+        assert_eq!(overlapping.build().err(), Some(LayoutError::BadRange { start: 0x0BF0, end: 0x0C0F }));
+
+        let inverted = MemoryMapLayout::new().with_mapping(RangeInclusive::new(0x10, 0x0F), DeviceKind::Guard);
+        assert_eq!(inverted.build().err(), Some(LayoutError::BadRange { start: 0x10, end: 0x0F }));
+
+        let odd_size = MemoryMapLayout::new().with_mapping(0x0000..=0x02FF, DeviceKind::Ram(Vec::new()));
+        assert_eq!(odd_size.build().err(), Some(LayoutError::UnsupportedSize { start: 0, length: 0x300 }));
+
+        let too_long = MemoryMapLayout::new().with_mapping(0x0400..=0x07FF, DeviceKind::Rom(vec![0; 0x401]));
+        assert_eq!(too_long.build().err(), Some(LayoutError::Image { start: 0x400, error: ImageError::LengthMismatch { length: 0x401, size: 0x400 } }));
+    }
+}
@@ -4,8 +4,103 @@ pub use interface::*;
 pub mod mapping;
 pub use mapping::*;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod srec;
+pub use srec::*;
 
-}
+pub mod ihex;
+pub use ihex::*;
+
+pub mod hexdump;
+pub use hexdump::*;
+
+pub mod checksum;
+pub use checksum::*;
+
+pub mod a20;
+pub use a20::*;
+
+pub mod diff;
+pub use diff::*;
+
+pub mod write_protect;
+pub use write_protect::*;
+
+pub mod write_once;
+pub use write_once::*;
+
+pub mod mirrored;
+pub use mirrored::*;
+
+pub mod constant;
+pub use constant::*;
+
+pub mod fn_device;
+pub use fn_device::*;
+
+pub mod shared;
+pub use shared::*;
+
+pub mod sync_shared;
+pub use sync_shared::*;
+
+pub mod counted;
+pub use counted::*;
+
+pub mod dirty;
+pub use dirty::*;
+
+pub mod watched;
+pub use watched::*;
+
+pub mod traced;
+pub use traced::*;
+
+pub mod io;
+pub use io::*;
+
+pub mod init_tracked;
+pub use init_tracked::*;
+
+pub mod guard;
+pub use guard::*;
+
+pub mod search;
+pub use search::*;
+
+pub mod read_only;
+pub use read_only::*;
+
+pub mod write_only;
+pub use write_only::*;
+
+pub mod latch;
+pub use latch::*;
+
+pub mod fifo;
+pub use fifo::*;
+
+pub mod register_bank;
+pub use register_bank::*;
+
+pub mod interleaved;
+pub use interleaved::*;
+
+pub mod aligned;
+pub use aligned::*;
+
+pub mod delayed;
+pub use delayed::*;
+
+pub mod pattern;
+pub use pattern::*;
+
+pub mod segmented;
+pub use segmented::*;
+
+pub mod poisoned;
+pub use poisoned::*;
+
+#[cfg(feature = "serde")]
+pub mod layout;
+#[cfg(feature = "serde")]
+pub use layout::*;
\ No newline at end of file
@@ -1,30 +1,156 @@
-use std::ops::RangeInclusive;
+use std::{cell::Cell, ops::RangeInclusive};
 
-use crate::BusDeviceError;
+use crate::{BusDeviceError, CloneBusDevice, GuardDevice, Poisoned, TimedBusDevice};
 
 use super::interface::BusDevice;
 
-pub struct MemoryMap {
-    // TODO: So this should be replaced with a different data structure that ensures two ranges can't overlap and can search for ranges using binary search.
-    entries: Vec<(RangeInclusive<usize>, Box<dyn BusDevice>)>
+/// The highest address of the 1 MiB address space of the 8086 in real mode.
+pub const MAX_REAL_MODE_ADDRESS: usize = 0xF_FFFF;
+
+/// An integer type which the ranges of a map are made up of.
+pub(crate) trait RangeIndex: Copy + Ord {
+    /// Returns the index after this one, or `None` if this is the last index.
+    fn successor(self) -> Option<Self>;
+
+    /// Returns the index before this one, which must not be zero.
+    fn predecessor(self) -> Self;
+}
+
+impl RangeIndex for usize {
+    fn successor(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+
+    fn predecessor(self) -> Self {
+        self - 1
+    }
+}
+
+impl RangeIndex for u16 {
+    fn successor(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+
+    fn predecessor(self) -> Self {
+        self - 1
+    }
+}
+
+/// Returns the holes within `range` which are not covered by any of `ranges`, in ascending order. `ranges` must be
+/// sorted by their starts.
+pub(crate) fn unmapped_ranges<'a, T: RangeIndex + 'a>(ranges: impl IntoIterator<Item = &'a RangeInclusive<T>>, range: RangeInclusive<T>) -> Vec<RangeInclusive<T>> {
+    let mut holes = Vec::new();
+
+    // The first index which has not yet been shown to be covered, `None` once the end of `range` is covered
+    let mut next = Some(*range.start());
+
+    for r in ranges {
+        let Some(start) = next.filter(|next| next <= range.end()) else {
+            break;
+        };
+
+        if *r.start() > start {
+            holes.push(start..=r.start().predecessor().min(*range.end()));
+        }
+
+        if *r.end() >= start {
+            next = r.end().successor();
+        }
+    }
+
+    if let Some(start) = next.filter(|next| next <= range.end()) {
+        holes.push(start..=*range.end());
+    }
+
+    holes
+}
+
+/// Maps address ranges onto bus devices.
+///
+/// The devices are stored as `Box<D>`, which defaults to `Box<dyn BusDevice>`. A `MemoryMap<dyn CloneBusDevice>`
+/// only accepts cloneable devices, and in exchange can itself be cloned.
+pub struct MemoryMap<D: ?Sized + BusDevice = dyn BusDevice> {
+    /// The mappings, sorted by the start of their ranges. As no two ranges overlap they are sorted by their ends too, so
+    /// the mapping covering an address can be found with a binary search.
+    entries: Vec<(RangeInclusive<usize>, Box<D>)>,
+    /// Ranges mapped to a `GuardDevice` by `with_guards`, which no later mapping may overlap.
+    guards: Vec<RangeInclusive<usize>>,
+    /// The index of the entry which covered the last address looked up, checked before searching as accesses tend to
+    /// hit the same device many times in a row. Cleared whenever the entries change.
+    last_hit: Cell<Option<usize>>
 }
 
 impl MemoryMap {
     /// Construct a new, empty `MemoryMap`
     #[must_use]
     pub fn new() -> Self {
-        Self {
-            entries: Vec::new()
+        Self::default()
+    }
+
+    /// Builder pattern for filling the unmapped holes between the current mappings with `GuardDevice`s, so that any
+    /// access to them fails with `GuardViolation` rather than `AddressNotMapped`.
+    ///
+    /// If `gap_fill` is set the holes below the lowest mapping and above the highest mapping, up to
+    /// `MAX_REAL_MODE_ADDRESS`, are guarded as well. The holes are computed from the mappings at the time of the call,
+    /// and any range added afterwards which overlaps a guard panics.
+    #[must_use]
+    pub fn with_guards(mut self, gap_fill: bool) -> Self {
+        let lowest = self.entries.first().map(|(range, _)| *range.start());
+        let highest = self.entries.last().map(|(range, _)| *range.end());
+
+        let space = match (lowest, highest) {
+            (_, Some(highest)) if gap_fill => 0..=highest.max(MAX_REAL_MODE_ADDRESS),
+            (Some(lowest), Some(highest)) => lowest..=highest,
+            _ if gap_fill => 0..=MAX_REAL_MODE_ADDRESS,
+            _ => return self
+        };
+
+        for hole in self.unmapped_ranges(space) {
+            let device = Box::new(GuardDevice::new(*hole.start()));
+
+            self.add_range(hole.clone(), device);
+            self.guards.push(hole);
         }
+
+        self
+    }
+
+    /// Removes the mapping of exactly `range` as `remove_range` does, but maps a `Poisoned` device labelled `label` in
+    /// its place, so that later accesses to the range fail with `Poisoned` rather than `AddressNotMapped`. Returns
+    /// `None`, leaving the map unchanged, if `range` is not mapped.
+    pub fn remove_range_poisoned(&mut self, range: &RangeInclusive<usize>, label: &'static str) -> Option<Box<dyn BusDevice>> {
+        let device = self.remove_range(range)?;
+        self.add_range(range.clone(), Box::new(Poisoned::new(*range.start(), label)));
+
+        Some(device)
+    }
+}
+
+impl MemoryMap<dyn CloneBusDevice> {
+    /// Construct a new, empty `MemoryMap` which only accepts cloneable devices, and so can itself be cloned.
+    #[must_use]
+    pub fn cloneable() -> Self {
+        Self::default()
+    }
+}
+
+impl MemoryMap<dyn TimedBusDevice> {
+    /// Construct a new, empty `MemoryMap` which only accepts timed devices, and so can report the wait states of each
+    /// access.
+    #[must_use]
+    pub fn timed() -> Self {
+        Self::default()
     }
+}
 
+impl<D: ?Sized + BusDevice> MemoryMap<D> {
     /// Builder pattern for adding a `range` mapped to a `bus_device` to the `MemoryMap`.
     ///
     /// # Panics
     ///
-    /// Panics if `range` is already mapped.
+    /// Panics if `range` is already mapped, or is longer than the size reported by `bus_device`.
     #[must_use]
-    pub fn with_range(mut self, range: RangeInclusive<usize>, bus_device: Box<dyn BusDevice>) -> Self {
+    pub fn with_range(mut self, range: RangeInclusive<usize>, bus_device: Box<D>) -> Self {
         self.add_range(range, bus_device);
         self
     }
@@ -33,49 +159,150 @@ impl MemoryMap {
     ///
     /// # Panics
     ///
-    /// Panics if `range` is already mapped.
-    pub fn add_range(&mut self, range: RangeInclusive<usize>, bus_device: Box<dyn BusDevice>) {
-        // Make sure that the range doesn't overlap another range
-        for (r, _) in &self.entries {
-            assert!(!(r.contains(range.start()) || r.contains(range.end())), "Memory Range {range:#x?} overlaps already mapped {r:#x?}");
+    /// Panics if `range` ends before it starts, is already mapped, or is longer than the size reported by `bus_device`.
+    pub fn add_range(&mut self, range: RangeInclusive<usize>, bus_device: Box<D>) {
+        assert!(range.start() <= range.end(), "Memory Range {range:#x?} is inverted");
+
+        // Make sure that the device covers the whole range
+        if let Some(size) = bus_device.size() {
+            assert!(range.end() - range.start() < size, "Memory Range {range:#x?} is larger than the mapped device of size {size:#x}");
         }
 
-        // Add the mapping
-        self.entries.push((range, bus_device));
+        // Guards were placed over the holes between the mappings, so nothing may be mapped over them later
+        for guard in &self.guards {
+            assert!(!(range.start() <= guard.end() && guard.start() <= range.end()), "Memory Range {range:#x?} overlaps guard {guard:#x?}");
+        }
+
+        // Only the mappings either side of where the range is inserted can overlap it, as the others are further away
+        let index = self.entries.partition_point(|(r, _)| r.start() < range.start());
+
+        for (r, _) in &self.entries[index.saturating_sub(1)..self.entries.len().min(index + 1)] {
+            assert!(!(range.start() <= r.end() && r.start() <= range.end()), "Memory Range {range:#x?} overlaps already mapped {r:#x?}");
+        }
+
+        // Add the mapping, keeping the entries sorted
+        self.entries.insert(index, (range, bus_device));
+        self.last_hit.set(None);
     }
 
-    /// Get a reference to the `dyn BusDevice` mapped to the given address
-    #[must_use]
-    pub fn mapping(&self, address: usize) -> Option<(&RangeInclusive<usize>, &dyn BusDevice)> {
-        for (range, device) in &self.entries {
-            if range.contains(&address) {
-                return Some((range, device.as_ref()));
-            }
+    /// Returns the index of the entry whose range covers `address`.
+    fn position(&self, address: usize) -> Option<usize> {
+        if let Some(index) = self.last_hit.get().filter(|index| self.entries[*index].0.contains(&address)) {
+            return Some(index);
+        }
+
+        let index = self.entries.partition_point(|(r, _)| *r.start() <= address).checked_sub(1)?;
+        let hit = self.entries[index].0.contains(&address).then_some(index);
+
+        if hit.is_some() {
+            self.last_hit.set(hit);
         }
 
-        None
+        hit
     }
 
-    /// Get a mutable reference to the `dyn BusDevice` mapped to the given address
+    /// Forgets the mapping found by the last lookup, so that the next lookup searches every mapping. Lookups are
+    /// correct either way, so this is only of use when measuring the cost of that search.
+    pub fn clear_lookup_cache(&self) {
+        self.last_hit.set(None);
+    }
+
+    /// Removes the mapping of exactly `range`, returning its device, or `None` if `range` is not mapped. Accesses to the
+    /// range then fail with `AddressNotMapped`, and it may be mapped again.
+    pub fn remove_range(&mut self, range: &RangeInclusive<usize>) -> Option<Box<D>> {
+        let index = self.entries.binary_search_by_key(&range.start(), |(r, _)| r.start()).ok().filter(|index| self.entries[*index].0 == *range)?;
+
+        self.guards.retain(|guard| guard != range);
+        self.last_hit.set(None);
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Removes whichever mapping covers `address`, returning its range and device, or `None` if `address` is not
+    /// mapped. As with `remove_range`, the range may then be mapped again.
+    pub fn remove_at(&mut self, address: usize) -> Option<(RangeInclusive<usize>, Box<D>)> {
+        let index = self.position(address)?;
+        let (range, device) = self.entries.remove(index);
+        self.last_hit.set(None);
+
+        self.guards.retain(|guard| *guard != range);
+        Some((range, device))
+    }
+
+    /// Get a reference to the device mapped to the given address
     #[must_use]
-    pub fn mut_mapping(&mut self, address: usize) -> Option<(&mut RangeInclusive<usize>, &mut dyn BusDevice)> {
-        for (range, device) in &mut self.entries {
-            if range.contains(&address) {
-                return Some((range, device.as_mut()));
-            }
-        }
+    pub fn mapping(&self, address: usize) -> Option<(&RangeInclusive<usize>, &D)> {
+        let (range, device) = &self.entries[self.position(address)?];
 
-        None
+        Some((range, device.as_ref()))
+    }
+
+    /// Get a mutable reference to the device mapped to the given address
+    #[must_use]
+    pub fn mut_mapping(&mut self, address: usize) -> Option<(&RangeInclusive<usize>, &mut D)> {
+        let index = self.position(address)?;
+        let (range, device) = &mut self.entries[index];
+
+        Some((range, device.as_mut()))
+    }
+
+    /// Returns an iterator over the mapped ranges and their devices, in ascending order of address. Guards placed by
+    /// `with_guards` are included.
+    pub fn iter(&self) -> impl Iterator<Item = (&RangeInclusive<usize>, &D)> {
+        self.entries.iter().map(|(range, device)| (range, device.as_ref()))
+    }
+
+    /// Returns an iterator over the mapped ranges and mutable references to their devices, in ascending order of
+    /// address, as `iter` does.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&RangeInclusive<usize>, &mut D)> {
+        self.entries.iter_mut().map(|(range, device)| (&*range, device.as_mut()))
+    }
+
+    /// Returns the number of mapped ranges, including guards.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if nothing is mapped.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the holes within `range` which are not covered by any mapped device, in ascending order.
+    #[must_use]
+    pub fn unmapped_ranges(&self, range: RangeInclusive<usize>) -> Vec<RangeInclusive<usize>> {
+        unmapped_ranges(self.entries.iter().map(|(r, _)| r), range)
+    }
+
+    /// Returns `true` if every address in `range` is covered by some mapped device.
+    #[must_use]
+    pub fn is_range_fully_mapped(&self, range: RangeInclusive<usize>) -> bool {
+        range.is_empty() || self.unmapped_ranges(range).is_empty()
     }
 }
 
-impl Default for MemoryMap {
+impl<D: ?Sized + BusDevice> Default for MemoryMap<D> {
     fn default() -> Self {
-        Self::new()
+        Self {
+            entries: Vec::new(),
+            guards: Vec::new(),
+            last_hit: Cell::new(None)
+        }
     }
 }
 
-impl BusDevice for MemoryMap {
+impl Clone for MemoryMap<dyn CloneBusDevice> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            guards: self.guards.clone(),
+            last_hit: self.last_hit.clone()
+        }
+    }
+}
+
+impl<D: ?Sized + BusDevice> BusDevice for MemoryMap<D> {
     fn read(&self, address: usize) -> Result<u8, crate::BusDeviceError> {
         self.mapping(address)
         .ok_or(BusDeviceError::AddressNotMapped { address })
@@ -89,11 +316,73 @@ impl BusDevice for MemoryMap {
         .map(|(range, mapped_device)| 
             mapped_device.write(address - range.start(), data))?
     }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.mapping(address)
+        .ok_or(BusDeviceError::AddressNotMapped { address })
+        .map(|(range, mapped_device)| 
+            mapped_device.peek(address - range.start()))?
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.mut_mapping(address)
+        .ok_or(BusDeviceError::AddressNotMapped { address })
+        .map(|(range, mapped_device)| 
+            mapped_device.poke(address - range.start(), data))?
+    }
+
+    /// Passes the access on to the mapped device as a whole if it lies within a single mapping, so that the device
+    /// sees its width, or otherwise reads each byte in turn.
+    fn read_bytes(&self, address: usize, buffer: &mut [u8]) -> Result<(), BusDeviceError> {
+        match self.mapping(address) {
+            Some((range, device)) if buffer.len() <= range.end() - address + 1 => device.read_bytes(address - range.start(), buffer),
+            _ => buffer.iter_mut().enumerate().try_for_each(|(i, slot)| {
+                *slot = self.read(address + i)?;
+                Ok(())
+            })
+        }
+    }
+
+    /// Passes the access on to the mapped device as a whole if it lies within a single mapping, so that the device
+    /// sees its width, or otherwise writes each byte in turn.
+    fn write_bytes(&mut self, address: usize, data: &[u8]) -> Result<(), BusDeviceError> {
+        match self.mut_mapping(address) {
+            Some((range, device)) if data.len() <= range.end() - address + 1 => device.write_bytes(address - *range.start(), data),
+            _ => data.iter().enumerate().try_for_each(|(i, byte)| self.write(address + i, *byte))
+        }
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(self.entries.last().map_or(0, |(range, _)| range.end() + 1))
+    }
+
+    /// Resets every mapped device, leaving the mappings themselves in place.
+    fn reset(&mut self) {
+        for (_, device) in &mut self.entries {
+            device.reset();
+        }
+    }
+}
+
+/// Reports the wait states of the mapped device, so that a nested map passes on those of the devices within it.
+impl<D: ?Sized + TimedBusDevice> TimedBusDevice for MemoryMap<D> {
+    fn read_timed(&self, address: usize) -> Result<(u8, u32), BusDeviceError> {
+        let (range, device) = self.mapping(address).ok_or(BusDeviceError::AddressNotMapped { address })?;
+        device.read_timed(address - range.start())
+    }
+
+    fn write_timed(&mut self, address: usize, data: u8) -> Result<u32, BusDeviceError> {
+        let (range, device) = self.mut_mapping(address).ok_or(BusDeviceError::AddressNotMapped { address })?;
+        device.write_timed(address - *range.start(), data)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::cast_possible_truncation)]
 mod tests {
-    use crate::Memory;
+    use std::cell::Cell;
+
+    use crate::{AccessKind, ConstantDevice, FifoDevice, FifoEmptyRead, FifoOverflow, Latch, Memory, ReadOnlyMemory, RegionBusDevice, FIFO_COUNT_OFFSET};
 
     use super::*;
 
@@ -111,61 +400,489 @@ mod tests {
 
     #[test]
     fn test_memory_map_single_at_start() {
-        let mut memory_map = MemoryMap::new().with_range((0..=7), Box::new(Memory::filled([0, 1, 2, 3, 4, 5, 6, 7])));
+        let memory_map = MemoryMap::new().with_range(0..=7, Box::new(Memory::filled([0, 1, 2, 3, 4, 5, 6, 7])));
+        crate::assert_region_eq!(memory_map, 0, [0, 1, 2, 3, 4, 5, 6, 7]);
 
-        for addr in TEST_ADDRESSES {
-            if *addr < 8 {
-                assert_eq!(memory_map.read(*addr), Ok((*addr % 256) as u8));
-            }
-            else {  
-                assert_eq!(memory_map.read(*addr), Err(BusDeviceError::AddressNotMapped { address: *addr }));
-            }
+        for addr in TEST_ADDRESSES.iter().filter(|addr| **addr >= 8) {
+            assert_eq!(memory_map.read(*addr), Err(BusDeviceError::AddressNotMapped { address: *addr }));
         }
     }
 
     #[test]
     fn test_memory_map_single_in_middle_start() {
-        let mut memory_map = MemoryMap::new().with_range((4..=11), Box::new(Memory::filled([0, 1, 2, 3, 4, 5, 6, 7])));
+        let memory_map = MemoryMap::new().with_range(4..=11, Box::new(Memory::filled([0, 1, 2, 3, 4, 5, 6, 7])));
+        crate::assert_region_eq!(memory_map, 4, [0, 1, 2, 3, 4, 5, 6, 7]);
 
-        for addr in 0..16 {
-            if addr >= 4 && addr < 12 {
-                assert_eq!(memory_map.read(addr), Ok(((addr - 4) % 256) as u8));
-            }
-            else {  
-                assert_eq!(memory_map.read(addr), Err(BusDeviceError::AddressNotMapped { address: addr }));
-            }
+        for addr in (0..4).chain(12..16) {
+            assert_eq!(memory_map.read(addr), Err(BusDeviceError::AddressNotMapped { address: addr }));
         }
     }
 
     #[test]
     fn test_memory_map_multiple_continuous() {
-        let mut memory_map = MemoryMap::new()
-            .with_range((0..=3), Box::new(Memory::filled([0, 1, 2, 3])))
-            .with_range((4..=7), Box::new(Memory::filled([4, 5, 6, 7])));
+        let memory_map = MemoryMap::new()
+            .with_range(0..=3, Box::new(Memory::filled([0, 1, 2, 3])))
+            .with_range(4..=7, Box::new(Memory::filled([4, 5, 6, 7])));
+        crate::assert_region_eq!(memory_map, 0, [0, 1, 2, 3, 4, 5, 6, 7]);
 
-        for addr in 0..16 {
-            if addr < 8 {
-                assert_eq!(memory_map.read(addr), Ok((addr % 256) as u8));
-            }
-            else {  
-                assert_eq!(memory_map.read(addr), Err(BusDeviceError::AddressNotMapped { address: addr }));
-            }
+        for addr in 8..16 {
+            assert_eq!(memory_map.read(addr), Err(BusDeviceError::AddressNotMapped { address: addr }));
         }
     }
 
     #[test]
     fn test_memory_map_multiple_discontinuous() {
+        let memory_map = MemoryMap::new()
+            .with_range(0..=3, Box::new(Memory::filled([0, 1, 2, 3])))
+            .with_range(6..=7, Box::new(Memory::filled([6, 7])));
+        crate::assert_region_eq!(memory_map, 0, [0, 1, 2, 3]);
+        crate::assert_region_eq!(memory_map, 6, [6, 7]);
+
+        for addr in (4..6).chain(8..16) {
+            assert_eq!(memory_map.read(addr), Err(BusDeviceError::AddressNotMapped { address: addr }));
+        }
+    }
+
+    /// A device whose reads count how many times they have happened, while peeks leave the count alone.
+    struct CountingDevice {
+        reads: Cell<u8>,
+        pokes: u8
+    }
+
+    impl BusDevice for CountingDevice {
+        fn read(&self, _address: usize) -> Result<u8, BusDeviceError> {
+            self.reads.set(self.reads.get() + 1);
+            Ok(self.reads.get())
+        }
+
+        fn write(&mut self, address: usize, _data: u8) -> Result<(), BusDeviceError> {
+            Err(BusDeviceError::AddressNotWritable { address })
+        }
+
+        fn peek(&self, _address: usize) -> Result<u8, BusDeviceError> {
+            Ok(self.reads.get())
+        }
+
+        fn poke(&mut self, _address: usize, data: u8) -> Result<(), BusDeviceError> {
+            self.pokes = data;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_memory_map_reset() {
+        let ports = MemoryMap::new()
+            .with_range(0x0..=0x2, Box::new(FifoDevice::new(4, FifoEmptyRead::Zero, FifoOverflow::DropNewest)));
+
+        let mut memory_map = MemoryMap::new()
+            .with_range(0x00..=0x0F, Box::new(Memory::<16>::empty()))
+            .with_range(0x80..=0x80, Box::new(Latch::new([0xFF])))
+            .with_range(0x90..=0x92, Box::new(ports));
+
+        assert_eq!(memory_map.write_region(0x00, &[1, 2, 3, 4]), Ok(()));
+        assert_eq!(memory_map.write(0x80, 0x42), Ok(()));
+        assert_eq!(memory_map.write(0x90, 0xAA), Ok(()));
+        assert_eq!(memory_map.read(0x90 + FIFO_COUNT_OFFSET), Ok(1));
+
+        // Registers return to their power-on values, including within nested maps, while RAM keeps its contents
+        memory_map.reset();
+        assert_eq!(memory_map.read(0x80), Ok(0xFF));
+        assert_eq!(memory_map.read(0x90 + FIFO_COUNT_OFFSET), Ok(0));
+        assert_eq!(memory_map.read_region(0x00), Ok([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_memory_map_peek_poke() {
+        let mut memory_map = MemoryMap::new()
+            .with_range(0x00..=0x0F, Box::new(Memory::<16>::filled([7; 16])))
+            .with_range(0x10..=0x10, Box::new(CountingDevice { reads: Cell::new(0), pokes: 0 }));
+
+        assert_eq!(memory_map.peek(0x10), Ok(0));
+        assert_eq!(memory_map.peek(0x10), Ok(0));
+        assert_eq!(memory_map.read(0x10), Ok(1));
+        assert_eq!(memory_map.read(0x10), Ok(2));
+        assert_eq!(memory_map.peek(0x10), Ok(2));
+
+        assert_eq!(memory_map.write(0x10, 5), Err(BusDeviceError::AddressNotWritable { address: 0 }));
+        assert_eq!(memory_map.poke(0x10, 5), Ok(()));
+
+        // Devices without side effects peek and poke through their reads and writes
+        assert_eq!(memory_map.poke(0x03, 9), Ok(()));
+        assert_eq!(memory_map.peek(0x03), Ok(9));
+        assert_eq!(memory_map.read(0x03), Ok(9));
+
+        assert_eq!(memory_map.peek(0x11), Err(BusDeviceError::AddressNotMapped { address: 0x11 }));
+        assert_eq!(memory_map.poke(0x11, 0), Err(BusDeviceError::AddressNotMapped { address: 0x11 }));
+    }
+
+    #[test]
+    fn test_memory_map_cstr() {
+        let mut memory_map = MemoryMap::new()
+            .with_range(0x00..=0x0F, Box::new(Memory::<16>::empty()))
+            .with_range(0x10..=0x1F, Box::new(Memory::<16>::empty()));
+
+        // Strings can straddle the boundary between devices
+        assert_eq!(memory_map.write_cstr(0x0C, b"Hello"), Ok(()));
+        assert_eq!(memory_map.read(0x11), Ok(0));
+        assert_eq!(memory_map.read_cstr(0x0C, 16), Ok((b"Hello".to_vec(), true)));
+        assert_eq!(memory_map.read_cstr(0x0E, 16), Ok((b"llo".to_vec(), true)));
+
+        // Running out of length without a terminator returns exactly `max_len` bytes, told apart by the flag
+        assert_eq!(memory_map.read_cstr(0x0C, 5), Ok((b"Hello".to_vec(), false)));
+        assert_eq!(memory_map.read_cstr(0x0C, 6), Ok((b"Hello".to_vec(), true)));
+        assert_eq!(memory_map.read_cstr(0x0C, 0), Ok((Vec::new(), false)));
+
+        assert_eq!(memory_map.write_terminated(0x18, b"DOS", b'$'), Ok(()));
+        assert_eq!(memory_map.read_terminated(0x18, b'$', 8), Ok((b"DOS".to_vec(), true)));
+        assert_eq!(memory_map.read_cstr(0x18, 8), Ok((b"DOS$".to_vec(), true)));
+
+        // Reading or writing past the end of the mapped memory returns the bus error
+        assert_eq!(memory_map.write_cstr(0x1C, b"abcd"), Err(BusDeviceError::AddressNotMapped { address: 0x20 }));
+        assert_eq!(memory_map.read_cstr(0x1C, 8), Err(BusDeviceError::AddressNotMapped { address: 0x20 }));
+        assert_eq!(memory_map.write_cstr(0x1C, b"abc"), Ok(()));
+        assert_eq!(memory_map.read_cstr(0x1C, 8), Ok((b"abc".to_vec(), true)));
+    }
+
+    #[test]
+    fn test_cstr_top_of_address_space() {
+        let mut device = ConstantDevice::open_bus();
+        let error = BusDeviceError::AddressOutOfBounds { address: usize::MAX, size: usize::MAX };
+
+        assert_eq!(device.read_cstr(usize::MAX - 1, 2), Ok((vec![0xFF, 0xFF], false)));
+        assert_eq!(device.read_cstr(usize::MAX - 1, 3), Err(error));
+        assert_eq!(device.read_terminated(usize::MAX, 0xFF, 8), Ok((Vec::new(), true)));
+        assert_eq!(device.write_cstr(usize::MAX - 2, b"ab"), Ok(()));
+        assert_eq!(device.write_cstr(usize::MAX - 1, b"ab"), Err(error));
+    }
+
+    #[test]
+    fn test_memory_map_size() {
+        assert_eq!(MemoryMap::new().size(), Some(0));
+        assert_eq!(Memory::<0x1000>::empty().size(), Some(0x1000));
+        assert_eq!(ReadOnlyMemory::<4>::empty().size(), Some(4));
+        assert_eq!(ConstantDevice::open_bus().size(), None);
+
+        let memory_map = MemoryMap::new()
+            .with_range(0x2000..=0x2FFF, Box::new(Memory::<0x1000>::empty()))
+            .with_range(0x0000..=0x07FF, Box::new(Memory::<0x1000>::empty()))
+            // Unbounded devices can be mapped over any range
+            .with_range(0x10000..=0xFFFFF, Box::new(ConstantDevice::open_bus()));
+
+        assert_eq!(memory_map.size(), Some(0x10_0000));
+    }
+
+    #[test]
+    #[should_panic(expected = "is larger than the mapped device of size 0x1000")]
+    fn test_memory_map_size_mismatch() {
+        let _ = MemoryMap::new()
+            .with_range(0x0000..=0x1FFF, Box::new(Memory::<0x1000>::empty()));
+    }
+
+    #[test]
+    #[should_panic(expected = "is inverted")]
+    fn test_memory_map_inverted_range() {
+        let _ = MemoryMap::new()
+            .with_range(RangeInclusive::new(0x1FFF, 0x0000), Box::new(Memory::<0x1000>::empty()));
+    }
+
+    #[test]
+    fn test_memory_map_clone() {
+        let mut original = MemoryMap::cloneable()
+            .with_range(0..=3, Box::new(Memory::filled([0, 1, 2, 3])))
+            .with_range(4..=5, Box::new(ReadOnlyMemory::filled([4, 5])));
+
+        let mut copy = original.clone();
+
+        assert_eq!(copy.write(1, 42), Ok(()));
+        assert_eq!(copy.write(4, 42), Err(BusDeviceError::AddressNotWritable { address: 0 }));
+        assert_eq!(original.write(2, 43), Ok(()));
+
+        assert_eq!(original.read_region(0), Ok([0, 1, 43, 3, 4, 5]));
+        assert_eq!(copy.read_region(0), Ok([0, 42, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn test_memory_map_clone_nested() {
+        let inner = MemoryMap::cloneable()
+            .with_range(0..=1, Box::new(Memory::filled([0, 1])));
+
+        let mut outer = MemoryMap::cloneable()
+            .with_range(0x100..=0x101, Box::new(inner));
+
+        let copy = outer.clone();
+
+        assert_eq!(outer.write(0x101, 42), Ok(()));
+        assert_eq!(outer.read(0x101), Ok(42));
+        assert_eq!(copy.read(0x101), Ok(1));
+
+        // A cloneable map can be mapped into an ordinary one
+        let map = MemoryMap::new().with_range(0..=0x101, Box::new(copy));
+        assert_eq!(map.read_region(0x100), Ok([0, 1]));
+    }
+
+    #[test]
+    fn test_memory_map_fully_mapped() {
+        let memory_map = MemoryMap::new()
+            .with_range(8..=11, Box::new(Memory::filled([8, 9, 10, 11])))
+            .with_range(0..=3, Box::new(Memory::filled([0, 1, 2, 3])))
+            .with_range(4..=7, Box::new(Memory::filled([4, 5, 6, 7])))
+            .with_range(14..=15, Box::new(Memory::filled([14, 15])));
+
+        assert!(memory_map.is_range_fully_mapped(0..=11));
+        assert!(memory_map.is_range_fully_mapped(2..=9));
+        assert!(memory_map.is_range_fully_mapped(5..=5));
+        assert!(memory_map.is_range_fully_mapped(14..=15));
+
+        assert!(!memory_map.is_range_fully_mapped(0..=12));
+        assert!(!memory_map.is_range_fully_mapped(10..=14));
+        assert!(!memory_map.is_range_fully_mapped(12..=13));
+        assert!(!memory_map.is_range_fully_mapped(14..=16));
+    }
+
+    #[test]
+    fn test_memory_map_fully_mapped_empty() {
+        let memory_map = MemoryMap::new();
+
+        assert!(!memory_map.is_range_fully_mapped(0..=0));
+        assert!(!memory_map.is_range_fully_mapped(0x0000..=0x9FFFF));
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 4..=3;
+        assert!(memory_map.is_range_fully_mapped(empty));
+    }
+
+    #[test]
+    fn test_memory_map_unmapped_ranges() {
+        let memory_map = MemoryMap::new()
+            .with_range(0x100..=0x1FF, Box::new(Memory::<0x100>::empty()))
+            .with_range(0x400..=0x4FF, Box::new(Memory::<0x100>::empty()))
+            .with_range(0x200..=0x2FF, Box::new(Memory::<0x100>::empty()));
+
+        assert_eq!(memory_map.unmapped_ranges(0..=0x5FF), [0..=0xFF, 0x300..=0x3FF, 0x500..=0x5FF]);
+        assert_eq!(memory_map.unmapped_ranges(0x180..=0x47F), [0x300..=0x3FF]);
+        assert_eq!(memory_map.unmapped_ranges(0x2F0..=0x310), [0x300..=0x310]);
+        assert_eq!(memory_map.unmapped_ranges(0x100..=0x2FF), []);
+        assert_eq!(MemoryMap::new().unmapped_ranges(0x10..=0x20), [0x10..=0x20]);
+
+        let top = MemoryMap::new().with_range(usize::MAX - 1..=usize::MAX, Box::new(Memory::<2>::empty()));
+        assert_eq!(top.unmapped_ranges(usize::MAX - 3..=usize::MAX), [usize::MAX - 3..=usize::MAX - 2]);
+    }
+
+    #[test]
+    fn test_memory_map_guards_between() {
+        let mut memory_map = MemoryMap::new()
+            .with_range(0x100..=0x1FF, Box::new(Memory::<0x100>::empty()))
+            .with_range(0x400..=0x4FF, Box::new(Memory::<0x100>::empty()))
+            .with_guards(false);
+
+        // Only the hole between the two mappings is guarded
+        assert_eq!(memory_map.mapping(0x200).map(|(range, _)| range.clone()), Some(0x200..=0x3FF));
+        assert_eq!(memory_map.read(0x37F), Err(BusDeviceError::GuardViolation { address: 0x37F, kind: AccessKind::Read }));
+        assert_eq!(memory_map.write(0x200, 0), Err(BusDeviceError::GuardViolation { address: 0x200, kind: AccessKind::Write }));
+
+        // A stray region write runs into the guard at the end of the first mapping
+        assert_eq!(memory_map.write_region(0x1FE, &[1, 2, 3]), Err(BusDeviceError::GuardViolation { address: 0x200, kind: AccessKind::Write }));
+
+        assert_eq!(memory_map.read(0x0FF), Err(BusDeviceError::AddressNotMapped { address: 0x0FF }));
+        assert_eq!(memory_map.read(0x500), Err(BusDeviceError::AddressNotMapped { address: 0x500 }));
+        assert_eq!(memory_map.unmapped_ranges(0x100..=0x4FF), []);
+    }
+
+    #[test]
+    fn test_memory_map_guards_gap_fill() {
+        let memory_map = MemoryMap::new()
+            .with_range(0x10000..=0x1FFFF, Box::new(Memory::<0x10000>::empty()))
+            .with_range(0x40000..=0x4FFFF, Box::new(Memory::<0x10000>::empty()))
+            .with_guards(true);
+
+        // The holes at address 0 and above the top mapping are guarded up to the top of the 1 MiB address space
+        let guards: Vec<_> = [0x00000, 0x20000, 0x50000].into_iter()
+            .map(|address| memory_map.mapping(address).map(|(range, _)| range.clone()))
+            .collect();
+
+        assert_eq!(guards, [Some(0x00000..=0x0FFFF), Some(0x20000..=0x3FFFF), Some(0x50000..=0xFFFFF)]);
+        assert_eq!(memory_map.read(0x00000), Err(BusDeviceError::GuardViolation { address: 0x00000, kind: AccessKind::Read }));
+        assert_eq!(memory_map.read(0xFFFFF), Err(BusDeviceError::GuardViolation { address: 0xFFFFF, kind: AccessKind::Read }));
+        assert_eq!(memory_map.read(0x10_0000), Err(BusDeviceError::AddressNotMapped { address: 0x10_0000 }));
+        assert!(memory_map.is_range_fully_mapped(0..=0xFFFFF));
+
+        let empty = MemoryMap::new().with_guards(true);
+        assert_eq!(empty.mapping(0x12345).map(|(range, _)| range.clone()), Some(0..=0xFFFFF));
+        assert!(MemoryMap::new().with_guards(false).mapping(0).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps guard")]
+    fn test_memory_map_guards_overlap() {
+        let _ = MemoryMap::new()
+            .with_range(0x100..=0x1FF, Box::new(Memory::<0x100>::empty()))
+            .with_range(0x400..=0x4FF, Box::new(Memory::<0x100>::empty()))
+            .with_guards(false)
+            // Covers the whole guard without either end falling inside it
+            .with_range(0x000..=0x5FF, Box::new(ConstantDevice::open_bus()));
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps already mapped")]
+    fn test_memory_map_overlap_enclosing() {
+        let _ = MemoryMap::new()
+            .with_range(16..=23, Box::new(Memory::<8>::empty()))
+            .with_range(0..=255, Box::new(Memory::<256>::empty()));
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps already mapped")]
+    fn test_memory_map_overlap_enclosed() {
+        let _ = MemoryMap::new()
+            .with_range(0..=255, Box::new(Memory::<256>::empty()))
+            .with_range(16..=23, Box::new(Memory::<8>::empty()));
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps already mapped")]
+    fn test_memory_map_overlap_identical() {
+        let _ = MemoryMap::new()
+            .with_range(16..=23, Box::new(Memory::<8>::empty()))
+            .with_range(16..=23, Box::new(Memory::<8>::empty()));
+    }
+
+    #[test]
+    fn test_memory_map_adjacent() {
+        let memory_map = MemoryMap::new()
+            .with_range(16..=23, Box::new(Memory::<8>::filled_with(0xAA)))
+            .with_range(8..=15, Box::new(Memory::<8>::filled_with(0xBB)))
+            .with_range(24..=31, Box::new(Memory::<8>::filled_with(0xCC)));
+
+        assert_eq!((memory_map.read(15), memory_map.read(16)), (Ok(0xBB), Ok(0xAA)));
+        assert_eq!((memory_map.read(23), memory_map.read(24)), (Ok(0xAA), Ok(0xCC)));
+    }
+
+    #[test]
+    fn test_memory_map_remove() {
         let mut memory_map = MemoryMap::new()
-            .with_range((0..=3), Box::new(Memory::filled([0, 1, 2, 3])))
-            .with_range((6..=7), Box::new(Memory::filled([6, 7])));
+            .with_range(0..=3, Box::new(Memory::filled([0, 1, 2, 3])))
+            .with_range(4..=7, Box::new(Memory::filled([4, 5, 6, 7])))
+            .with_range(8..=11, Box::new(Memory::filled([8, 9, 10, 11])));
 
-        for addr in 0..16 {
-            if addr < 4 || (addr >= 6 && addr < 8) {
-                assert_eq!(memory_map.read(addr), Ok((addr % 256) as u8));
-            }
-            else {  
-                assert_eq!(memory_map.read(addr), Err(BusDeviceError::AddressNotMapped { address: addr }));
-            }
+        assert!(memory_map.remove_range(&(4..=6)).is_none());
+        let middle = memory_map.remove_range(&(4..=7)).unwrap();
+        assert_eq!(middle.read(1), Ok(5));
+
+        for addr in 4..8 {
+            assert_eq!(memory_map.read(addr), Err(BusDeviceError::AddressNotMapped { address: addr }));
         }
+
+        memory_map.add_range(4..=7, Box::new(ConstantDevice::open_bus()));
+        crate::assert_region_eq!(memory_map, 0, [0, 1, 2, 3, 0xFF, 0xFF, 0xFF, 0xFF, 8, 9, 10, 11]);
+
+        let (range, device) = memory_map.remove_at(6).unwrap();
+        assert_eq!((range, device.read(0)), (4..=7, Ok(0xFF)));
+        assert!(memory_map.remove_at(6).is_none());
+        assert_eq!(memory_map.read(6), Err(BusDeviceError::AddressNotMapped { address: 6 }));
+
+        // Reusing the device removed first
+        memory_map.add_range(4..=7, middle);
+        crate::assert_region_eq!(memory_map, 0, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_memory_map_many_unordered() {
+        let mut memory_map = MemoryMap::new();
+
+        // Thirty mappings of 16 bytes with 16 byte holes between them, added out of order
+        for i in (0..30).map(|i| (i * 7) % 30) {
+            memory_map.add_range(i * 32..=i * 32 + 15, Box::new(Memory::<16>::filled_with(u8::try_from(i).unwrap())));
+        }
+
+        for i in 0..30 {
+            let (range, _) = memory_map.mapping(i * 32 + 8).unwrap();
+            assert_eq!(*range, i * 32..=i * 32 + 15);
+            assert_eq!(memory_map.read(i * 32 + 15), Ok(u8::try_from(i).unwrap()));
+            assert_eq!(memory_map.read(i * 32 + 16), Err(BusDeviceError::AddressNotMapped { address: i * 32 + 16 }));
+        }
+
+        assert_eq!(memory_map.size(), Some(29 * 32 + 16));
+        assert_eq!(memory_map.unmapped_ranges(0..=63), vec![16..=31, 48..=63]);
+        assert!(memory_map.remove_range(&(64..=79)).is_some());
+        assert!(memory_map.mapping(64).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps already mapped")]
+    fn test_memory_map_overlap_between_unordered() {
+        let _ = MemoryMap::new()
+            .with_range(32..=47, Box::new(Memory::<16>::empty()))
+            .with_range(0..=15, Box::new(Memory::<16>::empty()))
+            .with_range(64..=79, Box::new(Memory::<16>::empty()))
+            .with_range(40..=55, Box::new(Memory::<16>::empty()));
+    }
+
+    #[test]
+    fn test_memory_map_last_hit_alternating() {
+        let mut memory_map = MemoryMap::new()
+            .with_range(0x000..=0x0FF, Box::new(Memory::<0x100>::filled_with(0x11)))
+            .with_range(0x100..=0x1FF, Box::new(Memory::<0x100>::filled_with(0x22)));
+
+        for offset in 0..0x100 {
+            assert_eq!(memory_map.read(offset), Ok(0x11));
+            assert_eq!(memory_map.read(0x100 + offset), Ok(0x22));
+            assert_eq!(memory_map.write(offset, 0x33), Ok(()));
+            assert_eq!(memory_map.write(0x100 + offset, 0x44), Ok(()));
+            assert_eq!(memory_map.read(0x200 + offset), Err(BusDeviceError::AddressNotMapped { address: 0x200 + offset }));
+        }
+
+        crate::assert_region_eq!(memory_map, 0x000, [0x33; 0x100]);
+        crate::assert_region_eq!(memory_map, 0x100, [0x44; 0x100]);
+
+        memory_map.clear_lookup_cache();
+        assert_eq!(memory_map.read(0x1FF), Ok(0x44));
+    }
+
+    #[test]
+    fn test_memory_map_last_hit_after_removal() {
+        let mut memory_map = MemoryMap::new()
+            .with_range(0x00..=0x0F, Box::new(Memory::<0x10>::filled_with(0x11)))
+            .with_range(0x10..=0x1F, Box::new(Memory::<0x10>::filled_with(0x22)))
+            .with_range(0x20..=0x2F, Box::new(Memory::<0x10>::filled_with(0x33)));
+
+        // The last hit is the middle mapping, which is then removed, moving the entry after it down into its place
+        assert_eq!(memory_map.read(0x18), Ok(0x22));
+        assert!(memory_map.remove_range(&(0x10..=0x1F)).is_some());
+        assert_eq!(memory_map.read(0x18), Err(BusDeviceError::AddressNotMapped { address: 0x18 }));
+        assert_eq!(memory_map.read(0x28), Ok(0x33));
+
+        // Inserting before the last hit moves it up
+        assert!(memory_map.remove_at(0x08).is_some());
+        memory_map.add_range(0x00..=0x0F, Box::new(Memory::<0x10>::filled_with(0x44)));
+        assert_eq!(memory_map.read(0x28), Ok(0x33));
+        assert_eq!(memory_map.read(0x08), Ok(0x44));
+    }
+
+    #[test]
+    fn test_memory_map_iter() {
+        let mut memory_map = MemoryMap::new();
+        assert!(memory_map.is_empty());
+
+        for start in [0x40, 0x10, 0x30, 0x00, 0x20] {
+            memory_map.add_range(start..=start + 7, Box::new(Memory::<8>::filled_with(u8::try_from(start).unwrap())));
+        }
+
+        assert_eq!(memory_map.len(), 5);
+        assert!(!memory_map.is_empty());
+
+        let ranges: Vec<_> = memory_map.iter().map(|(range, device)| (range.clone(), device.read(0))).collect();
+        assert_eq!(ranges, [
+            (0x00..=0x07, Ok(0x00)),
+            (0x10..=0x17, Ok(0x10)),
+            (0x20..=0x27, Ok(0x20)),
+            (0x30..=0x37, Ok(0x30)),
+            (0x40..=0x47, Ok(0x40))
+        ]);
+
+        for (range, device) in memory_map.iter_mut() {
+            assert_eq!(device.write(0, u8::try_from(*range.end()).unwrap()), Ok(()));
+        }
+
+        assert_eq!(memory_map.read(0x30), Ok(0x37));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{BusDevice, BusDeviceError};
+
+/// Wraps a device so that it only sees the low bits of each address, as with a chip which is only partially decoded.
+///
+/// Every address is masked with `mask` before being passed to the inner device, so mapping a 16 KiB memory with a
+/// mask of `0x3FFF` over a 64 KiB range makes the same bytes visible at four aliases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Mirrored<T: BusDevice> {
+    inner: T,
+    mask: usize
+}
+
+impl<T: BusDevice> Mirrored<T> {
+    #[must_use]
+    /// Wraps `inner`, masking every address with `mask`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` is not a contiguous run of set low bits (one less than a power of two).
+    pub const fn new(inner: T, mask: usize) -> Self {
+        assert!(mask & mask.wrapping_add(1) == 0, "mirror mask must be one less than a power of two");
+
+        Self { inner, mask }
+    }
+
+    #[must_use]
+    /// Returns the mask applied to every address.
+    pub const fn mask(&self) -> usize {
+        self.mask
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: BusDevice> BusDevice for Mirrored<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.read(address & self.mask)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.write(address & self.mask, data)
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(address & self.mask)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.poke(address & self.mask, data)
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, ReadOnlyMemory};
+
+    use super::*;
+
+    #[test]
+    fn test_mirrored_aliases() {
+        let mut map = MemoryMap::new()
+            .with_range(0x0000..=0xFFFF, Box::new(Mirrored::new(Memory::<0x4000>::empty(), 0x3FFF)));
+
+        assert_eq!(map.write(0x4123, 0x5A), Ok(()));
+
+        for alias in [0x0123, 0x4123, 0x8123, 0xC123] {
+            assert_eq!(map.read(alias), Ok(0x5A));
+        }
+
+        assert_eq!(map.write(0xFFFF, 0xA5), Ok(()));
+        assert_eq!(map.read(0x3FFF), Ok(0xA5));
+    }
+
+    #[test]
+    fn test_mirrored_propagates_errors() {
+        // A mask wider than the device lets addresses past its end through
+        let mut device = Mirrored::new(Memory::<4>::empty(), 0x7);
+
+        assert_eq!(device.read(0x0B), Ok(0));
+        assert_eq!(device.read(0x0E), Err(BusDeviceError::AddressOutOfBounds { address: 6, size: 4 }));
+        assert_eq!(device.write(0x17, 0), Err(BusDeviceError::AddressOutOfBounds { address: 7, size: 4 }));
+
+        let mut device = Mirrored::new(ReadOnlyMemory::<4>::filled([1, 2, 3, 4]), 0x3);
+
+        assert_eq!(device.read(0x102), Ok(3));
+        assert_eq!(device.write(0x102, 0), Err(BusDeviceError::AddressNotWritable { address: 2 }));
+    }
+
+    #[test]
+    fn test_mirrored_full_mask() {
+        let device = Mirrored::new(Memory::<4>::filled([1, 2, 3, 4]), usize::MAX);
+
+        assert_eq!(device.mask(), usize::MAX);
+        assert_eq!(device.read(3), Ok(4));
+        assert_eq!(device.into_inner(), Memory::filled([1, 2, 3, 4]));
+    }
+
+    #[test]
+    #[should_panic(expected = "mirror mask must be one less than a power of two")]
+    fn test_mirrored_invalid_mask() {
+        let _ = Mirrored::new(Memory::<0x3000>::empty(), 0x2FFF);
+    }
+}
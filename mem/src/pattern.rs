@@ -0,0 +1,114 @@
+use crate::Memory;
+
+/// The contents of RAM at power on, which real DRAM does not guarantee to be zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Pattern {
+    /// Every byte is `00h`.
+    Zero,
+    /// Every byte is `FFh`.
+    AllOnes,
+    /// Rows of the given number of bytes alternate between `AAh` and `55h`, starting with `AAh`, as DRAM cells often
+    /// settle in stripes following the layout of the chip.
+    Alternating(usize),
+    /// Bytes from a small deterministic generator, so that identical seeds produce identical contents on every
+    /// platform and runs can be reproduced.
+    Random { seed: u64 }
+}
+
+impl Pattern {
+    /// Fills `buffer` with the pattern, as if it started at offset zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is `Alternating` with rows of zero bytes.
+    pub fn fill(self, buffer: &mut [u8]) {
+        match self {
+            Self::Zero => buffer.fill(0x00),
+            Self::AllOnes => buffer.fill(0xFF),
+            Self::Alternating(row) => {
+                assert!(row > 0, "Rows of the alternating pattern must be at least one byte long");
+
+                for (index, chunk) in buffer.chunks_mut(row).enumerate() {
+                    chunk.fill(if index.is_multiple_of(2) { 0xAA } else { 0x55 });
+                }
+            }
+            Self::Random { seed } => {
+                let mut generator = SplitMix64(seed);
+
+                for chunk in buffer.chunks_mut(8) {
+                    let bytes = generator.next().to_le_bytes();
+                    chunk.copy_from_slice(&bytes[..chunk.len()]);
+                }
+            }
+        }
+    }
+}
+
+/// The `SplitMix64` generator, chosen as it is tiny, fully specified, and uses only wrapping 64 bit arithmetic, so its
+/// output does not depend on the platform.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// Advances the generator, returning its next output.
+    const fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl<const SIZE: usize> Memory<SIZE> {
+    #[must_use]
+    /// Constructs a new memory region holding `pattern`, as RAM might at power on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is `Alternating` with rows of zero bytes.
+    pub fn power_on_pattern(pattern: Pattern) -> Self {
+        let mut memory = Self::empty();
+        pattern.fill(memory.as_mut_slice());
+
+        memory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_fixed() {
+        assert_eq!(Memory::<4>::power_on_pattern(Pattern::Zero), Memory::empty());
+        assert_eq!(Memory::<4>::power_on_pattern(Pattern::AllOnes), Memory::filled_with(0xFF));
+
+        let memory = Memory::<10>::power_on_pattern(Pattern::Alternating(4));
+        assert_eq!(memory.as_slice(), [0xAA, 0xAA, 0xAA, 0xAA, 0x55, 0x55, 0x55, 0x55, 0xAA, 0xAA]);
+    }
+
+    #[test]
+    fn test_pattern_random() {
+        // The first outputs of `SplitMix64`, little endian
+        let memory = Memory::<12>::power_on_pattern(Pattern::Random { seed: 0 });
+        assert_eq!(memory.as_slice(), [0xAF, 0xCD, 0x1D, 0x7B, 0x39, 0xA8, 0x20, 0xE2, 0xF4, 0x65, 0xB9, 0xA1]);
+
+        let memory = Memory::<8>::power_on_pattern(Pattern::Random { seed: 0x8086 });
+        assert_eq!(memory.as_slice(), [0xD5, 0x73, 0x61, 0xC8, 0xA6, 0xBD, 0x9C, 0xA5]);
+
+        // Identical seeds give identical contents, and a shorter fill is a prefix of a longer one
+        let a = Memory::<0x1000>::power_on_pattern(Pattern::Random { seed: 42 });
+        let b = Memory::<0x1000>::power_on_pattern(Pattern::Random { seed: 42 });
+        let c = Memory::<0x1000>::power_on_pattern(Pattern::Random { seed: 43 });
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(Memory::<5>::power_on_pattern(Pattern::Random { seed: 42 }).as_slice(), &a.as_slice()[..5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one byte long")]
+    fn test_pattern_empty_rows() {
+        let _memory = Memory::<4>::power_on_pattern(Pattern::Alternating(0));
+    }
+}
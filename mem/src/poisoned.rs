@@ -0,0 +1,89 @@
+use crate::{BusDevice, BusDeviceError};
+
+/// A device which fails every access with `Poisoned`, left in place of a mapping which has been removed.
+///
+/// Without it an access through a stale address would fail with `AddressNotMapped`, or worse succeed once something
+/// else is mapped there, giving no hint as to what the guest expected to find. The label names the old mapping, such
+/// as `"EMS page 2"`, and is carried by the error. As with `GuardDevice` the device knows the address it is mapped at,
+/// so that errors report the address as seen on the bus. Peeks and pokes fail in the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Poisoned {
+    base: usize,
+    label: &'static str
+}
+
+impl Poisoned {
+    #[must_use]
+    /// Constructs a poisoned region mapped starting at `base`, describing the removed mapping by `label`.
+    pub const fn new(base: usize, label: &'static str) -> Self {
+        Self { base, label }
+    }
+
+    #[must_use]
+    /// Returns the address the region is mapped at.
+    pub const fn base(&self) -> usize {
+        self.base
+    }
+
+    #[must_use]
+    /// Returns the label describing the removed mapping.
+    pub const fn label(&self) -> &'static str {
+        self.label
+    }
+}
+
+impl BusDevice for Poisoned {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        Err(BusDeviceError::Poisoned { address: self.base + address, label: self.label })
+    }
+
+    fn write(&mut self, address: usize, _data: u8) -> Result<(), BusDeviceError> {
+        Err(BusDeviceError::Poisoned { address: self.base + address, label: self.label })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_poisoned_device() {
+        let mut poisoned = Poisoned::new(0xD0000, "EMS page 0");
+
+        assert_eq!(poisoned.read(4), Err(BusDeviceError::Poisoned { address: 0xD0004, label: "EMS page 0" }));
+        assert_eq!(poisoned.write(0, 1), Err(BusDeviceError::Poisoned { address: 0xD0000, label: "EMS page 0" }));
+        assert_eq!(poisoned.peek(1), Err(BusDeviceError::Poisoned { address: 0xD0001, label: "EMS page 0" }));
+        assert_eq!(poisoned.size(), None);
+    }
+
+    #[test]
+    fn test_poisoned_after_unmap() {
+        let mut map = MemoryMap::new()
+            .with_range(0x00000..=0x00FFF, Box::new(Memory::<0x1000>::empty()))
+            .with_range(0xD0000..=0xD3FFF, Box::new(Memory::<0x4000>::filled_with(0x11)))
+            .with_range(0xD4000..=0xD7FFF, Box::new(Memory::<0x4000>::filled_with(0x22)));
+
+        // Switching out a page returns its device, and later accesses name it
+        let page = map.remove_range_poisoned(&(0xD4000..=0xD7FFF), "EMS page 1").unwrap();
+        assert_eq!(page.read(0), Ok(0x22));
+
+        assert_eq!(map.read(0xD4010), Err(BusDeviceError::Poisoned { address: 0xD4010, label: "EMS page 1" }));
+        assert_eq!(map.write_region(0xD3FFF, &[0, 0]), Err(BusDeviceError::Poisoned { address: 0xD4000, label: "EMS page 1" }));
+        assert_eq!(map.read(0xD3FFF), Ok(0x00));
+
+        // Without poisoning the range is simply unmapped
+        assert!(map.remove_range(&(0xD0000..=0xD3FFF)).is_some());
+        assert_eq!(map.read(0xD0000), Err(BusDeviceError::AddressNotMapped { address: 0xD0000 }));
+
+        // Only exact mappings are removed
+        assert!(map.remove_range_poisoned(&(0x00000..=0x007FF), "low RAM").is_none());
+        assert_eq!(map.read(0x00000), Ok(0x00));
+
+        // The poison has to be removed before the page can be mapped again
+        assert!(map.remove_range(&(0xD4000..=0xD7FFF)).is_some());
+        map.add_range(0xD4000..=0xD7FFF, page);
+        assert_eq!(map.read(0xD4010), Ok(0x22));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{BusDevice, BusDeviceError};
+
+/// Wraps any device so that it can only be read, such as a ROM image loaded into a memory map or a read only view of
+/// a shared framebuffer.
+///
+/// Writes and pokes fail with `AddressNotWritable` and leave the device untouched, whether or not the address is
+/// within the device. Reads, peeks and the size are forwarded to the inner device unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReadOnly<T: BusDevice> {
+    inner: T
+}
+
+impl<T: BusDevice> ReadOnly<T> {
+    #[must_use]
+    /// Wraps `inner`.
+    pub const fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device, bypassing the write protection.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device, making it writable again.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: BusDevice> BusDevice for ReadOnly<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.read(address)
+    }
+
+    fn write(&mut self, address: usize, _data: u8) -> Result<(), BusDeviceError> {
+        Err(BusDeviceError::AddressNotWritable { address })
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(address)
+    }
+
+    fn poke(&mut self, address: usize, _data: u8) -> Result<(), BusDeviceError> {
+        Err(BusDeviceError::AddressNotWritable { address })
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, ReadOnlyMemory, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_read_only_matches_read_only_memory() {
+        let mut wrapped = ReadOnly::new(Memory::<4>::filled([1, 2, 3, 4]));
+        let mut rom = ReadOnlyMemory::<4>::filled([1, 2, 3, 4]);
+
+        for address in 0..6 {
+            assert_eq!(wrapped.read(address), rom.read(address));
+            assert_eq!(wrapped.write(address, 0), rom.write(address, 0));
+        }
+
+        assert_eq!(wrapped.size(), rom.size());
+        assert_eq!(wrapped.poke(0, 0), Err(BusDeviceError::AddressNotWritable { address: 0 }));
+        assert_eq!(wrapped.into_inner(), Memory::filled([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_read_only_inner_access() {
+        let mut device = ReadOnly::new(Memory::<4>::empty());
+
+        assert_eq!(device.inner_mut().write(0, 7), Ok(()));
+        assert_eq!(device.inner().read(0), Ok(7));
+        assert_eq!(device.peek(0), Ok(7));
+    }
+
+    #[test]
+    fn test_read_only_memory_map() {
+        let mut map = MemoryMap::new()
+            .with_range(0x000..=0x0FF, Box::new(Memory::<0x100>::empty()))
+            .with_range(0x200..=0x2FF, Box::new(Memory::<0x100>::empty()));
+
+        assert_eq!(map.write_region(0x0FE, &[0x55, 0xAA]), Ok(()));
+        assert_eq!(map.write(0x200, 0xEA), Ok(()));
+
+        let mut view = ReadOnly::new(map);
+
+        assert_eq!(view.read_region(0x0FE), Ok([0x55, 0xAA]));
+        assert_eq!(view.read(0x200), Ok(0xEA));
+        assert_eq!(view.write(0x000, 1), Err(BusDeviceError::AddressNotWritable { address: 0x000 }));
+        assert_eq!(view.write_region(0x200, &[1, 2]), Err(BusDeviceError::AddressNotWritable { address: 0x200 }));
+
+        // Unmapped addresses still fail to read, but every write is rejected before reaching the map
+        assert_eq!(view.read(0x100), Err(BusDeviceError::AddressNotMapped { address: 0x100 }));
+        assert_eq!(view.write(0x100, 1), Err(BusDeviceError::AddressNotWritable { address: 0x100 }));
+
+        let mut map = view.into_inner();
+        assert_eq!(map.write(0x000, 1), Ok(()));
+        assert_eq!(map.read(0x200), Ok(0xEA));
+    }
+
+    #[test]
+    fn test_read_only_nested_in_memory_map() {
+        let mut map = MemoryMap::new()
+            .with_range(0xF0000..=0xF0003, Box::new(ReadOnly::new(Memory::<4>::filled([0xEA, 0x5B, 0xE0, 0x00]))));
+
+        assert_eq!(map.read_region(0xF0000), Ok([0xEA, 0x5B, 0xE0, 0x00]));
+        assert_eq!(map.write(0xF0002, 0), Err(BusDeviceError::AddressNotWritable { address: 2 }));
+    }
+}
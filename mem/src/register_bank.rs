@@ -0,0 +1,294 @@
+use std::fmt::Debug;
+
+use crate::{BusDevice, BusDeviceError, TimedBusDevice};
+
+/// Hook transforming the stored value of a register into the value read by the guest.
+pub type RegisterReadFn = Box<dyn Fn(u8) -> u8>;
+
+/// Hook invoked with the new stored value of a register after each guest write.
+pub type RegisterWriteFn = Box<dyn FnMut(u8)>;
+
+/// Describes a single byte register of a `RegisterBank`.
+///
+/// Only the bits of the writable mask are changed by guest writes, and writing a 1 to a bit of the write one to clear
+/// mask clears it. Every other bit is read only to the guest, and can only be changed by the host. Bits which are in
+/// neither mask and are clear in the reset value are therefore reserved bits, always reading as 0 unless the host sets
+/// them.
+pub struct RegisterDescriptor {
+    offset: usize,
+    reset_value: u8,
+    writable_mask: u8,
+    w1c_mask: u8,
+    read_hook: Option<RegisterReadFn>,
+    write_hook: Option<RegisterWriteFn>
+}
+
+impl RegisterDescriptor {
+    #[must_use]
+    /// Describes a read only register at `offset` within the bank, holding `reset_value` after a reset.
+    pub const fn new(offset: usize, reset_value: u8) -> Self {
+        Self { offset, reset_value, writable_mask: 0, w1c_mask: 0, read_hook: None, write_hook: None }
+    }
+
+    #[must_use]
+    /// Builder pattern for setting the bits changed by guest writes.
+    pub const fn with_writable_mask(mut self, mask: u8) -> Self {
+        self.writable_mask = mask;
+        self
+    }
+
+    #[must_use]
+    /// Builder pattern for setting the bits cleared by a guest writing 1 to them.
+    pub const fn with_w1c_mask(mut self, mask: u8) -> Self {
+        self.w1c_mask = mask;
+        self
+    }
+
+    #[must_use]
+    /// Builder pattern for setting a hook transforming the stored value into the value read by the guest.
+    pub fn with_read_hook(mut self, hook: impl Fn(u8) -> u8 + 'static) -> Self {
+        self.read_hook = Some(Box::new(hook));
+        self
+    }
+
+    #[must_use]
+    /// Builder pattern for setting a hook invoked with the new stored value after each guest write.
+    pub fn with_write_hook(mut self, hook: impl FnMut(u8) + 'static) -> Self {
+        self.write_hook = Some(Box::new(hook));
+        self
+    }
+
+    #[must_use]
+    /// Returns the offset of the register within the bank.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the value stored after a guest writes `data` over `value`.
+    const fn apply_write(&self, value: u8, data: u8) -> u8 {
+        let written = (value & !self.writable_mask) | (data & self.writable_mask);
+        written & !(data & self.w1c_mask)
+    }
+}
+
+impl Debug for RegisterDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegisterDescriptor")
+            .field("offset", &self.offset)
+            .field("reset_value", &self.reset_value)
+            .field("writable_mask", &self.writable_mask)
+            .field("w1c_mask", &self.w1c_mask)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A block of byte registers described by a list of `RegisterDescriptor`s, enforcing the masks of each register on
+/// every access.
+///
+/// Offsets within the bank which have no register read as a default value and ignore writes, as undecoded addresses
+/// within a peripheral do. Peeks and pokes skip the hooks, but pokes are still subject to the masks. The host can set
+/// the stored value of a register directly with `set_value`, as the hardware does to raise a status bit.
+#[derive(Debug)]
+pub struct RegisterBank {
+    size: usize,
+    undefined_value: u8,
+    registers: Vec<(RegisterDescriptor, u8)>
+}
+
+impl RegisterBank {
+    #[must_use]
+    /// Constructs a bank `size` bytes long holding the given registers at their reset values, with every other offset
+    /// reading as `undefined_value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a register lies outside of the bank, or two registers share an offset.
+    pub fn new(size: usize, undefined_value: u8, registers: impl IntoIterator<Item = RegisterDescriptor>) -> Self {
+        let mut bank = Self { size, undefined_value, registers: Vec::new() };
+
+        for register in registers {
+            assert!(register.offset < size, "Register at offset {} lies outside of the bank", register.offset);
+            assert!(bank.register(register.offset).is_none(), "Register at offset {} is defined twice", register.offset);
+
+            let value = register.reset_value;
+            bank.registers.push((register, value));
+        }
+
+        bank
+    }
+
+    /// Returns the register at `offset` and its stored value, if there is one.
+    fn register(&self, offset: usize) -> Option<&(RegisterDescriptor, u8)> {
+        self.registers.iter().find(|(register, _)| register.offset == offset)
+    }
+
+    /// Returns the register at `offset` and its stored value mutably, if there is one.
+    fn register_mut(&mut self, offset: usize) -> Option<&mut (RegisterDescriptor, u8)> {
+        self.registers.iter_mut().find(|(register, _)| register.offset == offset)
+    }
+
+    #[must_use]
+    /// Returns the stored value of the register at `offset`, or `None` if there is no register there.
+    pub fn value(&self, offset: usize) -> Option<u8> {
+        self.register(offset).map(|(_, value)| *value)
+    }
+
+    /// Sets the stored value of the register at `offset`, ignoring its masks and hooks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no register at `offset`.
+    pub fn set_value(&mut self, offset: usize, value: u8) {
+        let (_, stored) = self.register_mut(offset).unwrap_or_else(|| panic!("No register at offset {offset}"));
+        *stored = value;
+    }
+
+    /// Restores every register to its reset value.
+    pub fn reset(&mut self) {
+        for (register, value) in &mut self.registers {
+            *value = register.reset_value;
+        }
+    }
+
+    /// Checks that `address` lies within the bank.
+    const fn check_bounds(&self, address: usize) -> Result<(), BusDeviceError> {
+        if address < self.size {
+            Ok(())
+        }
+        else {
+            Err(BusDeviceError::AddressOutOfBounds { address, size: self.size })
+        }
+    }
+}
+
+impl BusDevice for RegisterBank {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.check_bounds(address)?;
+
+        Ok(match self.register(address) {
+            Some((RegisterDescriptor { read_hook: Some(hook), .. }, value)) => hook(*value),
+            Some((_, value)) => *value,
+            None => self.undefined_value
+        })
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.poke(address, data)?;
+
+        if let Some((RegisterDescriptor { write_hook: Some(hook), .. }, value)) = self.register_mut(address) {
+            hook(*value);
+        }
+
+        Ok(())
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.check_bounds(address)?;
+
+        Ok(self.value(address).unwrap_or(self.undefined_value))
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.check_bounds(address)?;
+
+        if let Some((register, value)) = self.register_mut(address) {
+            *value = register.apply_write(*value, data);
+        }
+
+        Ok(())
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(self.size)
+    }
+
+    fn reset(&mut self) {
+        Self::reset(self);
+    }
+}
+
+impl TimedBusDevice for RegisterBank {}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    const STATUS: usize = 0;
+    const CONTROL: usize = 1;
+
+    /// A status register whose low two bits are raised by the hardware and cleared by writing 1 to them, and a control
+    /// register whose low nibble is writable, with every other bit of both reserved.
+    fn status_control_bank() -> RegisterBank {
+        RegisterBank::new(4, 0xFF, [
+            RegisterDescriptor::new(STATUS, 0x00).with_w1c_mask(0x03),
+            RegisterDescriptor::new(CONTROL, 0x01).with_writable_mask(0x0F)
+        ])
+    }
+
+    #[test]
+    fn test_register_bank_status_control() {
+        let mut bank = status_control_bank();
+        assert_eq!(bank.read_region(STATUS), Ok([0x00, 0x01, 0xFF, 0xFF]));
+
+        // Reserved bits stay clear whatever is written
+        assert_eq!(bank.write(CONTROL, 0xFA), Ok(()));
+        assert_eq!(bank.read(CONTROL), Ok(0x0A));
+        assert_eq!(bank.write(STATUS, 0xFC), Ok(()));
+        assert_eq!(bank.read(STATUS), Ok(0x00));
+
+        // Writing 1 to a raised status bit clears it, writing 0 leaves it alone
+        bank.set_value(STATUS, 0x03);
+        assert_eq!(bank.write(STATUS, 0x00), Ok(()));
+        assert_eq!(bank.read(STATUS), Ok(0x03));
+        assert_eq!(bank.write(STATUS, 0x02), Ok(()));
+        assert_eq!(bank.read(STATUS), Ok(0x01));
+        assert_eq!(bank.write(STATUS, 0xFF), Ok(()));
+        assert_eq!(bank.read(STATUS), Ok(0x00));
+
+        bank.reset();
+        assert_eq!(bank.value(CONTROL), Some(0x01));
+        assert_eq!(bank.value(2), None);
+    }
+
+    #[test]
+    fn test_register_bank_undefined_offsets() {
+        let mut bank = status_control_bank();
+
+        assert_eq!(bank.write(3, 0x00), Ok(()));
+        assert_eq!(bank.read(3), Ok(0xFF));
+        assert_eq!(bank.peek(2), Ok(0xFF));
+        assert_eq!(bank.read(4), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+        assert_eq!(bank.write(4, 0x00), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+    }
+
+    #[test]
+    fn test_register_bank_hooks() {
+        let writes = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&writes);
+
+        let mut map = MemoryMap::new().with_range(0x3F8..=0x3F9, Box::new(RegisterBank::new(2, 0x00, [
+            RegisterDescriptor::new(0, 0x00).with_writable_mask(0xFF).with_write_hook(move |value| sink.borrow_mut().push(value)),
+            // Reads with the top bit always set, as an input pin pulled high
+            RegisterDescriptor::new(1, 0x00).with_writable_mask(0x0F).with_read_hook(|value| value | 0x80)
+        ])));
+
+        assert_eq!(map.write_region(0x3F8, &[0x41, 0xFF]), Ok(()));
+        assert_eq!(map.read_region(0x3F8), Ok([0x41, 0x8F]));
+        assert_eq!(map.peek(0x3F9), Ok(0x0F));
+
+        // Pokes apply the masks but skip the hooks
+        assert_eq!(map.poke(0x3F8, 0x42), Ok(()));
+        assert_eq!(map.read(0x3F8), Ok(0x42));
+        assert_eq!(*writes.borrow(), [0x41]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Register at offset 1 is defined twice")]
+    fn test_register_bank_duplicate_offset() {
+        let _bank = RegisterBank::new(2, 0x00, [RegisterDescriptor::new(1, 0x00), RegisterDescriptor::new(1, 0x00)]);
+    }
+}
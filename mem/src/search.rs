@@ -0,0 +1,155 @@
+use std::ops::RangeInclusive;
+
+use crate::{BusDeviceError, RegionBusDevice};
+
+/// Number of bytes read from the device at a time while searching.
+pub const SEARCH_CHUNK_LENGTH: usize = 256;
+
+/// Returns the address of the first occurrence of `needle` lying entirely within `range` of `device`, such as the
+/// `55 AA` signature at the start of an option ROM.
+///
+/// If `skip_unmapped` is set, addresses which fail with `AddressNotMapped` are skipped over, and no match can span
+/// them. An empty needle never matches.
+///
+/// # Errors
+///
+/// This function will return an error if a byte in the range cannot be read, other than an unmapped byte when
+/// `skip_unmapped` is set.
+pub fn find_bytes(device: &impl RegionBusDevice, range: RangeInclusive<usize>, needle: &[u8], skip_unmapped: bool) -> Result<Option<usize>, BusDeviceError> {
+    Ok(search(device, range, needle, skip_unmapped, true)?.first().copied())
+}
+
+/// Returns the addresses of every occurrence of `needle` lying entirely within `range` of `device`, in ascending
+/// order.
+///
+/// Overlapping occurrences are all reported, so searching `AA AA AA` for `AA AA` finds two. If `skip_unmapped` is
+/// set, addresses which fail with `AddressNotMapped` are skipped over, and no match can span them. An empty needle
+/// never matches.
+///
+/// # Errors
+///
+/// This function will return an error if a byte in the range cannot be read, other than an unmapped byte when
+/// `skip_unmapped` is set.
+pub fn find_all_bytes(device: &impl RegionBusDevice, range: RangeInclusive<usize>, needle: &[u8], skip_unmapped: bool) -> Result<Vec<usize>, BusDeviceError> {
+    search(device, range, needle, skip_unmapped, false)
+}
+
+/// Reads `range` of `device` in chunks of `SEARCH_CHUNK_LENGTH` bytes, collecting the addresses at which `needle`
+/// occurs. The end of each chunk is carried over to the next, so that matches spanning chunks are still found. If
+/// `first_only` is set the search stops at the end of the first chunk containing a match.
+fn search(device: &impl RegionBusDevice, range: RangeInclusive<usize>, needle: &[u8], skip_unmapped: bool, first_only: bool) -> Result<Vec<usize>, BusDeviceError> {
+    let mut matches = Vec::new();
+
+    if needle.is_empty() {
+        return Ok(matches);
+    }
+
+    // Contiguous bytes read but not yet searched in full, and the address of the first of them
+    let mut window: Vec<u8> = Vec::with_capacity(SEARCH_CHUNK_LENGTH + needle.len());
+    let mut window_start = *range.start();
+
+    for chunk_start in range.clone().step_by(SEARCH_CHUNK_LENGTH) {
+        let chunk_end = chunk_start.saturating_add(SEARCH_CHUNK_LENGTH - 1).min(*range.end());
+
+        for address in chunk_start..=chunk_end {
+            match device.read(address) {
+                Ok(byte) => window.push(byte),
+                Err(BusDeviceError::AddressNotMapped { .. }) if skip_unmapped => {
+                    scan(&window, window_start, needle, &mut matches);
+                    window.clear();
+                    window_start = address.saturating_add(1);
+                }
+                Err(error) => return Err(error)
+            }
+        }
+
+        scan(&window, window_start, needle, &mut matches);
+
+        if first_only && !matches.is_empty() {
+            break;
+        }
+
+        // Keep only the bytes which could still begin a match continuing into the next chunk
+        let discard = window.len().saturating_sub(needle.len() - 1);
+        window.drain(..discard);
+        window_start += discard;
+    }
+
+    Ok(matches)
+}
+
+/// Appends the address of every occurrence of `needle` within `window`, which starts at address `start`.
+fn scan(window: &[u8], start: usize, needle: &[u8], matches: &mut Vec<usize>) {
+    matches.extend(window.windows(needle.len())
+        .enumerate()
+        .filter(|(_, candidate)| *candidate == needle)
+        .map(|(offset, _)| start + offset));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BusDevice, Memory, MemoryMap};
+
+    use super::*;
+
+    #[test]
+    fn test_find_bytes_start_and_end() {
+        let mem = Memory::<0x400>::populated(&[0x55, 0xAA, 0x10]);
+
+        assert_eq!(find_bytes(&mem, 0..=0x3FF, &[0x55, 0xAA], false), Ok(Some(0)));
+        assert_eq!(find_bytes(&mem, 1..=0x3FF, &[0x55, 0xAA], false), Ok(None));
+        assert_eq!(find_bytes(&mem, 0..=0x3FF, &[0xAA, 0x10], false), Ok(Some(1)));
+
+        let mut mem = Memory::<0x400>::empty();
+        assert_eq!(mem.write_region(0x3FD, &[1, 2, 3]), Ok(()));
+
+        // The match must lie entirely within the range
+        assert_eq!(find_bytes(&mem, 0..=0x3FF, &[1, 2, 3], false), Ok(Some(0x3FD)));
+        assert_eq!(find_bytes(&mem, 0..=0x3FE, &[1, 2, 3], false), Ok(None));
+        assert_eq!(find_all_bytes(&mem, 0x3FF..=0x3FF, &[3], false), Ok(vec![0x3FF]));
+        assert_eq!(find_bytes(&mem, 0..=0x3FF, &[], false), Ok(None));
+    }
+
+    #[test]
+    fn test_find_bytes_across_chunks_and_devices() {
+        // A signature straddling both the end of the first chunk and the boundary between the two devices
+        let mut map = MemoryMap::new()
+            .with_range(0x000..=0x0FF, Box::new(Memory::<0x100>::empty()))
+            .with_range(0x100..=0x1FF, Box::new(Memory::<0x100>::empty()));
+
+        assert_eq!(map.write_region(0x0FE, b"PCIR"), Ok(()));
+        assert_eq!(map.write_region(0x1F0, b"PCIR"), Ok(()));
+
+        assert_eq!(find_bytes(&map, 0..=0x1FF, b"PCIR", false), Ok(Some(0x0FE)));
+        assert_eq!(find_all_bytes(&map, 0..=0x1FF, b"PCIR", false), Ok(vec![0x0FE, 0x1F0]));
+        assert_eq!(find_all_bytes(&map, 0x0FF..=0x1FF, b"PCIR", false), Ok(vec![0x1F0]));
+    }
+
+    #[test]
+    fn test_find_all_bytes_overlapping() {
+        let mem = Memory::<0x300>::populated(&[0xAA; 0x300]);
+
+        assert_eq!(find_all_bytes(&mem, 0..=4, &[0xAA, 0xAA], false), Ok(vec![0, 1, 2, 3]));
+
+        // Overlapping matches are neither lost nor repeated across chunks
+        let all = find_all_bytes(&mem, 0..=0x2FF, &[0xAA, 0xAA, 0xAA], false).unwrap();
+        assert_eq!(all, (0..=0x2FD).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_find_bytes_unmapped() {
+        let mut map = MemoryMap::new()
+            .with_range(0x000..=0x0FF, Box::new(Memory::<0x100>::empty()))
+            .with_range(0x200..=0x2FF, Box::new(Memory::<0x100>::empty()));
+
+        assert_eq!(map.write(0x0FF, 0x55), Ok(()));
+        assert_eq!(map.write(0x200, 0xAA), Ok(()));
+        assert_eq!(map.write_region(0x280, &[0x55, 0xAA]), Ok(()));
+
+        assert_eq!(find_bytes(&map, 0..=0x2FF, &[0x55, 0xAA], false), Err(BusDeviceError::AddressNotMapped { address: 0x100 }));
+
+        // No match can span the hole
+        assert_eq!(find_all_bytes(&map, 0..=0x2FF, &[0x55, 0xAA], true), Ok(vec![0x280]));
+        assert_eq!(find_bytes(&map, 0x100..=0x1FF, &[0x00], true), Ok(None));
+    }
+}
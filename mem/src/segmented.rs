@@ -0,0 +1,92 @@
+use crate::{BusDevice, BusDeviceError};
+
+/// Computes the physical address `segment * 16 + offset` of a segmented address.
+///
+/// The result can reach `10FFEFh`, past the 20 bit address space of the 8086. It is not wrapped here, as whether it
+/// wraps depends on the A20 line, which is modelled by an `A20Gate` on the bus.
+#[must_use]
+pub const fn segmented_linear(segment: u16, offset: u16) -> usize {
+    ((segment as usize) << 4) + offset as usize
+}
+
+/// Reads the little endian word at `offset` within `segment`.
+///
+/// As on the 8086 the offset wraps within the segment, so a word at offset `FFFFh` takes its high byte from offset
+/// `0000h` of the same segment rather than from the next paragraph. A word which does not wrap is read as a single two
+/// byte access.
+///
+/// # Errors
+///
+/// This function will return an error if either byte cannot be read.
+pub fn read_u16_seg<D: ?Sized + BusDevice>(device: &D, segment: u16, offset: u16) -> Result<u16, BusDeviceError> {
+    let mut bytes = [0; 2];
+
+    if offset == u16::MAX {
+        bytes[0] = device.read(segmented_linear(segment, offset))?;
+        bytes[1] = device.read(segmented_linear(segment, 0))?;
+    }
+    else {
+        device.read_bytes(segmented_linear(segment, offset), &mut bytes)?;
+    }
+
+    Ok(u16::from_le_bytes(bytes))
+}
+
+/// Writes `value` as a little endian word at `offset` within `segment`, wrapping the offset within the segment as
+/// `read_u16_seg` does.
+///
+/// # Errors
+///
+/// This function will return an error if either byte cannot be written.
+pub fn write_u16_seg<D: ?Sized + BusDevice>(device: &mut D, segment: u16, offset: u16, value: u16) -> Result<(), BusDeviceError> {
+    let bytes = value.to_le_bytes();
+
+    if offset == u16::MAX {
+        device.write(segmented_linear(segment, offset), bytes[0])?;
+        device.write(segmented_linear(segment, 0), bytes[1])
+    }
+    else {
+        device.write_bytes(segmented_linear(segment, offset), &bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{A20Gate, Memory, MemoryMap};
+
+    use super::*;
+
+    #[test]
+    fn test_segmented_word_wraps_offset() {
+        let mut memory = Memory::<0x20000>::incrementing();
+
+        // The high byte comes from the start of the segment, not from 1000:FFFF + 1 = 2000:0000
+        assert_eq!(read_u16_seg(&memory, 0x1000, 0xFFFF), Ok(0x00FF));
+        assert_eq!(read_u16_seg(&memory, 0x1000, 0xFFFE), Ok(0xFFFE));
+
+        assert_eq!(write_u16_seg(&mut memory, 0x1000, 0xFFFF, 0xBEEF), Ok(()));
+        assert_eq!(memory[0x1FFFF], 0xEF);
+        assert_eq!(memory[0x10000], 0xBE);
+        assert_eq!(read_u16_seg(&memory, 0x1000, 0xFFFF), Ok(0xBEEF));
+    }
+
+    #[test]
+    fn test_segmented_above_one_mebibyte() {
+        assert_eq!(segmented_linear(0xFFFF, 0x0010), 0x10_0000);
+        assert_eq!(segmented_linear(0xFFFF, 0xFFFF), 0x10_FFEF);
+
+        // Without a gate the address is passed on as is, and is not mapped
+        let map = MemoryMap::new()
+            .with_range(0x00000..=0x0FFFF, Box::new(Memory::<0x10000>::incrementing()))
+            .with_range(0xF0000..=0xFFFFF, Box::new(Memory::<0x10000>::empty()));
+        assert_eq!(read_u16_seg(&map, 0xFFFF, 0x0010), Err(BusDeviceError::AddressNotMapped { address: 0x10_0000 }));
+
+        // Wrapping to 20 bits is left to the A20 gate
+        let mut gated = A20Gate::new(map);
+        assert_eq!(read_u16_seg(&gated, 0xFFFF, 0x0010), Ok(0x0100));
+        assert_eq!(write_u16_seg(&mut gated, 0xFFFF, 0xFFFF, 0x1234), Ok(()));
+        assert_eq!(read_u16_seg(&gated, 0xFFFF, 0xFFFF), Ok(0x1234));
+        assert_eq!(gated.read(0x0FFEF), Ok(0x34));
+        assert_eq!(gated.read(0xFFFF0), Ok(0x12));
+    }
+}
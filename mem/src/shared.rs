@@ -0,0 +1,158 @@
+use std::{cell::{Ref, RefCell, RefMut}, rc::Rc};
+
+use crate::{BusDevice, BusDeviceError, IoDevice, IoError};
+
+/// A reference counted handle to a device, allowing the same device to be mapped at several ranges of a `MemoryMap`
+/// and to remain accessible to the host after it has been mapped.
+///
+/// I/O devices can be shared in the same way, so that a device mapped into an `IoMap` can also be driven by the host
+/// or by another part of the machine.
+///
+/// Cloning a `Shared` produces another handle to the same device rather than a copy of it.
+///
+/// # Panics
+///
+/// The device is held in a `RefCell`, so accessing it through the bus while the host holds a conflicting borrow
+/// (for example reading from a `MemoryMap` while a `borrow_mut()` guard is alive) panics.
+#[derive(Debug, Default)]
+pub struct Shared<T> {
+    inner: Rc<RefCell<T>>
+}
+
+impl<T> Shared<T> {
+    #[must_use]
+    /// Wraps `inner` in a new shared handle.
+    pub fn new(inner: T) -> Self {
+        Self { inner: Rc::new(RefCell::new(inner)) }
+    }
+
+    #[must_use]
+    /// Immutably borrows the device.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the device is currently mutably borrowed.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.inner.borrow()
+    }
+
+    #[must_use]
+    /// Mutably borrows the device.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the device is currently borrowed.
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.inner.borrow_mut()
+    }
+
+    #[must_use]
+    /// Returns `true` if both handles refer to the same device.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self { inner: Rc::clone(&self.inner) }
+    }
+}
+
+impl<T: BusDevice> BusDevice for Shared<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.borrow().read(address)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.borrow_mut().write(address, data)
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.borrow().peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.borrow_mut().poke(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.borrow().size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.borrow_mut().reset();
+    }
+}
+
+impl<T: IoDevice> IoDevice for Shared<T> {
+    fn read_port(&self, port: u16) -> Result<u8, IoError> {
+        self.inner.borrow().read_port(port)
+    }
+
+    fn write_port(&mut self, port: u16, data: u8) -> Result<(), IoError> {
+        self.inner.borrow_mut().write_port(port, data)
+    }
+
+    fn peek_port(&self, port: u16) -> Result<u8, IoError> {
+        self.inner.borrow().peek_port(port)
+    }
+
+    fn reset(&mut self) {
+        self.inner.borrow_mut().reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_shared_mapped_twice() {
+        let ram = Shared::new(Memory::<0x1000>::empty());
+
+        let mut map = MemoryMap::new()
+            .with_range(0xB8000..=0xB8FFF, Box::new(ram.clone()))
+            .with_range(0xBC000..=0xBCFFF, Box::new(ram.clone()));
+
+        assert_eq!(map.write_region(0xB8000, b"Hi"), Ok(()));
+        assert_eq!(map.read_region(0xBC000), Ok(*b"Hi"));
+
+        assert_eq!(map.write(0xBCFFF, 0x42), Ok(()));
+        assert_eq!(map.read(0xB8FFF), Ok(0x42));
+
+        assert_eq!(ram.borrow().read_region(0), Ok(*b"Hi"));
+        assert_eq!(ram.borrow()[0xFFF], 0x42);
+
+        ram.borrow_mut()[1] = b'o';
+        assert_eq!(map.read_region(0xB8000), Ok(*b"Ho"));
+        assert_eq!(map.read_region(0xBC000), Ok(*b"Ho"));
+    }
+
+    #[test]
+    fn test_shared_handles() {
+        let a = Shared::new(Memory::<4>::empty());
+        let b = a.clone();
+        let c = Shared::new(Memory::<4>::empty());
+
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&c));
+
+        let mut handle = b;
+        assert_eq!(handle.write(3, 7), Ok(()));
+        assert_eq!(a.read(3), Ok(7));
+        assert_eq!(handle.read(4), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "already mutably borrowed")]
+    fn test_shared_reentrant_borrow() {
+        let ram = Shared::new(Memory::<16>::empty());
+        let map = MemoryMap::new().with_range(0..=15, Box::new(ram.clone()));
+
+        let _guard = ram.borrow_mut();
+        let _ = map.read(0);
+    }
+}
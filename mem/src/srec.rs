@@ -0,0 +1,194 @@
+use crate::{BusDeviceError, RegionBusDevice};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SrecError {
+    MissingRecordMark{line: usize},
+    UnknownRecordType{line: usize, record_type: u8},
+    OddLength{line: usize},
+    InvalidHex{line: usize},
+    BadRecordLength{line: usize, expected: usize, actual: usize},
+    BadChecksum{line: usize, expected: u8, actual: u8},
+    Bus(BusDeviceError)
+}
+
+impl From<BusDeviceError> for SrecError {
+    fn from(value: BusDeviceError) -> Self {
+        Self::Bus(value)
+    }
+}
+
+/// Decodes a string of hex digit pairs into bytes, returning `None` if any character is not a hex digit.
+pub(crate) fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    text.as_bytes()
+        .chunks(2)
+        .map(|pair| core::str::from_utf8(pair).ok().and_then(|s| u8::from_str_radix(s, 16).ok()))
+        .collect()
+}
+
+/// Loads the Motorola S-record formatted `text` into `device`, returning the entry address given by the termination
+/// record, if one is present.
+///
+/// S1, S2 and S3 data records are written to the device, S7, S8 and S9 records end the load, and S0 header and S5/S6
+/// count records are validated but otherwise ignored. Blank lines are skipped.
+///
+/// # Errors
+///
+/// This function will return an error if a record is malformed, if a checksum does not match, or if the data cannot be
+/// written to the device.
+pub fn load_srec(device: &mut impl RegionBusDevice, text: &str) -> Result<Option<usize>, SrecError> {
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = index + 1;
+        let record = raw_line.trim();
+
+        if record.is_empty() {
+            continue;
+        }
+
+        let body = record.strip_prefix('S').ok_or(SrecError::MissingRecordMark { line })?;
+        let mut chars = body.chars();
+        let record_type = chars.next()
+            .and_then(|c| c.to_digit(10))
+            .ok_or(SrecError::InvalidHex { line })?;
+        let record_type = u8::try_from(record_type).map_err(|_| SrecError::InvalidHex { line })?;
+        let hex = chars.as_str();
+
+        if hex.len() % 2 != 0 {
+            return Err(SrecError::OddLength { line });
+        }
+
+        let bytes = decode_hex(hex).ok_or(SrecError::InvalidHex { line })?;
+
+        let address_length = match record_type {
+            0 | 1 | 5 | 9 => 2,
+            2 | 6 | 8 => 3,
+            3 | 7 => 4,
+            _ => return Err(SrecError::UnknownRecordType { line, record_type })
+        };
+
+        // The count byte covers the address, data and checksum bytes which follow it
+        let Some((&count, rest)) = bytes.split_first() else {
+            return Err(SrecError::BadRecordLength { line, expected: address_length + 2, actual: 0 });
+        };
+
+        if rest.len() != usize::from(count) || rest.len() < address_length + 1 {
+            return Err(SrecError::BadRecordLength { line, expected: usize::from(count).max(address_length + 1), actual: rest.len() });
+        }
+
+        let (payload, checksum) = rest.split_at(rest.len() - 1);
+        let expected = !bytes[..bytes.len() - 1].iter().fold(0u8, |acc, byte| acc.wrapping_add(*byte));
+
+        if checksum[0] != expected {
+            return Err(SrecError::BadChecksum { line, expected, actual: checksum[0] });
+        }
+
+        let (address_bytes, data) = payload.split_at(address_length);
+        let address = address_bytes.iter().fold(0usize, |acc, byte| (acc << 8) | usize::from(*byte));
+
+        match record_type {
+            1..=3 => device.write_region(address, data)?,
+            7..=9 => return Ok(Some(address)),
+            _ => {}
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap};
+
+    use super::*;
+
+    #[test]
+    fn test_srec_s1_records() {
+        let mut mem = Memory::<16>::empty();
+
+        let text = "S00600004844521B\n\
+                    S1060000010203F3\n\
+                    S1050008AABB8D\n";
+
+        assert_eq!(load_srec(&mut mem, text), Ok(None));
+        assert_eq!(mem.read_region(0), Ok([1, 2, 3, 0, 0, 0, 0, 0, 0xAA, 0xBB, 0]));
+    }
+
+    #[test]
+    fn test_srec_termination_records() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_srec(&mut mem, "S1050004424272\nS9031234B6\n"), Ok(Some(0x1234)));
+        assert_eq!(mem.read_region(4), Ok([0x42, 0x42]));
+
+        assert_eq!(load_srec(&mut mem, "S804FFFF00FD\n"), Ok(Some(0xFF_FF00)));
+        assert_eq!(load_srec(&mut mem, "S70512345678E6\n"), Ok(Some(0x1234_5678)));
+    }
+
+    #[test]
+    fn test_srec_stops_at_termination() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_srec(&mut mem, "S9030000FC\nS1050000FFFFFC\n"), Ok(Some(0)));
+        assert_eq!(mem.read_region(0), Ok([0, 0]));
+    }
+
+    #[test]
+    fn test_srec_wide_addresses_in_memory_map() {
+        let mut map = MemoryMap::new()
+            .with_range(0x10000..=0x1000F, Box::new(Memory::<16>::empty()))
+            .with_range(0xF0000..=0xF000F, Box::new(Memory::<16>::empty()));
+
+        let text = "S2060100021122C3\n\
+                    S307000F0000334472\n";
+
+        assert_eq!(load_srec(&mut map, text), Ok(None));
+        assert_eq!(map.read_region(0x10002), Ok([0x11, 0x22]));
+        assert_eq!(map.read_region(0xF0000), Ok([0x33, 0x44]));
+    }
+
+    #[test]
+    fn test_srec_bad_checksum() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_srec(&mut mem, "S1060000010203F3\nS1060000010203F4\n"), Err(SrecError::BadChecksum { line: 2, expected: 0xF3, actual: 0xF4 }));
+    }
+
+    #[test]
+    fn test_srec_odd_length() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_srec(&mut mem, "S1060000010203F"), Err(SrecError::OddLength { line: 1 }));
+    }
+
+    #[test]
+    fn test_srec_invalid_hex() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_srec(&mut mem, "S10600000102G3F3"), Err(SrecError::InvalidHex { line: 1 }));
+        assert_eq!(load_srec(&mut mem, "SX060000010203F3"), Err(SrecError::InvalidHex { line: 1 }));
+    }
+
+    #[test]
+    fn test_srec_bad_record_length() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_srec(&mut mem, "S1070000010203F3"), Err(SrecError::BadRecordLength { line: 1, expected: 7, actual: 6 }));
+        assert_eq!(load_srec(&mut mem, "S10200FD"), Err(SrecError::BadRecordLength { line: 1, expected: 3, actual: 2 }));
+        assert_eq!(load_srec(&mut mem, "S1"), Err(SrecError::BadRecordLength { line: 1, expected: 4, actual: 0 }));
+    }
+
+    #[test]
+    fn test_srec_unknown_record() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_srec(&mut mem, "hello"), Err(SrecError::MissingRecordMark { line: 1 }));
+        assert_eq!(load_srec(&mut mem, "S4030000FC"), Err(SrecError::UnknownRecordType { line: 1, record_type: 4 }));
+    }
+
+    #[test]
+    fn test_srec_address_beyond_device() {
+        let mut mem = Memory::<16>::empty();
+
+        assert_eq!(load_srec(&mut mem, "S105000FAABB86"), Err(SrecError::Bus(BusDeviceError::AddressOutOfBounds { address: 16, size: 16 })));
+        assert_eq!(load_srec(&mut mem, "S1050020AABB75"), Err(SrecError::Bus(BusDeviceError::AddressOutOfBounds { address: 32, size: 16 })));
+    }
+}
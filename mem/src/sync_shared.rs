@@ -0,0 +1,163 @@
+use std::sync::{Arc, LockResult, Mutex, MutexGuard, PoisonError};
+
+use crate::{BusDevice, BusDeviceError};
+
+/// A thread safe handle to a device, allowing it to be accessed through the bus on one thread while the host inspects
+/// it from another, such as when rendering a framebuffer.
+///
+/// Cloning a `SyncShared` produces another handle to the same device rather than a copy of it. If a thread panics
+/// while holding the lock, later reads and writes through the bus fail with `LockPoisoned` instead of panicking.
+#[derive(Debug, Default)]
+pub struct SyncShared<T: BusDevice> {
+    inner: Arc<Mutex<T>>
+}
+
+impl<T: BusDevice> SyncShared<T> {
+    #[must_use]
+    /// Wraps `inner` in a new shared handle.
+    pub fn new(inner: T) -> Self {
+        Self { inner: Arc::new(Mutex::new(inner)) }
+    }
+
+    /// Locks the device for host access, blocking until it is available.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock was poisoned, the guard can still be recovered from the error.
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        self.inner.lock()
+    }
+
+    #[must_use]
+    /// Returns `true` if both handles refer to the same device.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T: BusDevice> Clone for SyncShared<T> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+impl<T: BusDevice> BusDevice for SyncShared<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.lock()
+            .map_err(|_| BusDeviceError::LockPoisoned { address })?
+            .read(address)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.lock()
+            .map_err(|_| BusDeviceError::LockPoisoned { address })?
+            .write(address, data)
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.lock()
+            .map_err(|_| BusDeviceError::LockPoisoned { address })?
+            .peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.lock()
+            .map_err(|_| BusDeviceError::LockPoisoned { address })?
+            .poke(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.lock().ok()?.size()
+    }
+
+    /// Resets the device even if the lock was poisoned, as a reset is the usual way to recover from a failure.
+    fn reset(&mut self) {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner).reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{Memory, RegionBusDevice};
+
+    use super::*;
+
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_sync_shared_is_send_sync() {
+        assert_send_sync::<SyncShared<Memory<16>>>();
+    }
+
+    #[test]
+    fn test_sync_shared_handles() {
+        let mut device = SyncShared::new(Memory::<4>::empty());
+        let host = device.clone();
+
+        assert!(device.ptr_eq(&host));
+        assert_eq!(device.write_region(0, &[1, 2]), Ok(()));
+        assert_eq!(host.lock().unwrap().as_slice(), &[1, 2, 0, 0]);
+
+        host.lock().unwrap()[3] = 4;
+        assert_eq!(device.read(3), Ok(4));
+        assert_eq!(device.read(4), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+    }
+
+    #[test]
+    fn test_sync_shared_two_threads() {
+        const ITERATIONS: u8 = 200;
+
+        let mut bus = SyncShared::new(Memory::<256>::empty());
+        let host = bus.clone();
+
+        let writer = thread::spawn(move || {
+            for value in 1..=ITERATIONS {
+                for address in 0..256 {
+                    bus.write(address, value).unwrap();
+                }
+            }
+        });
+
+        let reader = thread::spawn(move || {
+            let mut last = 0;
+
+            // Bytes are written in order, so within any snapshot the values can only decrease along the region, and
+            // each snapshot's first byte can never go backwards
+            while last < ITERATIONS {
+                let snapshot = host.lock().unwrap().clone();
+                let bytes = snapshot.as_slice();
+
+                assert!(bytes.windows(2).all(|pair| pair[0] >= pair[1]));
+                assert!(bytes[0] >= last);
+                last = bytes[255];
+            }
+
+            host
+        });
+
+        writer.join().unwrap();
+        let host = reader.join().unwrap();
+
+        assert!(host.lock().unwrap().iter().all(|byte| byte == ITERATIONS));
+    }
+
+    #[test]
+    fn test_sync_shared_poisoned() {
+        let mut device = SyncShared::new(Memory::<4>::empty());
+        let handle = device.clone();
+
+        let result = thread::spawn(move || {
+            let _guard = handle.lock().unwrap();
+            panic!("poisoning the lock");
+        }).join();
+
+        assert!(result.is_err());
+        assert_eq!(device.read(1), Err(BusDeviceError::LockPoisoned { address: 1 }));
+        assert_eq!(device.write(2, 0), Err(BusDeviceError::LockPoisoned { address: 2 }));
+
+        // The host can still recover the device
+        assert_eq!(device.lock().unwrap_err().into_inner().as_slice(), &[0, 0, 0, 0]);
+    }
+}
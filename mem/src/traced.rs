@@ -0,0 +1,170 @@
+use std::{cell::RefCell, io::Write, ops::RangeInclusive};
+
+use crate::{AccessKind, BusDevice, BusDeviceError};
+
+/// Wraps a device, logging every access made to it as a line of text written to `sink`.
+///
+/// Reads are logged as `R 0x0B800 0x1A` and writes as `W 0x0B800 0x1A`, giving the address and the data read or
+/// written in hex. Accesses which fail have the error appended instead of the data read, as in
+/// `R 0x00020 ERROR AddressNotMapped { address: 32 }`. Tracing can be limited to a range of addresses with a filter.
+/// Errors writing to the sink are ignored so that tracing never changes the result of an access, and peeks and pokes
+/// are forwarded without being traced.
+#[derive(Debug)]
+pub struct Traced<T: BusDevice, W: Write> {
+    inner: T,
+    sink: RefCell<W>,
+    filter: Option<RangeInclusive<usize>>
+}
+
+impl<T: BusDevice, W: Write> Traced<T, W> {
+    #[must_use]
+    /// Wraps `inner`, tracing every access to `sink`.
+    pub const fn new(inner: T, sink: W) -> Self {
+        Self { inner, sink: RefCell::new(sink), filter: None }
+    }
+
+    #[must_use]
+    /// Limits tracing to accesses within `range`.
+    pub fn with_filter(self, range: RangeInclusive<usize>) -> Self {
+        Self { filter: Some(range), ..self }
+    }
+
+    /// Limits tracing to accesses within `filter`, or traces every access if it is `None`.
+    pub const fn set_filter(&mut self, filter: Option<RangeInclusive<usize>>) {
+        self.filter = filter;
+    }
+
+    #[must_use]
+    /// Returns the range tracing is limited to, if any.
+    pub const fn filter(&self) -> Option<&RangeInclusive<usize>> {
+        self.filter.as_ref()
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device. Accesses made through it are not traced.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device, discarding the sink.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device and the sink.
+    pub fn into_parts(self) -> (T, W) {
+        (self.inner, self.sink.into_inner())
+    }
+
+    /// Logs an access, if it passes the filter.
+    fn trace(&self, kind: AccessKind, address: usize, data: Option<u8>, result: Result<(), BusDeviceError>) {
+        if self.filter.as_ref().is_some_and(|filter| !filter.contains(&address)) {
+            return;
+        }
+
+        let kind = match kind {
+            AccessKind::Read => 'R',
+            AccessKind::Write => 'W'
+        };
+
+        let mut sink = self.sink.borrow_mut();
+
+        let _ = match (data, result) {
+            (Some(data), Ok(())) => writeln!(sink, "{kind} {address:#07X} {data:#04X}"),
+            (Some(data), Err(error)) => writeln!(sink, "{kind} {address:#07X} {data:#04X} ERROR {error:?}"),
+            (None, Err(error)) => writeln!(sink, "{kind} {address:#07X} ERROR {error:?}"),
+            (None, Ok(())) => writeln!(sink, "{kind} {address:#07X}")
+        };
+    }
+}
+
+impl<T: BusDevice, W: Write> BusDevice for Traced<T, W> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        let result = self.inner.read(address);
+        self.trace(AccessKind::Read, address, result.ok(), result.map(|_| ()));
+
+        result
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        let result = self.inner.write(address, data);
+        self.trace(AccessKind::Write, address, Some(data), result);
+
+        result
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.poke(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_traced_lines() {
+        let mut device = Traced::new(Memory::<0x20>::empty(), Vec::new());
+
+        assert_eq!(device.write(0x10, 0x1A), Ok(()));
+        assert_eq!(device.read(0x10), Ok(0x1A));
+        assert_eq!(device.write_region(0x1E, &[0xAB, 0xCD]), Ok(()));
+        assert_eq!(device.write(0x20, 0x00), Err(BusDeviceError::AddressOutOfBounds { address: 0x20, size: 0x20 }));
+        assert_eq!(device.read(0x21), Err(BusDeviceError::AddressOutOfBounds { address: 0x21, size: 0x20 }));
+
+        // Peeks and pokes are never traced
+        assert_eq!(device.poke(0, 1), Ok(()));
+        assert_eq!(device.peek(0), Ok(1));
+
+        let (_, sink) = device.into_parts();
+
+        assert_eq!(String::from_utf8(sink).unwrap(), "\
+            W 0x00010 0x1A\n\
+            R 0x00010 0x1A\n\
+            W 0x0001E 0xAB\n\
+            W 0x0001F 0xCD\n\
+            W 0x00020 0x00 ERROR AddressOutOfBounds { address: 32, size: 32 }\n\
+            R 0x00021 ERROR AddressOutOfBounds { address: 33, size: 32 }\n");
+    }
+
+    #[test]
+    fn test_traced_filter() {
+        let map = MemoryMap::new()
+            .with_range(0xB8000..=0xBFFFF, Box::new(Memory::<0x8000>::empty()));
+
+        let mut device = Traced::new(map, Vec::new()).with_filter(0xB8000..=0xB8FA0);
+
+        assert_eq!(device.write(0xB8000, b'A'), Ok(()));
+        assert_eq!(device.write(0xB9000, b'B'), Ok(()));
+        assert_eq!(device.read(0x00000), Err(BusDeviceError::AddressNotMapped { address: 0 }));
+
+        device.set_filter(None);
+        assert_eq!(device.read(0xB9000), Ok(b'B'));
+        assert_eq!(device.filter(), None);
+
+        let (_, sink) = device.into_parts();
+        assert_eq!(String::from_utf8(sink).unwrap(), "W 0xB8000 0x41\nR 0xB9000 0x42\n");
+    }
+}
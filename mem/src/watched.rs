@@ -0,0 +1,236 @@
+use std::{cell::RefCell, fmt::Debug, ops::RangeInclusive};
+
+use crate::{BusDevice, BusDeviceError};
+
+/// The direction of a single bus access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccessKind {
+    Read,
+    Write
+}
+
+/// Which accesses a watchpoint fires on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WatchKind {
+    Read,
+    Write,
+    ReadWrite
+}
+
+impl WatchKind {
+    #[must_use]
+    /// Returns `true` if a watchpoint of this kind fires on an access of the given kind.
+    pub const fn matches(self, access: AccessKind) -> bool {
+        matches!((self, access), (Self::ReadWrite, _) | (Self::Read, AccessKind::Read) | (Self::Write, AccessKind::Write))
+    }
+}
+
+/// A successful access which matched a watchpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WatchEvent {
+    /// Address of the access, relative to the start of the device.
+    pub address: usize,
+    /// Whether the access was a read or a write.
+    pub kind: AccessKind,
+    /// The byte which was read or written.
+    pub value: u8
+}
+
+/// Handle identifying a watchpoint added to a `Watched` device, used to remove it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WatchHandle(usize);
+
+/// Callback invoked by a `Watched` device for each matching access.
+pub type WatchFn = Box<dyn FnMut(WatchEvent)>;
+
+/// Wraps a device, invoking a callback whenever an access hits one of a set of watchpoints, acting as a data
+/// breakpoint.
+///
+/// The callback runs after the access has succeeded and cannot change its result, accesses which return an error
+/// never fire. An access matching several watchpoints fires the callback once. Peeks and pokes are forwarded without
+/// being watched.
+pub struct Watched<T: BusDevice> {
+    inner: T,
+    watchpoints: Vec<(WatchHandle, RangeInclusive<usize>, WatchKind)>,
+    next_handle: usize,
+    callback: RefCell<WatchFn>
+}
+
+impl<T: BusDevice> Watched<T> {
+    #[must_use]
+    /// Wraps `inner` with no watchpoints, invoking `callback` for each matching access once watchpoints are added.
+    pub fn new(inner: T, callback: impl FnMut(WatchEvent) + 'static) -> Self {
+        Self { inner, watchpoints: Vec::new(), next_handle: 0, callback: RefCell::new(Box::new(callback)) }
+    }
+
+    /// Adds a watchpoint over `range` firing on accesses of the given `kind`, returning a handle which can be used to
+    /// remove it.
+    pub fn watch(&mut self, range: RangeInclusive<usize>, kind: WatchKind) -> WatchHandle {
+        let handle = WatchHandle(self.next_handle);
+        self.next_handle += 1;
+
+        self.watchpoints.push((handle, range, kind));
+
+        handle
+    }
+
+    /// Removes the watchpoint with the given `handle`, returning `false` if it had already been removed.
+    pub fn unwatch(&mut self, handle: WatchHandle) -> bool {
+        let count = self.watchpoints.len();
+        self.watchpoints.retain(|(other, _, _)| *other != handle);
+
+        self.watchpoints.len() != count
+    }
+
+    /// Removes every watchpoint.
+    pub fn clear_watchpoints(&mut self) {
+        self.watchpoints.clear();
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device. Accesses made through it are not watched.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device, discarding the watchpoints and callback.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Fires the callback if the access matches any watchpoint.
+    fn notify(&self, address: usize, kind: AccessKind, value: u8) {
+        if self.watchpoints.iter().any(|(_, range, watch)| watch.matches(kind) && range.contains(&address)) {
+            (self.callback.borrow_mut())(WatchEvent { address, kind, value });
+        }
+    }
+}
+
+impl<T: BusDevice + Debug> Debug for Watched<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watched")
+            .field("inner", &self.inner)
+            .field("watchpoints", &self.watchpoints)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: BusDevice> BusDevice for Watched<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        let value = self.inner.read(address)?;
+        self.notify(address, AccessKind::Read, value);
+
+        Ok(value)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.write(address, data)?;
+        self.notify(address, AccessKind::Write, data);
+
+        Ok(())
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.poke(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::{Memory, ReadOnlyMemory, RegionBusDevice};
+
+    use super::*;
+
+    fn recorder() -> (Rc<RefCell<Vec<WatchEvent>>>, impl FnMut(WatchEvent) + 'static) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&events);
+
+        (events, move |event| sink.borrow_mut().push(event))
+    }
+
+    #[test]
+    fn test_watched_single_accesses() {
+        let (events, callback) = recorder();
+        let mut device = Watched::new(Memory::<0x500>::empty(), callback);
+
+        device.watch(0x0472..=0x0473, WatchKind::Write);
+        let reads = device.watch(0x0010..=0x0010, WatchKind::Read);
+
+        assert_eq!(device.write(0x0472, 0x34), Ok(()));
+        assert_eq!(device.write(0x0474, 0x12), Ok(()));
+        assert_eq!(device.read(0x0472), Ok(0x34));
+        assert_eq!(device.read(0x0010), Ok(0));
+        assert_eq!(device.write(0x0010, 7), Ok(()));
+
+        assert_eq!(*events.borrow(), [
+            WatchEvent { address: 0x0472, kind: AccessKind::Write, value: 0x34 },
+            WatchEvent { address: 0x0010, kind: AccessKind::Read, value: 0 }
+        ]);
+
+        // Once removed, the watchpoint no longer fires
+        assert!(device.unwatch(reads));
+        assert!(!device.unwatch(reads));
+        assert_eq!(device.read(0x0010), Ok(7));
+        assert_eq!(events.borrow().len(), 2);
+
+        // Peeks and pokes are never watched
+        assert_eq!(device.poke(0x0472, 0), Ok(()));
+        assert_eq!(device.peek(0x0472), Ok(0));
+        assert_eq!(events.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_watched_regions() {
+        let (events, callback) = recorder();
+        let mut device = Watched::new(Memory::<16>::empty(), callback);
+
+        device.watch(2..=4, WatchKind::ReadWrite);
+        device.watch(3..=3, WatchKind::Write);
+
+        assert_eq!(device.write_region(0, &[1, 2, 3, 4, 5, 6]), Ok(()));
+        assert_eq!(device.read_region::<2>(4), Ok([5, 6]));
+
+        assert_eq!(*events.borrow(), [
+            WatchEvent { address: 2, kind: AccessKind::Write, value: 3 },
+            WatchEvent { address: 3, kind: AccessKind::Write, value: 4 },
+            WatchEvent { address: 4, kind: AccessKind::Write, value: 5 },
+            WatchEvent { address: 4, kind: AccessKind::Read, value: 5 }
+        ]);
+    }
+
+    #[test]
+    fn test_watched_errors_do_not_fire() {
+        let (events, callback) = recorder();
+        let mut device = Watched::new(ReadOnlyMemory::<4>::filled([1, 2, 3, 4]), callback);
+
+        device.watch(0..=7, WatchKind::ReadWrite);
+
+        assert_eq!(device.write(1, 0), Err(BusDeviceError::AddressNotWritable { address: 1 }));
+        assert_eq!(device.read(5), Err(BusDeviceError::AddressOutOfBounds { address: 5, size: 4 }));
+        assert!(events.borrow().is_empty());
+
+        assert_eq!(device.read(1), Ok(2));
+        assert_eq!(*events.borrow(), [WatchEvent { address: 1, kind: AccessKind::Read, value: 2 }]);
+    }
+}
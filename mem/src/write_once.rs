@@ -0,0 +1,123 @@
+use crate::{BusDevice, BusDeviceError};
+
+/// A memory region in which every byte can be written exactly once, like a fuse or one-time programmable
+/// configuration region.
+///
+/// Reads always succeed. The first write to an address succeeds, and any later write to that address fails with
+/// `AddressNotWritable`, leaving the stored byte unchanged.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WriteOnceMemory<const SIZE: usize> {
+    data: [u8; SIZE],
+    written: [bool; SIZE]
+}
+
+impl<const SIZE: usize> WriteOnceMemory<SIZE> {
+    #[must_use]
+    /// Constructs a new, zeroed and entirely unwritten memory region.
+    pub const fn empty() -> Self {
+        Self::filled([0; SIZE])
+    }
+
+    #[must_use]
+    /// Constructs a new, entirely unwritten memory region which reads back the given data until written.
+    pub const fn filled(data: [u8; SIZE]) -> Self {
+        Self { data, written: [false; SIZE] }
+    }
+
+    #[must_use]
+    /// Returns `true` if the byte at `address` has already been written. Addresses outside of the region are never
+    /// written.
+    pub fn is_written(&self, address: usize) -> bool {
+        self.written.get(address).copied().unwrap_or(false)
+    }
+
+    #[must_use]
+    /// Returns a mask with one entry per byte, `true` for the bytes which have already been written.
+    pub const fn written_mask(&self) -> &[bool; SIZE] {
+        &self.written
+    }
+}
+
+impl<const SIZE: usize> BusDevice for WriteOnceMemory<SIZE> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.data.get(address).copied().ok_or(BusDeviceError::AddressOutOfBounds { address, size: SIZE })
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        let written = self.written.get_mut(address).ok_or(BusDeviceError::AddressOutOfBounds { address, size: SIZE })?;
+
+        if *written {
+            return Err(BusDeviceError::AddressNotWritable { address });
+        }
+
+        *written = true;
+        self.data[address] = data;
+
+        Ok(())
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_write_once_memory() {
+        let mut mem = WriteOnceMemory::<4>::filled([0xFF; 4]);
+
+        assert_eq!(mem.read(2), Ok(0xFF));
+        assert!(!mem.is_written(2));
+
+        assert_eq!(mem.write(2, 0x12), Ok(()));
+        assert!(mem.is_written(2));
+        assert_eq!(mem.read(2), Ok(0x12));
+
+        assert_eq!(mem.write(2, 0x34), Err(BusDeviceError::AddressNotWritable { address: 2 }));
+        assert_eq!(mem.write(2, 0x12), Err(BusDeviceError::AddressNotWritable { address: 2 }));
+        assert_eq!(mem.read(2), Ok(0x12));
+
+        assert_eq!(mem.written_mask(), &[false, false, true, false]);
+    }
+
+    #[test]
+    fn test_write_once_memory_bounds() {
+        let mut mem = WriteOnceMemory::<4>::empty();
+
+        assert_eq!(mem.read(4), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+        assert_eq!(mem.write(4, 0), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+        assert!(!mem.is_written(4));
+        assert_eq!(mem.written_mask(), &[false; 4]);
+    }
+
+    #[test]
+    fn test_write_once_memory_region_write() {
+        let mut mem = WriteOnceMemory::<8>::empty();
+
+        assert_eq!(mem.write(4, 0xAA), Ok(()));
+
+        // `write_region` is not transactional, so the bytes before the already written byte stick, and the bytes
+        // after it are never attempted
+        assert_eq!(mem.write_region(2, &[1, 2, 3, 4]), Err(BusDeviceError::AddressNotWritable { address: 4 }));
+        assert_eq!(mem.read_region(0), Ok([0, 0, 1, 2, 0xAA, 0, 0, 0]));
+        assert_eq!(mem.written_mask(), &[false, false, true, true, true, false, false, false]);
+
+        assert_eq!(mem.write_region(5, &[5, 6, 7]), Ok(()));
+        assert_eq!(mem.read_region(0), Ok([0, 0, 1, 2, 0xAA, 5, 6, 7]));
+    }
+
+    #[test]
+    fn test_write_once_memory_in_memory_map() {
+        let mut map = MemoryMap::new()
+            .with_range(0x100..=0x103, Box::new(WriteOnceMemory::<4>::empty()));
+
+        assert_eq!(map.write(0x101, 7), Ok(()));
+        assert_eq!(map.write(0x101, 8), Err(BusDeviceError::AddressNotWritable { address: 1 }));
+        assert_eq!(map.read(0x101), Ok(7));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{BusDevice, BusDeviceError};
+
+/// Wraps a device so that it can only be written, as with the write only registers of many peripherals.
+///
+/// Writes and pokes are forwarded to the inner device. Reads and peeks never reach it: they either fail with
+/// `AddressNotReadable` or return a constant, standing in for the bus noise real hardware reads back, depending on how
+/// the wrapper was constructed. Guest code reading such a register back is usually a bug, which plain memory would
+/// hide by returning the last value written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WriteOnly<T: BusDevice> {
+    inner: T,
+    read_value: Option<u8>
+}
+
+impl<T: BusDevice> WriteOnly<T> {
+    #[must_use]
+    /// Wraps `inner`, failing every read with `AddressNotReadable`.
+    pub const fn new(inner: T) -> Self {
+        Self { inner, read_value: None }
+    }
+
+    #[must_use]
+    /// Wraps `inner`, with every read returning `value`.
+    pub const fn reading_as(inner: T, value: u8) -> Self {
+        Self { inner, read_value: Some(value) }
+    }
+
+    #[must_use]
+    /// Returns the value every read returns, or `None` if reads fail.
+    pub const fn read_value(&self) -> Option<u8> {
+        self.read_value
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device, which can be used to inspect the values written.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: BusDevice> BusDevice for WriteOnly<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.read_value.ok_or(BusDeviceError::AddressNotReadable { address })
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.write(address, data)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        self.inner.poke(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, RegionBusDevice, Shared, OPEN_BUS_VALUE};
+
+    use super::*;
+
+    #[test]
+    fn test_write_only_error() {
+        let device = Shared::new(WriteOnly::new(Memory::<4>::empty()));
+        let mut map = MemoryMap::new()
+            .with_range(0x40..=0x43, Box::new(device.clone()));
+
+        assert_eq!(map.write_region(0x40, &[0x36, 0x00, 0x10]), Ok(()));
+        assert_eq!(map.read(0x40), Err(BusDeviceError::AddressNotReadable { address: 0 }));
+        assert_eq!(map.peek(0x42), Err(BusDeviceError::AddressNotReadable { address: 2 }));
+        assert_eq!(map.read_region::<2>(0x42), Err(BusDeviceError::AddressNotReadable { address: 2 }));
+
+        // The writes landed in the inner device
+        let device = device.borrow().clone();
+        assert_eq!(device.size(), Some(4));
+        assert_eq!(device.into_inner(), Memory::filled([0x36, 0x00, 0x10, 0x00]));
+    }
+
+    #[test]
+    fn test_write_only_constant() {
+        let mut device = WriteOnly::reading_as(Memory::<4>::empty(), OPEN_BUS_VALUE);
+        assert_eq!(device.read_value(), Some(0xFF));
+
+        assert_eq!(device.write_region(0, &[1, 2, 3, 4]), Ok(()));
+        assert_eq!(device.poke(3, 5), Ok(()));
+        assert_eq!(device.read_region(0), Ok([0xFF; 4]));
+        assert_eq!(device.inner().read(0), Ok(1));
+
+        // Writes are still checked by the inner device
+        assert_eq!(device.write(4, 0), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+
+        assert_eq!(device.into_inner(), Memory::filled([1, 2, 3, 5]));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{BusDevice, BusDeviceError};
+
+/// Wraps a device so that writes can be switched off at runtime, as with shadow RAM or flash.
+///
+/// While protected, writes fail with `AddressNotWritable` and leave the device untouched. While unprotected, the
+/// wrapper behaves exactly like the inner device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WriteProtect<T: BusDevice> {
+    inner: T,
+    protected: bool
+}
+
+impl<T: BusDevice> WriteProtect<T> {
+    #[must_use]
+    /// Wraps `inner`, initially unprotected.
+    pub const fn new(inner: T) -> Self {
+        Self { inner, protected: false }
+    }
+
+    #[must_use]
+    /// Wraps `inner`, initially protected.
+    pub const fn protected(inner: T) -> Self {
+        Self { inner, protected: true }
+    }
+
+    /// Enables or disables write protection.
+    pub const fn set_protected(&mut self, protected: bool) {
+        self.protected = protected;
+    }
+
+    #[must_use]
+    /// Returns `true` if writes are currently rejected.
+    pub const fn is_protected(&self) -> bool {
+        self.protected
+    }
+
+    #[must_use]
+    /// Returns a reference to the wrapped device.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Returns a mutable reference to the wrapped device, bypassing the write protection.
+    pub const fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    #[must_use]
+    /// Unwraps the device.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: BusDevice> BusDevice for WriteProtect<T> {
+    fn read(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.read(address)
+    }
+
+    fn write(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        if self.protected {
+            return Err(BusDeviceError::AddressNotWritable { address });
+        }
+
+        self.inner.write(address, data)
+    }
+
+    fn peek(&self, address: usize) -> Result<u8, BusDeviceError> {
+        self.inner.peek(address)
+    }
+
+    fn poke(&mut self, address: usize, data: u8) -> Result<(), BusDeviceError> {
+        if self.protected {
+            return Err(BusDeviceError::AddressNotWritable { address });
+        }
+
+        self.inner.poke(address, data)
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.inner.size()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Memory, MemoryMap, RegionBusDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_write_protect_toggle() {
+        let mut device = WriteProtect::new(Memory::<8>::empty());
+        assert!(!device.is_protected());
+
+        assert_eq!(device.write_region(0, &[1, 2, 3, 4]), Ok(()));
+
+        device.set_protected(true);
+        assert!(device.is_protected());
+        assert_eq!(device.write(1, 42), Err(BusDeviceError::AddressNotWritable { address: 1 }));
+        assert_eq!(device.write_region(4, &[5, 6]), Err(BusDeviceError::AddressNotWritable { address: 4 }));
+        assert_eq!(device.read_region(0), Ok([1, 2, 3, 4, 0, 0, 0, 0]));
+
+        device.set_protected(false);
+        assert_eq!(device.write_region(4, &[5, 6]), Ok(()));
+        assert_eq!(device.read_region(0), Ok([1, 2, 3, 4, 5, 6, 0, 0]));
+
+        assert_eq!(device.into_inner(), Memory::filled([1, 2, 3, 4, 5, 6, 0, 0]));
+    }
+
+    #[test]
+    fn test_write_protect_passes_errors_through() {
+        let mut device = WriteProtect::new(Memory::<4>::empty());
+
+        assert_eq!(device.write(4, 0), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+        assert_eq!(device.read(4), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+
+        device.set_protected(true);
+        assert_eq!(device.write(4, 0), Err(BusDeviceError::AddressNotWritable { address: 4 }));
+        assert_eq!(device.read(4), Err(BusDeviceError::AddressOutOfBounds { address: 4, size: 4 }));
+    }
+
+    #[test]
+    fn test_write_protect_inner_access() {
+        let mut device = WriteProtect::protected(Memory::<4>::empty());
+
+        assert_eq!(device.inner_mut().write(0, 7), Ok(()));
+        assert_eq!(device.inner().read(0), Ok(7));
+        assert_eq!(device.read(0), Ok(7));
+    }
+
+    #[test]
+    fn test_write_protect_in_memory_map() {
+        let mut map = MemoryMap::new()
+            .with_range(0xC0000..=0xC0007, Box::new(WriteProtect::new(Memory::<8>::empty())));
+
+        assert_eq!(map.write_region(0xC0000, &[0x55, 0xAA]), Ok(()));
+        assert_eq!(map.read_region(0xC0000), Ok([0x55, 0xAA, 0x00]));
+
+        let mut map = MemoryMap::new()
+            .with_range(0xC0000..=0xC0007, Box::new(WriteProtect::protected(Memory::<8>::filled([0x55; 8]))));
+
+        assert_eq!(map.write(0xC0003, 0), Err(BusDeviceError::AddressNotWritable { address: 3 }));
+        assert_eq!(map.read(0xC0003), Ok(0x55));
+    }
+}